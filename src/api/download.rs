@@ -0,0 +1,443 @@
+//! Reads response bodies as they arrive. The text is sent to the ui while it
+//! is received, so that long or chunked responses show before they end.
+//! Bodies bigger than the memory cap are written to a temp file and only
+//! their end is kept in memory, which is what a log tail or an NDJSON feed
+//! needs.
+
+use std::{
+    cell::Cell, env, error::Error, process, rc::Rc,
+    fs::{ self, File },
+    io::{ self, BufRead, BufReader, Read, Write },
+    path::PathBuf,
+    sync::atomic::{ AtomicBool, Ordering },
+    time::{ Duration, Instant },
+};
+
+use chrono::Local;
+use flate2::read::{ GzDecoder, ZlibDecoder, DeflateDecoder };
+use jsonxf::pretty_print;
+use reqwest::{ blocking::Response, header::CONTENT_ENCODING };
+
+use crate::{
+    api::{ ResponseData, APP_JSON, reader::BodyReader },
+    ui::state::response::ResponseInfo,
+};
+
+/// Bytes of the body kept in memory until it is written to a file.
+pub const DEFAULT_MEMORY_CAP: usize = 2 * 1024 * 1024;
+
+/// How often the text received is sent to the ui.
+const CHUNK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Message sent from the worker thread while the body is read.
+pub enum DownloadMessage {
+    /// The status and headers arrived, the body follows.
+    Started(ResponseInfo),
+
+    /// Text of the body received since the last chunk, with the number of
+    /// bytes received so far.
+    Chunk { text: String, received: usize },
+
+    /// The body got bigger than the cap and is written to the file from now
+    /// on.
+    Spilled(PathBuf),
+}
+
+/// The body read so far.
+struct Body {
+    cap: usize,
+
+    /// The body, or its end once it is written to `file`.
+    bytes: Vec<u8>,
+
+    /// Size of the whole body.
+    size: usize,
+    file: Option<(PathBuf, File)>,
+}
+
+impl Body {
+    fn new(cap: usize) -> Self {
+        Self { cap, bytes: vec![], size: 0, file: None }
+    }
+
+    /// Adds `bytes` to the body. Returns the file the body is written to if
+    /// it got bigger than the cap with them.
+    fn push(&mut self, bytes: &[u8]) -> io::Result<Option<PathBuf>> {
+        self.size += bytes.len();
+        self.bytes.extend_from_slice(bytes);
+
+        if let Some((_, file)) = self.file.as_mut() {
+            file.write_all(bytes)?;
+
+            // Drops the start of the body once twice the cap is kept
+            if self.bytes.len() / 2 > self.cap {
+                let end = self.bytes.len() - self.cap;
+                self.bytes.drain(..end);
+            }
+
+            return Ok(None);
+        }
+
+        if self.size <= self.cap {
+            return Ok(None);
+        }
+
+        let path = env::temp_dir().join(format!(
+            "dakia-{}-{}.body",
+            process::id(),
+            Local::now().format("%Y%m%d%H%M%S%f"),
+        ));
+
+        let mut file = File::create(&path)?;
+        file.write_all(&self.bytes)?;
+
+        self.file = Some((path.clone(), file));
+
+        Ok(Some(path))
+    }
+
+    /// The text of the body. Once it is written to a file, that is the
+    /// last lines fitting in the cap.
+    fn text(&self) -> String {
+        if self.file.is_none() {
+            return String::from_utf8_lossy(&self.bytes).to_string();
+        }
+
+        let tail = &self.bytes[self.bytes.len().saturating_sub(self.cap)..];
+
+        // The first line is cut off unless the whole body is kept
+        let tail = match (tail.len() < self.size, tail.iter().position(|b| *b == b'\n')) {
+            (true, Some(newline)) => &tail[newline + 1..],
+            _ => tail,
+        };
+
+        String::from_utf8_lossy(tail).to_string()
+    }
+}
+
+/// A body not read to its end, e.g. because the request was cancelled,
+/// leaves no file behind.
+impl Drop for Body {
+    fn drop(&mut self) {
+        if let Some((path, _)) = self.file.take() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Counts the bytes read from the network, before they are decompressed.
+struct Counter<R> {
+    inner: R,
+    count: Rc<Cell<usize>>,
+}
+
+impl<R: Read> Read for Counter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count.set(self.count.get() + read);
+
+        Ok(read)
+    }
+}
+
+/// Reads the body of `response`, sending it with `emit` as it arrives.
+/// Once the body is bigger than `cap` it is written to a temp file, and the
+/// text of the response is the end of the body. `started` is when the
+/// request was sent, for timing the response. Cancelling stops the read at
+/// once and drops the connection.
+pub fn download(
+    response: Response,
+    started: Instant,
+    cap: usize,
+    cancelled: &AtomicBool,
+    emit: &dyn Fn(DownloadMessage),
+) -> Result<ResponseData, Box<dyn Error + Send + Sync>> {
+    let mut info = ResponseInfo::from_response(&response);
+    let is_json = info.content_type().starts_with(APP_JSON);
+
+    let content_encoding = response.headers().get(CONTENT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+        .unwrap_or_default();
+
+    emit(DownloadMessage::Started(info.clone()));
+
+    let received = Rc::new(Cell::new(0));
+    let counter = Counter {
+        inner: BodyReader::new(response, false, cancelled),
+        count: received.clone(),
+    };
+    let mut reader = decoder(Box::new(counter), &content_encoding);
+
+    let mut body = Body::new(cap);
+    let mut pending: Vec<u8> = vec![];
+    let mut last_chunk = Instant::now();
+    let mut buf = [0u8; 8192];
+
+    loop {
+        let read = match reader.read(&mut buf) {
+            Ok(read) => read,
+            Err(_) if cancelled.load(Ordering::SeqCst) => {
+                return Err("The request was cancelled".into());
+            }
+            Err(e) => { return Err(e.into()); }
+        };
+
+        if cancelled.load(Ordering::SeqCst) {
+            return Err("The request was cancelled".into());
+        }
+
+        if read == 0 {
+            break;
+        }
+
+        if let Some(path) = body.push(&buf[..read])? {
+            emit(DownloadMessage::Spilled(path));
+        }
+
+        pending.extend_from_slice(&buf[..read]);
+
+        if last_chunk.elapsed() >= CHUNK_INTERVAL {
+            last_chunk = Instant::now();
+
+            emit(DownloadMessage::Chunk {
+                text: take_text(&mut pending),
+                received: received.get(),
+            });
+        }
+    }
+
+    info.set_elapsed(started.elapsed());
+    info.set_sizes(received.get(), body.size);
+
+    let text = body.text();
+
+    if let Some((path, _)) = body.file.take() {
+        info.set_body_file(Some(path));
+        return Ok(ResponseData::new(text, info));
+    }
+
+    match is_json.then(|| pretty_print(&text).ok()).flatten() {
+        Some(pretty) => {
+            let mut data = ResponseData::new(pretty, info);
+
+            data.set_raw_body(Some(text));
+            Ok(data)
+        }
+
+        None => Ok(ResponseData::new(text, info)),
+    }
+}
+
+/// Takes the text of `pending`, leaving the bytes of a character that isn't
+/// complete yet for the next chunk.
+fn take_text(pending: &mut Vec<u8>) -> String {
+    let complete = match std::str::from_utf8(pending) {
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        _ => pending.len(),
+    };
+
+    let text = String::from_utf8_lossy(&pending[..complete]).to_string();
+    pending.drain(..complete);
+
+    text
+}
+
+/// Decompresses `reader` according to the `Content-Encoding` header value.
+/// Encodings are listed in the order they were applied, so they are undone
+/// from the last. Unknown encodings are left as they are.
+pub fn decoder<'a>(reader: Box<dyn Read + 'a>, content_encoding: &str)
+    -> Box<dyn Read + 'a> {
+    let mut reader = reader;
+
+    for encoding in content_encoding.rsplit(',') {
+        reader = match encoding.trim().to_lowercase().as_str() {
+            "gzip" | "x-gzip" => Box::new(GzDecoder::new(reader)),
+
+            "deflate" => {
+                // Some servers send raw deflate data without the zlib
+                // wrapper.
+                let mut buffered = BufReader::new(reader);
+
+                let zlib = match buffered.fill_buf() {
+                    Ok([cmf, flg, ..]) => {
+                        cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0
+                    }
+
+                    _ => true,
+                };
+
+                match zlib {
+                    true => Box::new(ZlibDecoder::new(buffered)),
+                    false => Box::new(DeflateDecoder::new(buffered)),
+                }
+            }
+
+            "br" => Box::new(brotli::Decompressor::new(reader, 4096)),
+            _ => reader,
+        };
+    }
+
+    reader
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use flate2::{
+        Compression,
+        write::{ GzEncoder, ZlibEncoder, DeflateEncoder },
+    };
+
+    const TEXT: &str = "{\"name\": \"dakia\", \"items\": [1, 2, 3]}";
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn zlib(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn deflate(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = DeflateEncoder::new(vec![], Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn br(bytes: &[u8]) -> Vec<u8> {
+        let mut encoded = vec![];
+
+        {
+            let mut writer = brotli::CompressorWriter::new(&mut encoded, 4096, 5, 22);
+            writer.write_all(bytes).unwrap();
+        }
+
+        encoded
+    }
+
+    fn decode(bytes: Vec<u8>, content_encoding: &str) -> String {
+        let mut text = String::new();
+
+        decoder(Box::new(io::Cursor::new(bytes)), content_encoding)
+            .read_to_string(&mut text)
+            .unwrap();
+
+        text
+    }
+
+    #[test]
+    fn take_text_keeps_an_incomplete_character_for_the_next_chunk() {
+        let bytes = "añb".as_bytes();
+        let mut pending = bytes[..2].to_vec();
+
+        assert_eq!(take_text(&mut pending), "a");
+        assert_eq!(pending, [0xc3]);
+
+        pending.extend_from_slice(&bytes[2..]);
+
+        assert_eq!(take_text(&mut pending), "ñb");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn take_text_replaces_invalid_bytes() {
+        let mut pending = vec![b'a', 0xff, b'b'];
+
+        assert_eq!(take_text(&mut pending), "a\u{fffd}b");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn body_under_the_cap_stays_in_memory() {
+        let mut body = Body::new(16);
+
+        assert!(body.push(b"line1\n").unwrap().is_none());
+        assert!(body.push(b"line2\n").unwrap().is_none());
+
+        assert!(body.file.is_none());
+        assert_eq!(body.size, 12);
+        assert_eq!(body.text(), "line1\nline2\n");
+    }
+
+    #[test]
+    fn body_over_the_cap_spills_to_a_file() {
+        let mut body = Body::new(8);
+
+        assert!(body.push(b"line1\n").unwrap().is_none());
+
+        let path = body.push(b"line2\nl3\n").unwrap().expect("spilled");
+
+        assert_eq!(fs::read(&path).unwrap(), b"line1\nline2\nl3\n");
+        assert_eq!(body.size, 15);
+
+        // The tail is "ine2\nl3\n", whose cut first line is dropped
+        assert_eq!(body.text(), "l3\n");
+
+        // Later bytes go to the file without spilling again
+        assert!(body.push(b"l4\n").unwrap().is_none());
+        assert_eq!(fs::read(&path).unwrap(), b"line1\nline2\nl3\nl4\n");
+        assert_eq!(body.text(), "l3\nl4\n");
+
+        drop(body);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn body_keeps_at_most_twice_the_cap_once_spilled() {
+        let mut body = Body::new(8);
+
+        for _ in 0..100 {
+            body.push(b"abc\n").unwrap();
+            assert!(body.bytes.len() <= 2 * body.cap + 4);
+        }
+
+        assert_eq!(body.size, 400);
+        assert_eq!(body.text(), "abc\n");
+    }
+
+    #[test]
+    fn body_text_keeps_a_first_line_that_isnt_cut() {
+        let mut body = Body::new(4);
+
+        body.push(b"abcdef").unwrap();
+
+        // No newline in the tail, it is shown as it is
+        assert_eq!(body.text(), "cdef");
+    }
+
+    #[test]
+    fn decoder_undoes_gzip_and_brotli() {
+        assert_eq!(decode(gzip(TEXT.as_bytes()), "gzip"), TEXT);
+        assert_eq!(decode(gzip(TEXT.as_bytes()), "X-Gzip"), TEXT);
+        assert_eq!(decode(br(TEXT.as_bytes()), "br"), TEXT);
+    }
+
+    #[test]
+    fn decoder_detects_zlib_and_raw_deflate() {
+        assert_eq!(decode(zlib(TEXT.as_bytes()), "deflate"), TEXT);
+        assert_eq!(decode(deflate(TEXT.as_bytes()), "deflate"), TEXT);
+    }
+
+    #[test]
+    fn decoder_undoes_stacked_encodings_from_the_last() {
+        let encoded = br(&gzip(TEXT.as_bytes()));
+
+        assert_eq!(decode(encoded, "gzip, br"), TEXT);
+
+        let encoded = gzip(&deflate(TEXT.as_bytes()));
+
+        assert_eq!(decode(encoded, "deflate,gzip"), TEXT);
+    }
+
+    #[test]
+    fn decoder_leaves_unknown_encodings() {
+        assert_eq!(decode(TEXT.as_bytes().to_vec(), ""), TEXT);
+        assert_eq!(decode(TEXT.as_bytes().to_vec(), "identity"), TEXT);
+    }
+}
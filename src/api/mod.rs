@@ -1,29 +1,117 @@
+pub mod worker;
+pub mod sse;
+pub mod download;
+pub mod reader;
+
 use std::{
-    error::Error, time::Duration, str::FromStr, collections::HashMap,
+    error::Error, str::FromStr, collections::HashMap,
+    sync::atomic::AtomicBool, time::Instant,
 };
-use log::info;
 use reqwest::{
-    blocking::{ Response, Client, ClientBuilder },
-    header::{ self, HeaderMap, HeaderValue, HeaderName }, Method,
+    blocking::{
+        Response, Client, ClientBuilder, RequestBuilder,
+        multipart::{ Form, Part },
+    },
+    header::{ HeaderMap, HeaderValue, HeaderName, AUTHORIZATION }, Method,
 };
-
-use jsonxf::pretty_print;
-use crate::ui::state::{
-    UiState, app_status::AppStatus,
-    body::{ BodyContent, RawBodyContentType }
+use base64::{ Engine as _, engine::general_purpose::STANDARD as BASE64 };
+
+use crate::{
+    api::download::download,
+    ui::state::{
+        UiState, app_status::AppStatus, url::Url, response::ResponseInfo,
+        kv_data::{ KVData, PartKind },
+        body::BodyContent,
+        auth::{ Auth, AuthScheme, ApiKeyLocation },
+    },
+    environment::{ Environment, resolve },
+    collection::SavedRequest,
+    script::{ ScriptedRequest, pre_request },
+    graphql::body_payload,
 };
 
 const APP_JSON: &str = "application/json";
 const APP_FORM_URL_ENCODED: &str = "application/x-www-form-urlencoded";
 const APP_XHTML_XML: &str = "application/xhtml+xml";
 const APP_XML: &str = "application/xml";
-const MULTIPART_FORM_DATA: &str = "multipart/form-data";
 const TEXT_HTML: &str = "text/html";
 const TEXT_PLAIN: &str = "text/plain";
 
 const CONTENT_TYPE: &str = "content-type";
 
+/// Data read from the response on the worker thread.
+pub struct ResponseData {
+    /// Response text, pretty printed when it is json.
+    body: String,
+
+    /// The text as it was received, when `body` is pretty printed.
+    raw: Option<String>,
+
+    /// Status, headers, timing and sizes of the response.
+    info: ResponseInfo,
+}
+
+impl ResponseData {
+    pub fn new(body: String, info: ResponseInfo) -> Self {
+        Self { body, raw: None, info }
+    }
+
+    pub fn body(&self) -> &str { &self.body }
+
+    /// The text of the body as it was received, before pretty printing.
+    pub fn raw_body(&self) -> &str { self.raw.as_deref().unwrap_or(&self.body) }
+    pub fn set_raw_body(&mut self, raw: Option<String>) { self.raw = raw; }
+    pub fn info(&self) -> &ResponseInfo { &self.info }
+}
+
+/// Builds the request and hands it over to the request worker.
+/// The response is applied to the `uistate` once the worker sends it back,
+/// see [worker::receive].
 pub fn call_api(uistate: &mut UiState) -> Result<(), Box<dyn Error + 'static>> {
+    let scripted = pre_request(uistate)
+        .map_err(|e| format!("Pre-request script: {}", e))?;
+
+    // The request is sent as the pre-request script changed it
+    let (request, url) = sent_request(uistate, scripted.as_ref())?;
+
+    // The history keeps the request as it was edited, the script runs again
+    // when it's sent from the history
+    let snapshot = SavedRequest::from_uistate(uistate);
+
+    worker::dispatch(uistate, request, url, snapshot)
+}
+
+/// Builds the request of `uistate`, or the `scripted` one the pre-request
+/// script returned. Returns it with the url it is sent to.
+pub fn sent_request(uistate: &UiState, scripted: Option<&ScriptedRequest>)
+    -> Result<(RequestBuilder, String), Box<dyn Error + 'static>> {
+    let (method, url) = match scripted {
+        Some(scripted) => (scripted.method().clone(), String::from(scripted.url())),
+        None => (uistate.method(), resolved_url(uistate)),
+    };
+
+    Ok((build_request(uistate, method, scripted)?, url))
+}
+
+/// Builds the request from the url, headers, body and authorization in
+/// `uistate`, sent with `method`. The `{{variables}}` are resolved from the
+/// active environment.
+///
+/// The url, headers and raw body of a `scripted` request are sent instead of
+/// those of `uistate`, as they are: the script may have signed them.
+pub fn build_request(
+    uistate: &UiState,
+    method: Method,
+    scripted: Option<&ScriptedRequest>,
+) -> Result<RequestBuilder, Box<dyn Error + 'static>> {
+    let env = uistate.active_environment();
+    let var = |text: String| resolve(&text, env);
+
+    let raw_body = || match scripted {
+        Some(scripted) => String::from(scripted.body()),
+        None => var(uistate.body().text_data().text()),
+    };
+
     let mut def_headers: HeaderMap = HeaderMap::new();
     let accept_val = format!("{},{},{}", TEXT_HTML, APP_XHTML_XML, APP_XML);
 
@@ -33,54 +121,64 @@ pub fn call_api(uistate: &mut UiState) -> Result<(), Box<dyn Error + 'static>> {
     def_headers.insert("cache-control", HeaderValue::from_str("no-cache").unwrap());
     def_headers.insert("pragma", HeaderValue::from_str("no-cache").unwrap());
 
-    let client: Client = ClientBuilder::new()
-        .timeout(Duration::from_secs(20))
-        .user_agent("Dakia/0.1.0")
+    let settings = uistate.settings().effective(var)
+        .map_err(|e| format!("Settings: {}", e))?;
+
+    for (name, value) in settings.headers() {
+        def_headers.insert(
+            HeaderName::from_str(name)?,
+            HeaderValue::from_str(&var(value.clone()))?
+        );
+    }
+
+    // The body is decompressed by [read_response] so that both the
+    // compressed and decompressed sizes are known.
+    let builder = ClientBuilder::new()
+        .no_gzip()
+        .no_brotli()
+        .no_deflate()
         .default_headers(def_headers)
-        .build()?;
+        .cookie_provider(uistate.cookies().jars().store(&uistate.cookie_jar()));
 
-    let mut request = client.request(
-        uistate.method(),
-        uistate.url_deconst().to_string()
-    );
+    let client: Client = settings.configure(builder)?.build()?;
 
-    let mut headers: HeaderMap = HeaderMap::new();
+    let url = match scripted {
+        Some(scripted) => String::from(scripted.url()),
+        None => resolved_url(uistate),
+    };
 
-    if !uistate.request_headers().is_empty() {
+    let mut request = client.request(method.clone(), url);
 
-        for header in uistate.request_headers().iter() {
-            if !header.key().is_empty() && !header.value().is_empty() {
-                headers.insert(
-                    HeaderName::from_str(header.key().as_str()).unwrap(),
-                    HeaderValue::from_str(header.value().as_str()).unwrap()
-                );
-            }
+    let mut headers: HeaderMap = HeaderMap::new();
+
+    let rows: Vec<(String, String)> = match scripted {
+        Some(scripted) => scripted.headers().clone().into_iter().collect(),
+        None => uistate.request_headers().iter()
+            .map(|header| (var(header.key()), var(header.value())))
+            .collect(),
+    };
+
+    for (name, value) in rows.iter() {
+        if !name.is_empty() && !value.is_empty() {
+            headers.insert(
+                HeaderName::from_str(name)?,
+                HeaderValue::from_str(value)?
+            );
         }
     }
 
-    // Check if there is already a header that matches the body content type
-    if uistate.method() != Method::GET {
+    // The body is sent whether or not the "Headers" tab has a content type.
+    // The content type of the body is set unless the same one is typed.
+    if method != Method::GET {
         match uistate.body().body_content() {
             BodyContent::FormData => {
-                if !(
-                    headers.contains_key(CONTENT_TYPE)
-                    && headers.get(CONTENT_TYPE).unwrap().eq(MULTIPART_FORM_DATA)
-                ) {
-                    headers.insert(
-                        HeaderName::from_str(CONTENT_TYPE).unwrap(),
-                        HeaderValue::from_str(MULTIPART_FORM_DATA).unwrap()
-                    );
-
-                    if uistate.body().kv_data().len() > 0 {
-                        let mut params = HashMap::new();
+                // reqwest sets the content type with the part boundary, a
+                // content type from the "Headers" tab would lose it.
+                headers.remove(CONTENT_TYPE);
 
-                        for i in uistate.body().kv_data().iter() {
-                            params.insert(i.key(), i.value());
-                        }
-
-                        request = request.form(&params);
-                    }
-                }
+                request = request.multipart(
+                    multipart_form(uistate.body().kv_data(), &var)?
+                );
             }
 
             BodyContent::FormURLEncoded => {
@@ -92,16 +190,16 @@ pub fn call_api(uistate: &mut UiState) -> Result<(), Box<dyn Error + 'static>> {
                         HeaderName::from_str(CONTENT_TYPE).unwrap(),
                         HeaderValue::from_str(APP_FORM_URL_ENCODED).unwrap()
                     );
+                }
 
-                    if uistate.body().kv_data().len() > 0 {
-                        let mut params = HashMap::new();
-
-                        for i in uistate.body().kv_data().iter() {
-                            params.insert(i.key(), i.value());
-                        }
+                if !uistate.body().kv_data().is_empty() {
+                    let mut params = HashMap::new();
 
-                        request = request.form(&params);
+                    for i in uistate.body().kv_data().iter() {
+                        params.insert(var(i.key()), var(i.value()));
                     }
+
+                    request = request.form(&params);
                 }
             }
 
@@ -114,9 +212,9 @@ pub fn call_api(uistate: &mut UiState) -> Result<(), Box<dyn Error + 'static>> {
                         HeaderName::from_str(CONTENT_TYPE).unwrap(),
                         HeaderValue::from_str(APP_JSON).unwrap()
                     );
-
-                    request = request.body(uistate.body().text_data().text());
                 }
+
+                request = request.body(raw_body());
             }
 
             BodyContent::Xml => {
@@ -128,9 +226,9 @@ pub fn call_api(uistate: &mut UiState) -> Result<(), Box<dyn Error + 'static>> {
                         HeaderName::from_str(CONTENT_TYPE).unwrap(),
                         HeaderValue::from_str(APP_XML).unwrap()
                     );
-
-                    request = request.body(uistate.body().text_data().text());
                 }
+
+                request = request.body(raw_body());
             }
 
             BodyContent::Html => {
@@ -142,9 +240,9 @@ pub fn call_api(uistate: &mut UiState) -> Result<(), Box<dyn Error + 'static>> {
                         HeaderName::from_str(CONTENT_TYPE).unwrap(),
                         HeaderValue::from_str(TEXT_HTML).unwrap()
                     );
-
-                    request = request.body(uistate.body().text_data().text());
                 }
+
+                request = request.body(raw_body());
             }
 
             BodyContent::Text => {
@@ -156,61 +254,448 @@ pub fn call_api(uistate: &mut UiState) -> Result<(), Box<dyn Error + 'static>> {
                         HeaderName::from_str(CONTENT_TYPE).unwrap(),
                         HeaderValue::from_str(TEXT_PLAIN).unwrap()
                     );
+                }
 
-                    request = request.body(uistate.body().text_data().text());
+                request = request.body(raw_body());
+            }
+
+            BodyContent::GraphQL => {
+                if !(
+                    headers.contains_key(CONTENT_TYPE)
+                    && headers.get(CONTENT_TYPE).unwrap().eq(APP_JSON)
+                ) {
+                    headers.insert(
+                        HeaderName::from_str(CONTENT_TYPE).unwrap(),
+                        HeaderValue::from_str(APP_JSON).unwrap()
+                    );
                 }
+
+                let payload = match scripted {
+                    Some(scripted) => String::from(scripted.body()),
+                    None => body_payload(uistate.body(), var)?,
+                };
+
+                request = request.body(payload);
             }
 
             _ => {}
         }
     }
 
+    request = apply_auth(request, &mut headers, uistate.auth(), env)?;
     request = request.headers(headers);
 
-    let response = request.send()?;
+    Ok(request)
+}
 
-    uistate.increment_request_counter();
-    uistate.set_response_status_code(Some(response.status().as_u16()));
+/// The url of the request with the `{{variables}}` of the active environment
+/// resolved.
+pub fn resolved_url(uistate: &UiState) -> String {
+    // The url is parsed again after resolving so that variables can hold
+    // any part of it, e.g. `{{host}}/users?page={{page}}`.
+    let mut url = Url::default();
 
-    process_response(response, uistate);
+    url.update(resolve(&uistate.url(), uistate.active_environment()));
 
-    Ok(())
+    url.to_string()
 }
 
-fn process_response(resp: Response, uistate: &mut UiState) {
-    let headers = resp.headers();
-    let mut is_json = false;
-    let mut error = false;
+/// Builds the multipart form from the rows of the "Form Data" body.
+/// `var` resolves the `{{variables}}`.
+fn multipart_form(kv_data: &[KVData], var: &impl Fn(String) -> String)
+    -> Result<Form, Box<dyn Error + 'static>> {
+    let mut form = Form::new();
 
-    if headers.contains_key(header::CONTENT_TYPE) {
-        let hdr = String::from(
-            headers[header::CONTENT_TYPE].to_str().unwrap()
-        );
+    for kv in kv_data.iter().filter(|kv| !kv.key().is_empty()) {
+        let mut part = match kv.part_kind() {
+            PartKind::Text => Part::text(var(kv.value())),
 
-        is_json = hdr.starts_with(APP_JSON);
+            PartKind::File => {
+                let path = var(kv.value());
+
+                Part::file(&path)
+                    .map_err(|e| format!("couldn't read {}: {}", path, e))?
+            }
+        };
+
+        if !kv.filename().is_empty() {
+            part = part.file_name(var(kv.filename()));
+        }
+
+        if !kv.content_type().is_empty() {
+            part = part.mime_str(&var(kv.content_type()))?;
+        }
+
+        form = form.part(var(kv.key()), part);
     }
 
-    match resp.text() {
-        Ok(response_text) => {
-            let resp_state = uistate.response_mut();
+    Ok(form)
+}
+
+/// Adds the credentials from the "Authorization" tab to the request. The
+/// credential headers are set in `headers`, replacing a header of the same
+/// name typed in the "Headers" tab.
+fn apply_auth(
+    request: RequestBuilder,
+    headers: &mut HeaderMap,
+    auth: &Auth,
+    env: Option<&Environment>,
+) -> Result<RequestBuilder, Box<dyn Error + 'static>> {
+    let var = |text: String| resolve(&text, env);
+
+    let (name, value) = match auth.auth_scheme() {
+        AuthScheme::NoAuth => { return Ok(request); }
+
+        AuthScheme::Basic => {
+            if auth.username().is_empty() && auth.password().is_empty() {
+                return Ok(request);
+            }
+
+            let credentials = format!(
+                "{}:{}", var(auth.username()), var(auth.password()),
+            );
+
+            (AUTHORIZATION, format!("Basic {}", BASE64.encode(credentials)))
+        }
+
+        AuthScheme::Bearer => {
+            if auth.token().is_empty() {
+                return Ok(request);
+            }
 
-            if is_json {
-                match pretty_print(response_text.as_str()) {
-                    Ok (pretty_json) => { resp_state.from_str(pretty_json); },
-                    Err (just_json) => { resp_state.from_str(just_json); }
+            (AUTHORIZATION, format!("Bearer {}", var(auth.token())))
+        }
+
+        AuthScheme::ApiKey => {
+            if auth.api_key().is_empty() {
+                return Ok(request);
+            }
+
+            match auth.api_key_location() {
+                ApiKeyLocation::Header => (
+                    HeaderName::from_str(var(auth.api_key()).as_str())?,
+                    var(auth.api_key_value()),
+                ),
+
+                ApiKeyLocation::QueryParam => {
+                    return Ok(request.query(
+                        &[(var(auth.api_key()), var(auth.api_key_value()))]
+                    ));
                 }
-            } else {
-                resp_state.from_str(response_text);
             }
-        },
+        }
+    };
+
+    let mut value = HeaderValue::from_str(&value)?;
+    value.set_sensitive(true);
+
+    headers.insert(name, value);
+
+    Ok(request)
+}
+
+/// Reads the response text. Blocks until the whole body is received.
+/// `started` is when the request was sent, for timing the response.
+pub fn read_response(resp: Response, started: Instant)
+    -> Result<ResponseData, Box<dyn Error + Send + Sync>> {
+    download(resp, started, usize::MAX, &AtomicBool::new(false), &|_| {})
+}
+
+/// Shows the response read by [read_response] in the response area.
+pub fn apply_response(uistate: &mut UiState, data: ResponseData) {
+    uistate.increment_request_counter();
+    uistate.set_response_status_code(Some(data.info.status_code()));
+    uistate.response_mut().from_str(data.body);
+    uistate.response_mut().set_info(data.info);
+    uistate.response_mut().set_scroll_pos(0);
+    uistate.set_app_status(AppStatus::DONE);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs, process, thread,
+        io::{ Read, Write },
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// A POST request with the typed `headers` and a body of `content`.
+    fn posting(content: BodyContent, headers: &[(&str, &str)]) -> UiState {
+        let mut uistate = UiState::default();
+
+        uistate.load_url(String::from("https://example.com/items"));
+        uistate.set_method(Method::POST);
+
+        *uistate.request_headers_mut() = headers.iter()
+            .map(|(name, value)| KVData::new(String::from(*name), String::from(*value)))
+            .collect();
+
+        uistate.body_mut().set_body_content(content);
+
+        uistate
+    }
+
+    fn content_types(request: &reqwest::blocking::Request) -> Vec<&str> {
+        request.headers().get_all(CONTENT_TYPE).iter()
+            .map(|value| value.to_str().unwrap())
+            .collect()
+    }
+
+    fn body(request: &reqwest::blocking::Request) -> &[u8] {
+        request.body().and_then(|body| body.as_bytes()).unwrap_or_default()
+    }
+
+    #[test]
+    fn raw_body_is_sent_with_a_matching_typed_content_type() {
+        let mut uistate = posting(BodyContent::Json, &[("Content-Type", APP_JSON)]);
+
+        uistate.body_mut().text_data_mut().load_text("{\"id\": 1}");
+
+        let request = build_request(&uistate, Method::POST, None).unwrap()
+            .build().unwrap();
+
+        assert_eq!(body(&request), b"{\"id\": 1}");
+        assert_eq!(content_types(&request), [APP_JSON]);
+    }
+
+    #[test]
+    fn form_is_sent_with_a_matching_typed_content_type() {
+        let mut uistate = posting(
+            BodyContent::FormURLEncoded,
+            &[("Content-Type", APP_FORM_URL_ENCODED)],
+        );
+
+        *uistate.body_mut().kv_data_mut() = vec![
+            KVData::new(String::from("name"), String::from("ada")),
+        ];
+
+        let request = build_request(&uistate, Method::POST, None).unwrap()
+            .build().unwrap();
+
+        assert_eq!(body(&request), b"name=ada");
+        assert_eq!(content_types(&request), [APP_FORM_URL_ENCODED]);
+    }
+
+    #[test]
+    fn raw_body_sets_its_content_type_when_none_is_typed() {
+        let mut uistate = posting(BodyContent::Json, &[]);
+
+        uistate.body_mut().text_data_mut().load_text("[]");
+
+        let request = build_request(&uistate, Method::POST, None).unwrap()
+            .build().unwrap();
+
+        assert_eq!(body(&request), b"[]");
+        assert_eq!(content_types(&request), [APP_JSON]);
+    }
+
+    /// A form row sent as a part of `kind`.
+    fn part(key: &str, value: &str, kind: PartKind, filename: &str, content_type: &str)
+        -> KVData {
+        let mut kv = KVData::new(String::from(key), String::from(value));
+
+        kv.set_part_kind(kind);
+        kv.set_filename(String::from(filename));
+        kv.set_content_type(String::from(content_type));
+
+        kv
+    }
+
+    /// Sends the form of `kv_data` to a local server and returns the body it
+    /// received.
+    fn sent_form(kv_data: &[KVData]) -> String {
+        let var = |text: String| text.replace("{{name}}", "ada");
+        let form = multipart_form(kv_data, &var).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![];
+            let mut buf = [0u8; 4096];
+
+            // The form ends with the closing boundary
+            while !request.ends_with(b"--\r\n") {
+                let read = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..read]);
+            }
+
+            stream.write_all(
+                b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\
+                Connection: close\r\n\r\n"
+            ).unwrap();
+
+            String::from_utf8_lossy(&request).to_string()
+        });
+
+        Client::new().post(url).multipart(form).send().unwrap();
+
+        let request = server.join().unwrap();
+        let body = request.split_once("\r\n\r\n").unwrap().1;
 
-        Err(_e) => { error = true; },
+        String::from(body)
     }
 
-    if error {
-        uistate.set_app_status(AppStatus::ERROR);
-    } else {
-        uistate.set_app_status(AppStatus::DONE);
+    #[test]
+    fn multipart_form_sends_text_parts_with_their_variables() {
+        let body = sent_form(&[
+            part("user", "{{name}}", PartKind::Text, "", ""),
+            part("", "skipped", PartKind::Text, "", ""),
+            part("note", "hi", PartKind::Text, "{{name}}.txt", "text/markdown"),
+        ]);
+
+        assert!(body.contains("name=\"user\"\r\n\r\nada\r\n"), "{}", body);
+        assert!(body.contains(
+            "name=\"note\"; filename=\"ada.txt\"\r\n\
+            Content-Type: text/markdown\r\n\r\nhi\r\n"
+        ), "{}", body);
+        assert!(!body.contains("skipped"), "{}", body);
     }
-}
 
+    #[test]
+    fn multipart_form_sends_the_content_of_file_parts() {
+        let path = env::temp_dir()
+            .join(format!("dakia-test-{}-upload.csv", process::id()));
+        let value = path.to_str().unwrap();
+
+        fs::write(&path, "id,name\n1,ada\n").unwrap();
+
+        let body = sent_form(&[
+            part("data", value, PartKind::File, "", ""),
+            part("renamed", value, PartKind::File, "people.csv", "text/csv"),
+        ]);
+
+        fs::remove_file(&path).unwrap();
+
+        let file_name = path.file_name().unwrap().to_str().unwrap();
+
+        assert!(
+            body.contains(&format!("name=\"data\"; filename=\"{}\"", file_name)),
+            "{}", body,
+        );
+        assert!(body.contains(
+            "name=\"renamed\"; filename=\"people.csv\"\r\nContent-Type: text/csv\r\n\r\n\
+            id,name\n1,ada\n\r\n"
+        ), "{}", body);
+    }
+
+    #[test]
+    fn multipart_form_reports_missing_files_and_bad_content_types() {
+        let var = |text: String| text;
+
+        let error = multipart_form(
+            &[part("data", "/nonexistent/dakia.csv", PartKind::File, "", "")],
+            &var,
+        ).unwrap_err();
+
+        assert!(
+            error.to_string().starts_with("couldn't read /nonexistent/dakia.csv"),
+            "{}", error,
+        );
+
+        assert!(multipart_form(
+            &[part("note", "hi", PartKind::Text, "", "not a mime")],
+            &var,
+        ).is_err());
+    }
+
+    /// Applies `auth` to a GET request with an `Authorization` header typed
+    /// in the "Headers" tab. Returns the request and the headers it gets.
+    fn authorized(auth: &Auth)
+        -> Result<(reqwest::blocking::Request, HeaderMap), String> {
+        let mut env = Environment::default();
+
+        env.set(String::from("user"), String::from("ada"));
+        env.set(String::from("secret"), String::from("s3cret"));
+
+        let mut headers = HeaderMap::new();
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("typed"));
+
+        let request = Client::new().get("https://example.com/items?page=2");
+        let request = apply_auth(request, &mut headers, auth, Some(&env))
+            .map_err(|e| e.to_string())?;
+
+        Ok((request.build().unwrap(), headers))
+    }
+
+    fn auth(scheme: AuthScheme) -> Auth {
+        let mut auth = Auth::default();
+
+        auth.set_auth_scheme(scheme);
+        auth.set_username(String::from("{{user}}"));
+        auth.set_password(String::from("{{secret}}"));
+        auth.set_token(String::from("{{secret}}"));
+        auth.set_api_key(String::from("X-Api-Key"));
+        auth.set_api_key_value(String::from("{{secret}}"));
+
+        auth
+    }
+
+    #[test]
+    fn basic_and_bearer_auth_replace_the_typed_authorization() {
+        let (_, headers) = authorized(&auth(AuthScheme::Basic)).unwrap();
+        let expected = format!("Basic {}", BASE64.encode("ada:s3cret"));
+
+        assert_eq!(headers[AUTHORIZATION], expected.as_str());
+        assert!(headers[AUTHORIZATION].is_sensitive());
+        assert_eq!(headers.get_all(AUTHORIZATION).iter().count(), 1);
+
+        let (_, headers) = authorized(&auth(AuthScheme::Bearer)).unwrap();
+
+        assert_eq!(headers[AUTHORIZATION], "Bearer s3cret");
+    }
+
+    #[test]
+    fn empty_credentials_leave_the_request_as_it_is() {
+        let mut basic = auth(AuthScheme::Basic);
+
+        basic.set_username(String::new());
+        basic.set_password(String::new());
+
+        let mut bearer = auth(AuthScheme::Bearer);
+
+        bearer.set_token(String::new());
+
+        let mut api_key = auth(AuthScheme::ApiKey);
+
+        api_key.set_api_key(String::new());
+
+        for auth in [basic, bearer, api_key, auth(AuthScheme::NoAuth)] {
+            let (request, headers) = authorized(&auth).unwrap();
+
+            assert_eq!(headers[AUTHORIZATION], "typed");
+            assert_eq!(request.url().as_str(), "https://example.com/items?page=2");
+        }
+    }
+
+    #[test]
+    fn api_key_goes_in_a_header_or_the_query() {
+        let mut api_key = auth(AuthScheme::ApiKey);
+
+        api_key.set_api_key_location(ApiKeyLocation::Header);
+
+        let (_, headers) = authorized(&api_key).unwrap();
+
+        assert_eq!(headers["x-api-key"], "s3cret");
+        assert_eq!(headers[AUTHORIZATION], "typed");
+
+        api_key.set_api_key_location(ApiKeyLocation::QueryParam);
+
+        let (request, headers) = authorized(&api_key).unwrap();
+
+        assert_eq!(
+            request.url().as_str(),
+            "https://example.com/items?page=2&X-Api-Key=s3cret",
+        );
+        assert!(!headers.contains_key("x-api-key"));
+
+        api_key.set_api_key(String::from("bad name"));
+        api_key.set_api_key_location(ApiKeyLocation::Header);
+
+        assert!(authorized(&api_key).is_err());
+    }
+}
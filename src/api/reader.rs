@@ -0,0 +1,111 @@
+//! Reads response bodies from the network on a thread of their own. The
+//! worker reading the body waits for it in short steps, so that it stops as
+//! soon as the request is cancelled even while the server sends nothing.
+
+use std::{
+    io::{ self, Read }, thread,
+    sync::{
+        Arc, atomic::{ AtomicBool, Ordering },
+        mpsc::{ self, Receiver, RecvTimeoutError },
+    },
+    time::Duration,
+};
+
+use reqwest::blocking::Response;
+
+/// How often the cancellation is checked while waiting for the body.
+pub const CANCEL_POLL: Duration = Duration::from_millis(100);
+
+/// Number of chunks read ahead of the worker.
+const READ_AHEAD: usize = 16;
+
+/// The body of a response, read on another thread.
+pub struct BodyReader<'a> {
+    receiver: Receiver<io::Result<Vec<u8>>>,
+
+    /// The chunk being read, up to `pos`.
+    chunk: Vec<u8>,
+    pos: usize,
+
+    cancelled: &'a AtomicBool,
+
+    /// Set when the reader is dropped. The thread then stops reading and
+    /// drops the response, which closes the connection.
+    closed: Arc<AtomicBool>,
+}
+
+impl<'a> BodyReader<'a> {
+    /// Starts reading the body of `response`. The timeout of the request
+    /// ends the body with an error, unless `quiet`: an event stream waits
+    /// for its next event as long as it takes.
+    pub fn new(response: Response, quiet: bool, cancelled: &'a AtomicBool) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(READ_AHEAD);
+        let closed = Arc::new(AtomicBool::new(false));
+        let stop = closed.clone();
+
+        thread::spawn(move || {
+            let mut response = response;
+            let mut buf = [0u8; 8192];
+
+            while !stop.load(Ordering::SeqCst) {
+                let chunk = match response.read(&mut buf) {
+                    Ok(0) => { return; }
+                    Ok(read) => Ok(buf[..read].to_vec()),
+                    Err(e) if quiet && is_timeout(&e) => { continue; }
+                    Err(e) => Err(e),
+                };
+
+                let failed = chunk.is_err();
+
+                if sender.send(chunk).is_err() || failed {
+                    return;
+                }
+            }
+        });
+
+        Self { receiver, chunk: vec![], pos: 0, cancelled, closed }
+    }
+}
+
+impl Read for BodyReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            if self.cancelled.load(Ordering::SeqCst) {
+                return Err(io::Error::other("cancelled"));
+            }
+
+            match self.receiver.recv_timeout(CANCEL_POLL) {
+                Ok(chunk) => {
+                    self.chunk = chunk?;
+                    self.pos = 0;
+                }
+
+                Err(RecvTimeoutError::Timeout) => {}
+
+                // The thread is done, the body ended
+                Err(RecvTimeoutError::Disconnected) => { return Ok(0); }
+            }
+        }
+
+        let read = buf.len().min(self.chunk.len() - self.pos);
+
+        buf[..read].copy_from_slice(&self.chunk[self.pos..self.pos + read]);
+        self.pos += read;
+
+        Ok(read)
+    }
+}
+
+impl Drop for BodyReader<'_> {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Whether `e` is the timeout of the request, which reqwest applies to every
+/// read of the body.
+fn is_timeout(e: &io::Error) -> bool {
+    e.get_ref()
+        .and_then(|inner| inner.downcast_ref::<reqwest::Error>())
+        .is_some_and(|inner| inner.is_timeout())
+}
@@ -0,0 +1,530 @@
+//! Server-Sent Events. A `text/event-stream` response is read as it
+//! arrives instead of being buffered, and every event is sent to the ui as
+//! soon as it is complete. When the stream drops, the request is sent again
+//! after the retry delay with the `Last-Event-ID` of the last event.
+
+use std::{
+    io::Read, thread,
+    sync::atomic::{ AtomicBool, Ordering },
+    time::{ Duration, Instant },
+};
+
+use chrono::{ DateTime, Local };
+use reqwest::{
+    StatusCode,
+    blocking::{ RequestBuilder, Response },
+    header::{ CONTENT_ENCODING, CONTENT_TYPE },
+};
+
+use crate::{
+    api::{ download::decoder, reader::{ BodyReader, CANCEL_POLL } },
+    ui::state::response::ResponseInfo,
+};
+
+pub const EVENT_STREAM: &str = "text/event-stream";
+
+/// Header sent with the reconnects so the server can resume the stream.
+const LAST_EVENT_ID: &str = "Last-Event-ID";
+
+/// Delay before reconnecting until the server sets one with `retry`.
+const DEFAULT_RETRY: Duration = Duration::from_millis(3000);
+
+/// An event of the stream.
+#[derive(Clone)]
+pub struct SseEvent {
+    /// When the event was received.
+    at: DateTime<Local>,
+
+    /// The type of the event, `message` when the server doesn't set one.
+    event: String,
+
+    /// The last event id, which carries over to the events without one.
+    id: String,
+
+    data: String,
+
+    /// The reconnection time in milliseconds, if the event sets it.
+    retry: Option<u64>,
+}
+
+/// A row of the event list.
+#[derive(Clone)]
+pub enum StreamItem {
+    Event(SseEvent),
+
+    /// The stream dropped for `reason` and is opened again after `retry`.
+    Reconnect {
+        at: DateTime<Local>,
+        reason: String,
+        retry: Duration,
+        last_event_id: String,
+    },
+}
+
+/// Message sent from the worker thread while the stream is read.
+pub enum StreamMessage {
+    /// The stream was opened, or opened again after a reconnect.
+    Opened(ResponseInfo),
+
+    Item(StreamItem),
+
+    /// The stream is over, with the error that ended it if any.
+    Ended(Option<String>),
+}
+
+impl SseEvent {
+    pub fn at(&self) -> &DateTime<Local> { &self.at }
+    pub fn event(&self) -> &str { &self.event }
+    pub fn id(&self) -> &str { &self.id }
+    pub fn data(&self) -> &str { &self.data }
+    pub fn retry(&self) -> Option<u64> { self.retry }
+}
+
+/// Parses the stream into events as its bytes arrive, following the
+/// `text/event-stream` format.
+#[derive(Default)]
+pub struct EventParser {
+    /// The bytes of the line that hasn't ended yet.
+    line: Vec<u8>,
+
+    /// Whether the last byte was a `\r`, whose `\n` doesn't end another line.
+    after_cr: bool,
+
+    /// Whether the first line was read, which may start with a BOM.
+    started: bool,
+
+    event: String,
+    data: String,
+    last_event_id: String,
+    retry: Option<u64>,
+
+    /// Whether the event being read sets the retry.
+    sets_retry: bool,
+}
+
+impl EventParser {
+    /// Reads `bytes` of the stream and returns the events they complete.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<SseEvent> {
+        let mut events = vec![];
+
+        for &byte in bytes {
+            let after_cr = self.after_cr;
+            self.after_cr = byte == b'\r';
+
+            match byte {
+                b'\n' if after_cr => {}
+
+                b'\r' | b'\n' => {
+                    let line = String::from_utf8_lossy(&self.line).to_string();
+                    self.line.clear();
+
+                    if let Some(event) = self.line_ended(&line) {
+                        events.push(event);
+                    }
+                }
+
+                _ => { self.line.push(byte); }
+            }
+        }
+
+        events
+    }
+
+    /// The id of the last event, sent with the reconnects.
+    pub fn last_event_id(&self) -> &str { &self.last_event_id }
+
+    /// The reconnection time set by the server.
+    pub fn retry(&self) -> Option<u64> { self.retry }
+
+    /// Drops the event that was cut off by the end of the stream. The last
+    /// event id and the retry are kept for the reconnect.
+    pub fn end_stream(&mut self) {
+        self.line.clear();
+        self.after_cr = false;
+        self.started = false;
+        self.event.clear();
+        self.data.clear();
+        self.sets_retry = false;
+    }
+
+    fn line_ended(&mut self, line: &str) -> Option<SseEvent> {
+        let line = match self.started {
+            true => line,
+            false => {
+                self.started = true;
+                line.trim_start_matches('\u{feff}')
+            }
+        };
+
+        // A blank line dispatches the event
+        if line.is_empty() {
+            return self.dispatch();
+        }
+
+        // Comments, often sent to keep the connection open
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (
+                field,
+                value.strip_prefix(' ').unwrap_or(value),
+            ),
+
+            None => (line, ""),
+        };
+
+        match field {
+            "event" => { self.event = String::from(value); }
+
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+
+            "id" if !value.contains('\0') => {
+                self.last_event_id = String::from(value);
+            }
+
+            "retry" if !value.is_empty()
+                && value.chars().all(|c| c.is_ascii_digit()) =>
+            {
+                self.retry = value.parse().ok();
+                self.sets_retry = self.retry.is_some();
+            }
+
+            _ => {}
+        }
+
+        None
+    }
+
+    /// The event of the fields read since the last blank line. Blocks
+    /// without data aren't events, except the ones setting the retry, which
+    /// are shown as well.
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = std::mem::take(&mut self.event);
+        let mut data = std::mem::take(&mut self.data);
+        let sets_retry = std::mem::take(&mut self.sets_retry);
+
+        if data.is_empty() && !sets_retry {
+            return None;
+        }
+
+        if data.ends_with('\n') {
+            data.pop();
+        }
+
+        Some(SseEvent {
+            at: Local::now(),
+            event: match event.is_empty() {
+                true => String::from("message"),
+                false => event,
+            },
+            id: self.last_event_id.clone(),
+            data,
+            retry: self.retry.filter(|_| sets_retry),
+        })
+    }
+}
+
+/// Whether `response` is an event stream.
+pub fn is_event_stream(response: &Response) -> bool {
+    response.headers().get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_lowercase().starts_with(EVENT_STREAM))
+        .unwrap_or(false)
+}
+
+/// Reads the event stream of `response` until it ends or `cancelled` is set.
+/// `request` is sent again when the stream drops; without it the stream
+/// isn't reopened. `emit` sends the messages back to the ui.
+pub fn stream(
+    response: Response,
+    request: Option<RequestBuilder>,
+    started: Instant,
+    cancelled: &AtomicBool,
+    emit: &dyn Fn(StreamMessage),
+) {
+    let mut parser = EventParser::default();
+    let mut response = response;
+    let mut started = started;
+
+    loop {
+        let mut info = ResponseInfo::from_response(&response);
+        info.set_elapsed(started.elapsed());
+
+        emit(StreamMessage::Opened(info));
+
+        let mut reason = read_events(response, &mut parser, cancelled, emit);
+        parser.end_stream();
+
+        if cancelled.load(Ordering::SeqCst) {
+            return;
+        }
+
+        // Reconnects until the stream is opened again
+        response = loop {
+            let request = match request.as_ref().and_then(|r| r.try_clone()) {
+                Some(request) => request,
+                None => {
+                    emit(StreamMessage::Ended(None));
+                    return;
+                }
+            };
+
+            let retry = parser.retry()
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_RETRY);
+
+            emit(StreamMessage::Item(StreamItem::Reconnect {
+                at: Local::now(),
+                reason: reason.clone(),
+                retry,
+                last_event_id: String::from(parser.last_event_id()),
+            }));
+
+            if !wait(retry, cancelled) {
+                return;
+            }
+
+            let request = match parser.last_event_id() {
+                "" => request,
+                id => request.header(LAST_EVENT_ID, id),
+            };
+
+            started = Instant::now();
+
+            match request.send() {
+                Ok(response) if response.status() == StatusCode::OK
+                    && is_event_stream(&response) => { break response; }
+
+                // The server tells that the stream is over
+                Ok(response) if response.status() == StatusCode::NO_CONTENT => {
+                    emit(StreamMessage::Ended(None));
+                    return;
+                }
+
+                // Another status or content type ends the stream for good
+                Ok(response) => {
+                    emit(StreamMessage::Ended(Some(format!(
+                        "The stream wasn't reopened: {}",
+                        response.status(),
+                    ))));
+
+                    return;
+                }
+
+                Err(_) if cancelled.load(Ordering::SeqCst) => { return; }
+
+                // Network errors are retried
+                Err(e) => { reason = e.to_string(); }
+            }
+        };
+    }
+}
+
+/// Sends the events of `response` as they arrive. Returns why the stream
+/// ended. The stream can stay quiet for longer than the timeout of the
+/// request: only the server closing it or an error makes it reconnect.
+fn read_events(
+    response: Response,
+    parser: &mut EventParser,
+    cancelled: &AtomicBool,
+    emit: &dyn Fn(StreamMessage),
+) -> String {
+    let content_encoding = response.headers().get(CONTENT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+        .unwrap_or_default();
+
+    let body = BodyReader::new(response, true, cancelled);
+    let mut reader = decoder(Box::new(body), &content_encoding);
+    let mut buf = [0u8; 4096];
+
+    loop {
+        let read = match reader.read(&mut buf) {
+            Ok(0) => { return String::from("the server closed the stream"); }
+            Ok(read) => read,
+            Err(_) if cancelled.load(Ordering::SeqCst) => {
+                return String::from("cancelled");
+            }
+            Err(e) => { return e.to_string(); }
+        };
+
+        if cancelled.load(Ordering::SeqCst) {
+            return String::from("cancelled");
+        }
+
+        for event in parser.feed(&buf[..read]) {
+            emit(StreamMessage::Item(StreamItem::Event(event)));
+        }
+    }
+}
+
+/// Waits `delay`. Returns `false` if the stream was cancelled meanwhile.
+fn wait(delay: Duration, cancelled: &AtomicBool) -> bool {
+    let until = Instant::now() + delay;
+
+    while Instant::now() < until {
+        if cancelled.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        thread::sleep(CANCEL_POLL.min(until.saturating_duration_since(Instant::now())));
+    }
+
+    !cancelled.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(stream: &str) -> Vec<SseEvent> {
+        EventParser::default().feed(stream.as_bytes())
+    }
+
+    #[test]
+    fn feed_dispatches_on_a_blank_line() {
+        let mut parser = EventParser::default();
+
+        assert!(parser.feed(b"event: update\ndata: one\n").is_empty());
+
+        let events = parser.feed(b"\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event(), "update");
+        assert_eq!(events[0].data(), "one");
+        assert_eq!(events[0].retry(), None);
+    }
+
+    #[test]
+    fn feed_joins_lines_split_across_chunks() {
+        let mut parser = EventParser::default();
+
+        assert!(parser.feed(b"da").is_empty());
+        assert!(parser.feed(b"ta: hel").is_empty());
+
+        let events = parser.feed(b"lo\n\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data(), "hello");
+    }
+
+    #[test]
+    fn multi_line_data_is_joined_with_newlines() {
+        let events = events("data: first\ndata:second\ndata\n\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data(), "first\nsecond\n");
+        assert_eq!(events[0].event(), "message");
+    }
+
+    #[test]
+    fn only_one_leading_space_is_stripped_from_values() {
+        let events = events("data:  two spaces\n\n");
+
+        assert_eq!(events[0].data(), " two spaces");
+    }
+
+    #[test]
+    fn crlf_and_cr_end_lines() {
+        for stream in [
+            "data: a\r\n\r\ndata: b\r\n\r\n",
+            "data: a\r\rdata: b\r\r",
+            "data: a\n\rdata: b\r\n\n",
+        ] {
+            let events = events(stream);
+            let data: Vec<&str> = events.iter().map(SseEvent::data).collect();
+
+            assert_eq!(data, ["a", "b"], "{:?}", stream);
+        }
+    }
+
+    #[test]
+    fn crlf_split_across_chunks_ends_one_line() {
+        let mut parser = EventParser::default();
+
+        assert!(parser.feed(b"data: a\r").is_empty());
+        assert!(parser.feed(b"\n").is_empty());
+
+        let events = parser.feed(b"\r\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data(), "a");
+    }
+
+    #[test]
+    fn comments_and_blocks_without_data_are_not_events() {
+        assert!(events(": keep-alive\n\nevent: empty\n\nunknown: x\n\n").is_empty());
+    }
+
+    #[test]
+    fn the_bom_of_the_first_line_is_skipped() {
+        let events = events("\u{feff}data: x\n\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data(), "x");
+    }
+
+    #[test]
+    fn the_last_event_id_is_kept_for_the_next_events() {
+        let mut parser = EventParser::default();
+        let events = parser.feed(b"id: 7\ndata: a\n\ndata: b\n\nid\ndata: c\n\n");
+
+        let ids: Vec<&str> = events.iter().map(SseEvent::id).collect();
+
+        assert_eq!(ids, ["7", "7", ""]);
+        assert_eq!(parser.last_event_id(), "");
+
+        parser.feed(b"id: 8\n\n");
+        assert_eq!(parser.last_event_id(), "8");
+    }
+
+    #[test]
+    fn an_id_with_a_null_is_ignored() {
+        let mut parser = EventParser::default();
+
+        parser.feed(b"id: 1\n\nid: 2\0\n\n");
+
+        assert_eq!(parser.last_event_id(), "1");
+    }
+
+    #[test]
+    fn retry_is_set_by_digits_only() {
+        let mut parser = EventParser::default();
+        let events = parser.feed(b"retry: 2500\n\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].retry(), Some(2500));
+        assert_eq!(events[0].data(), "");
+        assert_eq!(parser.retry(), Some(2500));
+
+        for ignored in ["retry: 1s\n\n", "retry: -1\n\n", "retry:\n\n"] {
+            assert!(parser.feed(ignored.as_bytes()).is_empty(), "{:?}", ignored);
+            assert_eq!(parser.retry(), Some(2500), "{:?}", ignored);
+        }
+
+        let events = parser.feed(b"data: later\n\n");
+
+        assert_eq!(events[0].retry(), None);
+    }
+
+    #[test]
+    fn end_stream_drops_the_cut_event_and_keeps_the_id_and_retry() {
+        let mut parser = EventParser::default();
+
+        parser.feed(b"retry: 10\nid: 3\n\nevent: cut\ndata: partial\nda");
+        parser.end_stream();
+
+        let events = parser.feed(b"data: new\n\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event(), "message");
+        assert_eq!(events[0].data(), "new");
+        assert_eq!(events[0].id(), "3");
+        assert_eq!(parser.retry(), Some(10));
+    }
+}
@@ -0,0 +1,389 @@
+//! Sends requests from a worker thread so that the ui stays responsive while
+//! a request is in flight. Results are sent back to the `run_app` loop over a
+//! channel and applied with [receive].
+
+use std::{
+    error::Error, thread, path::Path,
+    sync::{ Arc, atomic::{ AtomicBool, Ordering }, mpsc::Sender },
+    time::{ Duration, Instant },
+};
+
+use chrono::{ DateTime, Local };
+use log::info;
+use reqwest::blocking::RequestBuilder;
+
+use crate::{
+    api::{
+        ResponseData, apply_response,
+        sse::{ self, StreamMessage, is_event_stream },
+        download::{ DownloadMessage, download },
+    },
+    script::post_response,
+    ui::state::scripts::ScriptKind,
+    assertion::{ check_response, unchecked_assertions },
+    ui::state::{ UiState, app_status::AppStatus, body::BodyContent },
+    collection::SavedRequest,
+    graphql::errors,
+    history::{ HistoryEntry, DEFAULT_HISTORY_DIR, save_entry },
+};
+
+/// Message sent from the worker thread back to the ui loop.
+pub struct ApiResult {
+    /// Id of the [InFlightRequest] this result belongs to.
+    request_id: u64,
+    kind: ApiResultKind,
+}
+
+enum ApiResultKind {
+    /// The whole response was read.
+    Response(Result<ResponseData, String>),
+
+    /// The response is an event stream, which stays in flight until it is
+    /// cancelled.
+    Stream(StreamMessage),
+
+    /// Part of the body, sent while it is received.
+    Download(DownloadMessage),
+}
+
+/// A request that has been dispatched and hasn't completed yet.
+#[derive(Clone)]
+pub struct InFlightRequest {
+    id: u64,
+    started: Instant,
+
+    /// When the request was sent, for the history.
+    sent_at: DateTime<Local>,
+
+    /// The url the request was sent to, with the variables resolved.
+    url: String,
+
+    /// Snapshot of the request that was sent, for the history.
+    request: SavedRequest,
+
+    /// Set when the user aborts the request. The worker thread checks it
+    /// before reading the body and before sending the result back.
+    cancelled: Arc<AtomicBool>,
+}
+
+impl InFlightRequest {
+    fn new(id: u64, url: String, request: SavedRequest) -> Self {
+        Self {
+            id,
+            started: Instant::now(),
+            sent_at: Local::now(),
+            url,
+            request,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn id(&self) -> u64 { self.id }
+
+    /// Time since the request was dispatched.
+    pub fn elapsed(&self) -> Duration { self.started.elapsed() }
+
+    fn cancel(&self) { self.cancelled.store(true, Ordering::SeqCst); }
+}
+
+/// Sends `request` from a new worker thread. A request that is already in
+/// flight is cancelled first. `url` and `snapshot` are the request as it is
+/// sent, for the history.
+pub fn dispatch(
+    uistate: &mut UiState,
+    request: RequestBuilder,
+    url: String,
+    snapshot: SavedRequest,
+) -> Result<(), Box<dyn Error + 'static>> {
+    let sender: Sender<ApiResult> = match uistate.api_sender() {
+        Some(sender) => sender.clone(),
+        None => { return Err("request worker is not available".into()); }
+    };
+
+    cancel(uistate);
+
+    let in_flight = InFlightRequest::new(uistate.next_request_id(), url, snapshot);
+    let request_id = in_flight.id();
+    let cancelled = in_flight.cancelled.clone();
+    let cap = uistate.body_memory_cap();
+
+    uistate.set_in_flight(Some(in_flight));
+    uistate.set_app_status(AppStatus::PROCESSING);
+
+    // Event streams are sent again when they drop
+    let retry_request = request.try_clone();
+
+    thread::spawn(move || {
+        let started = Instant::now();
+
+        let outcome = match request.send() {
+            Ok(response) => {
+                if cancelled.load(Ordering::SeqCst) {
+                    return;
+                }
+
+                if is_event_stream(&response) {
+                    sse::stream(response, retry_request, started, &cancelled, &|message| {
+                        let _ = sender.send(ApiResult {
+                            request_id,
+                            kind: ApiResultKind::Stream(message),
+                        });
+                    });
+
+                    return;
+                }
+
+                download(response, started, cap, &cancelled, &|message| {
+                    let _ = sender.send(ApiResult {
+                        request_id,
+                        kind: ApiResultKind::Download(message),
+                    });
+                }).map_err(|e| e.to_string())
+            }
+
+            Err(e) => Err(e.to_string()),
+        };
+
+        if cancelled.load(Ordering::SeqCst) {
+            info!("Dropping result of cancelled request #{}", request_id);
+            return;
+        }
+
+        // The receiver is gone only when the app is exiting.
+        let _ = sender.send(ApiResult {
+            request_id,
+            kind: ApiResultKind::Response(outcome),
+        });
+    });
+
+    Ok(())
+}
+
+/// Applies a result sent by the worker to the `uistate`. Results of requests
+/// that were cancelled or superseded are ignored.
+pub fn receive(uistate: &mut UiState, result: ApiResult) {
+    let in_flight = match uistate.in_flight() {
+        Some(in_flight) if in_flight.id() == result.request_id => {
+            in_flight.clone()
+        }
+        _ => { return; }
+    };
+
+    let outcome = match result.kind {
+        ApiResultKind::Response(outcome) => outcome,
+        ApiResultKind::Stream(message) => {
+            receive_stream(uistate, in_flight, message);
+            return;
+        }
+
+        ApiResultKind::Download(message) => {
+            receive_download(uistate, in_flight, message);
+            return;
+        }
+    };
+
+    uistate.set_in_flight(None);
+    uistate.response_mut().end_download();
+
+    let is_graphql = in_flight.request.body_content() == BodyContent::GraphQL;
+
+    let entry = HistoryEntry::new(
+        in_flight.sent_at,
+        in_flight.url,
+        in_flight.request,
+        &outcome,
+    );
+
+    record(uistate, entry);
+
+    match outcome {
+        Ok(data) => {
+            info!("No Errors for #{}", result.request_id);
+
+            // A body too big to keep in memory is shown from its end. The
+            // checks reading the body would only see that end, so they
+            // aren't run.
+            let tail = data.info().body_file().is_some();
+            let unchecked = unchecked_assertions(uistate, &data);
+
+            let mut tests = check_response(uistate, &data);
+            let scripted = match tail {
+                true => Ok(vec![]),
+                false => post_response(uistate, &data),
+            };
+
+            let skipped_script = tail
+                && uistate.scripts().has_script(ScriptKind::PostResponse);
+
+            // GraphQL errors come with a 200 status most of the time
+            let graphql_errors = match is_graphql {
+                true => errors(data.body()),
+                false => vec![],
+            };
+
+            apply_response(uistate, data);
+            uistate.response_mut().set_follow(tail);
+            uistate.response_mut().set_graphql_errors(graphql_errors);
+
+            match scripted {
+                Ok(mut script_tests) => { tests.append(&mut script_tests); }
+
+                Err(msg) => {
+                    info!("{}", msg);
+
+                    uistate.set_app_error(format!("Post-response script: {}", msg));
+                    uistate.set_app_status(AppStatus::ERROR);
+                }
+            }
+
+            uistate.response_mut().set_tests(tests);
+
+            if unchecked > 0 || skipped_script {
+                let mut skipped = vec![];
+
+                if unchecked > 0 {
+                    skipped.push(format!("{} body assertion(s)", unchecked));
+                }
+
+                if skipped_script {
+                    skipped.push(String::from("the post-response script"));
+                }
+
+                uistate.set_app_message(format!(
+                    "The body is too big to keep in memory, not run on it: {}",
+                    skipped.join(" and "),
+                ));
+                uistate.set_app_status(AppStatus::INFO);
+            }
+        }
+
+        Err(msg) => {
+            info!("{}", msg);
+
+            uistate.set_app_error(msg);
+            uistate.set_app_status(AppStatus::ERROR);
+        }
+    }
+}
+
+/// Applies a message of the event stream of the request `in_flight`.
+fn receive_stream(
+    uistate: &mut UiState,
+    in_flight: InFlightRequest,
+    message: StreamMessage,
+) {
+    match message {
+        StreamMessage::Opened(info) => {
+            let reopened = uistate.response().stream()
+                .map(|stream| stream.request_id() == in_flight.id())
+                .unwrap_or(false);
+
+            if reopened {
+                uistate.response_mut().set_info(info);
+                return;
+            }
+
+            info!("Streaming the events of #{}", in_flight.id());
+
+            // The history keeps the status and headers of the stream
+            let entry = HistoryEntry::new(
+                in_flight.sent_at,
+                in_flight.url.clone(),
+                in_flight.request.clone(),
+                &Ok(ResponseData::new(String::new(), info.clone())),
+            );
+
+            record(uistate, entry);
+
+            apply_response(uistate, ResponseData::new(String::new(), info));
+            uistate.response_mut().set_tests(vec![]);
+            uistate.response_mut().start_stream(in_flight.id());
+
+            // In flight until the stream is cancelled
+            uistate.set_app_status(AppStatus::PROCESSING);
+        }
+
+        StreamMessage::Item(item) => {
+            if let Some(stream) = uistate.response_mut().stream_mut() {
+                stream.push(item);
+            }
+        }
+
+        StreamMessage::Ended(error) => {
+            uistate.set_in_flight(None);
+
+            match error {
+                Some(msg) => {
+                    info!("{}", msg);
+
+                    uistate.set_app_error(msg);
+                    uistate.set_app_status(AppStatus::ERROR);
+                }
+
+                None => { uistate.set_app_status(AppStatus::DONE); }
+            }
+        }
+    }
+}
+
+/// Applies a part of the body of the request `in_flight` as it is received.
+fn receive_download(
+    uistate: &mut UiState,
+    in_flight: InFlightRequest,
+    message: DownloadMessage,
+) {
+    match message {
+        DownloadMessage::Started(info) => {
+            uistate.increment_request_counter();
+            uistate.set_response_status_code(Some(info.status_code()));
+            uistate.response_mut().set_info(info);
+            uistate.response_mut().set_tests(vec![]);
+            uistate.response_mut().start_download(in_flight.id());
+        }
+
+        DownloadMessage::Chunk { text, received } => {
+            uistate.response_mut().append(&text, received);
+        }
+
+        DownloadMessage::Spilled(file) => {
+            info!("Writing the body of #{} to {}", in_flight.id(), file.display());
+
+            let cap = uistate.body_memory_cap();
+            uistate.response_mut().spill(file, cap);
+        }
+    }
+}
+
+/// Adds `entry` to the history. It is saved from another thread, as pruning
+/// the history lists its folder. The request has been sent either way, so
+/// failing to save it is only logged.
+fn record(uistate: &mut UiState, entry: HistoryEntry) {
+    let saved = entry.clone();
+
+    thread::spawn(move || {
+        if let Err(e) = save_entry(Path::new(DEFAULT_HISTORY_DIR), &saved) {
+            info!("Couldn't save the history: {}", e);
+        }
+    });
+
+    uistate.history_mut().push(entry);
+}
+
+/// Aborts the request in flight, if any.
+/// Returns `true` if a request was cancelled.
+pub fn cancel(uistate: &mut UiState) -> bool {
+    match uistate.in_flight().clone() {
+        Some(in_flight) => {
+            info!("Cancelling request #{}", in_flight.id());
+
+            in_flight.cancel();
+            uistate.set_in_flight(None);
+            uistate.response_mut().end_download();
+            uistate.set_app_status(AppStatus::CANCELLED);
+
+            true
+        }
+
+        None => false,
+    }
+}
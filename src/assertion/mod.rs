@@ -0,0 +1,476 @@
+//! Declarative checks of the response, written one per line in the
+//! "Assertions" tab:
+//!
+//! ```text
+//! status == 200
+//! header Content-Type contains json
+//! header X-Request-Id exists
+//! json $.user.id == 42
+//! json .user.email matches ^.+@example\.com$
+//! body contains "welcome"
+//! time < 500
+//! ```
+//!
+//! A line is a subject (`status`, `header <name>`, `json <path>`, `body` or
+//! `time`), a check (`exists`, `==`, `matches`, `contains`, `<` or `>`) and
+//! the expected value. The JSON paths are JSONPath or jq-like, as in the
+//! response filter. `body` is the text as it was received, not as it is
+//! shown. `time` is in milliseconds. Empty lines and lines starting
+//! with `#` are ignored.
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{
+    api::ResponseData,
+    ui::state::{ UiState, response_filter::select_json },
+};
+
+/// The result of an assertion or of a `test` of the post-response script.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestResult {
+    pub name: String,
+    pub passed: bool,
+
+    /// Why the test failed, e.g. "got 404". Empty if there's nothing to add
+    /// to the name.
+    pub detail: String,
+}
+
+/// What an assertion checks.
+#[derive(Clone, Debug, PartialEq)]
+enum Subject {
+    Status,
+    Header(String),
+    Json(String),
+    Body,
+
+    /// Time to receive the response, in milliseconds.
+    Time,
+}
+
+/// How an assertion checks its subject.
+#[derive(Clone, Debug, PartialEq)]
+enum Check {
+    Exists,
+    Equals(String),
+    Matches(String),
+    Contains(String),
+    LessThan(f64),
+    GreaterThan(f64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Assertion {
+    /// The line the assertion was read from, shown as the test name.
+    text: String,
+    subject: Subject,
+    check: Check,
+}
+
+impl Assertion {
+    /// Parses an assertion line like `json $.id == 42`.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let text = line.trim();
+        let (subject_name, rest) = split_word(text);
+
+        let (subject, rest) = match subject_name {
+            "status" => (Subject::Status, rest),
+            "body" => (Subject::Body, rest),
+            "time" => (Subject::Time, rest),
+
+            "header" | "json" => {
+                let (name, rest) = split_word(rest);
+
+                if name.is_empty() {
+                    return Err(format!("expected a {} after `{}`", match subject_name {
+                        "header" => "header name",
+                        _ => "JSON path",
+                    }, subject_name));
+                }
+
+                let subject = match subject_name {
+                    "header" => Subject::Header(name.to_lowercase()),
+                    _ => Subject::Json(String::from(name)),
+                };
+
+                (subject, rest)
+            }
+
+            "" => { return Err(String::from("empty assertion")); }
+            other => { return Err(format!("unknown subject `{}`", other)); }
+        };
+
+        let (operator, expected) = split_word(rest);
+        let expected = unquote(expected);
+
+        let check = match operator {
+            "exists" if expected.is_empty() => Check::Exists,
+            "==" => Check::Equals(expected),
+            "contains" => Check::Contains(expected),
+
+            "matches" => {
+                Regex::new(&expected).map_err(|_| format!("invalid regex `{}`", expected))?;
+                Check::Matches(expected)
+            }
+
+            "<" | ">" => {
+                let number = expected.strip_suffix("ms").unwrap_or(&expected).trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("expected a number after `{}`", operator))?;
+
+                match operator {
+                    "<" => Check::LessThan(number),
+                    _ => Check::GreaterThan(number),
+                }
+            }
+
+            "" => { return Err(String::from("expected a check, e.g. `==`")); }
+            "exists" => { return Err(String::from("nothing is expected after `exists`")); }
+            other => { return Err(format!("unknown check `{}`", other)); }
+        };
+
+        let valid = match (&subject, &check) {
+            (Subject::Header(_) | Subject::Json(_), _) => true,
+            (Subject::Status, Check::Exists) => false,
+            (Subject::Status, _) => true,
+            (Subject::Body, Check::Equals(_) | Check::Matches(_) | Check::Contains(_)) => true,
+            (Subject::Time, Check::LessThan(_) | Check::GreaterThan(_)) => true,
+            _ => false,
+        };
+
+        if !valid {
+            return Err(format!("`{}` can't be checked with `{}`", subject_name, operator));
+        }
+
+        Ok(Self { text: String::from(text), subject, check })
+    }
+
+    /// Whether the assertion checks the body, as JSON or as text.
+    fn reads_body(&self) -> bool {
+        matches!(self.subject, Subject::Json(_) | Subject::Body)
+    }
+
+    /// Checks the assertion against the `response`. `json` is the body
+    /// parsed as JSON, `None` if it isn't JSON.
+    fn evaluate(&self, response: &ResponseData, json: Option<&Value>) -> TestResult {
+        let info = response.info();
+
+        let actual: Result<Option<Value>, String> = match &self.subject {
+            Subject::Status => Ok(Some(Value::from(info.status_code()))),
+
+            Subject::Header(name) => Ok(info.headers().iter()
+                .find(|h| h.key().to_lowercase() == *name)
+                .map(|h| Value::String(h.value()))),
+
+            Subject::Json(path) => match json {
+                Some(json) => select_json(json, path).map(|matches| {
+                    match matches.as_slice() {
+                        [] => None,
+                        [value] => Some((*value).clone()),
+                        values => Some(Value::Array(
+                            values.iter().map(|v| (*v).clone()).collect()
+                        )),
+                    }
+                }),
+
+                None => Err(String::from("the body isn't JSON")),
+            },
+
+            Subject::Body => Ok(Some(Value::String(String::from(response.raw_body())))),
+            Subject::Time => Ok(Some(Value::from(info.elapsed().as_millis() as u64))),
+        };
+
+        let (passed, detail) = match actual {
+            Ok(actual) => self.check(actual.as_ref()),
+            Err(e) => (false, e),
+        };
+
+        TestResult { name: self.text.clone(), passed, detail }
+    }
+
+    /// Whether `actual` passes the check, with the reason if it doesn't.
+    fn check(&self, actual: Option<&Value>) -> (bool, String) {
+        let Some(actual) = actual else {
+            return (false, String::from("not found"));
+        };
+
+        let text = match actual {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+
+        let passed = match &self.check {
+            Check::Exists => true,
+
+            // Strings are compared as they are, other values as JSON so that
+            // e.g. `1.0` equals `1`
+            Check::Equals(expected) => match actual {
+                Value::String(s) => s == expected,
+                _ => serde_json::from_str::<Value>(expected)
+                    .map(|expected| json_equals(actual, &expected))
+                    .unwrap_or(false),
+            },
+
+            Check::Matches(pattern) => Regex::new(pattern)
+                .map(|re| re.is_match(&text))
+                .unwrap_or(false),
+
+            Check::Contains(expected) => text.contains(expected.as_str()),
+
+            Check::LessThan(n) => number(actual).is_some_and(|v| v < *n),
+            Check::GreaterThan(n) => number(actual).is_some_and(|v| v > *n),
+        };
+
+        let detail = match passed {
+            true => String::new(),
+            false if self.subject == Subject::Body => String::from("not in the body"),
+            false => format!("got {}", shorten(&text)),
+        };
+
+        (passed, detail)
+    }
+}
+
+/// Parses the assertions of `text`, one per line. Lines that can't be
+/// parsed are returned as failed tests.
+pub fn parse_assertions(text: &str) -> (Vec<Assertion>, Vec<TestResult>) {
+    let mut assertions = vec![];
+    let mut invalid = vec![];
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match Assertion::parse(line) {
+            Ok(assertion) => { assertions.push(assertion); }
+
+            Err(e) => {
+                invalid.push(TestResult {
+                    name: String::from(line),
+                    passed: false,
+                    detail: format!("invalid assertion: {}", e),
+                });
+            }
+        }
+    }
+
+    (assertions, invalid)
+}
+
+/// Checks the assertions of the "Assertions" tab of `uistate` against the
+/// response `data`. When the body was too big to keep in memory, the
+/// assertions on the body aren't checked, see [unchecked_assertions].
+pub fn check_response(uistate: &UiState, data: &ResponseData) -> Vec<TestResult> {
+    let (assertions, mut results) = parse_assertions(&uistate.assertions().text());
+    let spilled = data.info().body_file().is_some();
+
+    if assertions.is_empty() {
+        return results;
+    }
+
+    let json = match spilled {
+        true => None,
+        false => serde_json::from_str::<Value>(data.raw_body()).ok(),
+    };
+
+    let mut checked: Vec<TestResult> = assertions.iter()
+        .filter(|assertion| !(spilled && assertion.reads_body()))
+        .map(|assertion| assertion.evaluate(data, json.as_ref()))
+        .collect();
+
+    // The invalid lines are reported last
+    checked.append(&mut results);
+
+    checked
+}
+
+/// Number of the assertions of `uistate` that [check_response] doesn't
+/// check for `data`: the ones on the body, when only its end is kept.
+pub fn unchecked_assertions(uistate: &UiState, data: &ResponseData) -> usize {
+    if data.info().body_file().is_none() {
+        return 0;
+    }
+
+    parse_assertions(&uistate.assertions().text()).0.iter()
+        .filter(|assertion| assertion.reads_body())
+        .count()
+}
+
+/// Splits the first word off `text`.
+fn split_word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+
+    match text.find(char::is_whitespace) {
+        Some(i) => (&text[..i], text[i..].trim()),
+        None => (text, ""),
+    }
+}
+
+/// `text` without the double quotes around it, if any.
+fn unquote(text: &str) -> String {
+    match text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+        true => String::from(&text[1..text.len() - 1]),
+        false => String::from(text),
+    }
+}
+
+fn json_equals(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// The number in `value`, also read from a string.
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// `text` cut to fit in the test results.
+fn shorten(text: &str) -> String {
+    const MAX_CHARS: usize = 60;
+
+    match text.chars().count() > MAX_CHARS {
+        true => format!("{}\u{2026}", text.chars().take(MAX_CHARS).collect::<String>()),
+        false => String::from(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::ui::state::{ kv_data::KVData, response::ResponseInfo };
+
+    /// A JSON response with `raw` as it was received and shown pretty
+    /// printed.
+    fn response(raw: &str) -> ResponseData {
+        let mut info = ResponseInfo::restore(
+            200,
+            String::from("OK"),
+            String::from("HTTP/1.1"),
+            vec![KVData::new(
+                String::from("Content-Type"),
+                String::from("application/json; charset=utf-8"),
+            )],
+        );
+
+        info.set_elapsed(Duration::from_millis(120));
+
+        let mut data = ResponseData::new(
+            jsonxf::pretty_print(raw).unwrap(),
+            info,
+        );
+
+        data.set_raw_body(Some(String::from(raw)));
+        data
+    }
+
+    fn evaluate(line: &str, data: &ResponseData) -> TestResult {
+        let json = serde_json::from_str::<Value>(data.raw_body()).ok();
+
+        Assertion::parse(line).unwrap().evaluate(data, json.as_ref())
+    }
+
+    #[test]
+    fn parse_reads_subjects_and_checks() {
+        let assertion = Assertion::parse("  header Content-Type contains json ").unwrap();
+
+        assert_eq!(assertion.text, "header Content-Type contains json");
+        assert_eq!(assertion.subject, Subject::Header(String::from("content-type")));
+        assert_eq!(assertion.check, Check::Contains(String::from("json")));
+
+        assert_eq!(
+            Assertion::parse("json $.name == \"a b\"").unwrap().check,
+            Check::Equals(String::from("a b")),
+        );
+        assert_eq!(
+            Assertion::parse("time < 500ms").unwrap().check,
+            Check::LessThan(500.0),
+        );
+        assert_eq!(
+            Assertion::parse("json .id exists").unwrap().subject,
+            Subject::Json(String::from(".id")),
+        );
+    }
+
+    #[test]
+    fn parse_rejects_invalid_lines() {
+        for line in [
+            "", "code == 200", "status", "status is 200", "status exists",
+            "time == 5", "time < fast", "body exists", "header", "json",
+            "json .a exists 1", "body matches (",
+        ] {
+            assert!(Assertion::parse(line).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn parse_assertions_skips_comments_and_reports_invalid_lines() {
+        let (assertions, invalid) = parse_assertions(
+            "status == 200\n\n# a comment\nstatus is 200\n  time < 100"
+        );
+
+        assert_eq!(assertions.len(), 2);
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(invalid[0].name, "status is 200");
+        assert!(!invalid[0].passed);
+    }
+
+    #[test]
+    fn evaluate_checks_status_headers_and_time() {
+        let data = response(r#"{"id":42}"#);
+
+        assert!(evaluate("status == 200", &data).passed);
+        assert!(evaluate("status < 300", &data).passed);
+        assert!(!evaluate("status > 299", &data).passed);
+        assert!(evaluate("header content-type matches ^application/json", &data).passed);
+        assert!(!evaluate("header X-Missing exists", &data).passed);
+        assert!(evaluate("time < 500", &data).passed);
+        assert!(!evaluate("time < 100", &data).passed);
+
+        assert_eq!(evaluate("status == 404", &data).detail, "got 200");
+        assert_eq!(evaluate("header X-Missing exists", &data).detail, "not found");
+    }
+
+    #[test]
+    fn evaluate_compares_json_values() {
+        let data = response(
+            r#"{"id":42,"ratio":1.0,"user":{"email":"a@example.com"},"tags":["x","y"]}"#
+        );
+
+        assert!(evaluate("json $.id == 42", &data).passed);
+        assert!(evaluate("json .ratio == 1", &data).passed);
+        assert!(evaluate("json .user.email matches ^.+@example\\.com$", &data).passed);
+        assert!(evaluate("json .tags[] contains y", &data).passed);
+        assert!(evaluate("json .tags == [\"x\",\"y\"]", &data).passed);
+        assert!(!evaluate("json .id == 43", &data).passed);
+        assert!(!evaluate("json .missing exists", &data).passed);
+    }
+
+    #[test]
+    fn evaluate_reports_bodies_that_arent_json() {
+        let data = ResponseData::new(String::from("<html/>"), ResponseInfo::default());
+        let result = evaluate("json .id exists", &data);
+
+        assert!(!result.passed);
+        assert_eq!(result.detail, "the body isn't JSON");
+    }
+
+    #[test]
+    fn body_assertions_check_the_body_as_received() {
+        let data = response(r#"{"a":1,"b":2}"#);
+
+        assert!(evaluate(r#"body contains "a":1,"b""#, &data).passed);
+        assert!(evaluate(r#"body == {"a":1,"b":2}"#, &data).passed);
+        assert!(!evaluate(r#"body contains "a": 1"#, &data).passed);
+        assert_eq!(evaluate("body contains nope", &data).detail, "not in the body");
+    }
+}
@@ -0,0 +1,360 @@
+//! The command line interface. Without a subcommand dakia starts the tui,
+//! the subcommands run without it so that the collections can be used from
+//! scripts and CI.
+
+use std::{
+    error::Error, io::{ self, Write }, path::{ Path, PathBuf },
+    sync::atomic::AtomicBool, time::Duration,
+};
+
+use clap::{ Args, Parser, Subcommand };
+use log::info;
+
+use crate::{
+    ui::state::{ UiState, response::format_size },
+    collection::{ DEFAULT_COLLECTION_DIR, folder_path, request_path, load_request },
+    api::{ ResponseData, download::DEFAULT_MEMORY_CAP },
+    assertion::TestResult,
+    runner::{ RunPlan, RequestResult, execute, load_data, run as run_plan, write_report },
+};
+
+/// Exit code when the request couldn't be built or sent.
+const EXIT_FAILURE: i32 = 1;
+
+/// Exit code when an assertion or a test of the post-response script failed.
+const EXIT_TESTS_FAILED: i32 = 2;
+
+#[derive(Parser)]
+#[command(name = "dakia", version, about = "API client for the terminal")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Kilobytes of a response body kept in memory by the tui. Bigger
+    /// bodies are written to a temp file and only their end is shown.
+    #[arg(long, value_name = "KB", default_value_t = DEFAULT_MEMORY_CAP / 1024)]
+    pub body_memory: usize,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Sends a request saved in the collection.
+    ///
+    /// The response body is written to stdout and the status, timing and
+    /// test results to stderr. Exits with 2 if a test failed, otherwise with
+    /// 0 for 2xx responses, 3, 4 or 5 for 3xx, 4xx or 5xx responses and 1 if
+    /// the request failed.
+    Run {
+        /// Name of the request, e.g. `users/get-user`.
+        request: String,
+
+        /// Environment to resolve the `{{variables}}` from.
+        #[arg(short, long)]
+        env: Option<String>,
+    },
+
+    /// Runs the requests of a collection folder in order.
+    ///
+    /// The result of every request is written to stderr. Exits with 2 if a
+    /// request failed or one of its tests failed, 1 if the folder or the data
+    /// file couldn't be read and 0 otherwise.
+    RunFolder(RunFolderArgs),
+}
+
+#[derive(Args)]
+pub struct RunFolderArgs {
+    /// Folder of the collection, e.g. `users`. `.` runs the whole
+    /// collection.
+    folder: String,
+
+    /// Environment to resolve the `{{variables}}` from.
+    #[arg(short, long)]
+    env: Option<String>,
+
+    /// CSV or JSON file with the variables of an iteration per row.
+    #[arg(short, long)]
+    data: Option<PathBuf>,
+
+    /// Milliseconds to wait between two requests.
+    #[arg(long, default_value_t = 0)]
+    delay: u64,
+
+    /// Stops at the first request that fails.
+    #[arg(long)]
+    stop_on_failure: bool,
+
+    /// Writes a JUnit XML report to this file.
+    #[arg(long)]
+    junit: Option<PathBuf>,
+
+    /// Writes a JSON report to this file.
+    #[arg(long)]
+    json: Option<PathBuf>,
+}
+
+/// Runs the `run` subcommand. Returns the exit code.
+pub fn run(request: &str, env: Option<&str>) -> i32 {
+    match send(request, env) {
+        Ok((data, tests)) => {
+            let info = data.info();
+
+            let mut stdout = io::stdout().lock();
+
+            // A closed pipe (e.g. `dakia run ... | head`) is not an error
+            let _ = stdout.write_all(data.body().as_bytes());
+
+            if !data.body().is_empty() && !data.body().ends_with('\n') {
+                let _ = stdout.write_all(b"\n");
+            }
+
+            let _ = stdout.flush();
+
+            eprintln!(
+                "{} {} \u{2502} {} ms \u{2502} {}",
+                info.status_code(),
+                info.reason(),
+                info.elapsed().as_millis(),
+                format_size(info.size()),
+            );
+
+            print_tests(&tests, "");
+
+            exit_code(Some(info.status_code()), &tests)
+        }
+
+        Err(e) => {
+            info!("{}", e);
+            eprintln!("dakia: {}", e);
+
+            exit_code(None, &[])
+        }
+    }
+}
+
+/// Loads the saved `request` and sends it with the variables of the
+/// environment `env`, running its scripts. Returns the response and the
+/// results of the tests of the post-response script.
+fn send(request: &str, env: Option<&str>)
+    -> Result<(ResponseData, Vec<TestResult>), Box<dyn Error>> {
+    let path = request_path(Path::new(DEFAULT_COLLECTION_DIR), request)?;
+
+    if !path.is_file() {
+        return Err(format!("no request named \"{}\" in the collection", request).into());
+    }
+
+    let saved = load_request(&path)
+        .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+
+    // The cookie jar of the environment is read from the cookies folder by
+    // the request and saved when the response sets cookies, as in the tui
+    let mut uistate = UiState::default();
+
+    uistate.settings_mut().reload()?;
+    saved.apply(&mut uistate);
+
+    if let Some(env) = env {
+        uistate.environments_mut().reload()?;
+
+        if !uistate.environments_mut().set_active_by_name(env) {
+            return Err(format!("no environment named \"{}\"", env).into());
+        }
+    }
+
+    let exchange = execute(&mut uistate)?;
+
+    Ok((exchange.response, exchange.tests))
+}
+
+/// Runs the `run-folder` subcommand. Returns the exit code.
+pub fn run_folder(args: &RunFolderArgs) -> i32 {
+    let root = Path::new(DEFAULT_COLLECTION_DIR);
+
+    let plan = match plan_run(root, args) {
+        Ok(plan) => plan,
+
+        Err(e) => {
+            info!("{}", e);
+            eprintln!("dakia: {}", e);
+
+            return EXIT_FAILURE;
+        }
+    };
+
+    // All the requests of the run share the persisted cookie jars
+    let mut uistate = UiState::default();
+
+    if let Err(e) = uistate.settings_mut().reload() {
+        eprintln!("dakia: {}", e);
+        return EXIT_FAILURE;
+    }
+
+    if let Some(env) = &args.env {
+        let environments = uistate.environments_mut();
+
+        if let Err(e) = environments.reload() {
+            eprintln!("dakia: {}", e);
+            return EXIT_FAILURE;
+        }
+
+        if !environments.set_active_by_name(env) {
+            eprintln!("dakia: no environment named \"{}\"", env);
+            return EXIT_FAILURE;
+        }
+    }
+
+    let iterations = plan.iterations();
+    let report = run_plan(&mut uistate, &plan, &AtomicBool::new(false), |result| {
+        print_result(result, iterations);
+    });
+
+    eprintln!(
+        "{} passed, {} failed \u{2502} {} of {} requests \u{2502} {:.1} s",
+        report.passed_count(),
+        report.failed_count(),
+        report.results().len(),
+        report.total(),
+        report.duration().as_secs_f32(),
+    );
+
+    let reports = [
+        (&args.junit, report.to_junit()),
+        (&args.json, report.to_json()),
+    ];
+
+    for (path, content) in reports {
+        if let Some(path) = path {
+            if let Err(e) = write_report(path, &content) {
+                eprintln!("dakia: couldn't write {}: {}", path.display(), e);
+                return EXIT_FAILURE;
+            }
+        }
+    }
+
+    match report.failed_count() {
+        0 => 0,
+        _ => EXIT_TESTS_FAILED,
+    }
+}
+
+/// The run of `args.folder` with the options of `args`.
+fn plan_run(root: &Path, args: &RunFolderArgs) -> Result<RunPlan, Box<dyn Error>> {
+    let folder = folder_path(root, &args.folder)?;
+
+    let mut plan = RunPlan::new(root, &folder)?;
+
+    if let Some(data) = &args.data {
+        plan.set_data(load_data(data)?);
+    }
+
+    plan.set_delay(Duration::from_millis(args.delay));
+    plan.set_stop_on_failure(args.stop_on_failure);
+
+    Ok(plan)
+}
+
+/// Writes the result of a request of a run to stderr.
+fn print_result(result: &RequestResult, iterations: usize) {
+    let mark = match result.passed() {
+        true => "\u{2713}",
+        false => "\u{2717}",
+    };
+
+    let iteration = match iterations {
+        1 => String::new(),
+        _ => format!("#{} ", result.iteration()),
+    };
+
+    match (result.status(), result.error()) {
+        (_, Some(error)) => {
+            eprintln!("{} {}{} {} \u{2502} {}", mark, iteration, result.method(), result.name(), error);
+        }
+
+        (Some(status), None) => {
+            eprintln!(
+                "{} {}{} {} \u{2502} {} {} \u{2502} {} ms",
+                mark,
+                iteration,
+                result.method(),
+                result.name(),
+                status,
+                result.reason(),
+                result.elapsed().as_millis(),
+            );
+        }
+
+        (None, None) => {}
+    }
+
+    print_tests(result.tests(), "    ");
+}
+
+/// Writes the results of `tests` to stderr, each line starting with
+/// `indent`.
+fn print_tests(tests: &[TestResult], indent: &str) {
+    for test in tests.iter() {
+        match test.passed {
+            true => eprintln!("{}\u{2713} {}", indent, test.name),
+            false if test.detail.is_empty() => {
+                eprintln!("{}\u{2717} {}", indent, test.name);
+            }
+            false => eprintln!("{}\u{2717} {} ({})", indent, test.name, test.detail),
+        }
+    }
+}
+
+/// Exit code of the `run` subcommand for a response with `status_code` and
+/// the results of its `tests`. `None` when the request failed.
+fn exit_code(status_code: Option<u16>, tests: &[TestResult]) -> i32 {
+    let status_code = match status_code {
+        Some(status_code) => status_code,
+        None => { return EXIT_FAILURE; }
+    };
+
+    if !tests.iter().all(|test| test.passed) {
+        return EXIT_TESTS_FAILED;
+    }
+
+    match status_code {
+        200..=299 => 0,
+        300..=599 => (status_code / 100) as i32,
+        _ => EXIT_FAILURE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test(passed: bool) -> TestResult {
+        TestResult { name: String::from("status == 200"), passed, detail: String::new() }
+    }
+
+    #[test]
+    fn exit_code_follows_the_status_class() {
+        for (status_code, expected) in [
+            (200, 0), (204, 0), (299, 0),
+            (301, 3), (304, 3),
+            (404, 4), (429, 4),
+            (500, 5), (503, 5),
+            (101, EXIT_FAILURE), (600, EXIT_FAILURE),
+        ] {
+            assert_eq!(
+                exit_code(Some(status_code), &[test(true)]), expected,
+                "{}", status_code,
+            );
+        }
+    }
+
+    #[test]
+    fn exit_code_is_failure_without_a_response() {
+        assert_eq!(exit_code(None, &[]), EXIT_FAILURE);
+        assert_eq!(exit_code(None, &[test(false)]), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn failed_tests_win_over_the_status() {
+        assert_eq!(exit_code(Some(200), &[test(true), test(false)]), EXIT_TESTS_FAILED);
+        assert_eq!(exit_code(Some(500), &[test(false)]), EXIT_TESTS_FAILED);
+        assert_eq!(exit_code(Some(200), &[]), 0);
+    }
+}
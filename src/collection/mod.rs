@@ -0,0 +1,666 @@
+//! Named requests saved on disk.
+//!
+//! A collection is a plain folder: every request is a `.toml` file named
+//! after the request and sub-folders group requests. This keeps collections
+//! easy to review and check into git. Passwords, tokens and API keys are
+//! only saved as `{{variables}}`, whose values stay in the environment.
+
+use std::{
+    error::Error, fs, io,
+    path::{ Component, Path, PathBuf },
+};
+
+use reqwest::Method;
+use serde::{ Deserialize, Serialize };
+
+use crate::{
+    environment::find_variables,
+    ui::state::{
+        UiState,
+        kv_data::{ KVData, PartKind },
+        body::BodyContent,
+        auth::{ AuthScheme, ApiKeyLocation },
+        scripts::ScriptKind,
+        settings::TlsVerification,
+    },
+};
+
+/// Folder the collections are read from and saved to, relative to the
+/// working directory.
+pub const DEFAULT_COLLECTION_DIR: &str = "collections";
+
+/// Extension of the saved request files.
+pub const REQUEST_FILE_EXT: &str = "toml";
+
+/// A request as it is stored in a collection file.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SavedRequest {
+    method: String,
+    url: String,
+
+    #[serde(default)]
+    headers: Vec<SavedKV>,
+
+    #[serde(default)]
+    body: SavedBody,
+
+    #[serde(default)]
+    auth: SavedAuth,
+
+    #[serde(default, skip_serializing_if = "SavedScripts::is_empty")]
+    scripts: SavedScripts,
+
+    /// The lines of the "Assertions" tab, e.g. `status == 200`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    assertions: Vec<String>,
+
+    /// Whether the WebSocket switch is on. `ws://` urls don't need it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    websocket: bool,
+
+    #[serde(default, skip_serializing_if = "SavedSettings::is_empty")]
+    settings: SavedSettings,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SavedKV {
+    key: String,
+
+    #[serde(default)]
+    value: String,
+
+    /// `File` for multipart rows whose value is a file path.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    kind: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    filename: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    content_type: String,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SavedBody {
+    /// The body "Content Type" as shown in the content type select.
+    #[serde(default)]
+    content: String,
+
+    /// Key-value data of form bodies.
+    #[serde(default)]
+    kv: Vec<SavedKV>,
+
+    /// Text of the raw bodies, the query of the GraphQL body.
+    #[serde(default)]
+    text: String,
+
+    /// The JSON variables of the GraphQL body.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    variables: String,
+
+    /// The GraphQL operation to run, empty for the first one.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    operation: String,
+}
+
+/// The "Authorization" tab. The password, token and API key are only written
+/// to a file when they refer to `{{variables}}`, see
+/// [SavedRequest::strip_secrets].
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SavedAuth {
+    #[serde(default)]
+    scheme: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    username: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    password: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    token: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    api_key: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    api_key_value: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    api_key_location: String,
+}
+
+/// The scripts of the "Scripts" tab.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SavedScripts {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pre_request: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    post_response: String,
+}
+
+impl SavedScripts {
+    fn is_empty(&self) -> bool {
+        self.pre_request.is_empty() && self.post_response.is_empty()
+    }
+}
+
+/// The overrides of the "Settings" tab, as typed. Empty fields use the
+/// global settings.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SavedSettings {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    timeout: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    redirects: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    proxy: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    no_proxy: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    ca_bundle: String,
+
+    /// The "Verify TLS" select, empty for the global setting.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    verify_tls: String,
+}
+
+impl SavedSettings {
+    fn is_empty(&self) -> bool {
+        [
+            &self.timeout, &self.redirects, &self.proxy, &self.no_proxy,
+            &self.ca_bundle, &self.verify_tls,
+        ].iter().all(|field| field.is_empty())
+    }
+}
+
+/// A folder or a request in a collection.
+#[derive(Clone)]
+pub enum CollectionEntry {
+    Folder {
+        name: String,
+        path: PathBuf,
+        children: Vec<CollectionEntry>,
+    },
+
+    Request {
+        name: String,
+        path: PathBuf,
+    },
+}
+
+impl SavedKV {
+    fn from_kv_data(kv_data: &[KVData]) -> Vec<SavedKV> {
+        kv_data.iter()
+            .filter(|kv| !kv.key().is_empty() || !kv.value().is_empty())
+            .map(|kv| SavedKV {
+                key: kv.key(),
+                value: kv.value(),
+                kind: match kv.part_kind() {
+                    PartKind::Text => String::new(),
+                    PartKind::File => String::from(kv.part_kind().to_str()),
+                },
+                filename: kv.filename(),
+                content_type: kv.content_type(),
+            })
+            .collect()
+    }
+
+    fn to_kv_data(saved: &[SavedKV]) -> Vec<KVData> {
+        let mut kv_data: Vec<KVData> = saved.iter()
+            .map(|kv| {
+                let mut kv_data = KVData::new(kv.key.clone(), kv.value.clone());
+
+                kv_data.set_part_kind(PartKind::from_str(&kv.kind));
+                kv_data.set_filename(kv.filename.clone());
+                kv_data.set_content_type(kv.content_type.clone());
+
+                kv_data
+            })
+            .collect();
+
+        if kv_data.is_empty() {
+            kv_data.push(KVData::default());
+        }
+
+        kv_data
+    }
+}
+
+impl SavedRequest {
+    /// Takes a snapshot of the request being edited in `uistate`.
+    pub fn from_uistate(uistate: &UiState) -> Self {
+        let body = uistate.body();
+        let auth = uistate.auth();
+        let scripts = uistate.scripts();
+        let [timeout, redirects, proxy, no_proxy, ca_bundle] =
+            uistate.settings().fields().clone();
+
+        Self {
+            method: String::from(uistate.method().as_str()),
+            url: uistate.url(),
+            headers: SavedKV::from_kv_data(uistate.request_headers()),
+            body: SavedBody {
+                content: body.body_content().to_string(),
+                kv: SavedKV::from_kv_data(body.kv_data()),
+                text: body.text_data().text(),
+                variables: body.variables().text(),
+                operation: String::from(body.operation()),
+            },
+            auth: SavedAuth {
+                scheme: String::from(auth.auth_scheme().to_str()),
+                username: auth.username(),
+                password: auth.password(),
+                token: auth.token(),
+                api_key: auth.api_key(),
+                api_key_value: auth.api_key_value(),
+                api_key_location: String::from(
+                    auth.api_key_location().to_str()
+                ),
+            },
+            scripts: SavedScripts {
+                pre_request: scripts.script(ScriptKind::PreRequest),
+                post_response: scripts.script(ScriptKind::PostResponse),
+            },
+            assertions: uistate.assertions().text_vec().into_iter()
+                .filter(|line| !line.trim().is_empty())
+                .collect(),
+            websocket: uistate.websocket().enabled(),
+            settings: SavedSettings {
+                timeout, redirects, proxy, no_proxy, ca_bundle,
+                verify_tls: match uistate.settings().tls_verification() {
+                    TlsVerification::Global => String::new(),
+                    verification => String::from(verification.to_str()),
+                },
+            },
+        }
+    }
+
+    pub fn method(&self) -> &str { &self.method }
+
+    /// Empties the password, token and API key that are typed as they are,
+    /// before the request is written to a file. Returns `true` if one was
+    /// emptied.
+    pub fn strip_secrets(&mut self) -> bool {
+        let auth = &mut self.auth;
+        let mut stripped = false;
+
+        for secret in [&mut auth.password, &mut auth.token, &mut auth.api_key_value] {
+            let saved = saved_secret(secret.clone());
+
+            stripped |= saved != *secret;
+            *secret = saved;
+        }
+
+        stripped
+    }
+
+    pub fn body_content(&self) -> BodyContent {
+        BodyContent::from_str(&self.body.content)
+    }
+
+    /// Loads this request into `uistate` so it can be edited and sent.
+    pub fn apply(&self, uistate: &mut UiState) {
+        uistate.set_method(
+            Method::from_bytes(self.method.to_uppercase().as_bytes())
+                .unwrap_or(Method::GET)
+        );
+
+        uistate.load_url(self.url.clone());
+        uistate.websocket_mut().set_enabled(self.websocket);
+        *uistate.request_headers_mut() = SavedKV::to_kv_data(&self.headers);
+        uistate.request_headers_ui_mut().set_active_row(0);
+
+        let body = uistate.body_mut();
+
+        body.select_body_content(BodyContent::from_str(&self.body.content));
+        *body.kv_data_mut() = SavedKV::to_kv_data(&self.body.kv);
+        body.kv_tab_state_mut().set_active_row(0);
+        body.text_data_mut().load_text(&self.body.text);
+        body.variables_mut().load_text(&self.body.variables);
+        body.set_operation(self.body.operation.clone());
+
+        let auth = uistate.auth_mut();
+
+        auth.set_auth_scheme(AuthScheme::from_str(&self.auth.scheme));
+        auth.set_username(self.auth.username.clone());
+        auth.set_password(self.auth.password.clone());
+        auth.set_token(self.auth.token.clone());
+        auth.set_api_key(self.auth.api_key.clone());
+        auth.set_api_key_value(self.auth.api_key_value.clone());
+        auth.set_api_key_location(
+            ApiKeyLocation::from_str(&self.auth.api_key_location)
+        );
+
+        let scripts = uistate.scripts_mut();
+
+        scripts.editor_mut(ScriptKind::PreRequest)
+            .load_text(&self.scripts.pre_request);
+        scripts.editor_mut(ScriptKind::PostResponse)
+            .load_text(&self.scripts.post_response);
+
+        uistate.assertions_mut().load_text(&self.assertions.join("\n"));
+
+        let settings = uistate.settings_mut();
+
+        settings.set_fields([
+            self.settings.timeout.clone(),
+            self.settings.redirects.clone(),
+            self.settings.proxy.clone(),
+            self.settings.no_proxy.clone(),
+            self.settings.ca_bundle.clone(),
+        ]);
+        settings.set_tls_verification(
+            TlsVerification::from_str(&self.settings.verify_tls)
+        );
+    }
+}
+
+impl CollectionEntry {
+    pub fn name(&self) -> &str {
+        match self {
+            CollectionEntry::Folder { name, .. } => name,
+            CollectionEntry::Request { name, .. } => name,
+        }
+    }
+}
+
+/// Reads the tree of folders and requests under `root`. Folders are listed
+/// before requests, both sorted by name. A missing `root` is an empty
+/// collection.
+pub fn load_tree(root: &Path) -> io::Result<Vec<CollectionEntry>> {
+    if !root.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut folders: Vec<CollectionEntry> = vec![];
+    let mut requests: Vec<CollectionEntry> = vec![];
+
+    for dir_entry in fs::read_dir(root)? {
+        let path = dir_entry?.path();
+        let name_os = if path.is_dir() {
+            path.file_name()
+        } else {
+            path.file_stem()
+        };
+
+        let name = match name_os {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => { continue; }
+        };
+
+        if name.starts_with('.') {
+            continue;
+        }
+
+        if path.is_dir() {
+            let children = load_tree(&path)?;
+            folders.push(CollectionEntry::Folder { name, path, children });
+        } else if path.extension().is_some_and(|ext| ext == REQUEST_FILE_EXT) {
+            requests.push(CollectionEntry::Request { name, path });
+        }
+    }
+
+    folders.sort_by(|a, b| a.name().cmp(b.name()));
+    requests.sort_by(|a, b| a.name().cmp(b.name()));
+    folders.append(&mut requests);
+
+    Ok(folders)
+}
+
+/// `secret` if it only refers to `{{variables}}`, which keep the secret in
+/// the environment, empty otherwise. Collections are meant to be checked
+/// into git and the history is a plain folder, so a password, token or API
+/// key typed as it is isn't written to them.
+fn saved_secret(secret: String) -> String {
+    let mut literal = false;
+    let mut pos = 0;
+
+    for var in find_variables(&secret) {
+        literal |= !secret[pos..var.start].trim().is_empty();
+        pos = var.end;
+    }
+
+    match literal || !secret[pos..].trim().is_empty() {
+        true => String::new(),
+        false => secret,
+    }
+}
+
+/// Returns the file path of the request `name` in `root`. `name` may contain
+/// `/` to place the request in (nested) folders. Fails on an empty name or
+/// one outside `root`.
+pub fn request_path(root: &Path, name: &str) -> io::Result<PathBuf> {
+    let parts = name_parts(name)?;
+
+    let (file_name, folders) = parts.split_last().ok_or_else(|| io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "name must not be empty",
+    ))?;
+
+    let mut path = root.to_path_buf();
+
+    for folder in folders {
+        path.push(folder);
+    }
+
+    path.push(format!("{}.{}", file_name, REQUEST_FILE_EXT));
+
+    Ok(path)
+}
+
+/// Returns the path of the folder `name` in `root`, `root` itself if `name`
+/// is empty. Fails on a name outside `root`.
+pub fn folder_path(root: &Path, name: &str) -> io::Result<PathBuf> {
+    let mut path = root.to_path_buf();
+
+    for folder in name_parts(name)? {
+        path.push(folder);
+    }
+
+    Ok(path)
+}
+
+/// Splits the name of a request or a folder into the folders leading to it
+/// and its own name. Absolute names and names with `..` would leave the
+/// collection, so they are rejected.
+fn name_parts(name: &str) -> io::Result<Vec<&str>> {
+    let inside = Path::new(name).components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+
+    if !inside {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is outside the collection", name),
+        ));
+    }
+
+    Ok(name.split('/').filter(|part| !part.is_empty() && *part != ".").collect())
+}
+
+/// Returns the name of the request saved at `path`, relative to `root` and
+/// without the extension, e.g. `users/get-user`.
+pub fn request_name(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+
+    relative.with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+pub fn load_request(path: &Path) -> Result<SavedRequest, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+
+    Ok(toml::from_str(&content)?)
+}
+
+/// Saves `request` to `path`, creating the parent folders if needed. The
+/// secrets typed as they are aren't written, returns `true` if one was left
+/// out.
+pub fn save_request(path: &Path, request: &SavedRequest)
+    -> Result<bool, Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut saved = request.clone();
+    let stripped = saved.strip_secrets();
+
+    fs::write(path, toml::to_string(&saved)?)?;
+
+    Ok(stripped)
+}
+
+/// Renames the request or folder at `path` to `new_name`. The entry stays in
+/// the same folder. Returns the new path.
+pub fn rename_entry(path: &Path, new_name: &str) -> io::Result<PathBuf> {
+    let inside = matches!(
+        Path::new(new_name).components().collect::<Vec<_>>().as_slice(),
+        [Component::Normal(_)]
+    );
+
+    if !inside || new_name.contains('/') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "name must not be empty, `.`, `..` or contain '/'",
+        ));
+    }
+
+    let new_path = if path.is_file() {
+        path.with_file_name(format!("{}.{}", new_name, REQUEST_FILE_EXT))
+    } else {
+        path.with_file_name(new_name)
+    };
+
+    if new_path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", new_name),
+        ));
+    }
+
+    fs::rename(path, &new_path)?;
+
+    Ok(new_path)
+}
+
+/// Deletes the request or the folder (with everything in it) at `path`.
+pub fn delete_entry(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ env, process };
+
+    use super::*;
+
+    #[test]
+    fn request_path_places_the_request_in_its_folders() {
+        let root = Path::new("collections");
+
+        assert_eq!(
+            request_path(root, "users/get-user").unwrap(),
+            root.join("users").join("get-user.toml"),
+        );
+
+        assert_eq!(
+            request_path(root, "./users//get-user/").unwrap(),
+            root.join("users").join("get-user.toml"),
+        );
+    }
+
+    #[test]
+    fn request_path_rejects_names_outside_the_collection() {
+        let root = Path::new("collections");
+
+        for name in ["", "/", "..", "../x", "users/../../x", "/etc/passwd"] {
+            assert!(request_path(root, name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn folder_path_is_the_root_for_an_empty_name() {
+        let root = Path::new("collections");
+
+        assert_eq!(folder_path(root, "").unwrap(), root);
+        assert_eq!(folder_path(root, ".").unwrap(), root);
+        assert_eq!(folder_path(root, "users/").unwrap(), root.join("users"));
+        assert!(folder_path(root, "../..").is_err());
+        assert!(folder_path(root, "/tmp").is_err());
+    }
+
+    #[test]
+    fn rename_entry_rejects_names_leaving_the_folder() {
+        let path = Path::new("collections/users/get-user.toml");
+
+        for name in ["", ".", "..", "a/b", "/x"] {
+            assert!(rename_entry(path, name).is_err(), "{}", name);
+        }
+    }
+
+    fn request_with_token(token: &str) -> SavedRequest {
+        let mut uistate = UiState::default();
+
+        uistate.auth_mut().set_auth_scheme(AuthScheme::Bearer);
+        uistate.auth_mut().set_token(String::from(token));
+        uistate.auth_mut().set_password(String::from("{{password}}"));
+
+        SavedRequest::from_uistate(&uistate)
+    }
+
+    #[test]
+    fn from_uistate_keeps_the_secrets() {
+        assert_eq!(request_with_token("hunter2").auth.token, "hunter2");
+    }
+
+    #[test]
+    fn strip_secrets_empties_the_typed_secrets() {
+        let mut typed = request_with_token("hunter2");
+        let mut variable = request_with_token("{{token}}");
+
+        assert!(typed.strip_secrets());
+        assert_eq!(typed.auth.token, "");
+        assert_eq!(typed.auth.password, "{{password}}");
+
+        assert!(!variable.strip_secrets());
+        assert_eq!(variable.auth.token, "{{token}}");
+    }
+
+    #[test]
+    fn save_request_leaves_the_typed_secrets_out_of_the_file() {
+        let path = env::temp_dir()
+            .join(format!("dakia-test-{}-save.toml", process::id()));
+        let request = request_with_token("hunter2");
+
+        let stripped = save_request(&path, &request).unwrap();
+        let saved = load_request(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(stripped);
+        assert_eq!(saved.auth.token, "");
+        assert_eq!(request.auth.token, "hunter2");
+    }
+
+    #[test]
+    fn saved_secret_keeps_only_variables() {
+        assert_eq!(saved_secret(String::from("{{token}}")), "{{token}}");
+        assert_eq!(saved_secret(String::from(" {{ a }}{{b}} ")), " {{ a }}{{b}} ");
+        assert_eq!(saved_secret(String::from("")), "");
+        assert_eq!(saved_secret(String::from("hunter2")), "");
+        assert_eq!(saved_secret(String::from("abc{{token}}")), "");
+        assert_eq!(saved_secret(String::from("{{token}}abc")), "");
+        assert_eq!(saved_secret(String::from("{{}}")), "");
+    }
+}
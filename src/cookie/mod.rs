@@ -0,0 +1,629 @@
+//! Cookie jars: the cookies set by the responses, sent back with the
+//! requests to the same site.
+//!
+//! Every environment has its own jar, kept in a `.toml` file in the cookies
+//! folder named after the environment, e.g. `cookies/staging.toml`. The
+//! requests sent without an environment use `cookies/default.toml`:
+//!
+//! ```toml
+//! [[cookies]]
+//! name = "session"
+//! value = "abc123"
+//! domain = "example.com"
+//! path = "/"
+//! expires = "2024-05-01T10:00:00+00:00"
+//! http_only = true
+//! ```
+//!
+//! A jar is read the first time a request uses it and written again every
+//! time it changes, so the cookies outlive dakia.
+//!
+//! A response can set a cookie for its host or a parent domain of it, but
+//! not for a top-level domain like `com`. dakia doesn't ship the public
+//! suffix list the browsers use, so a response of `evil.co.uk` can still set
+//! a cookie for `co.uk`, which is then sent to every `.co.uk` site the jar
+//! of the environment is used with.
+
+use std::{
+    collections::BTreeMap, error::Error, fs,
+    path::{ Path, PathBuf }, sync::{ Arc, Mutex },
+};
+
+use chrono::{ DateTime, Duration, SecondsFormat, Utc };
+use log::{ info, error };
+use reqwest::{ cookie::CookieStore, header::HeaderValue, Url };
+use serde::{ Deserialize, Serialize };
+
+use crate::{
+    environment::Environment,
+    ui::state::response::ResponseCookie,
+};
+
+/// Folder the cookie jars are kept in, relative to the working directory.
+pub const DEFAULT_COOKIE_DIR: &str = "cookies";
+
+/// Extension of the cookie jar files.
+pub const COOKIE_FILE_EXT: &str = "toml";
+
+/// Jar of the requests sent without an environment.
+pub const DEFAULT_JAR: &str = "default";
+
+/// Longest `Max-Age` in seconds.
+const MAX_AGE_LIMIT: i64 = 400 * 24 * 60 * 60;
+
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cookie {
+    name: String,
+    value: String,
+
+    /// The host the cookie is sent to, with its subdomains unless
+    /// `host_only`.
+    domain: String,
+    path: String,
+
+    /// When the cookie expires, in RFC 3339 format. Empty for a session
+    /// cookie, which is kept until it's deleted.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    expires: String,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    secure: bool,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    http_only: bool,
+
+    /// Whether the cookie is sent to `domain` only. Set when the response
+    /// didn't give a `Domain` attribute.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    host_only: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct CookieJar {
+    /// Sorted by domain, path and name.
+    #[serde(default)]
+    cookies: Vec<Cookie>,
+}
+
+/// The cookie jars of the environments by their name, shared with the
+/// threads sending the requests.
+#[derive(Clone)]
+pub struct CookieJars {
+    /// The folder the jars are kept in.
+    dir: PathBuf,
+    jars: Arc<Mutex<BTreeMap<String, CookieJar>>>,
+}
+
+/// The jar of a request, which reqwest reads the `Cookie` header from and
+/// stores the `Set-Cookie` headers of the responses in, the redirects
+/// included.
+pub struct JarStore {
+    jars: CookieJars,
+    name: String,
+}
+
+impl Cookie {
+    /// A session cookie sent to the host `domain` only.
+    pub fn new(name: String, value: String, domain: String, path: String) -> Self {
+        Self { name, value, domain, path, host_only: true, ..Default::default() }
+    }
+
+    /// Reads the value of a `Set-Cookie` header of the response to `url`.
+    /// Returns `None` for a cookie that `url` isn't allowed to set.
+    pub fn from_set_cookie(header: &str, url: &Url) -> Option<Self> {
+        let parsed = ResponseCookie::parse(header)?;
+        let host = url.host_str()?.to_ascii_lowercase();
+
+        let mut cookie = Self::new(
+            String::from(parsed.name()),
+            String::from(parsed.value()),
+            host.clone(),
+            default_path(url),
+        );
+
+        let mut max_age: Option<i64> = None;
+
+        for attribute in parsed.attributes() {
+            let value = attribute.value();
+
+            match attribute.key().to_ascii_lowercase().as_str() {
+                "domain" => {
+                    let domain = value.trim_start_matches('.').to_ascii_lowercase();
+
+                    if domain.is_empty() {
+                        continue;
+                    }
+
+                    // A top-level domain like `com` is taken as the host
+                    // only when it is the host itself
+                    if !domain.contains('.') && domain == host {
+                        continue;
+                    }
+
+                    if !domain.contains('.') || !domain_matches(&host, &domain) {
+                        info!("Ignoring the cookie {} of {} for {}", cookie.name, host, domain);
+                        return None;
+                    }
+
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+
+                "path" if value.starts_with('/') => { cookie.path = value; }
+
+                "expires" => {
+                    if let Some(expires) = parse_http_date(&value) {
+                        cookie.expires = format_expiry(expires);
+                    }
+                }
+
+                "max-age" => { max_age = value.parse().ok(); }
+                "secure" => { cookie.secure = true; }
+                "httponly" => { cookie.http_only = true; }
+                _ => {}
+            }
+        }
+
+        // Max-Age wins over Expires. Like the browsers, cookies aren't kept
+        // longer than 400 days.
+        if let Some(seconds) = max_age {
+            let seconds = seconds.clamp(-1, MAX_AGE_LIMIT);
+            cookie.expires = format_expiry(Utc::now() + Duration::seconds(seconds));
+        }
+
+        Some(cookie)
+    }
+
+    pub fn name(&self) -> &str { &self.name }
+    pub fn value(&self) -> &str { &self.value }
+    pub fn domain(&self) -> &str { &self.domain }
+    pub fn path(&self) -> &str { &self.path }
+    pub fn expires(&self) -> &str { &self.expires }
+    pub fn secure(&self) -> bool { self.secure }
+    pub fn http_only(&self) -> bool { self.http_only }
+    pub fn host_only(&self) -> bool { self.host_only }
+
+    /// Changes the fields edited in the cookie manager. An empty `expires`
+    /// makes it a session cookie.
+    pub fn edit(
+        &mut self,
+        name: String,
+        value: String,
+        domain: String,
+        path: String,
+        expires: String,
+    ) -> Result<(), Box<dyn Error>> {
+        if name.trim().is_empty() {
+            return Err("the name is empty".into());
+        }
+
+        if domain.trim().is_empty() {
+            return Err("the domain is empty".into());
+        }
+
+        if !path.starts_with('/') {
+            return Err("the path doesn't start with /".into());
+        }
+
+        let expires = match expires.trim() {
+            "" => String::new(),
+            expires => format_expiry(
+                DateTime::parse_from_rfc3339(expires)
+                    .map_err(|e| format!("the expiry isn't an RFC 3339 date: {}", e))?
+                    .with_timezone(&Utc)
+            ),
+        };
+
+        let domain = domain.trim().trim_start_matches('.').to_ascii_lowercase();
+
+        // A cookie moved to another host isn't tied to the old one anymore
+        if domain != self.domain {
+            self.host_only = false;
+        }
+
+        self.name = String::from(name.trim());
+        self.value = value;
+        self.domain = domain;
+        self.path = path;
+        self.expires = expires;
+
+        Ok(())
+    }
+
+    /// Whether the cookie expired. Session cookies never do.
+    pub fn expired(&self) -> bool {
+        match DateTime::parse_from_rfc3339(&self.expires) {
+            Ok(expires) => expires <= Utc::now(),
+            Err(_) => false,
+        }
+    }
+
+    /// Whether the cookie is sent with a request to `url`.
+    pub fn matches(&self, url: &Url) -> bool {
+        let host = match url.host_str() {
+            Some(host) => host.to_ascii_lowercase(),
+            None => { return false; }
+        };
+
+        let host_matches = match self.host_only {
+            true => host == self.domain,
+            false => domain_matches(&host, &self.domain),
+        };
+
+        host_matches
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https" || url.scheme() == "wss")
+            && !self.expired()
+    }
+
+    /// Whether `other` is the same cookie, which replaces this one.
+    fn same(&self, other: &Cookie) -> bool {
+        self.name == other.name && self.domain == other.domain
+            && self.path == other.path
+    }
+}
+
+impl CookieJar {
+    pub fn cookies(&self) -> &Vec<Cookie> { &self.cookies }
+
+    /// Adds the cookie, replacing the cookie with the same name, domain and
+    /// path. An expired cookie deletes it instead.
+    pub fn insert(&mut self, cookie: Cookie) {
+        self.cookies.retain(|c| !c.same(&cookie));
+
+        if !cookie.expired() {
+            self.cookies.push(cookie);
+            self.sort();
+        }
+    }
+
+    /// Replaces the cookie at `index` with `cookie`, which may be the cookie
+    /// edited in the cookie manager.
+    pub fn replace(&mut self, index: usize, cookie: Cookie) {
+        if index < self.cookies.len() {
+            self.cookies.remove(index);
+        }
+
+        self.insert(cookie);
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.cookies.len() {
+            self.cookies.remove(index);
+        }
+    }
+
+    /// The value of the `Cookie` header of a request to `url`, with the
+    /// cookies of the longer paths first. `None` when no cookie matches.
+    pub fn header(&self, url: &Url) -> Option<String> {
+        let mut cookies: Vec<&Cookie> = self.cookies.iter()
+            .filter(|cookie| cookie.matches(url))
+            .collect();
+
+        if cookies.is_empty() {
+            return None;
+        }
+
+        cookies.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+
+        Some(
+            cookies.iter()
+                .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+                .collect::<Vec<String>>()
+                .join("; ")
+        )
+    }
+
+    fn remove_expired(&mut self) {
+        self.cookies.retain(|cookie| !cookie.expired());
+    }
+
+    fn sort(&mut self) {
+        self.cookies.sort_by(|a, b| {
+            (&a.domain, &a.path, &a.name).cmp(&(&b.domain, &b.path, &b.name))
+        });
+    }
+}
+
+impl Default for CookieJars {
+    fn default() -> Self { Self::new(PathBuf::from(DEFAULT_COOKIE_DIR)) }
+}
+
+impl CookieJars {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, jars: Arc::new(Mutex::new(BTreeMap::new())) }
+    }
+
+    /// Runs `f` with the jar `name`, reading it from its file the first
+    /// time. The expired cookies are deleted first.
+    pub fn with_jar<T>(&self, name: &str, f: impl FnOnce(&mut CookieJar) -> T) -> T {
+        let mut jars = self.jars.lock().unwrap();
+
+        let jar = jars.entry(String::from(name)).or_insert_with(|| {
+            load_jar(&self.path(name)).unwrap_or_else(|e| {
+                error!("Couldn't read the cookie jar {}: {}", name, e);
+                CookieJar::default()
+            })
+        });
+
+        jar.remove_expired();
+
+        f(jar)
+    }
+
+    /// Writes the jar `name` to its file.
+    pub fn save(&self, name: &str) -> Result<(), Box<dyn Error>> {
+        let path = self.path(name);
+
+        self.with_jar(name, |jar| save_jar(&path, jar))
+    }
+
+    /// The jar `name` for the client sending a request.
+    pub fn store(&self, name: &str) -> Arc<JarStore> {
+        Arc::new(JarStore { jars: self.clone(), name: String::from(name) })
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", name, COOKIE_FILE_EXT))
+    }
+}
+
+impl CookieStore for JarStore {
+    fn set_cookies(
+        &self,
+        cookie_headers: &mut dyn Iterator<Item = &HeaderValue>,
+        url: &Url,
+    ) {
+        let cookies: Vec<Cookie> = cookie_headers
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| Cookie::from_set_cookie(value, url))
+            .collect();
+
+        if cookies.is_empty() {
+            return;
+        }
+
+        self.jars.with_jar(&self.name, |jar| {
+            for cookie in cookies {
+                jar.insert(cookie);
+            }
+        });
+
+        if let Err(e) = self.jars.save(&self.name) {
+            error!("Couldn't save the cookie jar {}: {}", self.name, e);
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        self.jars.with_jar(&self.name, |jar| jar.header(url))
+            .and_then(|header| HeaderValue::from_str(&header).ok())
+    }
+}
+
+/// The name of the jar used with `environment`.
+pub fn jar_name(environment: Option<&Environment>) -> String {
+    match environment {
+        Some(environment) => String::from(environment.name()),
+        None => String::from(DEFAULT_JAR),
+    }
+}
+
+/// Reads the jar in the file at `path`. A missing file is an empty jar.
+fn load_jar(path: &Path) -> Result<CookieJar, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(CookieJar::default());
+    }
+
+    let mut jar: CookieJar = toml::from_str(&fs::read_to_string(path)?)?;
+
+    jar.sort();
+
+    Ok(jar)
+}
+
+fn save_jar(path: &Path, jar: &CookieJar) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, toml::to_string(jar)?)?;
+
+    Ok(())
+}
+
+/// The path a cookie without a `Path` attribute is sent to: the folder of
+/// the path of `url`.
+fn default_path(url: &Url) -> String {
+    match url.path().rfind('/') {
+        Some(0) | None => String::from("/"),
+        Some(i) => String::from(&url.path()[..i]),
+    }
+}
+
+/// Whether `host` is `domain` or one of its subdomains.
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host.ends_with(domain)
+            && host[..host.len() - domain.len()].ends_with('.')
+            && host.parse::<std::net::IpAddr>().is_err())
+}
+
+/// Whether a request to `path` gets the cookies of `cookie_path`.
+fn path_matches(path: &str, cookie_path: &str) -> bool {
+    path == cookie_path
+        || (path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/')
+                || path[cookie_path.len()..].starts_with('/')))
+}
+
+fn format_expiry(expires: DateTime<Utc>) -> String {
+    expires.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+/// Parses the date of an `Expires` attribute, e.g.
+/// `Wed, 21 Oct 2015 07:28:00 GMT` or the older `Wed, 21-Oct-2015 07:28:00 GMT`.
+fn parse_http_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(&date.replace('-', " "))
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn url(url: &str) -> Url { Url::parse(url).unwrap() }
+
+    #[test]
+    fn domains_match_themselves_and_their_subdomains() {
+        assert!(domain_matches("example.com", "example.com"));
+        assert!(domain_matches("api.example.com", "example.com"));
+        assert!(domain_matches("a.b.example.com", "example.com"));
+        assert!(!domain_matches("badexample.com", "example.com"));
+        assert!(!domain_matches("example.com", "api.example.com"));
+        assert!(!domain_matches("1.2.3.4", "2.3.4"));
+        assert!(domain_matches("1.2.3.4", "1.2.3.4"));
+    }
+
+    #[test]
+    fn paths_match_themselves_and_their_sub_paths() {
+        assert!(path_matches("/", "/"));
+        assert!(path_matches("/api", "/"));
+        assert!(path_matches("/api", "/api"));
+        assert!(path_matches("/api/users", "/api"));
+        assert!(path_matches("/api/users", "/api/"));
+        assert!(!path_matches("/apis", "/api"));
+        assert!(!path_matches("/", "/api"));
+    }
+
+    #[test]
+    fn default_path_is_the_folder_of_the_url() {
+        assert_eq!(default_path(&url("http://x")), "/");
+        assert_eq!(default_path(&url("http://x/login")), "/");
+        assert_eq!(default_path(&url("http://x/api/v1/login")), "/api/v1");
+        assert_eq!(default_path(&url("http://x/api/")), "/api");
+    }
+
+    #[test]
+    fn http_dates_are_parsed_in_both_formats() {
+        let expected = Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap();
+
+        assert_eq!(parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT"), Some(expected));
+        assert_eq!(parse_http_date("Wed, 21-Oct-2015 07:28:00 GMT"), Some(expected));
+        assert_eq!(parse_http_date("Wed, 21 Oct 2015 09:28:00 +0200"), Some(expected));
+        assert_eq!(parse_http_date("tomorrow"), None);
+        assert_eq!(parse_http_date(""), None);
+    }
+
+    #[test]
+    fn set_cookie_defaults_to_the_host_and_folder_of_the_url() {
+        let cookie = Cookie::from_set_cookie(
+            "session=abc; HttpOnly; Secure",
+            &url("https://API.example.com/v1/login"),
+        ).unwrap();
+
+        assert_eq!(cookie.name(), "session");
+        assert_eq!(cookie.value(), "abc");
+        assert_eq!(cookie.domain(), "api.example.com");
+        assert_eq!(cookie.path(), "/v1");
+        assert_eq!(cookie.expires(), "");
+        assert!(cookie.host_only() && cookie.secure() && cookie.http_only());
+    }
+
+    #[test]
+    fn set_cookie_reads_the_domain_path_and_expiry() {
+        let cookie = Cookie::from_set_cookie(
+            "id=1; Domain=.Example.com; Path=/api; Expires=Wed, 21 Oct 2099 07:28:00 GMT",
+            &url("https://www.example.com/"),
+        ).unwrap();
+
+        assert_eq!(cookie.domain(), "example.com");
+        assert_eq!(cookie.path(), "/api");
+        assert_eq!(cookie.expires(), "2099-10-21T07:28:00+00:00");
+        assert!(!cookie.host_only());
+
+        // Max-Age wins over Expires
+        let cookie = Cookie::from_set_cookie(
+            "id=1; Max-Age=0; Expires=Wed, 21 Oct 2099 07:28:00 GMT",
+            &url("https://www.example.com/"),
+        ).unwrap();
+
+        assert!(cookie.expired());
+    }
+
+    #[test]
+    fn set_cookie_for_another_domain_is_ignored() {
+        let from = url("https://www.example.com/");
+
+        assert!(Cookie::from_set_cookie("id=1; Domain=other.com", &from).is_none());
+        assert!(Cookie::from_set_cookie("id=1; Domain=api.example.com", &from).is_none());
+        assert!(Cookie::from_set_cookie("no equals sign", &from).is_none());
+    }
+
+    #[test]
+    fn set_cookie_for_a_top_level_domain_is_ignored() {
+        let from = url("https://www.example.com/");
+
+        assert!(Cookie::from_set_cookie("id=1; Domain=com", &from).is_none());
+        assert!(Cookie::from_set_cookie("id=1; Domain=.com", &from).is_none());
+
+        // A host without a dot keeps the cookie for itself only
+        let cookie = Cookie::from_set_cookie(
+            "id=1; Domain=localhost",
+            &url("http://localhost:8080/"),
+        ).unwrap();
+
+        assert_eq!(cookie.domain(), "localhost");
+        assert!(cookie.host_only());
+
+        assert!(
+            Cookie::from_set_cookie("id=1; Domain=other", &url("http://localhost/"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn cookies_match_their_host_path_and_scheme() {
+        let host_only = Cookie::new(
+            String::from("a"), String::from("1"),
+            String::from("example.com"), String::from("/api"),
+        );
+
+        assert!(host_only.matches(&url("http://example.com/api/users")));
+        assert!(!host_only.matches(&url("http://www.example.com/api")));
+        assert!(!host_only.matches(&url("http://example.com/")));
+
+        let secure = Cookie::from_set_cookie(
+            "b=2; Domain=example.com; Secure",
+            &url("https://example.com/"),
+        ).unwrap();
+
+        assert!(secure.matches(&url("https://www.example.com/")));
+        assert!(!secure.matches(&url("http://www.example.com/")));
+    }
+
+    #[test]
+    fn jar_replaces_same_cookies_and_sends_longer_paths_first() {
+        let mut jar = CookieJar::default();
+        let from = url("https://example.com/api/v1/x");
+
+        jar.insert(Cookie::from_set_cookie("a=1; Path=/", &from).unwrap());
+        jar.insert(Cookie::from_set_cookie("b=2", &from).unwrap());
+        jar.insert(Cookie::from_set_cookie("a=3; Path=/", &from).unwrap());
+
+        assert_eq!(jar.cookies().len(), 2);
+        assert_eq!(
+            jar.header(&url("https://example.com/api/v1/y")).as_deref(),
+            Some("b=2; a=3"),
+        );
+        assert_eq!(jar.header(&url("https://example.com/")).as_deref(), Some("a=3"));
+        assert_eq!(jar.header(&url("https://other.com/")), None);
+
+        // An expired cookie deletes the one it replaces
+        jar.insert(Cookie::from_set_cookie("a=; Path=/; Max-Age=0", &from).unwrap());
+
+        assert_eq!(jar.header(&url("https://example.com/")), None);
+    }
+}
@@ -0,0 +1,249 @@
+//! Environments: named sets of variables substituted in requests.
+//!
+//! An environment is a `.toml` file in the environments folder with one
+//! `name = "value"` pair per variable, e.g. `environments/staging.toml`:
+//!
+//! ```toml
+//! host = "https://staging.example.com"
+//! token = "abc123"
+//! ```
+//!
+//! Variables are used as `{{name}}` in the URL, query params, headers, body
+//! and authorization fields and are resolved when the request is sent.
+
+use std::{ collections::BTreeMap, error::Error, fs, path::Path };
+
+/// Folder the environments are read from, relative to the working directory.
+pub const DEFAULT_ENVIRONMENT_DIR: &str = "environments";
+
+/// Extension of the environment files.
+pub const ENVIRONMENT_FILE_EXT: &str = "toml";
+
+const VAR_START: &str = "{{";
+const VAR_END: &str = "}}";
+
+#[derive(Clone, Default)]
+pub struct Environment {
+    /// File name of the environment without the extension.
+    name: String,
+    variables: BTreeMap<String, String>,
+}
+
+/// A `{{name}}` reference found in a text.
+pub struct VariableRef<'a> {
+    /// Byte offset of the opening `{{`.
+    pub start: usize,
+
+    /// Byte offset just after the closing `}}`.
+    pub end: usize,
+
+    /// Name of the variable with the surrounding whitespace trimmed.
+    pub name: &'a str,
+}
+
+impl Environment {
+    pub fn name(&self) -> &str { &self.name }
+
+    pub fn variables(&self) -> &BTreeMap<String, String> { &self.variables }
+
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.variables.get(name)
+    }
+
+    /// Sets the variable `name` in memory, the file isn't changed.
+    pub fn set(&mut self, name: String, value: String) {
+        self.variables.insert(name, value);
+    }
+
+    /// Parses an environment from the content of its file.
+    pub fn from_toml(name: &str, content: &str)
+        -> Result<Self, Box<dyn Error>> {
+        let table: toml::Table = toml::from_str(content)?;
+        let mut variables = BTreeMap::new();
+
+        for (key, value) in table {
+            let value = match value {
+                toml::Value::String(s) => s,
+                other => other.to_string(),
+            };
+
+            variables.insert(key, value);
+        }
+
+        Ok(Self { name: String::from(name), variables })
+    }
+}
+
+/// Reads all the environments in `dir`, sorted by name. A missing `dir`
+/// means there are no environments.
+pub fn load_environments(dir: &Path) -> Result<Vec<Environment>, Box<dyn Error>> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut environments: Vec<Environment> = vec![];
+
+    for dir_entry in fs::read_dir(dir)? {
+        let path = dir_entry?.path();
+
+        let is_environment = path.is_file()
+            && path.extension().is_some_and(|ext| ext == ENVIRONMENT_FILE_EXT);
+
+        if !is_environment {
+            continue;
+        }
+
+        let name = match path.file_stem() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => { continue; }
+        };
+
+        let content = fs::read_to_string(&path)?;
+
+        environments.push(
+            Environment::from_toml(&name, &content)
+                .map_err(|e| format!("{}: {}", path.display(), e))?
+        );
+    }
+
+    environments.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(environments)
+}
+
+/// Finds the `{{name}}` references in `text`. References spanning several
+/// lines or with an empty name are ignored.
+pub fn find_variables(text: &str) -> Vec<VariableRef<'_>> {
+    let mut refs: Vec<VariableRef> = vec![];
+    let mut pos = 0;
+
+    while let Some(offset) = text[pos..].find(VAR_START) {
+        let start = pos + offset;
+        let inner_start = start + VAR_START.len();
+
+        let inner_len = match text[inner_start..].find(VAR_END) {
+            Some(len) => len,
+            None => { break; }
+        };
+
+        let inner = &text[inner_start..inner_start + inner_len];
+
+        // `{{{` is read as a `{` followed by a reference
+        if inner.contains(['{', '\n']) {
+            pos = start + 1;
+            continue;
+        }
+
+        let end = inner_start + inner_len + VAR_END.len();
+        let name = inner.trim();
+
+        if !name.is_empty() {
+            refs.push(VariableRef { start, end, name });
+        }
+
+        pos = end;
+    }
+
+    refs
+}
+
+/// Replaces the `{{name}}` references in `text` with the values from
+/// `environment`. References to undefined variables are left as they are.
+pub fn resolve(text: &str, environment: Option<&Environment>) -> String {
+    let environment = match environment {
+        Some(environment) => environment,
+        None => { return String::from(text); }
+    };
+
+    let mut resolved = String::with_capacity(text.len());
+    let mut pos = 0;
+
+    for var in find_variables(text) {
+        if let Some(value) = environment.get(var.name) {
+            resolved.push_str(&text[pos..var.start]);
+            resolved.push_str(value);
+            pos = var.end;
+        }
+    }
+
+    resolved.push_str(&text[pos..]);
+
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The `(start, end, name)` of the references of `text`.
+    fn refs(text: &str) -> Vec<(usize, usize, &str)> {
+        find_variables(text).iter()
+            .map(|var| (var.start, var.end, var.name))
+            .collect()
+    }
+
+    fn environment() -> Environment {
+        Environment::from_toml(
+            "staging",
+            "host = \"https://staging.example.com\"\nport = 8080\nself = \"{{host}}\"",
+        ).unwrap()
+    }
+
+    #[test]
+    fn find_variables_reads_names_and_offsets() {
+        assert_eq!(refs("{{host}}/users"), vec![(0, 8, "host")]);
+        assert_eq!(refs("a{{ x }}b{{y}}"), vec![(1, 8, "x"), (9, 14, "y")]);
+        assert_eq!(refs("{{a}}{{b}}"), vec![(0, 5, "a"), (5, 10, "b")]);
+        assert_eq!(refs("é{{ü}}"), vec![(2, 8, "ü")]);
+    }
+
+    #[test]
+    fn find_variables_skips_what_isnt_a_reference() {
+        assert!(refs("no variables").is_empty());
+        assert!(refs("{{}} {{  }}").is_empty());
+        assert!(refs("{{open").is_empty());
+        assert!(refs("{{a\nb}}").is_empty());
+        assert!(refs("{single}").is_empty());
+    }
+
+    #[test]
+    fn find_variables_reads_a_brace_before_a_reference() {
+        assert_eq!(refs("{{{a}}}"), vec![(1, 6, "a")]);
+        assert_eq!(refs("{{ {{b}}"), vec![(3, 8, "b")]);
+    }
+
+    #[test]
+    fn resolve_replaces_defined_variables() {
+        let env = environment();
+
+        assert_eq!(
+            resolve("{{host}}:{{ port }}/users", Some(&env)),
+            "https://staging.example.com:8080/users",
+        );
+    }
+
+    #[test]
+    fn resolve_leaves_undefined_variables() {
+        let env = environment();
+
+        assert_eq!(
+            resolve("{{host}}/{{id}}", Some(&env)),
+            "https://staging.example.com/{{id}}",
+        );
+        assert_eq!(resolve("{{host}}", None), "{{host}}");
+    }
+
+    #[test]
+    fn resolve_doesnt_resolve_values_again() {
+        assert_eq!(resolve("{{self}}", Some(&environment())), "{{host}}");
+    }
+
+    #[test]
+    fn from_toml_reads_values_as_text() {
+        let env = environment();
+
+        assert_eq!(env.name(), "staging");
+        assert_eq!(env.get("port").map(String::as_str), Some("8080"));
+        assert!(Environment::from_toml("bad", "host = ").is_err());
+    }
+}
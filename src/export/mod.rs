@@ -0,0 +1,818 @@
+//! Code snippets that reproduce the request being edited with other tools,
+//! to hand a call over to someone who doesn't use dakia.
+
+use std::{ path::Path, slice::Iter };
+
+use reqwest::{ Method, Url as ReqwestUrl };
+
+use crate::{
+    ui::state::{
+        UiState, url::Url, body::BodyContent, kv_data::PartKind,
+        auth::{ AuthScheme, ApiKeyLocation },
+    },
+    environment::resolve,
+    graphql::{ body_payload, operation_name, payload },
+};
+
+/// The tools a request can be exported for.
+#[derive(Clone, PartialEq)]
+pub enum SnippetFormat {
+    Curl,
+    HTTPie,
+    PythonRequests,
+    JsFetch,
+    RustReqwest,
+}
+
+/// The body of an [ExportRequest].
+enum ExportBody {
+    None,
+
+    /// `application/x-www-form-urlencoded` fields.
+    Form(Vec<(String, String)>),
+
+    /// `multipart/form-data` fields.
+    Multipart(Vec<ExportPart>),
+
+    /// Any other body, sent as it is.
+    Raw(String),
+}
+
+/// A part of a multipart body.
+struct ExportPart {
+    name: String,
+
+    /// The text, or the path of the file for file parts.
+    value: String,
+    is_file: bool,
+    filename: String,
+    content_type: String,
+}
+
+/// The request being edited with the `{{variables}}` resolved, in the shape
+/// the snippets need.
+struct ExportRequest {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    basic_auth: Option<(String, String)>,
+    body: ExportBody,
+}
+
+impl SnippetFormat {
+    pub fn to_str(&self) -> &str {
+        match self {
+            SnippetFormat::Curl => "curl",
+            SnippetFormat::HTTPie => "HTTPie",
+            SnippetFormat::PythonRequests => "Python requests",
+            SnippetFormat::JsFetch => "JavaScript fetch",
+            SnippetFormat::RustReqwest => "Rust reqwest",
+        }
+    }
+
+    pub fn iter() -> Iter<'static, SnippetFormat> {
+        static SNIPPET_FORMATS: [SnippetFormat; 5] = [
+            SnippetFormat::Curl,
+            SnippetFormat::HTTPie,
+            SnippetFormat::PythonRequests,
+            SnippetFormat::JsFetch,
+            SnippetFormat::RustReqwest,
+        ];
+
+        SNIPPET_FORMATS.iter()
+    }
+}
+
+impl ExportRequest {
+    fn from_uistate(uistate: &UiState) -> Self {
+        let env = uistate.active_environment();
+        let var = |text: String| resolve(&text, env);
+
+        let mut url = Url::default();
+        url.update(var(uistate.url()));
+
+        let mut url = url.to_string();
+        let mut headers: Vec<(String, String)> = uistate.request_headers()
+            .iter()
+            .filter(|h| !h.key().is_empty() && !h.value().is_empty())
+            .map(|h| (var(h.key()), var(h.value())))
+            .collect();
+
+        let mut basic_auth = None;
+        let auth = uistate.auth();
+
+        // The authorization replaces the typed headers of the same name
+        // when the request is sent
+
+        match auth.auth_scheme() {
+            AuthScheme::NoAuth => {}
+
+            AuthScheme::Basic => {
+                if !auth.username().is_empty() || !auth.password().is_empty() {
+                    remove_header(&mut headers, "Authorization");
+                    basic_auth = Some(
+                        (var(auth.username()), var(auth.password()))
+                    );
+                }
+            }
+
+            AuthScheme::Bearer => {
+                if !auth.token().is_empty() {
+                    remove_header(&mut headers, "Authorization");
+                    headers.push((
+                        String::from("Authorization"),
+                        format!("Bearer {}", var(auth.token())),
+                    ));
+                }
+            }
+
+            AuthScheme::ApiKey => {
+                if !auth.api_key().is_empty() {
+                    let key = var(auth.api_key());
+                    let value = var(auth.api_key_value());
+
+                    match auth.api_key_location() {
+                        ApiKeyLocation::Header => {
+                            remove_header(&mut headers, &key);
+                            headers.push((key, value));
+                        }
+
+                        // Encoded the way reqwest encodes it when the
+                        // request is sent. A url reqwest can't parse isn't
+                        // sent, the key is shown as it is typed then.
+                        ApiKeyLocation::QueryParam => match ReqwestUrl::parse(&url) {
+                            Ok(mut parsed) => {
+                                parsed.query_pairs_mut().append_pair(&key, &value);
+                                url = parsed.to_string();
+                            }
+
+                            Err(_) => {
+                                url.push(if url.contains('?') { '&' } else { '?' });
+                                url.push_str(&format!("{}={}", key, value));
+                            }
+                        },
+                    }
+                }
+            }
+        }
+
+        let body = uistate.body();
+
+        // GET requests are sent without their body
+        let body_content = match uistate.method() {
+            Method::GET => &BodyContent::NONE,
+            _ => body.body_content(),
+        };
+
+        let kv = || -> Vec<(String, String)> {
+            body.kv_data().iter()
+                .filter(|kv| !kv.key().is_empty())
+                .map(|kv| (var(kv.key()), var(kv.value())))
+                .collect()
+        };
+
+        let content_type = match body_content {
+            BodyContent::Json | BodyContent::GraphQL => Some("application/json"),
+            BodyContent::Xml => Some("application/xml"),
+            BodyContent::Html => Some("text/html"),
+            BodyContent::Text => Some("text/plain"),
+            _ => None,
+        };
+
+        let parts = || -> Vec<ExportPart> {
+            body.kv_data().iter()
+                .filter(|kv| !kv.key().is_empty())
+                .map(|kv| ExportPart {
+                    name: var(kv.key()),
+                    value: var(kv.value()),
+                    is_file: *kv.part_kind() == PartKind::File,
+                    filename: var(kv.filename()),
+                    content_type: var(kv.content_type()),
+                })
+                .collect()
+        };
+
+        let export_body = match body_content {
+            BodyContent::NONE => ExportBody::None,
+            BodyContent::FormData => ExportBody::Multipart(parts()),
+            BodyContent::FormURLEncoded => ExportBody::Form(kv()),
+
+            // Variables that aren't valid JSON are left out of the snippet
+            BodyContent::GraphQL => ExportBody::Raw(
+                body_payload(body, var).unwrap_or_else(|_| {
+                    let query = var(body.text_data().text());
+                    let name = operation_name(&query, body.operation());
+
+                    payload(&query, "", name).unwrap_or_default()
+                })
+            ),
+
+            _ => ExportBody::Raw(var(body.text_data().text())),
+        };
+
+        if let Some(content_type) = content_type {
+            if !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case("content-type")) {
+                headers.push((
+                    String::from("Content-Type"),
+                    String::from(content_type),
+                ));
+            }
+        }
+
+        Self {
+            method: String::from(uistate.method().as_str()),
+            url,
+            headers,
+            basic_auth,
+            body: export_body,
+        }
+    }
+}
+
+/// Removes the headers named `name`, ignoring the case.
+fn remove_header(headers: &mut Vec<(String, String)>, name: &str) {
+    headers.retain(|(header, _)| !header.eq_ignore_ascii_case(name));
+}
+
+/// Generates the snippet for the request being edited in `uistate`.
+pub fn snippet(format: &SnippetFormat, uistate: &UiState) -> String {
+    let request = ExportRequest::from_uistate(uistate);
+
+    match format {
+        SnippetFormat::Curl => curl(&request),
+        SnippetFormat::HTTPie => httpie(&request),
+        SnippetFormat::PythonRequests => python_requests(&request),
+        SnippetFormat::JsFetch => js_fetch(&request),
+        SnippetFormat::RustReqwest => rust_reqwest(&request),
+    }
+}
+
+fn curl(request: &ExportRequest) -> String {
+    let mut lines = vec![format!(
+        "curl -X {} {}",
+        request.method,
+        shell_quote(&request.url),
+    )];
+
+    for (key, value) in request.headers.iter() {
+        lines.push(format!("-H {}", shell_quote(&format!("{}: {}", key, value))));
+    }
+
+    if let Some((username, password)) = &request.basic_auth {
+        lines.push(format!(
+            "-u {}",
+            shell_quote(&format!("{}:{}", username, password)),
+        ));
+    }
+
+    match &request.body {
+        ExportBody::None => {}
+
+        ExportBody::Form(fields) => {
+            for (key, value) in fields {
+                lines.push(format!(
+                    "--data-urlencode {}",
+                    shell_quote(&format!("{}={}", key, value)),
+                ));
+            }
+        }
+
+        ExportBody::Multipart(parts) => {
+            for part in parts {
+                let mut field = format!("{}=", part.name);
+
+                if part.is_file {
+                    field.push('@');
+                } else if part.content_type.is_empty() {
+                    // Sent as it is, even when it starts with `@` or `<`
+                    lines.push(format!(
+                        "--form-string {}",
+                        shell_quote(&format!("{}={}", part.name, part.value)),
+                    ));
+
+                    continue;
+                }
+
+                field.push_str(&part.value);
+
+                if !part.filename.is_empty() {
+                    field.push_str(&format!(";filename={}", part.filename));
+                }
+
+                if !part.content_type.is_empty() {
+                    field.push_str(&format!(";type={}", part.content_type));
+                }
+
+                lines.push(format!("-F {}", shell_quote(&field)));
+            }
+        }
+
+        ExportBody::Raw(text) => {
+            lines.push(format!("--data-raw {}", shell_quote(text)));
+        }
+    }
+
+    lines.join(" \\\n  ")
+}
+
+fn httpie(request: &ExportRequest) -> String {
+    let mut lines = vec![];
+
+    match &request.body {
+        ExportBody::Form(_) => { lines.push(String::from("http --form")); }
+        ExportBody::Multipart(_) => {
+            lines.push(String::from("http --multipart"));
+        }
+        _ => { lines.push(String::from("http")); }
+    }
+
+    lines[0].push_str(&format!(
+        " {} {}",
+        request.method,
+        shell_quote(&request.url),
+    ));
+
+    if let Some((username, password)) = &request.basic_auth {
+        lines.push(format!(
+            "-a {}",
+            shell_quote(&format!("{}:{}", username, password)),
+        ));
+    }
+
+    for (key, value) in request.headers.iter() {
+        lines.push(shell_quote(&format!("{}:{}", key, value)));
+    }
+
+    match &request.body {
+        ExportBody::None => {}
+
+        ExportBody::Form(fields) => {
+            for (key, value) in fields {
+                lines.push(shell_quote(&format!("{}={}", key, value)));
+            }
+        }
+
+        ExportBody::Multipart(parts) => {
+            for part in parts {
+                let mut field = if part.is_file {
+                    format!("{}@{}", part.name, part.value)
+                } else {
+                    format!("{}={}", part.name, part.value)
+                };
+
+                if part.is_file && !part.content_type.is_empty() {
+                    field.push_str(&format!(";type={}", part.content_type));
+                }
+
+                lines.push(shell_quote(&field));
+            }
+        }
+
+        ExportBody::Raw(text) => {
+            lines.push(format!("--raw {}", shell_quote(text)));
+        }
+    }
+
+    lines.join(" \\\n  ")
+}
+
+fn python_requests(request: &ExportRequest) -> String {
+    let mut code = String::from("import requests\n\n");
+    let mut args = vec![
+        quote(&request.method),
+        String::from("url"),
+    ];
+
+    code.push_str(&format!("url = {}\n", quote(&request.url)));
+
+    if !request.headers.is_empty() {
+        code.push_str(&format!("headers = {}\n", dict(&request.headers, "    ")));
+        args.push(String::from("headers=headers"));
+    }
+
+    match &request.body {
+        ExportBody::None => {}
+
+        ExportBody::Form(fields) => {
+            code.push_str(&format!("data = {}\n", dict(fields, "    ")));
+            args.push(String::from("data=data"));
+        }
+
+        ExportBody::Multipart(parts) => {
+            // A `None` file name sends the field as text
+            let files: Vec<String> = parts.iter()
+                .map(|part| {
+                    let mut fields = if part.is_file {
+                        vec![
+                            quote(&part_filename(part)),
+                            format!("open({}, \"rb\")", quote(&part.value)),
+                        ]
+                    } else {
+                        vec![String::from("None"), quote(&part.value)]
+                    };
+
+                    if !part.content_type.is_empty() {
+                        fields.push(quote(&part.content_type));
+                    }
+
+                    format!("    {}: ({}),\n", quote(&part.name), fields.join(", "))
+                })
+                .collect();
+
+            code.push_str(&format!("files = {{\n{}}}\n", files.join("")));
+            args.push(String::from("files=files"));
+        }
+
+        ExportBody::Raw(text) => {
+            code.push_str(&format!("data = {}\n", quote(text)));
+            args.push(String::from("data=data"));
+        }
+    }
+
+    if let Some((username, password)) = &request.basic_auth {
+        args.push(format!("auth=({}, {})", quote(username), quote(password)));
+    }
+
+    code.push_str(&format!(
+        "\nresponse = requests.request({})\n\nprint(response.status_code)\nprint(response.text)\n",
+        args.join(", "),
+    ));
+
+    code
+}
+
+fn js_fetch(request: &ExportRequest) -> String {
+    let mut code = String::new();
+    let mut options = vec![format!("  method: {},\n", quote(&request.method))];
+
+    let mut header_lines: Vec<String> = request.headers.iter()
+        .map(|(k, v)| format!("    {}: {},\n", quote(k), quote(v)))
+        .collect();
+
+    if let Some((username, password)) = &request.basic_auth {
+        header_lines.push(format!(
+            "    \"Authorization\": \"Basic \" + btoa({}),\n",
+            quote(&format!("{}:{}", username, password)),
+        ));
+    }
+
+    if !header_lines.is_empty() {
+        options.push(format!("  headers: {{\n{}  }},\n", header_lines.join("")));
+    }
+
+    match &request.body {
+        ExportBody::None => {}
+
+        ExportBody::Form(fields) => {
+            let field_lines: Vec<String> = fields.iter()
+                .map(|(k, v)| format!("    [{}, {}],\n", quote(k), quote(v)))
+                .collect();
+
+            options.push(format!(
+                "  body: new URLSearchParams([\n{}  ]),\n",
+                field_lines.join(""),
+            ));
+        }
+
+        ExportBody::Multipart(parts) => {
+            if parts.iter().any(|part| part.is_file) {
+                code.push_str("import fs from \"node:fs\";\n\n");
+            }
+
+            code.push_str("const formData = new FormData();\n");
+
+            for part in parts {
+                let blob_options = if part.content_type.is_empty() {
+                    String::new()
+                } else {
+                    format!(", {{ type: {} }}", quote(&part.content_type))
+                };
+
+                let value = if part.is_file {
+                    format!(
+                        "await fs.openAsBlob({}{}), {}",
+                        quote(&part.value),
+                        blob_options,
+                        quote(&part_filename(part)),
+                    )
+                } else if !part.content_type.is_empty() {
+                    format!("new Blob([{}]{})", quote(&part.value), blob_options)
+                } else {
+                    quote(&part.value)
+                };
+
+                code.push_str(&format!(
+                    "formData.append({}, {});\n",
+                    quote(&part.name),
+                    value,
+                ));
+            }
+
+            code.push('\n');
+            options.push(String::from("  body: formData,\n"));
+        }
+
+        ExportBody::Raw(text) => {
+            options.push(format!("  body: {},\n", quote(text)));
+        }
+    }
+
+    code.push_str(&format!(
+        "const response = await fetch({}, {{\n{}}});\n\nconsole.log(response.status);\nconsole.log(await response.text());\n",
+        quote(&request.url),
+        options.join(""),
+    ));
+
+    code
+}
+
+fn rust_reqwest(request: &ExportRequest) -> String {
+    let mut code = String::from(
+        "let client = reqwest::blocking::Client::new();\n\nlet response = client\n"
+    );
+
+    code.push_str(&format!(
+        "    .request(reqwest::Method::{}, {})\n",
+        request.method,
+        quote(&request.url),
+    ));
+
+    for (key, value) in request.headers.iter() {
+        code.push_str(&format!("    .header({}, {})\n", quote(key), quote(value)));
+    }
+
+    if let Some((username, password)) = &request.basic_auth {
+        code.push_str(&format!(
+            "    .basic_auth({}, Some({}))\n",
+            quote(username),
+            quote(password),
+        ));
+    }
+
+    match &request.body {
+        ExportBody::None => {}
+
+        ExportBody::Form(fields) => {
+            let pairs: Vec<String> = fields.iter()
+                .map(|(k, v)| format!("({}, {})", quote(k), quote(v)))
+                .collect();
+
+            code.push_str(&format!("    .form(&[{}])\n", pairs.join(", ")));
+        }
+
+        ExportBody::Multipart(parts) => {
+            code.push_str("    .multipart(\n        reqwest::blocking::multipart::Form::new()\n");
+
+            for part in parts {
+                if part.filename.is_empty() && part.content_type.is_empty() {
+                    code.push_str(&format!(
+                        "            .{}({}, {}){}\n",
+                        if part.is_file { "file" } else { "text" },
+                        quote(&part.name),
+                        quote(&part.value),
+                        if part.is_file { "?" } else { "" },
+                    ));
+
+                    continue;
+                }
+
+                let mut part_code = if part.is_file {
+                    format!(
+                        "reqwest::blocking::multipart::Part::file({})?",
+                        quote(&part.value),
+                    )
+                } else {
+                    format!(
+                        "reqwest::blocking::multipart::Part::text({})",
+                        quote(&part.value),
+                    )
+                };
+
+                if !part.filename.is_empty() {
+                    part_code.push_str(&format!(
+                        ".file_name({})",
+                        quote(&part.filename),
+                    ));
+                }
+
+                if !part.content_type.is_empty() {
+                    part_code.push_str(&format!(
+                        ".mime_str({})?",
+                        quote(&part.content_type),
+                    ));
+                }
+
+                code.push_str(&format!(
+                    "            .part({}, {})\n",
+                    quote(&part.name),
+                    part_code,
+                ));
+            }
+
+            code.push_str("    )\n");
+        }
+
+        ExportBody::Raw(text) => {
+            code.push_str(&format!("    .body({})\n", quote(text)));
+        }
+    }
+
+    code.push_str(
+        "    .send()?;\n\nprintln!(\"{}\", response.status());\nprintln!(\"{}\", response.text()?);\n"
+    );
+
+    code
+}
+
+/// File name of a file part: the one set on the part or the name of the
+/// file.
+fn part_filename(part: &ExportPart) -> String {
+    if !part.filename.is_empty() {
+        return part.filename.clone();
+    }
+
+    Path::new(&part.value)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Quotes `text` for a POSIX shell.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// Quotes `text` as a double quoted string literal, which reads the same in
+/// Python, JavaScript and Rust.
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+
+    quoted.push('"');
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+
+    quoted
+}
+
+/// A Python dict literal of `pairs`.
+fn dict(pairs: &[(String, String)], indent: &str) -> String {
+    let entries: Vec<String> = pairs.iter()
+        .map(|(k, v)| format!("{}{}: {},\n", indent, quote(k), quote(v)))
+        .collect();
+
+    format!("{{\n{}}}", entries.join(""))
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{ Engine as _, engine::general_purpose::STANDARD as BASE64 };
+
+    use super::*;
+    use crate::{ api::build_request, ui::state::kv_data::KVData };
+
+    /// A request with the typed `headers`, authorized with `scheme`.
+    fn authorized(scheme: AuthScheme, headers: &[(&str, &str)]) -> UiState {
+        let mut uistate = UiState::default();
+
+        uistate.load_url(String::from("https://example.com"));
+
+        *uistate.request_headers_mut() = headers.iter()
+            .map(|(name, value)| KVData::new(String::from(*name), String::from(*value)))
+            .collect();
+
+        let auth = uistate.auth_mut();
+
+        auth.set_auth_scheme(scheme);
+        auth.set_username(String::from("ada"));
+        auth.set_password(String::from("s3cret"));
+        auth.set_token(String::from("t0ken"));
+        auth.set_api_key(String::from("X-Api-Key"));
+        auth.set_api_key_value(String::from("k3y"));
+        auth.set_api_key_location(ApiKeyLocation::Header);
+
+        uistate
+    }
+
+    /// The values of the header `name` of the request, as it is sent and as
+    /// it is exported.
+    fn sent_and_exported(uistate: &UiState, name: &str) -> (Vec<String>, Vec<String>) {
+        let sent = build_request(uistate, uistate.method(), None).unwrap()
+            .build().unwrap();
+        let exported = ExportRequest::from_uistate(uistate);
+
+        let mut exported_values: Vec<String> = exported.headers.iter()
+            .filter(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+            .collect();
+
+        if let Some((username, password)) = exported.basic_auth {
+            let credentials = BASE64.encode(format!("{}:{}", username, password));
+            exported_values.push(format!("Basic {}", credentials));
+        }
+
+        let sent_values = sent.headers().get_all(name).iter()
+            .map(|value| String::from(value.to_str().unwrap()))
+            .collect();
+
+        (sent_values, exported_values)
+    }
+
+    #[test]
+    fn basic_auth_replaces_a_typed_authorization() {
+        let uistate = authorized(AuthScheme::Basic, &[("authorization", "Token x")]);
+        let (sent, exported) = sent_and_exported(&uistate, "Authorization");
+
+        assert_eq!(sent, vec!["Basic YWRhOnMzY3JldA=="]);
+        assert_eq!(exported, sent);
+    }
+
+    #[test]
+    fn bearer_auth_replaces_a_typed_authorization() {
+        let uistate = authorized(AuthScheme::Bearer, &[("AUTHORIZATION", "Token x")]);
+        let (sent, exported) = sent_and_exported(&uistate, "Authorization");
+
+        assert_eq!(sent, vec!["Bearer t0ken"]);
+        assert_eq!(exported, sent);
+    }
+
+    #[test]
+    fn header_api_key_replaces_a_typed_header() {
+        let uistate = authorized(
+            AuthScheme::ApiKey,
+            &[("x-api-key", "typed"), ("Accept", "text/plain")],
+        );
+        let (sent, exported) = sent_and_exported(&uistate, "X-Api-Key");
+
+        assert_eq!(sent, vec!["k3y"]);
+        assert_eq!(exported, sent);
+        assert_eq!(sent_and_exported(&uistate, "Accept").1, vec!["text/plain"]);
+    }
+
+    #[test]
+    fn typed_authorization_is_kept_without_auth() {
+        let uistate = authorized(AuthScheme::NoAuth, &[("Authorization", "Token x")]);
+        let (sent, exported) = sent_and_exported(&uistate, "Authorization");
+
+        assert_eq!(sent, vec!["Token x"]);
+        assert_eq!(exported, sent);
+    }
+
+    #[test]
+    fn query_api_key_is_encoded_as_it_is_sent() {
+        let mut uistate = UiState::default();
+
+        uistate.load_url(String::from("https://example.com/search?q=a"));
+
+        let auth = uistate.auth_mut();
+
+        auth.set_auth_scheme(AuthScheme::ApiKey);
+        auth.set_api_key(String::from("api key"));
+        auth.set_api_key_value(String::from("a&b=c d"));
+        auth.set_api_key_location(ApiKeyLocation::QueryParam);
+
+        let request = ExportRequest::from_uistate(&uistate);
+
+        let sent = build_request(&uistate, Method::GET, None).unwrap()
+            .build().unwrap();
+
+        assert_eq!(
+            request.url,
+            "https://example.com/search?q=a&api+key=a%26b%3Dc+d",
+        );
+        assert_eq!(request.url, sent.url().as_str());
+    }
+
+    #[test]
+    fn get_requests_are_exported_without_a_body() {
+        let mut uistate = UiState::default();
+
+        uistate.load_url(String::from("https://example.com"));
+        uistate.body_mut().select_body_content(BodyContent::Json);
+        uistate.body_mut().text_data_mut().load_text("{}");
+
+        let request = ExportRequest::from_uistate(&uistate);
+
+        assert!(matches!(request.body, ExportBody::None));
+        assert!(request.headers.is_empty());
+
+        uistate.set_method(Method::POST);
+
+        let request = ExportRequest::from_uistate(&uistate);
+
+        assert!(matches!(request.body, ExportBody::Raw(ref text) if text == "{}"));
+        assert_eq!(request.headers, vec![
+            (String::from("Content-Type"), String::from("application/json")),
+        ]);
+    }
+}
@@ -0,0 +1,695 @@
+//! GraphQL requests.
+//!
+//! The "GraphQL" body sends the query of the body editor and the variables
+//! of the variables editor as a JSON payload. The schema of the endpoint is
+//! read with the introspection query, for the completion of the fields and
+//! arguments and for the schema browser.
+
+pub mod schema;
+pub mod worker;
+
+use serde_json::{ Map, Value };
+
+use schema::Schema;
+use crate::ui::state::body::Body;
+
+/// The kinds of operations of a query document.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OperationKind {
+    Query,
+    Mutation,
+    Subscription,
+}
+
+impl OperationKind {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::Mutation => "mutation",
+            Self::Subscription => "subscription",
+        }
+    }
+
+    /// The kind of the operations starting with `keyword`.
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "query" => Some(Self::Query),
+            "mutation" => Some(Self::Mutation),
+            "subscription" => Some(Self::Subscription),
+            _ => None,
+        }
+    }
+}
+
+/// An operation defined in the query document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Operation {
+    kind: OperationKind,
+
+    /// `None` for anonymous operations.
+    name: Option<String>,
+}
+
+impl Operation {
+    pub fn name(&self) -> Option<&str> { self.name.as_deref() }
+
+    /// The name shown in the operation select.
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous {})", self.kind.to_str()),
+        }
+    }
+}
+
+/// An error of the `errors` array of a GraphQL response.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphQLError {
+    message: String,
+
+    /// The path of the field that failed, e.g. `user.friends[0].name`.
+    path: String,
+
+    /// The (line, column) positions in the query the error is about.
+    locations: Vec<(u64, u64)>,
+}
+
+impl GraphQLError {
+    pub fn message(&self) -> &str { &self.message }
+    pub fn path(&self) -> &str { &self.path }
+    pub fn locations(&self) -> &Vec<(u64, u64)> { &self.locations }
+}
+
+/// A completion of the query editor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Completion {
+    label: String,
+
+    /// The type of the field or the argument, the kind of the type.
+    detail: String,
+}
+
+impl Completion {
+    fn new(label: &str, detail: String) -> Self {
+        Self { label: String::from(label), detail }
+    }
+
+    pub fn label(&self) -> &str { &self.label }
+    pub fn detail(&self) -> &str { &self.detail }
+}
+
+/// A token of the query document. Strings, numbers and variables are all
+/// values, which the completion doesn't look into.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Name(String),
+    Punct(char),
+    Spread,
+    Value,
+}
+
+/// What is completed at the cursor.
+enum Context {
+    /// The keywords starting a definition.
+    Keywords,
+
+    /// The fields of the type.
+    Fields(String),
+
+    /// The arguments of the field (type, field).
+    Arguments(String, String),
+
+    /// The type condition of an inline fragment in the selection of a type.
+    Types(Option<String>),
+
+    None,
+}
+
+/// Splits `text` into tokens, skipping the whitespace, the commas and the
+/// comments.
+fn tokens(text: &str) -> Vec<Token> {
+    let chars: Vec<char> = text.chars().collect();
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        match c {
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+
+            '"' if chars[i..].starts_with(&['"', '"', '"']) => {
+                i += 3;
+
+                while i < chars.len() && !chars[i..].starts_with(&['"', '"', '"']) {
+                    i += 1;
+                }
+
+                i += 3;
+                tokens.push(Token::Value);
+            }
+
+            '"' => {
+                i += 1;
+
+                while i < chars.len() && chars[i] != '"' && chars[i] != '\n' {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+
+                i += 1;
+                tokens.push(Token::Value);
+            }
+
+            '.' if chars[i..].starts_with(&['.', '.', '.']) => {
+                i += 3;
+                tokens.push(Token::Spread);
+            }
+
+            '$' | '-' | '0'..='9' => {
+                i += 1;
+
+                while i < chars.len() && (is_name(chars[i]) || chars[i] == '.') {
+                    i += 1;
+                }
+
+                tokens.push(Token::Value);
+            }
+
+            c if is_name(c) => {
+                let start = i;
+
+                while i < chars.len() && is_name(chars[i]) {
+                    i += 1;
+                }
+
+                tokens.push(Token::Name(chars[start..i].iter().collect()));
+            }
+
+            c if c.is_whitespace() || c == ',' => { i += 1; }
+
+            c => {
+                i += 1;
+                tokens.push(Token::Punct(c));
+            }
+        }
+    }
+
+    tokens
+}
+
+/// The operations defined in `query`, in order.
+pub fn operations(query: &str) -> Vec<Operation> {
+    let tokens = tokens(query);
+    let mut operations = vec![];
+    let mut depth = 0;
+    let mut parens = 0;
+
+    // Whether the definition of the next selection set was read
+    let mut defined = false;
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Punct('(') => { parens += 1; }
+            Token::Punct(')') => { parens -= 1; }
+
+            Token::Punct('{') if parens == 0 => {
+                // A query without the `query` keyword
+                if depth == 0 && !defined {
+                    operations.push(Operation {
+                        kind: OperationKind::Query,
+                        name: None,
+                    });
+                }
+
+                defined = false;
+                depth += 1;
+            }
+
+            Token::Punct('}') if parens == 0 => {
+                depth = (depth - 1).max(0);
+            }
+
+            Token::Name(keyword) if depth == 0 && parens == 0 && !defined => {
+                defined = true;
+
+                if let Some(kind) = OperationKind::from_keyword(keyword) {
+                    let name = match tokens.get(i + 1) {
+                        Some(Token::Name(name)) => Some(name.clone()),
+                        _ => None,
+                    };
+
+                    operations.push(Operation { kind, name });
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    operations
+}
+
+/// The `operationName` to send: the `selected` operation if the query
+/// defines it, otherwise the first one when there are several.
+pub fn operation_name(query: &str, selected: &str) -> Option<String> {
+    let operations = operations(query);
+    let names: Vec<&str> = operations.iter()
+        .filter_map(|operation| operation.name())
+        .collect();
+
+    if !selected.is_empty() && names.contains(&selected) {
+        return Some(String::from(selected));
+    }
+
+    match operations.len() > 1 {
+        true => names.first().map(|name| String::from(*name)),
+        false => None,
+    }
+}
+
+/// The JSON payload of a GraphQL request. The `variables` must be a JSON
+/// object, or empty to send none.
+pub fn payload(query: &str, variables: &str, operation_name: Option<String>)
+    -> Result<String, String> {
+    let mut payload = Map::new();
+
+    payload.insert(String::from("query"), Value::String(String::from(query)));
+
+    if !variables.trim().is_empty() {
+        let variables: Value = serde_json::from_str(variables)
+            .map_err(|e| format!("The GraphQL variables aren't valid JSON: {}", e))?;
+
+        if !variables.is_object() && !variables.is_null() {
+            return Err(String::from("The GraphQL variables must be a JSON object"));
+        }
+
+        payload.insert(String::from("variables"), variables);
+    }
+
+    if let Some(name) = operation_name {
+        payload.insert(String::from("operationName"), Value::String(name));
+    }
+
+    Ok(Value::Object(payload).to_string())
+}
+
+/// The payload of the GraphQL `body`, with the `{{variables}}` of the query
+/// and the variables resolved by `var`.
+pub fn body_payload(body: &Body, var: impl Fn(String) -> String)
+    -> Result<String, String> {
+    let query = var(body.text_data().text());
+
+    payload(
+        &query,
+        &var(body.variables().text()),
+        operation_name(&query, body.operation()),
+    )
+}
+
+/// The errors of the `errors` array of a GraphQL response body. Bodies that
+/// aren't GraphQL responses have none.
+pub fn errors(body: &str) -> Vec<GraphQLError> {
+    let response: Value = match serde_json::from_str(body) {
+        Ok(response) => response,
+        Err(_) => { return vec![]; }
+    };
+
+    let errors = match response.get("errors").and_then(Value::as_array) {
+        Some(errors) => errors,
+        None => { return vec![]; }
+    };
+
+    errors.iter()
+        .map(|error| {
+            let message = match error.get("message") {
+                Some(Value::String(message)) => message.clone(),
+                _ => error.to_string(),
+            };
+
+            let mut path = String::new();
+
+            for segment in error.get("path").and_then(Value::as_array).into_iter().flatten() {
+                match segment {
+                    Value::Number(index) => { path.push_str(&format!("[{}]", index)); }
+
+                    Value::String(name) => {
+                        if !path.is_empty() {
+                            path.push('.');
+                        }
+
+                        path.push_str(name);
+                    }
+
+                    _ => {}
+                }
+            }
+
+            let locations = error.get("locations").and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|location| Some((
+                    location.get("line")?.as_u64()?,
+                    location.get("column")?.as_u64()?,
+                )))
+                .collect();
+
+            GraphQLError { message, path, locations }
+        })
+        .collect()
+}
+
+/// The completions for the word being typed at the end of `before`, the
+/// query before the cursor. Returns the part of the word typed already and
+/// the completions starting with it.
+pub fn completions(schema: &Schema, before: &str) -> (String, Vec<Completion>) {
+    let prefix: String = before.chars().rev()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect::<Vec<char>>()
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tokens = tokens(&before[..before.len() - prefix.len()]);
+
+    let completions = match context(schema, &tokens) {
+        Context::Keywords => ["query", "mutation", "subscription", "fragment"]
+            .iter()
+            .map(|keyword| Completion::new(keyword, String::from("keyword")))
+            .collect(),
+
+        Context::Fields(type_name) => {
+            let mut fields: Vec<Completion> = schema.type_named(&type_name)
+                .map(|t| t.fields())
+                .unwrap_or_default()
+                .iter()
+                .map(|field| Completion::new(field.name(), field.type_ref().to_string()))
+                .collect();
+
+            fields.push(Completion::new("__typename", String::from("String!")));
+            fields
+        }
+
+        Context::Arguments(type_name, field) => schema.type_named(&type_name)
+            .and_then(|t| t.field(&field))
+            .map(|field| field.args())
+            .unwrap_or_default()
+            .iter()
+            .map(|arg| Completion::new(arg.name(), arg.type_ref().to_string()))
+            .collect(),
+
+        Context::Types(type_name) => {
+            let possible = type_name.as_deref()
+                .and_then(|name| schema.type_named(name))
+                .map(|t| t.possible_types())
+                .unwrap_or_default();
+
+            match possible.is_empty() {
+                true => schema.types().into_iter()
+                    .filter(|t| t.kind() == "OBJECT")
+                    .map(|t| Completion::new(t.name(), String::from(t.kind())))
+                    .collect(),
+
+                false => possible.iter()
+                    .map(|t| Completion::new(t.named(), String::from("OBJECT")))
+                    .collect(),
+            }
+        }
+
+        Context::None => vec![],
+    };
+
+    let lower = prefix.to_lowercase();
+
+    let completions = completions.into_iter()
+        .filter(|c| c.label.to_lowercase().starts_with(&lower) && c.label != prefix)
+        .collect();
+
+    (prefix, completions)
+}
+
+/// Finds what is completed after `tokens`, following the selection sets
+/// from the root types down to the types of the fields.
+fn context(schema: &Schema, tokens: &[Token]) -> Context {
+    // The types of the selection sets the cursor is in, `None` if unknown
+    let mut stack: Vec<Option<String>> = vec![];
+
+    // The type of the selection set opened by the next `{`
+    let mut next_type: Option<String> = None;
+
+    // The last field read and the type it is in
+    let mut field: Option<(String, String)> = None;
+
+    // The field whose arguments are being read
+    let mut args: Option<(String, String)> = None;
+    let mut parens = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|p| &tokens[p]);
+
+        if parens > 0 {
+            match token {
+                Token::Punct('(') => { parens += 1; }
+                Token::Punct(')') => { parens -= 1; }
+                _ => {}
+            }
+
+            continue;
+        }
+
+        match token {
+            Token::Punct('(') => {
+                parens = 1;
+                args = field.clone().filter(|_| !stack.is_empty());
+            }
+
+            Token::Punct('{') => {
+                // A query without the `query` keyword
+                if stack.is_empty() && next_type.is_none() {
+                    next_type = schema.root_type(OperationKind::Query)
+                        .map(|t| String::from(t.name()));
+                }
+
+                stack.push(next_type.take());
+                field = None;
+            }
+
+            Token::Punct('}') => {
+                stack.pop();
+                next_type = None;
+                field = None;
+            }
+
+            // The name of a directive
+            Token::Punct('@') => { field = None; }
+            Token::Name(_) if prev == Some(&Token::Punct('@')) => {}
+
+            // The type of a fragment or of an inline fragment
+            Token::Name(name) if prev == Some(&Token::Name(String::from("on"))) => {
+                next_type = Some(name.clone());
+            }
+
+            // `on` or a fragment spread
+            Token::Name(_) if prev == Some(&Token::Spread) => {}
+
+            Token::Name(name) => match stack.last() {
+                None => {
+                    if let Some(kind) = OperationKind::from_keyword(name) {
+                        next_type = schema.root_type(kind)
+                            .map(|t| String::from(t.name()));
+                    }
+                }
+
+                Some(parent) => {
+                    next_type = parent.as_deref()
+                        .and_then(|parent| schema.type_named(parent))
+                        .and_then(|parent| parent.field(name))
+                        .map(|field| String::from(field.type_ref().named()));
+
+                    field = parent.clone().map(|parent| (parent, name.clone()));
+                }
+            },
+
+            _ => {}
+        }
+    }
+
+    if parens > 0 {
+        return match (args, tokens.last()) {
+            // After `:` comes the value
+            (_, Some(Token::Punct(':'))) => Context::None,
+            (Some((type_name, field)), _) => Context::Arguments(type_name, field),
+            (None, _) => Context::None,
+        };
+    }
+
+    if let [.., Token::Spread, Token::Name(on)] = tokens {
+        if on == "on" {
+            return Context::Types(stack.last().cloned().flatten());
+        }
+    }
+
+    match (tokens.last(), stack.last()) {
+        (Some(Token::Spread), _) => Context::None,
+        (_, None) => Context::Keywords,
+        (_, Some(Some(type_name))) => Context::Fields(type_name.clone()),
+        (_, Some(None)) => Context::None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The introspection response of a small schema.
+    pub(crate) const INTROSPECTION: &str = r#"{"data": {"__schema": {
+        "queryType": {"name": "Query"},
+        "mutationType": {"name": "Mutation"},
+        "subscriptionType": null,
+        "types": [
+            {"kind": "OBJECT", "name": "Query", "fields": [
+                {"name": "user", "args": [
+                    {"name": "id", "type": {"kind": "NON_NULL", "name": null,
+                        "ofType": {"kind": "SCALAR", "name": "ID", "ofType": null}}}
+                ], "type": {"kind": "OBJECT", "name": "User", "ofType": null}},
+                {"name": "search", "args": [
+                    {"name": "term", "type": {"kind": "SCALAR", "name": "String"},
+                        "defaultValue": "\"\""}
+                ], "type": {"kind": "NON_NULL", "ofType": {"kind": "LIST",
+                    "ofType": {"kind": "NON_NULL", "ofType":
+                        {"kind": "UNION", "name": "SearchResult"}}}}}
+            ]},
+            {"kind": "OBJECT", "name": "Mutation", "fields": [
+                {"name": "rename", "args": [], "type": {"kind": "OBJECT", "name": "User"}}
+            ]},
+            {"kind": "OBJECT", "name": "User", "description": "A user", "fields": [
+                {"name": "id", "args": [], "type": {"kind": "NON_NULL",
+                    "ofType": {"kind": "SCALAR", "name": "ID"}}},
+                {"name": "name", "args": [],
+                    "type": {"kind": "SCALAR", "name": "String"}},
+                {"name": "friends", "args": [], "type": {"kind": "LIST",
+                    "ofType": {"kind": "OBJECT", "name": "User"}}}
+            ]},
+            {"kind": "OBJECT", "name": "Post", "fields": [
+                {"name": "title", "args": [],
+                    "type": {"kind": "SCALAR", "name": "String"}}
+            ]},
+            {"kind": "UNION", "name": "SearchResult", "fields": null, "possibleTypes": [
+                {"kind": "OBJECT", "name": "User"}, {"kind": "OBJECT", "name": "Post"}
+            ]},
+            {"kind": "ENUM", "name": "Role", "enumValues": [{"name": "ADMIN"}]},
+            {"kind": "SCALAR", "name": "ID"},
+            {"kind": "SCALAR", "name": "String"},
+            {"kind": "OBJECT", "name": "__Schema", "fields": []}
+        ]
+    }}}"#;
+
+    fn labels(query: &str) -> Vec<String> {
+        operations(query).iter().map(Operation::label).collect()
+    }
+
+    fn completed(before: &str) -> (String, Vec<String>) {
+        let schema = Schema::from_response(INTROSPECTION).unwrap();
+        let (prefix, completions) = completions(&schema, before);
+
+        (prefix, completions.iter().map(|c| String::from(c.label())).collect())
+    }
+
+    #[test]
+    fn operations_are_read_in_order() {
+        assert_eq!(
+            labels("query GetUser($id: ID!) { user(id: $id) { name } }\n\
+                mutation { rename { id } }\n\
+                fragment F on User { id }\n\
+                subscription OnPost { posts { title } }"),
+            ["GetUser", "(anonymous mutation)", "OnPost"],
+        );
+    }
+
+    #[test]
+    fn shorthand_query_is_an_anonymous_query() {
+        assert_eq!(labels("{ user { name } }"), ["(anonymous query)"]);
+        assert_eq!(labels(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn operations_skip_comments_strings_and_argument_objects() {
+        let query = "# query Commented { x }\n\
+            query Search($filter: In = { term: \"query Fake {\" }) {\n\
+                search(filter: { nested: { deep: 1 } }) { id }\n\
+            }\n\
+            query Other { id }";
+
+        assert_eq!(labels(query), ["Search", "Other"]);
+    }
+
+    #[test]
+    fn operation_name_is_the_selected_or_the_first_operation() {
+        let query = "query A { a } query B { b }";
+
+        assert_eq!(operation_name(query, "B").as_deref(), Some("B"));
+        assert_eq!(operation_name(query, "Missing").as_deref(), Some("A"));
+        assert_eq!(operation_name(query, "").as_deref(), Some("A"));
+
+        assert_eq!(operation_name("query A { a }", "A").as_deref(), Some("A"));
+
+        // A single operation doesn't need its name
+        assert_eq!(operation_name("query A { a }", ""), None);
+        assert_eq!(operation_name("{ a }", "A"), None);
+    }
+
+    #[test]
+    fn payload_checks_the_variables() {
+        let sent: Value = serde_json::from_str(
+            &payload("{ a }", "{\"id\": 1}", Some(String::from("A"))).unwrap()
+        ).unwrap();
+
+        assert_eq!(sent["query"], "{ a }");
+        assert_eq!(sent["variables"]["id"], 1);
+        assert_eq!(sent["operationName"], "A");
+
+        let sent: Value = serde_json::from_str(&payload("{ a }", " ", None).unwrap())
+            .unwrap();
+
+        assert!(sent.get("variables").is_none() && sent.get("operationName").is_none());
+        assert!(payload("{ a }", "[1]", None).is_err());
+        assert!(payload("{ a }", "{", None).is_err());
+    }
+
+    #[test]
+    fn errors_are_read_with_their_path_and_locations() {
+        let errors = errors(r#"{"errors": [
+            {"message": "denied", "path": ["user", "friends", 0, "name"],
+                "locations": [{"line": 2, "column": 5}]},
+            {"extensions": {"code": "X"}}
+        ]}"#);
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].message(), "denied");
+        assert_eq!(errors[0].path(), "user.friends[0].name");
+        assert_eq!(errors[0].locations(), &vec![(2, 5)]);
+        assert_eq!(errors[1].message(), "{\"extensions\":{\"code\":\"X\"}}");
+        assert!(super::errors("{\"data\": {}}").is_empty());
+        assert!(super::errors("not json").is_empty());
+    }
+
+    #[test]
+    fn completions_follow_the_selection_sets() {
+        let (prefix, keywords) = completed("mut");
+
+        assert_eq!(prefix, "mut");
+        assert_eq!(keywords, ["mutation"]);
+        assert_eq!(completed("{ user { fr").1, ["friends"]);
+        assert_eq!(completed("query { user { friends { na").1, ["name"]);
+        assert_eq!(completed("mutation { rename { i").1, ["id"]);
+        assert_eq!(completed("{ user(").1, ["id"]);
+        assert!(completed("{ user(id: ").1.is_empty());
+        assert_eq!(completed("{ search { ... on ").1, ["User", "Post"]);
+        assert!(completed("{ unknown { ").1.is_empty());
+    }
+}
@@ -0,0 +1,353 @@
+//! The schema of a GraphQL endpoint, as read by the introspection query.
+
+use std::fmt;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::graphql::OperationKind;
+
+/// The introspection query, with the fields the completion and the schema
+/// browser need.
+pub const INTROSPECTION_QUERY: &str = "query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types {
+      kind
+      name
+      description
+      fields(includeDeprecated: true) {
+        name
+        description
+        args { ...InputValue }
+        type { ...TypeRef }
+      }
+      inputFields { ...InputValue }
+      enumValues(includeDeprecated: true) { name description }
+      possibleTypes { ...TypeRef }
+    }
+  }
+}
+
+fragment InputValue on __InputValue {
+  name
+  description
+  type { ...TypeRef }
+  defaultValue
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType {
+    kind
+    name
+    ofType {
+      kind
+      name
+      ofType {
+        kind
+        name
+        ofType { kind name ofType { kind name ofType { kind name } } }
+      }
+    }
+  }
+}";
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schema {
+    query_type: Option<RootType>,
+    mutation_type: Option<RootType>,
+    subscription_type: Option<RootType>,
+    types: Vec<SchemaType>,
+}
+
+#[derive(Clone, Deserialize)]
+struct RootType {
+    name: String,
+}
+
+/// A named type of the schema.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaType {
+    /// `OBJECT`, `INTERFACE`, `UNION`, `ENUM`, `INPUT_OBJECT` or `SCALAR`.
+    kind: String,
+    name: String,
+
+    #[serde(default)]
+    description: Option<String>,
+
+    /// The fields of objects and interfaces, `null` for the other kinds.
+    #[serde(default)]
+    fields: Option<Vec<Field>>,
+
+    #[serde(default)]
+    input_fields: Option<Vec<InputValue>>,
+
+    #[serde(default)]
+    enum_values: Option<Vec<EnumValue>>,
+
+    /// The types of a union or the implementations of an interface.
+    #[serde(default)]
+    possible_types: Option<Vec<TypeRef>>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Field {
+    name: String,
+
+    #[serde(default)]
+    description: Option<String>,
+
+    #[serde(default)]
+    args: Vec<InputValue>,
+
+    #[serde(rename = "type")]
+    type_ref: TypeRef,
+}
+
+/// An argument of a field or a field of an input object.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputValue {
+    name: String,
+
+    #[serde(default)]
+    description: Option<String>,
+
+    #[serde(rename = "type")]
+    type_ref: TypeRef,
+
+    #[serde(default)]
+    default_value: Option<String>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct EnumValue {
+    name: String,
+
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// The type of a field or an argument: a named type, possibly wrapped in
+/// lists and non-null markers.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeRef {
+    kind: String,
+
+    #[serde(default)]
+    name: Option<String>,
+
+    #[serde(default)]
+    of_type: Option<Box<TypeRef>>,
+}
+
+impl Schema {
+    /// Reads the schema from the response to [INTROSPECTION_QUERY].
+    pub fn from_response(body: &str) -> Result<Self, String> {
+        let response: Value = serde_json::from_str(body)
+            .map_err(|e| format!("The response isn't JSON: {}", e))?;
+
+        match response.pointer("/data/__schema") {
+            Some(schema) if !schema.is_null() => {
+                serde_json::from_value(schema.clone())
+                    .map_err(|e| format!("The schema can't be read: {}", e))
+            }
+
+            // Endpoints with the introspection turned off answer with errors
+            _ => Err(super::errors(body).first()
+                .map(|e| String::from(e.message()))
+                .unwrap_or_else(|| String::from("The response has no schema"))),
+        }
+    }
+
+    /// The type the operations of `kind` start from.
+    pub fn root_type(&self, kind: OperationKind) -> Option<&SchemaType> {
+        let root = match kind {
+            OperationKind::Query => &self.query_type,
+            OperationKind::Mutation => &self.mutation_type,
+            OperationKind::Subscription => &self.subscription_type,
+        };
+
+        root.as_ref().and_then(|root| self.type_named(&root.name))
+    }
+
+    pub fn type_named(&self, name: &str) -> Option<&SchemaType> {
+        self.types.iter().find(|t| t.name == name)
+    }
+
+    /// The types of the schema by name, without the types of the
+    /// introspection itself.
+    pub fn types(&self) -> Vec<&SchemaType> {
+        let mut types: Vec<&SchemaType> = self.types.iter()
+            .filter(|t| !t.name.starts_with("__"))
+            .collect();
+
+        types.sort_by(|a, b| a.name.cmp(&b.name));
+        types
+    }
+}
+
+impl SchemaType {
+    pub fn kind(&self) -> &str { &self.kind }
+    pub fn name(&self) -> &str { &self.name }
+    pub fn description(&self) -> &str {
+        self.description.as_deref().unwrap_or_default()
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        self.fields.as_deref().unwrap_or_default()
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields().iter().find(|f| f.name == name)
+    }
+
+    pub fn input_fields(&self) -> &[InputValue] {
+        self.input_fields.as_deref().unwrap_or_default()
+    }
+
+    pub fn enum_values(&self) -> &[EnumValue] {
+        self.enum_values.as_deref().unwrap_or_default()
+    }
+
+    pub fn possible_types(&self) -> &[TypeRef] {
+        self.possible_types.as_deref().unwrap_or_default()
+    }
+}
+
+impl Field {
+    pub fn name(&self) -> &str { &self.name }
+    pub fn description(&self) -> &str {
+        self.description.as_deref().unwrap_or_default()
+    }
+
+    pub fn args(&self) -> &[InputValue] { &self.args }
+    pub fn type_ref(&self) -> &TypeRef { &self.type_ref }
+}
+
+impl InputValue {
+    pub fn name(&self) -> &str { &self.name }
+    pub fn description(&self) -> &str {
+        self.description.as_deref().unwrap_or_default()
+    }
+
+    pub fn type_ref(&self) -> &TypeRef { &self.type_ref }
+    pub fn default_value(&self) -> Option<&str> { self.default_value.as_deref() }
+}
+
+impl EnumValue {
+    pub fn name(&self) -> &str { &self.name }
+    pub fn description(&self) -> &str {
+        self.description.as_deref().unwrap_or_default()
+    }
+}
+
+impl TypeRef {
+    /// The named type inside the lists and non-null markers.
+    pub fn named(&self) -> &str {
+        match (&self.name, &self.of_type) {
+            (Some(name), _) => name,
+            (None, Some(of_type)) => of_type.named(),
+            (None, None) => "",
+        }
+    }
+}
+
+/// Writes the type as in the schema language, e.g. `[User!]!`.
+impl fmt::Display for TypeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.kind.as_str(), &self.of_type) {
+            ("NON_NULL", Some(of_type)) => write!(f, "{}!", of_type),
+            ("LIST", Some(of_type)) => write!(f, "[{}]", of_type),
+            _ => write!(f, "{}", self.named()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::tests::INTROSPECTION;
+
+    #[test]
+    fn introspection_response_is_read() {
+        let schema = Schema::from_response(INTROSPECTION).unwrap();
+
+        assert_eq!(schema.root_type(OperationKind::Query).unwrap().name(), "Query");
+        assert_eq!(schema.root_type(OperationKind::Mutation).unwrap().name(), "Mutation");
+        assert!(schema.root_type(OperationKind::Subscription).is_none());
+
+        let user = schema.type_named("User").unwrap();
+
+        assert_eq!(user.kind(), "OBJECT");
+        assert_eq!(user.description(), "A user");
+        assert_eq!(user.fields().len(), 3);
+
+        let search = schema.type_named("Query").unwrap().field("search").unwrap();
+
+        assert_eq!(search.args()[0].default_value(), Some("\"\""));
+        assert_eq!(search.args()[0].type_ref().to_string(), "String");
+
+        let union = schema.type_named("SearchResult").unwrap();
+
+        assert!(union.fields().is_empty());
+        assert_eq!(union.possible_types().len(), 2);
+        assert_eq!(schema.type_named("Role").unwrap().enum_values()[0].name(), "ADMIN");
+    }
+
+    #[test]
+    fn type_refs_are_written_as_in_the_schema_language() {
+        let schema = Schema::from_response(INTROSPECTION).unwrap();
+        let query = schema.type_named("Query").unwrap();
+        let user = schema.type_named("User").unwrap();
+
+        let search = query.field("search").unwrap().type_ref();
+
+        assert_eq!(search.to_string(), "[SearchResult!]!");
+        assert_eq!(search.named(), "SearchResult");
+        assert_eq!(user.field("friends").unwrap().type_ref().to_string(), "[User]");
+        assert_eq!(query.field("user").unwrap().args()[0].type_ref().to_string(), "ID!");
+    }
+
+    #[test]
+    fn types_are_sorted_without_the_introspection_ones() {
+        let schema = Schema::from_response(INTROSPECTION).unwrap();
+        let names: Vec<&str> = schema.types().iter().map(|t| t.name()).collect();
+
+        assert_eq!(
+            names,
+            ["ID", "Mutation", "Post", "Query", "Role", "SearchResult", "String", "User"],
+        );
+    }
+
+    #[test]
+    fn responses_without_a_schema_are_reported() {
+        assert_eq!(
+            Schema::from_response(
+                r#"{"errors": [{"message": "introspection is disabled"}]}"#
+            ).err().as_deref(),
+            Some("introspection is disabled"),
+        );
+        assert_eq!(
+            Schema::from_response(r#"{"data": {"__schema": null}}"#).err().as_deref(),
+            Some("The response has no schema"),
+        );
+        assert!(
+            Schema::from_response("<html>").err().unwrap_or_default()
+                .starts_with("The response isn't JSON")
+        );
+        assert!(
+            Schema::from_response(r#"{"data": {"__schema": {"types": 1}}}"#)
+                .err().unwrap_or_default()
+                .starts_with("The schema can't be read")
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Reads the schema of GraphQL endpoints with the introspection query from a
+//! worker thread. The schema is sent back to the `run_app` loop and cached
+//! with [receive].
+
+use std::{ thread, time::Instant };
+
+use log::info;
+use reqwest::Method;
+
+use crate::{
+    api::{ build_request, read_response, resolved_url },
+    graphql::schema::{ Schema, INTROSPECTION_QUERY },
+    ui::state::{ UiState, app_status::AppStatus, body::BodyContent },
+};
+
+/// Message sent from the worker thread back to the ui loop.
+pub struct SchemaEvent {
+    /// The url the schema was read from.
+    url: String,
+    result: Result<Schema, String>,
+}
+
+/// Reads the schema of the request url again. The introspection query is
+/// sent with the headers and the authorization of the request.
+pub fn fetch(uistate: &mut UiState) {
+    if let Err(e) = try_fetch(uistate) {
+        info!("{}", e);
+
+        uistate.set_app_error(format!("GraphQL schema: {}", e));
+        uistate.set_app_status(AppStatus::ERROR);
+    }
+}
+
+fn try_fetch(uistate: &mut UiState) -> Result<(), String> {
+    let url = resolved_url(uistate);
+
+    if uistate.graphql().fetching() == Some(url.as_str()) {
+        return Ok(());
+    }
+
+    let sender = match uistate.graphql().sender() {
+        Some(sender) => sender.clone(),
+        None => { return Err(String::from("the schema reader is not available")); }
+    };
+
+    let mut state = uistate.clone();
+    let body = state.body_mut();
+
+    body.set_body_content(BodyContent::GraphQL);
+    body.text_data_mut().load_text(INTROSPECTION_QUERY);
+    body.variables_mut().load_text("");
+    body.set_operation(String::new());
+
+    let request = build_request(&state, Method::POST, None)
+        .map_err(|e| e.to_string())?;
+
+    info!("Reading the GraphQL schema of {}", url);
+
+    uistate.graphql_mut().set_fetching(Some(url.clone()));
+    uistate.set_app_message(format!("Reading the schema of {}", url));
+    uistate.set_app_status(AppStatus::INFO);
+
+    thread::spawn(move || {
+        let started = Instant::now();
+
+        let result = request.send()
+            .map_err(|e| e.to_string())
+            .and_then(|response| {
+                read_response(response, started).map_err(|e| e.to_string())
+            })
+            .and_then(|data| Schema::from_response(data.body()));
+
+        // The receiver is gone only when the app is exiting.
+        let _ = sender.send(SchemaEvent { url, result });
+    });
+
+    Ok(())
+}
+
+/// Caches the schema read by the worker thread.
+pub fn receive(uistate: &mut UiState, event: SchemaEvent) {
+    let graphql = uistate.graphql_mut();
+
+    if graphql.fetching() == Some(event.url.as_str()) {
+        graphql.set_fetching(None);
+    }
+
+    match event.result {
+        Ok(schema) => {
+            let count = schema.types().len();
+
+            graphql.set_schema(event.url.clone(), schema);
+
+            uistate.set_app_message(format!(
+                "Read the schema of {}: {} types",
+                event.url,
+                count,
+            ));
+            uistate.set_app_status(AppStatus::INFO);
+        }
+
+        Err(e) => {
+            info!("{}", e);
+
+            uistate.set_app_error(format!("GraphQL schema of {}: {}", event.url, e));
+            uistate.set_app_status(AppStatus::ERROR);
+        }
+    }
+}
@@ -0,0 +1,444 @@
+//! History of the sent requests.
+//!
+//! Every request that is sent is recorded with its response in its own
+//! `.toml` file in the history folder, named after the time it was sent so
+//! that the files sort from the oldest to the newest.
+
+use std::{
+    error::Error, fs,
+    path::{ Path, PathBuf }, time::Duration,
+};
+
+use chrono::{ DateTime, Local };
+use log::info;
+use serde::{ Deserialize, Serialize };
+
+use crate::{
+    collection::SavedRequest,
+    ui::state::{ kv_data::KVData, response::ResponseInfo },
+    api::ResponseData,
+};
+
+/// Folder the history is kept in, relative to the working directory.
+pub const DEFAULT_HISTORY_DIR: &str = "history";
+
+/// Extension of the history files.
+pub const HISTORY_FILE_EXT: &str = "toml";
+
+/// Number of entries kept. The oldest entries are deleted first.
+pub const MAX_HISTORY_ENTRIES: usize = 500;
+
+/// A sent request and its response.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// When the request was sent, in RFC 3339 format.
+    timestamp: String,
+
+    /// The url with the variables resolved, as it was sent.
+    url: String,
+
+    /// The request as it was in the editor. The secrets typed as they are
+    /// aren't written to the file.
+    request: SavedRequest,
+
+    /// Whether a secret typed as it is was left out of the file, see
+    /// [SavedRequest::strip_secrets]. The request can't be sent again as it
+    /// was.
+    #[serde(default)]
+    secret_dropped: bool,
+
+    /// Why the request failed. Empty when a response was received.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    error: String,
+
+    #[serde(default)]
+    response: HistoryResponse,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct HistoryResponse {
+    status_code: u16,
+
+    #[serde(default)]
+    reason: String,
+
+    #[serde(default)]
+    version: String,
+
+    /// Time from sending the request to receiving the whole body.
+    #[serde(default)]
+    elapsed_ms: u64,
+
+    #[serde(default)]
+    compressed_size: usize,
+
+    #[serde(default)]
+    size: usize,
+
+    #[serde(default)]
+    headers: Vec<HistoryHeader>,
+
+    #[serde(default)]
+    body: String,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct HistoryHeader {
+    name: String,
+    value: String,
+}
+
+impl HistoryEntry {
+    /// Records the `request` sent to `url` at `sent_at`, with the `outcome`
+    /// of the worker.
+    pub fn new(
+        sent_at: DateTime<Local>,
+        url: String,
+        request: SavedRequest,
+        outcome: &Result<ResponseData, String>,
+    ) -> Self {
+        let (response, error) = match outcome {
+            Ok(data) => (HistoryResponse::from_response(data), String::new()),
+            Err(e) => (HistoryResponse::default(), e.clone()),
+        };
+
+        Self {
+            timestamp: sent_at.to_rfc3339(),
+            url,
+            request,
+            secret_dropped: false,
+            error,
+            response,
+        }
+    }
+
+    /// Time the request was sent. `None` if the file was edited by hand.
+    pub fn sent_at(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(&self.timestamp)
+            .ok()
+            .map(|time| time.with_timezone(&Local))
+    }
+
+    pub fn url(&self) -> &str { &self.url }
+    pub fn request(&self) -> &SavedRequest { &self.request }
+    pub fn method(&self) -> &str { self.request.method() }
+    pub fn secret_dropped(&self) -> bool { self.secret_dropped }
+
+    /// `None` if the request failed.
+    pub fn status_code(&self) -> Option<u16> {
+        match self.error.is_empty() {
+            true => Some(self.response.status_code),
+            false => None,
+        }
+    }
+
+    pub fn error(&self) -> &str { &self.error }
+
+    /// The response as it is shown in the response area.
+    pub fn response_data(&self) -> ResponseData {
+        let response = &self.response;
+
+        let headers = response.headers.iter()
+            .map(|h| KVData::new(h.name.clone(), h.value.clone()))
+            .collect();
+
+        let mut info = ResponseInfo::restore(
+            response.status_code,
+            response.reason.clone(),
+            response.version.clone(),
+            headers,
+        );
+
+        info.set_elapsed(Duration::from_millis(response.elapsed_ms));
+        info.set_sizes(response.compressed_size, response.size);
+
+        ResponseData::new(response.body.clone(), info)
+    }
+
+    /// Whether every word of `query` is found in the method, url or status
+    /// of the entry, ignoring the case.
+    pub fn matches(&self, query: &str) -> bool {
+        let status = match self.status_code() {
+            Some(code) => code.to_string(),
+            None => String::from("error"),
+        };
+
+        let text = format!("{} {} {}", self.method(), self.url, status)
+            .to_lowercase();
+
+        query.to_lowercase()
+            .split_whitespace()
+            .all(|word| text.contains(word))
+    }
+
+    /// File name of the entry without the extension, which sorts by the
+    /// time it was sent.
+    fn file_stem(&self) -> String {
+        let millis = self.sent_at()
+            .map(|time| time.timestamp_millis())
+            .unwrap_or_default();
+
+        format!("{:013}", millis)
+    }
+}
+
+impl HistoryResponse {
+    fn from_response(data: &ResponseData) -> Self {
+        let info = data.info();
+
+        Self {
+            status_code: info.status_code(),
+            reason: String::from(info.reason()),
+            version: String::from(info.version()),
+            elapsed_ms: info.elapsed().as_millis() as u64,
+            compressed_size: info.compressed_size(),
+            size: info.size(),
+            headers: info.headers().iter()
+                .map(|h| HistoryHeader { name: h.key(), value: h.value() })
+                .collect(),
+            // Only the end of a body too big to keep in memory is known,
+            // and its file is deleted with the next response
+            body: match info.body_file() {
+                Some(_) => String::new(),
+                None => String::from(data.body()),
+            },
+        }
+    }
+}
+
+/// Reads the history in `dir`, newest first. Files that can't be read are
+/// skipped.
+pub fn load_history(dir: &Path) -> Result<Vec<HistoryEntry>, Box<dyn Error>> {
+    let mut entries = vec![];
+
+    for path in history_files(dir)?.iter().rev() {
+        let entry = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                toml::from_str::<HistoryEntry>(&content)
+                    .map_err(|e| e.to_string())
+            });
+
+        match entry {
+            Ok(entry) => { entries.push(entry); }
+            Err(e) => { info!("Skipping {}: {}", path.display(), e); }
+        }
+
+        if entries.len() == MAX_HISTORY_ENTRIES {
+            break;
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Saves `entry` in `dir` and deletes the oldest entries over
+/// [MAX_HISTORY_ENTRIES].
+pub fn save_entry(dir: &Path, entry: &HistoryEntry) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(dir)?;
+
+    let stem = entry.file_stem();
+    let mut path = dir.join(format!("{}.{}", stem, HISTORY_FILE_EXT));
+
+    // Requests sent in the same millisecond
+    let mut n = 1;
+
+    while path.exists() {
+        path = dir.join(format!("{}-{}.{}", stem, n, HISTORY_FILE_EXT));
+        n += 1;
+    }
+
+    // The history is a plain folder, like the collections
+    let mut saved = entry.clone();
+    saved.secret_dropped = saved.request.strip_secrets();
+
+    fs::write(&path, toml::to_string(&saved)?)?;
+
+    let files = history_files(dir)?;
+
+    if files.len() > MAX_HISTORY_ENTRIES {
+        for old in files.iter().take(files.len() - MAX_HISTORY_ENTRIES) {
+            fs::remove_file(old)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The history files in `dir`, oldest first.
+fn history_files(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path.extension().map(|ext| ext == HISTORY_FILE_EXT)
+                    .unwrap_or(false)
+        })
+        .collect();
+
+    // By stem, so that `1-1.toml` comes after `1.toml`
+    files.sort_by(|a, b| a.file_stem().cmp(&b.file_stem()));
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ env, process };
+
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::ui::state::UiState;
+
+    /// A temp folder for the history of the test `name`, empty.
+    fn history_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir()
+            .join(format!("dakia-test-{}-history-{}", process::id(), name));
+
+        let _ = fs::remove_dir_all(&dir);
+
+        dir
+    }
+
+    /// An entry sent at `millis` to `url`, answered with `status`, or
+    /// failed when `status` is `None`.
+    fn entry(millis: i64, url: &str, status: Option<u16>) -> HistoryEntry {
+        let outcome = match status {
+            Some(code) => {
+                let headers = vec![
+                    KVData::new(String::from("Content-Type"), String::from("text/plain")),
+                ];
+                let info = ResponseInfo::restore(
+                    code, String::from("OK"), String::from("HTTP/1.1"), headers,
+                );
+
+                Ok(ResponseData::new(String::from("pong"), info))
+            }
+
+            None => Err(String::from("connection refused")),
+        };
+
+        HistoryEntry::new(
+            Local.timestamp_millis_opt(millis).unwrap(),
+            String::from(url),
+            SavedRequest::from_uistate(&UiState::default()),
+            &outcome,
+        )
+    }
+
+    #[test]
+    fn saved_entries_load_back_newest_first() {
+        let dir = history_dir("round-trip");
+
+        save_entry(&dir, &entry(1_000, "https://example.com/a", Some(200))).unwrap();
+        save_entry(&dir, &entry(2_000, "https://example.com/b", None)).unwrap();
+        save_entry(&dir, &entry(2_000, "https://example.com/c", Some(404))).unwrap();
+
+        let entries = load_history(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let urls: Vec<&str> = entries.iter().map(|entry| entry.url()).collect();
+
+        assert_eq!(urls, vec![
+            "https://example.com/c", "https://example.com/b", "https://example.com/a",
+        ]);
+
+        assert_eq!(entries[0].status_code(), Some(404));
+        assert_eq!(entries[1].status_code(), None);
+        assert_eq!(entries[1].error(), "connection refused");
+
+        let data = entries[2].response_data();
+
+        assert_eq!(data.body(), "pong");
+        assert_eq!(data.info().status_code(), 200);
+        assert_eq!(data.info().headers()[0].value(), "text/plain");
+        assert_eq!(entries[2].sent_at().unwrap().timestamp_millis(), 1_000);
+    }
+
+    #[test]
+    fn saved_entries_leave_typed_secrets_out() {
+        let dir = history_dir("secrets");
+        let mut uistate = UiState::default();
+
+        uistate.auth_mut().set_token(String::from("hunter2"));
+
+        let mut sent = entry(1_000, "https://example.com", Some(200));
+        sent.request = SavedRequest::from_uistate(&uistate);
+
+        save_entry(&dir, &sent).unwrap();
+
+        let entries = load_history(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!sent.secret_dropped());
+        assert!(entries[0].secret_dropped());
+    }
+
+    #[test]
+    fn save_entry_prunes_the_oldest_entries() {
+        let dir = history_dir("prune");
+        let first = entry(1_000, "https://example.com/first", Some(200));
+
+        fs::create_dir_all(&dir).unwrap();
+
+        for i in 0..MAX_HISTORY_ENTRIES {
+            fs::write(
+                dir.join(format!("{:013}.{}", 1_000 + i, HISTORY_FILE_EXT)),
+                toml::to_string(&first).unwrap(),
+            ).unwrap();
+        }
+
+        save_entry(&dir, &entry(1_000_000, "https://example.com/last", Some(200)))
+            .unwrap();
+
+        let files = history_files(&dir).unwrap();
+        let entries = load_history(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(files.len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(files[0].file_stem().unwrap(), "0000000001001");
+        assert_eq!(entries[0].url(), "https://example.com/last");
+    }
+
+    #[test]
+    fn load_history_skips_unreadable_files() {
+        let dir = history_dir("unreadable");
+
+        save_entry(&dir, &entry(1_000, "https://example.com", Some(200))).unwrap();
+        fs::write(dir.join("0000000002000.toml"), "not = [toml").unwrap();
+        fs::write(dir.join("notes.txt"), "not an entry").unwrap();
+
+        let entries = load_history(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert!(load_history(&dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn matches_every_word_of_the_query() {
+        let ok = entry(1_000, "https://api.example.com/users", Some(201));
+        let failed = entry(1_000, "https://api.example.com/users", None);
+
+        let cases = [
+            (&ok, "users", true),
+            (&ok, "GET USERS", true),
+            (&ok, "get 201", true),
+            (&ok, "", true),
+            (&ok, "post", false),
+            (&ok, "users 404", false),
+            (&failed, "error", true),
+            (&failed, "users 201", false),
+        ];
+
+        for (entry, query, matches) in cases {
+            assert_eq!(entry.matches(query), matches, "{}", query);
+        }
+    }
+}
@@ -0,0 +1,829 @@
+//! Parses `curl` command lines, e.g. the ones found in bug reports and API
+//! docs, into requests that can be edited and sent from dakia.
+
+use std::{ error::Error, fs, iter::Peekable, str::Chars, vec::IntoIter };
+
+use reqwest::Method;
+
+use crate::ui::state::{
+    UiState, kv_data::{ KVData, PartKind }, body::BodyContent,
+    auth::AuthScheme, settings::TlsVerification,
+};
+
+/// Options that take a value but don't change the request itself (like
+/// `-o file`). Their value is skipped.
+const IGNORED_VALUE_OPTIONS: [&str; 19] = [
+    "-o", "--output", "--connect-timeout", "-w", "--write-out", "--retry",
+    "--retry-delay", "--capath", "-E", "--cert", "--key", "-c",
+    "--cookie-jar", "--resolve", "-r", "--range", "--limit-rate", "-y",
+    "--speed-time",
+];
+
+/// A request read from a curl command line.
+pub struct CurlRequest {
+    method: Method,
+    url: String,
+    headers: Vec<KVData>,
+    body_content: BodyContent,
+
+    /// Key-value data of form bodies.
+    body_kv: Vec<KVData>,
+
+    /// Text of the raw bodies.
+    body_text: String,
+
+    /// User name and password given with `-u`.
+    basic_auth: Option<(String, String)>,
+
+    /// Fields of the "Settings" tab, in the order of
+    /// [SETTINGS_FIELDS](crate::ui::state::settings::SETTINGS_FIELDS).
+    settings: [String; 5],
+
+    /// Whether `-k` turned off the certificate checks.
+    insecure: bool,
+}
+
+impl CurlRequest {
+    /// Loads this request into `uistate`, replacing the request being
+    /// edited. The imported request isn't saved in the collection yet.
+    pub fn apply(&self, uistate: &mut UiState) {
+        uistate.set_method(self.method.clone());
+        uistate.load_url(self.url.clone());
+
+        let mut headers = self.headers.clone();
+
+        if headers.is_empty() {
+            headers.push(KVData::default());
+        }
+
+        *uistate.request_headers_mut() = headers;
+        uistate.request_headers_ui_mut().set_active_row(0);
+
+        let body = uistate.body_mut();
+        let mut body_kv = self.body_kv.clone();
+
+        if body_kv.is_empty() {
+            body_kv.push(KVData::default());
+        }
+
+        body.select_body_content(self.body_content.clone());
+        *body.kv_data_mut() = body_kv;
+        body.kv_tab_state_mut().set_active_row(0);
+        body.text_data_mut().load_text(&self.body_text);
+
+        let auth = uistate.auth_mut();
+
+        match &self.basic_auth {
+            Some((username, password)) => {
+                auth.set_auth_scheme(AuthScheme::Basic);
+                auth.set_username(username.clone());
+                auth.set_password(password.clone());
+            }
+
+            None => { auth.set_auth_scheme(AuthScheme::NoAuth); }
+        }
+
+        let settings = uistate.settings_mut();
+
+        settings.set_fields(self.settings.clone());
+        settings.set_tls_verification(match self.insecure {
+            true => TlsVerification::Skip,
+            false => TlsVerification::Global,
+        });
+
+        uistate.set_current_request_path(None);
+    }
+}
+
+/// Whether `text` looks like a curl command.
+pub fn is_curl_command(text: &str) -> bool {
+    let text = text.trim_start();
+
+    text == "curl" || text.starts_with("curl ")
+}
+
+/// Parses a curl command line. Supports `-X`, `-H`, `-d`/`--data*`, `-F`,
+/// `-u`, `-G`, `-I`, `-A`, `-e`, `-b` and `--json`, and `-m`,
+/// `--max-redirs`, `-x`, `--noproxy`, `-k` and `--cacert` for the settings
+/// of the request. Options that don't affect the request (like `-s`, `-L`
+/// and `--compressed`) are ignored.
+pub fn parse(command: &str) -> Result<CurlRequest, Box<dyn Error>> {
+    let mut args = split_args(command)?.into_iter();
+
+    if args.next().as_deref() != Some("curl") {
+        return Err("not a curl command".into());
+    }
+
+    let mut method: Option<String> = None;
+    let mut url: Option<String> = None;
+    let mut headers: Vec<KVData> = vec![];
+    let mut data: Vec<String> = vec![];
+    let mut form: Vec<KVData> = vec![];
+    let mut basic_auth: Option<(String, String)> = None;
+    let mut data_in_query = false;
+    let mut head = false;
+    let mut settings: [String; 5] = Default::default();
+    let mut insecure = false;
+
+    while let Some(arg) = args.next() {
+        let (option, attached) = split_option(&arg);
+
+        match option.as_str() {
+            "-X" | "--request" => {
+                method = Some(option_value(&option, attached, &mut args)?);
+            }
+
+            "-H" | "--header" => {
+                let header = option_value(&option, attached, &mut args)?;
+
+                if let Some(kv) = parse_header(&header) {
+                    headers.push(kv);
+                }
+            }
+
+            "-d" | "--data" | "--data-ascii" => {
+                let value = option_value(&option, attached, &mut args)?;
+
+                // curl strips the line breaks of files read with `-d`
+                data.push(read_data(&value)?.replace(['\r', '\n'], ""));
+            }
+
+            "--data-binary" => {
+                let value = option_value(&option, attached, &mut args)?;
+                data.push(read_data(&value)?);
+            }
+
+            "--data-raw" => {
+                data.push(option_value(&option, attached, &mut args)?);
+            }
+
+            "--data-urlencode" => {
+                let value = option_value(&option, attached, &mut args)?;
+                data.push(urlencode_data(&value)?);
+            }
+
+            "--json" => {
+                let value = option_value(&option, attached, &mut args)?;
+
+                data.push(read_data(&value)?);
+                set_default_header(&mut headers, "Content-Type", "application/json");
+                set_default_header(&mut headers, "Accept", "application/json");
+            }
+
+            "-F" | "--form" => {
+                let value = option_value(&option, attached, &mut args)?;
+                form.push(parse_form_field(&option, &value)?);
+            }
+
+            "--form-string" => {
+                let value = option_value(&option, attached, &mut args)?;
+
+                match value.split_once('=') {
+                    Some((k, v)) => form.push(
+                        KVData::new(String::from(k), String::from(v))
+                    ),
+
+                    None => {
+                        return Err(
+                            format!("{} value must be name=content", option)
+                                .into()
+                        );
+                    }
+                }
+            }
+
+            "-u" | "--user" => {
+                let value = option_value(&option, attached, &mut args)?;
+
+                basic_auth = Some(match value.split_once(':') {
+                    Some((user, password)) => {
+                        (String::from(user), String::from(password))
+                    }
+                    None => (value, String::new()),
+                });
+            }
+
+            "-A" | "--user-agent" => {
+                let value = option_value(&option, attached, &mut args)?;
+                headers.push(KVData::new(String::from("User-Agent"), value));
+            }
+
+            "-e" | "--referer" => {
+                let value = option_value(&option, attached, &mut args)?;
+                headers.push(KVData::new(String::from("Referer"), value));
+            }
+
+            "-b" | "--cookie" => {
+                let value = option_value(&option, attached, &mut args)?;
+
+                // Without a `=` the value is a file to read the cookies from
+                if value.contains('=') {
+                    headers.push(KVData::new(String::from("Cookie"), value));
+                }
+            }
+
+            "--url" => {
+                url = Some(option_value(&option, attached, &mut args)?);
+            }
+
+            "-m" | "--max-time" => {
+                let value = option_value(&option, attached, &mut args)?;
+
+                // curl takes fractions of seconds, the timeout is in whole
+                // seconds
+                settings[0] = match value.parse::<f64>() {
+                    Ok(secs) if secs > 0.0 => secs.ceil().to_string(),
+                    _ => value,
+                };
+            }
+
+            "--max-redirs" => {
+                let value = option_value(&option, attached, &mut args)?;
+
+                // -1 is curl's "no limit", left to the global settings
+                if value != "-1" {
+                    settings[1] = value;
+                }
+            }
+
+            "-x" | "--proxy" => {
+                settings[2] = option_value(&option, attached, &mut args)?;
+            }
+
+            "--noproxy" => {
+                settings[3] = option_value(&option, attached, &mut args)?;
+            }
+
+            "--cacert" => {
+                settings[4] = option_value(&option, attached, &mut args)?;
+            }
+
+            "-G" | "--get" => { data_in_query = true; }
+            "-I" | "--head" => { head = true; }
+            "-k" | "--insecure" => { insecure = true; }
+
+            _ if IGNORED_VALUE_OPTIONS.contains(&option.as_str()) => {
+                option_value(&option, attached, &mut args)?;
+            }
+
+            _ if option.starts_with("--") => {}
+
+            _ if option.starts_with('-') && option.len() > 1 => {
+                // Combined short flags like `-sSLk`
+                for flag in option.chars().skip(1) {
+                    match flag {
+                        'G' => { data_in_query = true; }
+                        'I' => { head = true; }
+                        'k' => { insecure = true; }
+                        _ => {}
+                    }
+                }
+            }
+
+            _ => {
+                if url.is_none() {
+                    url = Some(arg);
+                }
+            }
+        }
+    }
+
+    let mut url = match url {
+        Some(url) => url,
+        None => { return Err("the curl command has no url".into()); }
+    };
+
+    if data_in_query && !data.is_empty() {
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str(&data.join("&"));
+        data.clear();
+    }
+
+    let method = match method {
+        Some(method) => Method::from_bytes(method.to_uppercase().as_bytes())?,
+        None if head => Method::HEAD,
+        None if !data.is_empty() || !form.is_empty() => Method::POST,
+        None => Method::GET,
+    };
+
+    let mut request = CurlRequest {
+        method,
+        url,
+        headers,
+        body_content: BodyContent::NONE,
+        body_kv: vec![],
+        body_text: String::new(),
+        basic_auth,
+        settings,
+        insecure,
+    };
+
+    if !form.is_empty() {
+        request.body_content = BodyContent::FormData;
+        request.body_kv = form;
+    } else if !data.is_empty() {
+        let text = data.join("&");
+        let content_type = request.headers.iter()
+            .find(|h| h.key().eq_ignore_ascii_case("content-type"))
+            .map(|h| h.value().to_lowercase());
+
+        request.body_content = body_content_for(content_type.as_deref(), &text);
+
+        if request.body_content == BodyContent::FormURLEncoded {
+            request.body_kv = parse_urlencoded(&text);
+        } else {
+            request.body_text = text;
+        }
+    }
+
+    Ok(request)
+}
+
+/// Picks the body content type from the `Content-Type` header, or from the
+/// data itself when the header isn't set.
+fn body_content_for(content_type: Option<&str>, text: &str) -> BodyContent {
+    let is_form = text.split('&').all(|pair| pair.contains('='));
+
+    match content_type {
+        Some(ct) if ct.contains("json") => BodyContent::Json,
+        Some(ct) if ct.contains("xml") => BodyContent::Xml,
+        Some(ct) if ct.contains("html") => BodyContent::Html,
+        Some(ct) if ct.contains("x-www-form-urlencoded") && is_form => {
+            BodyContent::FormURLEncoded
+        }
+        Some(_) => BodyContent::Text,
+
+        None => {
+            let trimmed = text.trim_start();
+
+            if trimmed.starts_with('{') || trimmed.starts_with('[') {
+                BodyContent::Json
+            } else if is_form {
+                BodyContent::FormURLEncoded
+            } else {
+                BodyContent::Text
+            }
+        }
+    }
+}
+
+/// Parses a `-F` value: `name=content`, `name=@file` to upload a file or
+/// `name=<file` to send the content of a file as text. `;type=` and
+/// `;filename=` set the content type and file name of the part.
+fn parse_form_field(option: &str, value: &str)
+    -> Result<KVData, Box<dyn Error>> {
+    let (name, content) = match value.split_once('=') {
+        Some(pair) => pair,
+
+        None => {
+            return Err(format!("{} value must be name=content", option).into());
+        }
+    };
+
+    let mut kv = KVData::new(String::from(name), String::new());
+    let mut parts = content.split(';');
+    let content = parts.next().unwrap_or_default();
+
+    if let Some(path) = content.strip_prefix('@') {
+        kv.set_part_kind(PartKind::File);
+        kv.set_value(String::from(path));
+    } else if let Some(path) = content.strip_prefix('<') {
+        kv.set_value(
+            fs::read_to_string(path)
+                .map_err(|e| format!("couldn't read {}: {}", path, e))?
+        );
+    } else {
+        kv.set_value(String::from(content));
+    }
+
+    for attribute in parts {
+        match attribute.trim().split_once('=') {
+            Some(("type", content_type)) => {
+                kv.set_content_type(String::from(content_type));
+            }
+
+            Some(("filename", filename)) => {
+                kv.set_filename(String::from(filename.trim_matches('"')));
+            }
+
+            _ => {}
+        }
+    }
+
+    Ok(kv)
+}
+
+/// Splits `-XPOST` into `-X` and `POST`. Options that don't take a value are
+/// returned whole.
+fn split_option(arg: &str) -> (String, Option<String>) {
+    const SHORT_WITH_VALUE: [&str; 14] = [
+        "-X", "-H", "-d", "-F", "-u", "-A", "-e", "-b", "-o", "-m", "-w",
+        "-x", "-E", "-r",
+    ];
+
+    if !arg.starts_with("--") && arg.len() > 2 {
+        if let Some(short) = arg.get(..2) {
+            if SHORT_WITH_VALUE.contains(&short) {
+                return (String::from(short), Some(String::from(&arg[2..])));
+            }
+        }
+    }
+
+    (String::from(arg), None)
+}
+
+/// Returns the value of `option`, either attached to it or the next
+/// argument.
+fn option_value(
+    option: &str,
+    attached: Option<String>,
+    args: &mut IntoIter<String>,
+) -> Result<String, Box<dyn Error>> {
+    match attached.or_else(|| args.next()) {
+        Some(value) => Ok(value),
+        None => Err(format!("{} requires a value", option).into()),
+    }
+}
+
+/// Parses a `Name: value` header. `Name;` is a header with an empty value.
+fn parse_header(header: &str) -> Option<KVData> {
+    if let Some((name, value)) = header.split_once(':') {
+        let name = name.trim();
+
+        // `Name:` removes a header curl would send by default
+        if name.is_empty() || value.trim().is_empty() {
+            return None;
+        }
+
+        return Some(KVData::new(String::from(name), String::from(value.trim())));
+    }
+
+    header.strip_suffix(';')
+        .map(|name| KVData::new(String::from(name.trim()), String::new()))
+}
+
+fn set_default_header(headers: &mut Vec<KVData>, name: &str, value: &str) {
+    if !headers.iter().any(|h| h.key().eq_ignore_ascii_case(name)) {
+        headers.push(KVData::new(String::from(name), String::from(value)));
+    }
+}
+
+/// Reads the data from a file for `@file` values.
+fn read_data(value: &str) -> Result<String, Box<dyn Error>> {
+    match value.strip_prefix('@') {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| format!("couldn't read {}: {}", path, e).into()),
+        None => Ok(String::from(value)),
+    }
+}
+
+/// Builds the data of a `--data-urlencode` value, which can be `content`,
+/// `=content`, `name=content`, `@file` or `name@file`.
+fn urlencode_data(value: &str) -> Result<String, Box<dyn Error>> {
+    if let Some((name, content)) = value.split_once('=') {
+        if name.is_empty() {
+            return Ok(percent_encode(content));
+        }
+
+        return Ok(format!("{}={}", name, percent_encode(content)));
+    }
+
+    if let Some((name, path)) = value.split_once('@') {
+        let content = read_data(&format!("@{}", path))?;
+
+        if name.is_empty() {
+            return Ok(percent_encode(&content));
+        }
+
+        return Ok(format!("{}={}", name, percent_encode(&content)));
+    }
+
+    Ok(percent_encode(value))
+}
+
+/// Splits `a=1&b=2` into decoded key-value pairs.
+fn parse_urlencoded(text: &str) -> Vec<KVData> {
+    text.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+
+            KVData::new(percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9'
+                | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+
+            b'%' if i + 2 < bytes.len() => {
+                let byte = std::str::from_utf8(&bytes[i + 1..i + 3]).ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+                match byte {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+
+            byte => decoded.push(byte),
+        }
+
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Splits a command line into arguments the way a POSIX shell does:
+/// handles single and double quotes, `$'...'` strings, backslash escapes
+/// and line continuations.
+fn split_args(command: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut args: Vec<String> = vec![];
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_arg = true;
+
+                loop {
+                    match chars.next() {
+                        Some('\'') => { break; }
+                        Some(ch) => current.push(ch),
+                        None => { return Err("unterminated ' quote".into()); }
+                    }
+                }
+            }
+
+            '"' => {
+                in_arg = true;
+
+                loop {
+                    match chars.next() {
+                        Some('"') => { break; }
+
+                        Some('\\') => match chars.next() {
+                            Some(ch @ ('"' | '\\' | '$' | '`')) => {
+                                current.push(ch);
+                            }
+                            Some('\n') => {}
+                            Some(ch) => {
+                                current.push('\\');
+                                current.push(ch);
+                            }
+                            None => {
+                                return Err("unterminated \" quote".into());
+                            }
+                        },
+
+                        Some(ch) => current.push(ch),
+                        None => { return Err("unterminated \" quote".into()); }
+                    }
+                }
+            }
+
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                in_arg = true;
+                read_ansi_c_string(&mut chars, &mut current)?;
+            }
+
+            '\\' => match chars.next() {
+                // Line continuation
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => { chars.next(); }
+
+                Some(ch) => {
+                    in_arg = true;
+                    current.push(ch);
+                }
+
+                None => {}
+            },
+
+            c if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+
+            c => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_arg {
+        args.push(current);
+    }
+
+    Ok(args)
+}
+
+/// Reads a `$'...'` string (after the opening quote) into `out`.
+fn read_ansi_c_string(chars: &mut Peekable<Chars>, out: &mut String)
+    -> Result<(), Box<dyn Error>> {
+    loop {
+        match chars.next() {
+            Some('\'') => { return Ok(()); }
+
+            Some('\\') => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('r') => out.push('\r'),
+                Some(ch @ ('\\' | '\'' | '"')) => out.push(ch),
+                Some(ch) => {
+                    out.push('\\');
+                    out.push(ch);
+                }
+                None => { return Err("unterminated $' quote".into()); }
+            },
+
+            Some(ch) => out.push(ch),
+            None => { return Err("unterminated $' quote".into()); }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(kv: &[KVData]) -> Vec<(String, String)> {
+        kv.iter().map(|kv| (kv.key(), kv.value())).collect()
+    }
+
+    #[test]
+    fn split_args_handles_quotes_and_escapes() {
+        let args = split_args(
+            r#"curl 'a b' "c \"d\" \$e \x" f\ g $'h\n\'i' "j"'k'"#
+        ).unwrap();
+
+        assert_eq!(args, vec![
+            "curl", "a b", "c \"d\" $e \\x", "f g", "h\n'i", "jk",
+        ]);
+    }
+
+    #[test]
+    fn split_args_joins_continued_lines() {
+        let args = split_args("curl \\\n  -X POST \\\r\n  http://x").unwrap();
+
+        assert_eq!(args, vec!["curl", "-X", "POST", "http://x"]);
+    }
+
+    #[test]
+    fn split_args_keeps_empty_quoted_args() {
+        assert_eq!(split_args("curl '' \"\"").unwrap(), vec!["curl", "", ""]);
+    }
+
+    #[test]
+    fn split_args_rejects_unterminated_quotes() {
+        assert!(split_args("curl 'a").is_err());
+        assert!(split_args("curl \"a").is_err());
+        assert!(split_args("curl $'a").is_err());
+    }
+
+    #[test]
+    fn parse_reads_method_url_and_headers() {
+        let request = parse(
+            "curl -XPUT https://api.example.com/users/1 \
+            -H 'Accept: application/json' -H 'X-Empty;' -H 'User-Agent:'"
+        ).unwrap();
+
+        assert_eq!(request.method, Method::PUT);
+        assert_eq!(request.url, "https://api.example.com/users/1");
+        assert_eq!(pairs(&request.headers), vec![
+            (String::from("Accept"), String::from("application/json")),
+            (String::from("X-Empty"), String::new()),
+        ]);
+    }
+
+    #[test]
+    fn parse_defaults_to_post_with_data() {
+        let request = parse(r#"curl http://x -d '{"a": 1}'"#).unwrap();
+
+        assert_eq!(request.method, Method::POST);
+        assert!(request.body_content == BodyContent::Json);
+        assert_eq!(request.body_text, r#"{"a": 1}"#);
+    }
+
+    #[test]
+    fn parse_reads_urlencoded_data_as_a_form() {
+        let request = parse(
+            "curl http://x -d a=1 --data-urlencode 'b=x y' -d c=%26"
+        ).unwrap();
+
+        assert!(request.body_content == BodyContent::FormURLEncoded);
+        assert_eq!(pairs(&request.body_kv), vec![
+            (String::from("a"), String::from("1")),
+            (String::from("b"), String::from("x y")),
+            (String::from("c"), String::from("&")),
+        ]);
+    }
+
+    #[test]
+    fn parse_uses_the_content_type_for_the_body() {
+        let request = parse(
+            "curl http://x -H 'Content-Type: text/xml' -d '<a/>'"
+        ).unwrap();
+
+        assert!(request.body_content == BodyContent::Xml);
+
+        let request = parse(
+            "curl http://x -H 'content-type: text/plain' -d a=1"
+        ).unwrap();
+
+        assert!(request.body_content == BodyContent::Text);
+        assert_eq!(request.body_text, "a=1");
+    }
+
+    #[test]
+    fn parse_moves_data_to_the_query_with_get() {
+        let request = parse("curl -G 'http://x?a=1' -d b=2 -d c=3").unwrap();
+
+        assert_eq!(request.method, Method::GET);
+        assert_eq!(request.url, "http://x?a=1&b=2&c=3");
+        assert!(request.body_content == BodyContent::NONE);
+    }
+
+    #[test]
+    fn parse_reads_json_and_form_options() {
+        let request = parse(r#"curl --json '{}' http://x"#).unwrap();
+
+        assert!(request.body_content == BodyContent::Json);
+        assert_eq!(pairs(&request.headers), vec![
+            (String::from("Content-Type"), String::from("application/json")),
+            (String::from("Accept"), String::from("application/json")),
+        ]);
+
+        let request = parse(
+            "curl http://x -F name=dakia -F 'file=@a.png;type=image/png'"
+        ).unwrap();
+
+        assert!(request.body_content == BodyContent::FormData);
+        assert_eq!(request.body_kv[1].value(), "a.png");
+        assert!(*request.body_kv[1].part_kind() == PartKind::File);
+        assert_eq!(request.body_kv[1].content_type(), "image/png");
+    }
+
+    #[test]
+    fn parse_reads_auth_and_settings() {
+        let request = parse(
+            "curl -sSLk -u user:pa:ss -m 1.5 --max-redirs -1 -x socks5://p \
+            --noproxy localhost --cacert ca.pem -o out.json http://x"
+        ).unwrap();
+
+        assert_eq!(
+            request.basic_auth,
+            Some((String::from("user"), String::from("pa:ss"))),
+        );
+        assert!(request.insecure);
+        assert_eq!(request.settings, [
+            String::from("2"), String::new(), String::from("socks5://p"),
+            String::from("localhost"), String::from("ca.pem"),
+        ]);
+        assert_eq!(request.url, "http://x");
+    }
+
+    #[test]
+    fn parse_reads_head_requests() {
+        assert_eq!(parse("curl -I http://x").unwrap().method, Method::HEAD);
+        assert_eq!(parse("curl -sI http://x").unwrap().method, Method::HEAD);
+    }
+
+    #[test]
+    fn parse_rejects_bad_commands() {
+        assert!(parse("wget http://x").is_err());
+        assert!(parse("curl -s").is_err());
+        assert!(parse("curl http://x -H").is_err());
+        assert!(parse("curl http://x -F novalue").is_err());
+    }
+}
@@ -0,0 +1,3 @@
+//! Importing requests written for other tools.
+
+pub mod curl;
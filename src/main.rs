@@ -1,9 +1,23 @@
 mod ui;
 mod user_input;
 mod api;
-
-use std::io;
-
+mod collection;
+mod environment;
+mod import;
+mod export;
+mod cli;
+mod history;
+mod script;
+mod assertion;
+mod runner;
+mod websocket;
+mod graphql;
+mod cookie;
+mod settings;
+
+use std::{ io, process, sync::mpsc::{ self, Receiver } };
+
+use clap::Parser;
 use log::{ info, error };
 use log4rs;
 use tui::{
@@ -23,12 +37,52 @@ use crossterm::{
 use ui::{ ui_func, state::UiState };
 
 use user_input::process_user_input;
+use api::worker::{ self, ApiResult };
+use runner::worker::{ self as run_worker, RunEvent };
+use websocket::worker::{ self as ws_worker, WsEvent };
+use graphql::worker::{ self as schema_worker, SchemaEvent };
+use cli::{ Cli, Command };
 
 const VERSION: &str = "v0.0.1";
 
 fn main() -> Result<(), io::Error> {
+    let cli = Cli::parse();
+
+    if let Some(command) = cli.command {
+        // Scripts may run dakia where there is no log config, which is fine
+        // without the tui.
+        let _ = log4rs::init_file("log4rs.yaml", Default::default());
+
+        process::exit(match command {
+            Command::Run { request, env } => cli::run(&request, env.as_deref()),
+            Command::RunFolder(args) => cli::run_folder(&args),
+        });
+    }
+
     log4rs::init_file("log4rs.yaml", Default::default()).unwrap();
-    let uistate = UiState::default();
+    let mut uistate = UiState::default();
+    let (api_sender, api_receiver) = mpsc::channel();
+    let (run_sender, run_receiver) = mpsc::channel();
+    let (ws_sender, ws_receiver) = mpsc::channel();
+    let (schema_sender, schema_receiver) = mpsc::channel();
+
+    uistate.set_api_sender(api_sender);
+    uistate.set_body_memory_cap(cli.body_memory.saturating_mul(1024));
+    uistate.runner_mut().set_sender(run_sender);
+    uistate.websocket_mut().set_sender(ws_sender);
+    uistate.graphql_mut().set_sender(schema_sender);
+
+    if let Err(e) = uistate.environments_mut().reload() {
+        error!("Couldn't read the environments: {}", e);
+    }
+
+    if let Err(e) = uistate.settings_mut().reload() {
+        error!("Couldn't read the settings: {}", e);
+    }
+
+    if let Err(e) = uistate.history_mut().reload() {
+        error!("Couldn't read the history: {}", e);
+    }
 
     info!("dakia {}. Starting up...", VERSION);
 
@@ -41,7 +95,10 @@ fn main() -> Result<(), io::Error> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let res = run_app(&mut terminal, uistate);
+    let res = run_app(
+        &mut terminal, &mut uistate, api_receiver, run_receiver, ws_receiver,
+        schema_receiver,
+    );
 
     disable_raw_mode()?;
 
@@ -53,6 +110,10 @@ fn main() -> Result<(), io::Error> {
 
     terminal.show_cursor()?;
 
+    // A body too big to keep in memory isn't needed once the app exits
+    worker::cancel(&mut uistate);
+    uistate.response_mut().remove_body_file();
+
     if let Err(err) = res {
         error!("{:?}", err);
     }
@@ -60,12 +121,34 @@ fn main() -> Result<(), io::Error> {
     Ok(())
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut uistate: UiState)
-    -> io::Result<()> {
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    uistate: &mut UiState,
+    api_receiver: Receiver<ApiResult>,
+    run_receiver: Receiver<RunEvent>,
+    ws_receiver: Receiver<WsEvent>,
+    schema_receiver: Receiver<SchemaEvent>,
+) -> io::Result<()> {
     loop {
-        terminal.draw(|f| ui_func(f, &mut uistate))?;
+        terminal.draw(|f| ui_func(f, uistate))?;
+
+        while let Ok(result) = api_receiver.try_recv() {
+            worker::receive(uistate, result);
+        }
+
+        while let Ok(event) = run_receiver.try_recv() {
+            run_worker::receive(uistate, event);
+        }
+
+        while let Ok(event) = ws_receiver.try_recv() {
+            ws_worker::receive(uistate, event);
+        }
+
+        while let Ok(event) = schema_receiver.try_recv() {
+            schema_worker::receive(uistate, event);
+        }
 
-        match process_user_input(&mut uistate) {
+        match process_user_input(uistate) {
             Ok(exit) => {
                 if exit {
                     return Ok(());
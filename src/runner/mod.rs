@@ -0,0 +1,948 @@
+//! Runs the requests of a collection folder in order.
+//!
+//! The requests share the variables: a variable set by the post-response
+//! script of a request is used by the requests after it. With a data file
+//! the folder is run once per row (iteration), with the columns of the row
+//! as variables. A CSV file has the variable names in its first line, a JSON
+//! file is an array of objects:
+//!
+//! ```json
+//! [
+//!     { "username": "alice", "password": "secret" },
+//!     { "username": "bob", "password": "hunter2" }
+//! ]
+//! ```
+//!
+//! The `iteration` variable is the number of the iteration, from 1.
+
+pub mod worker;
+
+use std::{
+    collections::BTreeMap, error::Error, fs, io, thread,
+    path::{ Path, PathBuf },
+    sync::atomic::{ AtomicBool, Ordering },
+    time::{ Duration, Instant },
+};
+
+use chrono::{ DateTime, Local };
+use serde_json::{ json, Value };
+
+use crate::{
+    api::{ ResponseData, read_response, resolved_url, sent_request },
+    assertion::{ TestResult, check_response },
+    collection::{ CollectionEntry, load_tree, load_request, request_name },
+    script::{ pre_request, post_response },
+    ui::state::UiState,
+};
+
+/// Folder the reports exported from the runner are saved to, relative to
+/// the working directory.
+pub const DEFAULT_REPORT_DIR: &str = "reports";
+
+/// The variables of an iteration, read from a row of the data file.
+pub type DataRow = BTreeMap<String, String>;
+
+/// The requests to run and how to run them.
+#[derive(Clone)]
+pub struct RunPlan {
+    /// Name of the folder, relative to the collection root.
+    name: String,
+
+    /// The collection root, for the names of the requests.
+    root: PathBuf,
+
+    /// Files of the requests, in the order they run.
+    requests: Vec<PathBuf>,
+
+    /// A row per iteration. Empty for a single iteration without data.
+    data: Vec<DataRow>,
+
+    /// Time to wait between two requests.
+    delay: Duration,
+
+    /// Whether the run stops at the first request that fails.
+    stop_on_failure: bool,
+}
+
+/// The outcome of a request of the run.
+#[derive(Clone)]
+pub struct RequestResult {
+    /// Number of the iteration, from 1.
+    iteration: usize,
+
+    /// Name of the request in the collection, e.g. `users/get-user`.
+    name: String,
+    method: String,
+
+    /// The url the request was sent to, with the variables resolved. Empty
+    /// if the request couldn't be built.
+    url: String,
+    status: Option<u16>,
+    reason: String,
+    elapsed: Duration,
+    tests: Vec<TestResult>,
+
+    /// Why the request couldn't be sent or its scripts failed.
+    error: Option<String>,
+}
+
+/// The results of a run.
+#[derive(Clone)]
+pub struct RunReport {
+    name: String,
+    started_at: DateTime<Local>,
+    duration: Duration,
+    iterations: usize,
+
+    /// Number of requests the run would send if it isn't stopped.
+    total: usize,
+    results: Vec<RequestResult>,
+}
+
+/// A request sent with its scripts run and its assertions checked.
+pub struct Exchange {
+    /// The url the request was sent to, with the variables resolved.
+    pub url: String,
+    pub response: ResponseData,
+
+    /// Results of the assertions and of the tests of the post-response
+    /// script.
+    pub tests: Vec<TestResult>,
+}
+
+impl RunPlan {
+    /// Plans a run of the requests in `folder` and its sub-folders, sorted
+    /// by name.
+    pub fn new(root: &Path, folder: &Path) -> Result<Self, Box<dyn Error>> {
+        let name = folder_name(root, folder);
+
+        if !folder.is_dir() {
+            return Err(format!("no folder \"{}\" in the collection", name).into());
+        }
+
+        let mut requests = vec![];
+
+        collect_requests(&load_tree(folder)?, &mut requests);
+
+        if requests.is_empty() {
+            return Err(format!("no requests in \"{}\"", name).into());
+        }
+
+        Ok(Self {
+            name,
+            root: root.to_path_buf(),
+            requests,
+            data: vec![],
+            delay: Duration::ZERO,
+            stop_on_failure: false,
+        })
+    }
+
+    pub fn name(&self) -> &str { &self.name }
+
+    pub fn set_data(&mut self, data: Vec<DataRow>) { self.data = data; }
+    pub fn set_delay(&mut self, delay: Duration) { self.delay = delay; }
+    pub fn set_stop_on_failure(&mut self, stop_on_failure: bool) {
+        self.stop_on_failure = stop_on_failure;
+    }
+
+    pub fn iterations(&self) -> usize { self.data.len().max(1) }
+
+    /// Number of requests sent by the whole run.
+    pub fn total(&self) -> usize { self.requests.len() * self.iterations() }
+}
+
+impl RequestResult {
+    pub fn iteration(&self) -> usize { self.iteration }
+    pub fn name(&self) -> &str { &self.name }
+    pub fn method(&self) -> &str { &self.method }
+    pub fn status(&self) -> Option<u16> { self.status }
+    pub fn reason(&self) -> &str { &self.reason }
+    pub fn elapsed(&self) -> Duration { self.elapsed }
+    pub fn tests(&self) -> &Vec<TestResult> { &self.tests }
+    pub fn error(&self) -> &Option<String> { &self.error }
+
+    /// Whether the request was sent and all its tests passed.
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.tests.iter().all(|test| test.passed)
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "iteration": self.iteration,
+            "request": self.name,
+            "method": self.method,
+            "url": self.url,
+            "status": self.status,
+            "time_ms": self.elapsed.as_millis() as u64,
+            "passed": self.passed(),
+            "error": self.error,
+            "tests": self.tests.iter().map(|test| json!({
+                "name": test.name,
+                "passed": test.passed,
+                "detail": test.detail,
+            })).collect::<Vec<Value>>(),
+        })
+    }
+}
+
+impl RunReport {
+    pub fn name(&self) -> &str { &self.name }
+    pub fn duration(&self) -> Duration { self.duration }
+    pub fn total(&self) -> usize { self.total }
+    pub fn results(&self) -> &Vec<RequestResult> { &self.results }
+
+    pub fn passed_count(&self) -> usize {
+        self.results.iter().filter(|result| result.passed()).count()
+    }
+
+    pub fn failed_count(&self) -> usize {
+        self.results.len() - self.passed_count()
+    }
+
+    /// The report as JSON.
+    pub fn to_json(&self) -> String {
+        let report = json!({
+            "name": self.name,
+            "started_at": self.started_at.to_rfc3339(),
+            "duration_ms": self.duration.as_millis() as u64,
+            "iterations": self.iterations,
+            "total": self.total,
+            "passed": self.passed_count(),
+            "failed": self.failed_count(),
+            "results": self.results.iter()
+                .map(RequestResult::to_json)
+                .collect::<Vec<Value>>(),
+        });
+
+        serde_json::to_string_pretty(&report).unwrap_or_default()
+    }
+
+    /// The report in the JUnit XML format read by CI servers. Every
+    /// iteration is a test suite and every request a test case.
+    pub fn to_junit(&self) -> String {
+        let errors = self.results.iter().filter(|r| r.error.is_some()).count();
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+        xml.push_str(&format!(
+            "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">\n",
+            xml_escape(&self.name),
+            self.results.len(),
+            self.failed_count() - errors,
+            errors,
+            self.duration.as_secs_f64(),
+        ));
+
+        for iteration in 1..=self.iterations {
+            let results: Vec<&RequestResult> = self.results.iter()
+                .filter(|result| result.iteration == iteration)
+                .collect();
+
+            if results.is_empty() {
+                continue;
+            }
+
+            let suite_name = match self.iterations {
+                1 => self.name.clone(),
+                _ => format!("{} #{}", self.name, iteration),
+            };
+
+            let errors = results.iter().filter(|r| r.error.is_some()).count();
+            let failures = results.iter().filter(|r| !r.passed()).count() - errors;
+            let time: f64 = results.iter().map(|r| r.elapsed.as_secs_f64()).sum();
+
+            xml.push_str(&format!(
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\" timestamp=\"{}\">\n",
+                xml_escape(&suite_name),
+                results.len(),
+                failures,
+                errors,
+                time,
+                self.started_at.format("%Y-%m-%dT%H:%M:%S"),
+            ));
+
+            for result in results {
+                xml.push_str(&format!(
+                    "    <testcase name=\"{} {}\" classname=\"{}\" time=\"{:.3}\"",
+                    xml_escape(&result.method),
+                    xml_escape(&result.name),
+                    xml_escape(&self.name),
+                    result.elapsed.as_secs_f64(),
+                ));
+
+                let failed: Vec<&TestResult> = result.tests.iter()
+                    .filter(|test| !test.passed)
+                    .collect();
+
+                if let Some(error) = &result.error {
+                    xml.push_str(&format!(
+                        ">\n      <error message=\"{}\"/>\n    </testcase>\n",
+                        xml_escape(error),
+                    ));
+                } else if !failed.is_empty() {
+                    let lines: Vec<String> = failed.iter()
+                        .map(|test| match test.detail.is_empty() {
+                            true => test.name.clone(),
+                            false => format!("{}: {}", test.name, test.detail),
+                        })
+                        .collect();
+
+                    xml.push_str(&format!(
+                        ">\n      <failure message=\"{} of {} tests failed\">{}</failure>\n    </testcase>\n",
+                        failed.len(),
+                        result.tests.len(),
+                        xml_escape(&lines.join("\n")),
+                    ));
+                } else {
+                    xml.push_str("/>\n");
+                }
+            }
+
+            xml.push_str("  </testsuite>\n");
+        }
+
+        xml.push_str("</testsuites>\n");
+
+        xml
+    }
+}
+
+/// Adds the requests of `entries` to `requests`. The requests of a folder run
+/// before those of its sub-folders.
+fn collect_requests(entries: &[CollectionEntry], requests: &mut Vec<PathBuf>) {
+    for entry in entries {
+        if let CollectionEntry::Request { path, .. } = entry {
+            requests.push(path.clone());
+        }
+    }
+
+    for entry in entries {
+        if let CollectionEntry::Folder { children, .. } = entry {
+            collect_requests(children, requests);
+        }
+    }
+}
+
+/// Name of `folder` relative to the collection `root`, the name of the root
+/// for the root itself.
+pub fn folder_name(root: &Path, folder: &Path) -> String {
+    match folder.strip_prefix(root) {
+        Ok(relative) if !relative.as_os_str().is_empty() => {
+            relative.to_string_lossy().to_string()
+        }
+        _ => folder.to_string_lossy().to_string(),
+    }
+}
+
+/// Reads the rows of a CSV or JSON data file. Files that don't end with
+/// `.json` are read as CSV.
+pub fn load_data(path: &Path) -> Result<Vec<DataRow>, Box<dyn Error>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+
+    let rows = match path.extension().is_some_and(|ext| ext == "json") {
+        true => parse_json_data(&content),
+        false => parse_csv_data(&content),
+    };
+
+    rows.map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+/// Reads an array of objects, or a single object for one iteration.
+fn parse_json_data(content: &str) -> Result<Vec<DataRow>, String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+
+    let objects = match value {
+        Value::Array(values) => values,
+        object @ Value::Object(_) => vec![object],
+        _ => { return Err(String::from("expected an array of objects")); }
+    };
+
+    objects.into_iter()
+        .map(|value| match value {
+            Value::Object(object) => Ok(object.into_iter()
+                .map(|(name, value)| {
+                    let value = match value {
+                        Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+
+                    (name, value)
+                })
+                .collect()),
+
+            _ => Err(String::from("expected an array of objects")),
+        })
+        .collect()
+}
+
+/// Reads CSV with the variable names in the first line. Fields can be
+/// quoted with `"`, a quote in a quoted field is written `""`.
+fn parse_csv_data(content: &str) -> Result<Vec<DataRow>, String> {
+    let mut records = parse_csv(content)?.into_iter();
+
+    let names = match records.next() {
+        Some(names) => names,
+        None => { return Ok(vec![]); }
+    };
+
+    Ok(records
+        .map(|record| {
+            names.iter()
+                .cloned()
+                .zip(record.into_iter().chain(std::iter::repeat(String::new())))
+                .collect()
+        })
+        .collect())
+}
+
+fn parse_csv(content: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = vec![];
+    let mut record: Vec<String> = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    quoted = false;
+                }
+            }
+
+            '"' if field.is_empty() => { quoted = true; }
+            _ if quoted => { field.push(c); }
+            ',' => { record.push(std::mem::take(&mut field)); }
+            '\r' => {}
+
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+
+                // Blank lines are skipped
+                if record.len() > 1 || !record[0].is_empty() {
+                    records.push(std::mem::take(&mut record));
+                } else {
+                    record.clear();
+                }
+            }
+
+            _ => { field.push(c); }
+        }
+    }
+
+    if quoted {
+        return Err(String::from("a quoted field isn't closed"));
+    }
+
+    if !record.is_empty() || !field.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+/// Sends the request loaded in `uistate`: runs the pre-request script, sends
+/// the request as the script changed it, then checks the assertions and runs
+/// the post-response script. Blocks until the response is received.
+pub fn execute(uistate: &mut UiState) -> Result<Exchange, Box<dyn Error>> {
+    let scripted = pre_request(uistate)
+        .map_err(|e| format!("pre-request script: {}", e))?;
+
+    let (request, url) = sent_request(uistate, scripted.as_ref())?;
+    let started = Instant::now();
+    let response = request.send()?;
+
+    let response = read_response(response, started).map_err(|e| e.to_string())?;
+
+    let mut tests = check_response(uistate, &response);
+
+    tests.append(
+        &mut post_response(uistate, &response)
+            .map_err(|e| format!("post-response script: {}", e))?
+    );
+
+    Ok(Exchange { url, response, tests })
+}
+
+/// Runs the `plan`, calling `on_result` after every request. The variables
+/// are read from and set in the environments of `uistate`. Stops early when
+/// `cancelled` is set.
+pub fn run(
+    uistate: &mut UiState,
+    plan: &RunPlan,
+    cancelled: &AtomicBool,
+    mut on_result: impl FnMut(&RequestResult),
+) -> RunReport {
+    let started_at = Local::now();
+    let started = Instant::now();
+    let mut results: Vec<RequestResult> = vec![];
+
+    'run: for iteration in 1..=plan.iterations() {
+        let environments = uistate.environments_mut();
+
+        environments.set_variable(String::from("iteration"), iteration.to_string());
+
+        if let Some(row) = plan.data.get(iteration - 1) {
+            for (name, value) in row {
+                environments.set_variable(name.clone(), value.clone());
+            }
+        }
+
+        for path in plan.requests.iter() {
+            if !results.is_empty() && !plan.delay.is_zero() {
+                thread::sleep(plan.delay);
+            }
+
+            if cancelled.load(Ordering::SeqCst) {
+                break 'run;
+            }
+
+            let result = run_request(uistate, &plan.root, path, iteration);
+
+            on_result(&result);
+
+            let failed = !result.passed();
+
+            results.push(result);
+
+            if failed && plan.stop_on_failure {
+                break 'run;
+            }
+        }
+    }
+
+    RunReport {
+        name: plan.name.clone(),
+        started_at,
+        duration: started.elapsed(),
+        iterations: plan.iterations(),
+        total: plan.total(),
+        results,
+    }
+}
+
+/// Loads the request at `path` in `uistate` and sends it.
+fn run_request(uistate: &mut UiState, root: &Path, path: &Path, iteration: usize)
+    -> RequestResult {
+    let mut result = RequestResult {
+        iteration,
+        name: request_name(root, path),
+        method: String::new(),
+        url: String::new(),
+        status: None,
+        reason: String::new(),
+        elapsed: Duration::ZERO,
+        tests: vec![],
+        error: None,
+    };
+
+    let saved = match load_request(path) {
+        Ok(saved) => saved,
+
+        Err(e) => {
+            result.error = Some(format!("couldn't read {}: {}", path.display(), e));
+            return result;
+        }
+    };
+
+    result.method = saved.method().to_uppercase();
+    saved.apply(uistate);
+
+    let started = Instant::now();
+
+    match execute(uistate) {
+        Ok(exchange) => {
+            let info = exchange.response.info();
+
+            result.url = exchange.url;
+            result.status = Some(info.status_code());
+            result.reason = String::from(info.reason());
+            result.elapsed = info.elapsed();
+            result.tests = exchange.tests;
+        }
+
+        Err(e) => {
+            result.url = resolved_url(uistate);
+            result.elapsed = started.elapsed();
+            result.error = Some(e.to_string());
+        }
+    }
+
+    result
+}
+
+/// Writes `content` to `path`, creating the parent folders if needed.
+pub fn write_report(path: &Path, content: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, content)
+}
+
+/// Saves the JUnit XML and JSON reports of `report` in `dir`, named after
+/// the folder and the time of the run. Returns the paths of both files.
+pub fn save_reports(dir: &Path, report: &RunReport)
+    -> io::Result<(PathBuf, PathBuf)> {
+    let base = format!(
+        "{}-{}",
+        report.name.replace(['/', '\\'], "-"),
+        report.started_at.format("%Y%m%d-%H%M%S"),
+    );
+
+    let junit_path = dir.join(format!("{}.xml", base));
+    let json_path = dir.join(format!("{}.json", base));
+
+    write_report(&junit_path, &report.to_junit())?;
+    write_report(&json_path, &report.to_json())?;
+
+    Ok((junit_path, json_path))
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, process,
+        io::{ Read, Write },
+        net::TcpListener,
+        thread::JoinHandle,
+    };
+
+    use super::*;
+    use crate::{
+        collection::{ SavedRequest, save_request },
+        ui::state::scripts::ScriptKind,
+    };
+
+    fn row(pairs: &[(&str, &str)]) -> DataRow {
+        pairs.iter()
+            .map(|(name, value)| (String::from(*name), String::from(*value)))
+            .collect()
+    }
+
+    /// Writes `content` to a temp file ending with `file_name` and loads it.
+    fn load(file_name: &str, content: &str) -> Result<Vec<DataRow>, String> {
+        let path = env::temp_dir()
+            .join(format!("dakia-test-{}-{}", process::id(), file_name));
+
+        fs::write(&path, content).unwrap();
+
+        let rows = load_data(&path).map_err(|e| e.to_string());
+        fs::remove_file(&path).unwrap();
+
+        rows
+    }
+
+    #[test]
+    fn csv_rows_are_named_after_the_header() {
+        let rows = parse_csv_data("user,id\r\nalice,1\r\nbob,2\r\n").unwrap();
+
+        assert_eq!(rows, vec![
+            row(&[("user", "alice"), ("id", "1")]),
+            row(&[("user", "bob"), ("id", "2")]),
+        ]);
+    }
+
+    #[test]
+    fn csv_quoted_fields_keep_commas_quotes_and_line_breaks() {
+        let rows = parse_csv_data(
+            "name,note\n\"Doe, Jane\",\"says \"\"hi\"\"\ntwice\"\n"
+        ).unwrap();
+
+        assert_eq!(rows, vec![
+            row(&[("name", "Doe, Jane"), ("note", "says \"hi\"\ntwice")]),
+        ]);
+    }
+
+    #[test]
+    fn csv_blank_lines_are_skipped_and_short_rows_padded() {
+        let rows = parse_csv_data("a,b,c\n\n1\n\n,2\n3,4,5,6").unwrap();
+
+        assert_eq!(rows, vec![
+            row(&[("a", "1"), ("b", ""), ("c", "")]),
+            row(&[("a", ""), ("b", "2"), ("c", "")]),
+            row(&[("a", "3"), ("b", "4"), ("c", "5")]),
+        ]);
+    }
+
+    #[test]
+    fn csv_without_rows_has_no_iterations() {
+        assert!(parse_csv_data("").unwrap().is_empty());
+        assert!(parse_csv_data("a,b\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn csv_with_an_unclosed_quote_is_rejected() {
+        assert!(parse_csv_data("a\n\"open\n").is_err());
+    }
+
+    #[test]
+    fn load_data_reads_json_by_extension() {
+        let rows = load(
+            "rows.json",
+            r#"[{"user": "alice", "id": 1, "admin": true}, {"user": "bob"}]"#,
+        ).unwrap();
+
+        assert_eq!(rows, vec![
+            row(&[("user", "alice"), ("id", "1"), ("admin", "true")]),
+            row(&[("user", "bob")]),
+        ]);
+
+        assert_eq!(
+            load("object.json", r#"{"id": "7"}"#).unwrap(),
+            vec![row(&[("id", "7")])],
+        );
+        assert!(load("numbers.json", "[1, 2]").is_err());
+    }
+
+    #[test]
+    fn load_data_reads_other_files_as_csv() {
+        assert_eq!(
+            load("rows.txt", "id\n1\n").unwrap(),
+            vec![row(&[("id", "1")])],
+        );
+    }
+
+    #[test]
+    fn load_data_names_the_file_in_errors() {
+        let error = load("bad.csv", "\"a").unwrap_err();
+
+        assert!(error.contains("bad.csv"), "{}", error);
+        assert!(load_data(Path::new("/nonexistent/dakia.csv")).is_err());
+    }
+
+    /// Answers a connection with each of `responses` in turn. Returns the
+    /// base url of the server and the request lines it received.
+    fn serve(responses: Vec<(&'static str, &'static str)>)
+        -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let mut lines = vec![];
+
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = vec![];
+                let mut buf = [0u8; 1024];
+
+                while !request.ends_with(b"\r\n\r\n") {
+                    let read = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..read]);
+                }
+
+                let request = String::from_utf8_lossy(&request).to_string();
+                lines.push(String::from(request.lines().next().unwrap_or_default()));
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\n\
+                    Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, body.len(), body,
+                ).unwrap();
+            }
+
+            lines
+        });
+
+        (base, server)
+    }
+
+    /// A url nothing listens on.
+    fn closed_url() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        format!("http://{}/", listener.local_addr().unwrap())
+    }
+
+    /// Writes a GET request of `url` to `dir`, with its `assertions` and
+    /// post-response `script`.
+    fn write_request(dir: &Path, name: &str, url: &str, assertions: &str, script: &str) {
+        let mut uistate = UiState::default();
+
+        uistate.load_url(String::from(url));
+        uistate.assertions_mut().load_text(assertions);
+        uistate.scripts_mut().editor_mut(ScriptKind::PostResponse).load_text(script);
+
+        save_request(
+            &dir.join(format!("{}.toml", name)),
+            &SavedRequest::from_uistate(&uistate),
+        ).unwrap();
+    }
+
+    fn collection(name: &str) -> PathBuf {
+        let root = env::temp_dir()
+            .join(format!("dakia-test-{}-runner-{}", process::id(), name));
+
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("folder")).unwrap();
+
+        root
+    }
+
+    fn result(name: &str, tests: &[(&str, bool)], error: Option<&str>) -> RequestResult {
+        RequestResult {
+            iteration: 1,
+            name: String::from(name),
+            method: String::from("GET"),
+            url: String::from("https://example.com"),
+            status: error.is_none().then_some(200),
+            reason: String::from("OK"),
+            elapsed: Duration::from_millis(1500),
+            tests: tests.iter()
+                .map(|(name, passed)| TestResult {
+                    name: String::from(*name),
+                    passed: *passed,
+                    detail: String::new(),
+                })
+                .collect(),
+            error: error.map(String::from),
+        }
+    }
+
+    fn report(results: Vec<RequestResult>, total: usize) -> RunReport {
+        RunReport {
+            name: String::from("api/<users> & \"admins\""),
+            started_at: Local::now(),
+            duration: Duration::from_secs(2),
+            iterations: 1,
+            total,
+            results,
+        }
+    }
+
+    #[test]
+    fn to_junit_counts_failures_and_errors_apart() {
+        let report = report(vec![
+            result("passes", &[("status == 200", true)], None),
+            result(
+                "fails",
+                &[("status == 200", true), ("body contains <ok>", false)],
+                None,
+            ),
+            result("errors", &[], Some("connection refused")),
+        ], 4);
+
+        let xml = report.to_junit();
+
+        assert!(xml.contains(
+            "<testsuites name=\"api/&lt;users&gt; &amp; &quot;admins&quot;\" \
+            tests=\"3\" failures=\"1\" errors=\"1\" time=\"2.000\">"
+        ), "{}", xml);
+        assert!(
+            xml.contains("tests=\"3\" failures=\"1\" errors=\"1\" time=\"4.500\""),
+            "{}", xml,
+        );
+        assert!(xml.contains("<testcase name=\"GET passes\""), "{}", xml);
+        assert!(xml.contains(
+            "<failure message=\"1 of 2 tests failed\">body contains &lt;ok&gt;</failure>"
+        ), "{}", xml);
+        assert!(xml.contains("<error message=\"connection refused\"/>"), "{}", xml);
+        assert_eq!(xml.matches("<testcase ").count(), 3);
+    }
+
+    #[test]
+    fn to_json_counts_the_results_and_keeps_the_total() {
+        let report = report(vec![
+            result("passes", &[("status == 200", true)], None),
+            result("fails", &[("status == 200", false)], None),
+        ], 3);
+
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+
+        assert_eq!(json["passed"], 1);
+        assert_eq!(json["failed"], 1);
+        assert_eq!(json["total"], 3);
+        assert_eq!(json["results"].as_array().unwrap().len(), 2);
+        assert_eq!(json["results"][1]["passed"], false);
+        assert_eq!(json["results"][1]["tests"][0]["name"], "status == 200");
+        assert_eq!(json["results"][0]["time_ms"], 1500);
+    }
+
+    #[test]
+    fn run_carries_variables_to_the_next_requests() {
+        let (base, server) = serve(vec![
+            ("200 OK", r#"{"token": "t0k"}"#),
+            ("200 OK", "{}"),
+        ]);
+        let root = collection("variables");
+        let folder = root.join("folder");
+
+        write_request(
+            &folder, "1-login", &format!("{}/login", base), "status == 200",
+            "set_var(\"token\", response.json.token);",
+        );
+        write_request(
+            &folder, "2-me", &format!("{}/me/{{{{token}}}}", base), "status == 200", "",
+        );
+
+        let plan = RunPlan::new(&root, &folder).unwrap();
+        let report = run(&mut UiState::default(), &plan, &AtomicBool::new(false), |_| {});
+
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            server.join().unwrap(),
+            ["GET /login HTTP/1.1", "GET /me/t0k HTTP/1.1"],
+        );
+        assert_eq!(report.passed_count(), 2);
+        assert_eq!(report.results()[1].name(), "folder/2-me");
+    }
+
+    #[test]
+    fn execute_reports_a_failing_assertion() {
+        let (base, server) = serve(vec![("404 Not Found", "{}")]);
+        let mut uistate = UiState::default();
+
+        uistate.load_url(base);
+        uistate.assertions_mut().load_text("status == 200\nstatus == 404");
+
+        let exchange = execute(&mut uistate).unwrap();
+
+        server.join().unwrap();
+
+        let passed: Vec<bool> = exchange.tests.iter().map(|test| test.passed).collect();
+
+        assert_eq!(passed, [false, true]);
+        assert_eq!(exchange.response.info().status_code(), 404);
+    }
+
+    #[test]
+    fn run_records_request_errors_and_stops_on_failure() {
+        let root = collection("errors");
+        let folder = root.join("folder");
+
+        write_request(&folder, "1-down", &closed_url(), "", "");
+        write_request(&folder, "2-never", &closed_url(), "", "");
+
+        let mut plan = RunPlan::new(&root, &folder).unwrap();
+
+        plan.set_stop_on_failure(true);
+
+        let report = run(&mut UiState::default(), &plan, &AtomicBool::new(false), |_| {});
+
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(report.results().len(), 1);
+        assert_eq!(report.total(), 2);
+        assert_eq!(report.failed_count(), 1);
+        assert!(report.results()[0].error().is_some());
+        assert_eq!(report.results()[0].status(), None);
+        assert!(report.to_junit().contains("errors=\"1\""));
+    }
+}
@@ -0,0 +1,166 @@
+//! Runs a collection folder from a worker thread so that the ui stays
+//! responsive during the run. The results are sent back to the `run_app`
+//! loop as they arrive and applied with [receive].
+
+use std::{ path::PathBuf, thread, time::Duration };
+
+use log::info;
+
+use crate::{
+    runner::{ RunPlan, RequestResult, RunReport, load_data, run },
+    ui::state::{ UiState, app_status::AppStatus },
+};
+
+/// Message sent from the worker thread back to the ui loop.
+pub struct RunEvent {
+    /// Id of the run this event belongs to.
+    run_id: u64,
+    kind: RunEventKind,
+}
+
+enum RunEventKind {
+    /// A request of the run completed.
+    Result(RequestResult),
+
+    /// The run completed or was stopped.
+    Finished(RunReport),
+}
+
+/// Starts running the folder opened in the runner with its options. The
+/// requests use the variables of the active environment.
+pub fn start(uistate: &mut UiState) {
+    if let Err(e) = try_start(uistate) {
+        info!("{}", e);
+
+        uistate.set_app_error(format!("Runner: {}", e));
+        uistate.set_app_status(AppStatus::ERROR);
+    }
+}
+
+fn try_start(uistate: &mut UiState) -> Result<(), String> {
+    let runner = uistate.runner();
+
+    if runner.running() {
+        return Ok(());
+    }
+
+    let folder = match runner.folder() {
+        Some(folder) => folder.to_path_buf(),
+        None => { return Err(String::from("no folder to run")); }
+    };
+
+    let sender = match runner.sender() {
+        Some(sender) => sender.clone(),
+        None => { return Err(String::from("the runner is not available")); }
+    };
+
+    let mut plan = RunPlan::new(uistate.sidebar().root(), &folder)
+        .map_err(|e| e.to_string())?;
+
+    let data_file = runner.data_file().trim();
+
+    if !data_file.is_empty() {
+        plan.set_data(
+            load_data(&PathBuf::from(data_file)).map_err(|e| e.to_string())?
+        );
+    }
+
+    let delay = match runner.delay().trim() {
+        "" => 0,
+        delay => delay.parse::<u64>()
+            .map_err(|_| String::from("the delay must be a number of milliseconds"))?,
+    };
+
+    plan.set_delay(Duration::from_millis(delay));
+    plan.set_stop_on_failure(runner.stop_on_failure());
+
+    // The run has its own copy of the environments, so the variables its
+    // scripts set don't change the request being edited. The cookie jars
+    // are shared, so the cookies set during the run reach the cookie
+    // manager and aren't lost when the ui saves the jar.
+    let mut state = UiState::default();
+    *state.environments_mut() = uistate.environments().clone();
+    state.cookies_mut().set_jars(uistate.cookies().jars().clone());
+    state.settings_mut().set_global(uistate.settings().global().clone());
+
+    info!("Running {} ({} requests)", plan.name(), plan.total());
+
+    let run_state = uistate.runner_mut().start(plan.total());
+    let run_id = run_state.id();
+    let cancelled = run_state.cancelled();
+
+    uistate.set_app_message(format!("Running {}", plan.name()));
+    uistate.set_app_status(AppStatus::INFO);
+
+    thread::spawn(move || {
+        let report = run(&mut state, &plan, &cancelled, |result| {
+            // The receiver is gone only when the app is exiting.
+            let _ = sender.send(RunEvent {
+                run_id,
+                kind: RunEventKind::Result(result.clone()),
+            });
+        });
+
+        let _ = sender.send(RunEvent {
+            run_id,
+            kind: RunEventKind::Finished(report),
+        });
+    });
+
+    Ok(())
+}
+
+/// Applies an event sent by the worker to the `uistate`. Events of runs that
+/// were stopped are ignored.
+pub fn receive(uistate: &mut UiState, event: RunEvent) {
+    match uistate.runner().active() {
+        Some(active) if active.id() == event.run_id => {}
+        _ => { return; }
+    }
+
+    match event.kind {
+        RunEventKind::Result(result) => {
+            uistate.runner_mut().push_result(result);
+        }
+
+        RunEventKind::Finished(report) => {
+            // Stopped by the user or at a failure
+            let verb = match report.results().len() < report.total() {
+                true => "Stopped",
+                false => "Ran",
+            };
+
+            let message = format!(
+                "{} {}: {} passed, {} failed",
+                verb,
+                report.name(),
+                report.passed_count(),
+                report.failed_count(),
+            );
+
+            info!("{}", message);
+
+            uistate.runner_mut().finish(report);
+            uistate.set_app_message(message);
+            uistate.set_app_status(AppStatus::INFO);
+        }
+    }
+}
+
+/// Stops the run in progress, keeping the results received so far.
+/// Returns `true` if a run was stopped.
+pub fn cancel(uistate: &mut UiState) -> bool {
+    match uistate.runner().active().clone() {
+        Some(active) => {
+            info!("Stopping run #{}", active.id());
+
+            // The worker sends the report of the requests run so far
+            active.cancel();
+            uistate.set_app_status(AppStatus::CANCELLED);
+
+            true
+        }
+
+        None => false,
+    }
+}
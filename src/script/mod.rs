@@ -0,0 +1,653 @@
+//! Pre-request and post-response scripts, written in [Rhai](https://rhai.rs).
+//!
+//! The pre-request script runs before the request is built and can change
+//! the `method`, `url`, `headers` and `body` of the `request` map. Their
+//! `{{variables}}` are resolved before the script runs, so the script sees
+//! and signs what is sent:
+//!
+//! ```rhai
+//! let ts = timestamp();
+//! request.headers["X-Timestamp"] = ts;
+//! request.headers["X-Signature"] = hmac_sha256(get_var("secret"), request.body + ts);
+//! ```
+//!
+//! The body keeps its kind: a form body is sent from its rows, so the
+//! script can't change it.
+//!
+//! The post-response script runs once the response is received, with the
+//! `status`, `headers`, `body` and `json` of the `response` map:
+//!
+//! ```rhai
+//! set_var("token", response.json.token);
+//! test("logged in", response.status == 200);
+//! ```
+//!
+//! Variables set with `set_var` are used as `{{name}}` in the requests like
+//! the variables of the environments, which they override until dakia exits.
+//! Those set by a pre-request script reach the fields it doesn't see, like
+//! the authorization, and the next requests.
+
+use std::{ cell::RefCell, collections::BTreeMap, rc::Rc, str::FromStr };
+
+use base64::{ Engine as _, engine::general_purpose::STANDARD as BASE64 };
+use chrono::Utc;
+use hmac::{ Hmac, Mac };
+use log::info;
+use reqwest::Method;
+use rhai::{ Dynamic, Engine, EvalAltResult, ImmutableString, Map, Scope };
+use sha2::{ Digest, Sha256 };
+
+use crate::{
+    api::{ ResponseData, resolved_url },
+    environment::resolve,
+    assertion::TestResult,
+    graphql::body_payload,
+    ui::state::{
+        UiState, kv_data::KVData, scripts::ScriptKind, body::BodyContent,
+    },
+};
+
+/// Number of operations after which a script is stopped, so that a script
+/// that loops forever doesn't freeze the ui.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// The parts of the request a pre-request script can change.
+#[derive(Clone, PartialEq)]
+pub struct ScriptRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+/// The request as the pre-request script returned it. Its `{{variables}}`
+/// were resolved before the script ran, it is sent as it is.
+pub struct ScriptedRequest {
+    method: Method,
+    url: String,
+    headers: BTreeMap<String, String>,
+
+    /// The raw body, or the payload of a GraphQL body. Form bodies are sent
+    /// from their rows.
+    body: String,
+}
+
+impl ScriptedRequest {
+    pub fn method(&self) -> &Method { &self.method }
+    pub fn url(&self) -> &str { &self.url }
+    pub fn headers(&self) -> &BTreeMap<String, String> { &self.headers }
+    pub fn body(&self) -> &str { &self.body }
+}
+
+/// The response given to a post-response script.
+pub struct ScriptResponse<'a> {
+    pub status: u16,
+    pub headers: &'a [KVData],
+    pub body: &'a str,
+}
+
+/// What a script did besides changing the request.
+#[derive(Default)]
+pub struct ScriptOutcome {
+    /// Variables set with `set_var`, in the order they were set.
+    pub variables: Vec<(String, String)>,
+    pub tests: Vec<TestResult>,
+}
+
+/// State shared by the functions the scripts call.
+#[derive(Default)]
+struct ScriptContext {
+    /// The variables `get_var` reads, including those set by the script.
+    variables: BTreeMap<String, String>,
+    outcome: ScriptOutcome,
+}
+
+/// Runs a pre-request `script`, which can change the `request`.
+/// `variables` are the variables the script can read.
+pub fn run_pre_request(
+    script: &str,
+    request: &mut ScriptRequest,
+    variables: &BTreeMap<String, String>,
+) -> Result<ScriptOutcome, String> {
+    let context = Rc::new(RefCell::new(ScriptContext {
+        variables: variables.clone(),
+        ..Default::default()
+    }));
+
+    let mut headers = Map::new();
+
+    for (name, value) in request.headers.iter() {
+        headers.insert(name.into(), Dynamic::from(value.clone()));
+    }
+
+    let mut map = Map::new();
+    map.insert("method".into(), Dynamic::from(request.method.clone()));
+    map.insert("url".into(), Dynamic::from(request.url.clone()));
+    map.insert("headers".into(), Dynamic::from_map(headers));
+    map.insert("body".into(), Dynamic::from(request.body.clone()));
+
+    let mut scope = Scope::new();
+    scope.push("request", map);
+
+    engine(context.clone())
+        .run_with_scope(&mut scope, script)
+        .map_err(|e| e.to_string())?;
+
+    let map = scope.get_value::<Map>("request")
+        .ok_or("`request` must be a map")?;
+
+    let field = |name: &str| map.get(name)
+        .map(|value| value.to_string())
+        .unwrap_or_default();
+
+    request.method = field("method");
+    request.url = field("url");
+    request.body = field("body");
+
+    request.headers = match map.get("headers").and_then(|h| h.read_lock::<Map>()) {
+        Some(headers) => headers.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+
+        None => { return Err(String::from("`request.headers` must be a map")); }
+    };
+
+    Ok(outcome(context))
+}
+
+/// Runs a post-response `script` on the `response`. `variables` are the
+/// variables the script can read.
+pub fn run_post_response(
+    script: &str,
+    response: &ScriptResponse,
+    variables: &BTreeMap<String, String>,
+) -> Result<ScriptOutcome, String> {
+    let context = Rc::new(RefCell::new(ScriptContext {
+        variables: variables.clone(),
+        ..Default::default()
+    }));
+
+    let mut headers = Map::new();
+
+    // Header names are case insensitive, the script reads them lowercase
+    for header in response.headers.iter() {
+        headers.insert(
+            header.key().to_lowercase().into(),
+            Dynamic::from(header.value()),
+        );
+    }
+
+    // `()` when the body isn't JSON
+    let json = serde_json::from_str::<serde_json::Value>(response.body).ok()
+        .and_then(|value| rhai::serde::to_dynamic(value).ok())
+        .unwrap_or(Dynamic::UNIT);
+
+    let mut map = Map::new();
+    map.insert("status".into(), Dynamic::from(response.status as i64));
+    map.insert("headers".into(), Dynamic::from_map(headers));
+    map.insert("body".into(), Dynamic::from(String::from(response.body)));
+    map.insert("json".into(), json);
+
+    let mut scope = Scope::new();
+    scope.push_constant("response", map);
+
+    engine(context.clone())
+        .run_with_scope(&mut scope, script)
+        .map_err(|e| e.to_string())?;
+
+    Ok(outcome(context))
+}
+
+/// Runs the pre-request script of `uistate`, setting the variables it sets.
+/// Returns the request as changed by the script, `None` if there is no
+/// script.
+pub fn pre_request(uistate: &mut UiState)
+    -> Result<Option<ScriptedRequest>, String> {
+    if !uistate.scripts().has_script(ScriptKind::PreRequest) {
+        return Ok(None);
+    }
+
+    let original = script_request(uistate)?;
+    let mut request = original.clone();
+
+    let outcome = run_pre_request(
+        &uistate.scripts().script(ScriptKind::PreRequest),
+        &mut request,
+        &variables(uistate),
+    )?;
+
+    set_variables(uistate, outcome.variables);
+
+    scripted_request(
+        uistate.body().body_content(),
+        uistate.method(),
+        &original,
+        request,
+    ).map(Some)
+}
+
+/// Runs the post-response script of `uistate` on the response `data`,
+/// setting the variables it sets. Returns the results of its tests.
+pub fn post_response(uistate: &mut UiState, data: &ResponseData)
+    -> Result<Vec<TestResult>, String> {
+    if !uistate.scripts().has_script(ScriptKind::PostResponse) {
+        return Ok(vec![]);
+    }
+
+    let response = ScriptResponse {
+        status: data.info().status_code(),
+        headers: data.info().headers(),
+        body: data.body(),
+    };
+
+    let outcome = run_post_response(
+        &uistate.scripts().script(ScriptKind::PostResponse),
+        &response,
+        &variables(uistate),
+    )?;
+
+    set_variables(uistate, outcome.variables);
+
+    Ok(outcome.tests)
+}
+
+/// The request of `uistate` as the pre-request script sees it, with the
+/// `{{variables}}` resolved as they are sent.
+fn script_request(uistate: &UiState) -> Result<ScriptRequest, String> {
+    let env = uistate.active_environment();
+    let var = |text: String| resolve(&text, env);
+
+    let body = match uistate.body().body_content() {
+        BodyContent::Text | BodyContent::Json | BodyContent::Html
+            | BodyContent::Xml => var(uistate.body().text_data().text()),
+
+        // The JSON payload that is sent
+        BodyContent::GraphQL => body_payload(uistate.body(), var)?,
+
+        _ => String::new(),
+    };
+
+    Ok(ScriptRequest {
+        method: uistate.method().to_string(),
+        url: resolved_url(uistate),
+        headers: uistate.request_headers().iter()
+            .filter(|h| !h.key().is_empty())
+            .map(|h| (var(h.key()), var(h.value())))
+            .collect(),
+        body,
+    })
+}
+
+/// The `request` the script returned for a body of kind `content`. The
+/// method is parsed only when the script changed it. The body keeps its
+/// kind: form bodies are sent from their rows, so the script can't change
+/// them.
+fn scripted_request(
+    content: &BodyContent,
+    method: Method,
+    original: &ScriptRequest,
+    request: ScriptRequest,
+) -> Result<ScriptedRequest, String> {
+    let method = match request.method == original.method {
+        true => method,
+        false => Method::from_str(&request.method.to_uppercase())
+            .map_err(|_| format!("invalid method \"{}\"", request.method))?,
+    };
+
+    match content {
+        BodyContent::Text | BodyContent::Json | BodyContent::Html
+            | BodyContent::Xml | BodyContent::GraphQL => {}
+
+        _ if request.body == original.body => {}
+
+        _ => {
+            return Err(format!(
+                "the body can't be changed with a \"{}\" body, select a raw body",
+                content.to_string(),
+            ));
+        }
+    }
+
+    Ok(ScriptedRequest {
+        method,
+        url: request.url,
+        headers: request.headers,
+        body: request.body,
+    })
+}
+
+/// The variables the scripts can read.
+fn variables(uistate: &UiState) -> BTreeMap<String, String> {
+    uistate.active_environment()
+        .map(|environment| environment.variables().clone())
+        .unwrap_or_default()
+}
+
+fn set_variables(uistate: &mut UiState, variables: Vec<(String, String)>) {
+    for (name, value) in variables {
+        uistate.environments_mut().set_variable(name, value);
+    }
+}
+
+fn outcome(context: Rc<RefCell<ScriptContext>>) -> ScriptOutcome {
+    std::mem::take(&mut context.borrow_mut().outcome)
+}
+
+/// The engine with the functions available to the scripts.
+fn engine(context: Rc<RefCell<ScriptContext>>) -> Engine {
+    let mut engine = Engine::new();
+
+    engine.set_max_operations(MAX_OPERATIONS);
+
+    // Printing to stdout would garble the ui
+    engine.on_print(|text| info!("Script: {}", text));
+    engine.on_debug(|text, _, pos| info!("Script ({}): {}", pos, text));
+
+    let ctx = context.clone();
+    engine.register_fn("get_var", move |name: &str| -> Dynamic {
+        match ctx.borrow().variables.get(name) {
+            Some(value) => Dynamic::from(value.clone()),
+            None => Dynamic::UNIT,
+        }
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("set_var", move |name: &str, value: Dynamic| {
+        let mut ctx = ctx.borrow_mut();
+        let value = value.to_string();
+
+        ctx.variables.insert(String::from(name), value.clone());
+        ctx.outcome.variables.push((String::from(name), value));
+    });
+
+    let ctx = context;
+    engine.register_fn("test", move |name: &str, passed: bool| {
+        ctx.borrow_mut().outcome.tests.push(TestResult {
+            name: String::from(name),
+            passed,
+            detail: String::new(),
+        });
+    });
+
+    engine.register_fn("timestamp", || Utc::now().timestamp());
+    engine.register_fn("timestamp_ms", || Utc::now().timestamp_millis());
+    engine.register_fn("iso_time", || Utc::now().to_rfc3339());
+
+    engine.register_fn("sha256", |text: &str| hex(&Sha256::digest(text)));
+
+    engine.register_fn("hmac_sha256", |key: &str, message: &str| {
+        hmac_sha256(key, message).map(|mac| hex(&mac))
+    });
+
+    engine.register_fn("hmac_sha256_base64", |key: &str, message: &str| {
+        hmac_sha256(key, message).map(|mac| BASE64.encode(mac))
+    });
+
+    engine.register_fn("base64_encode", |text: &str| BASE64.encode(text));
+
+    engine.register_fn("base64_decode",
+        |text: &str| -> Result<ImmutableString, Box<EvalAltResult>> {
+            let bytes = BASE64.decode(text)
+                .map_err(|e| format!("base64_decode: {}", e))?;
+
+            Ok(String::from_utf8_lossy(&bytes).to_string().into())
+        },
+    );
+
+    engine
+}
+
+fn hmac_sha256(key: &str, message: &str)
+    -> Result<Vec<u8>, Box<EvalAltResult>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes())
+        .map_err(|e| format!("hmac_sha256: {}", e))?;
+
+    mac.update(message.as_bytes());
+
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+/// `bytes` as lowercase hexadecimal.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> ScriptRequest {
+        ScriptRequest {
+            method: String::from("POST"),
+            url: String::from("https://api.example.com/users"),
+            headers: BTreeMap::from([
+                (String::from("Accept"), String::from("application/json")),
+            ]),
+            body: String::from("{\"name\":\"ada\"}"),
+        }
+    }
+
+    /// The value of the variable `name` the script set last.
+    fn variable<'a>(outcome: &'a ScriptOutcome, name: &str) -> &'a str {
+        outcome.variables.iter().rev()
+            .find(|(var, _)| var == name)
+            .map(|(_, value)| value.as_str())
+            .unwrap()
+    }
+
+    /// Runs `script` and returns the value of its variable `out`.
+    fn eval(script: &str) -> String {
+        let outcome = run_post_response(
+            script,
+            &ScriptResponse { status: 200, headers: &[], body: "" },
+            &BTreeMap::new(),
+        ).unwrap();
+
+        String::from(variable(&outcome, "out"))
+    }
+
+    #[test]
+    fn run_pre_request_changes_the_request() {
+        let mut request = request();
+        let variables = BTreeMap::from([
+            (String::from("secret"), String::from("s3cr3t")),
+        ]);
+
+        let outcome = run_pre_request(
+            r#"
+                request.method = "PUT";
+                request.url += "/1";
+                request.headers["X-Secret"] = get_var("secret");
+                request.body = "{}";
+                set_var("sent", "yes");
+            "#,
+            &mut request,
+            &variables,
+        ).unwrap();
+
+        assert_eq!(request.method, "PUT");
+        assert_eq!(request.url, "https://api.example.com/users/1");
+        assert_eq!(request.headers["X-Secret"], "s3cr3t");
+        assert_eq!(request.headers["Accept"], "application/json");
+        assert_eq!(request.body, "{}");
+        assert_eq!(outcome.variables, vec![(String::from("sent"), String::from("yes"))]);
+    }
+
+    #[test]
+    fn run_pre_request_reads_the_variables_it_sets() {
+        let mut request = request();
+
+        let outcome = run_pre_request(
+            "set_var(\"id\", 7); request.url += \"/\" + get_var(\"id\");",
+            &mut request,
+            &BTreeMap::new(),
+        ).unwrap();
+
+        assert_eq!(request.url, "https://api.example.com/users/7");
+        assert_eq!(variable(&outcome, "id"), "7");
+    }
+
+    #[test]
+    fn run_pre_request_fails_on_invalid_scripts() {
+        let cases = [
+            "request.url = ",
+            "request.headers = 1;",
+            "loop {}",
+            "throw \"no token\";",
+        ];
+
+        for case in cases {
+            assert!(
+                run_pre_request(case, &mut request(), &BTreeMap::new()).is_err(),
+                "{}", case,
+            );
+        }
+    }
+
+    #[test]
+    fn run_post_response_reads_the_response() {
+        let headers = [KVData::new(String::from("X-Request-Id"), String::from("abc"))];
+        let response = ScriptResponse {
+            status: 201,
+            headers: &headers,
+            body: "{\"token\":\"t0k\",\"user\":{\"id\":3}}",
+        };
+
+        let outcome = run_post_response(
+            r#"
+                set_var("token", response.json.token);
+                set_var("request_id", response.headers["x-request-id"]);
+                test("created", response.status == 201);
+                test("user 4", response.json.user.id == 4);
+            "#,
+            &response,
+            &BTreeMap::new(),
+        ).unwrap();
+
+        assert_eq!(variable(&outcome, "token"), "t0k");
+        assert_eq!(variable(&outcome, "request_id"), "abc");
+
+        let tests: Vec<(&str, bool)> = outcome.tests.iter()
+            .map(|test| (test.name.as_str(), test.passed))
+            .collect();
+
+        assert_eq!(tests, vec![("created", true), ("user 4", false)]);
+    }
+
+    #[test]
+    fn run_post_response_has_no_json_for_other_bodies() {
+        let outcome = run_post_response(
+            "test(\"no json\", response.json == ()); set_var(\"body\", response.body);",
+            &ScriptResponse { status: 200, headers: &[], body: "plain" },
+            &BTreeMap::new(),
+        ).unwrap();
+
+        assert!(outcome.tests[0].passed);
+        assert_eq!(variable(&outcome, "body"), "plain");
+    }
+
+    #[test]
+    fn scripted_request_keeps_the_method_unless_changed() {
+        let original = request();
+        let mut changed = request();
+        changed.method = String::from("patch");
+
+        let json = BodyContent::Json;
+        let kept = scripted_request(&json, Method::POST, &original, request()).unwrap();
+        let patched = scripted_request(&json, Method::POST, &original, changed).unwrap();
+
+        assert_eq!(*kept.method(), Method::POST);
+        assert_eq!(*patched.method(), Method::PATCH);
+    }
+
+    #[test]
+    fn scripted_request_fails_on_an_invalid_method() {
+        let mut changed = request();
+        changed.method = String::from("NOT A METHOD");
+
+        let json = BodyContent::Json;
+
+        assert!(scripted_request(&json, Method::POST, &request(), changed).is_err());
+    }
+
+    #[test]
+    fn scripted_request_sends_raw_and_graphql_bodies_as_changed() {
+        let contents = [
+            BodyContent::Text, BodyContent::Json, BodyContent::Html,
+            BodyContent::Xml, BodyContent::GraphQL,
+        ];
+
+        for content in contents {
+            let mut changed = request();
+            changed.body = String::from("changed");
+
+            let scripted = scripted_request(&content, Method::POST, &request(), changed)
+                .unwrap();
+
+            assert_eq!(scripted.body(), "changed", "{}", content.to_string());
+        }
+    }
+
+    #[test]
+    fn scripted_request_refuses_to_change_a_form_body() {
+        let contents = [
+            BodyContent::NONE, BodyContent::FormData, BodyContent::FormURLEncoded,
+        ];
+
+        for content in contents {
+            let mut changed = request();
+            changed.body = String::from("changed");
+
+            assert!(
+                scripted_request(&content, Method::POST, &request(), request()).is_ok(),
+                "{}", content.to_string(),
+            );
+            assert!(
+                scripted_request(&content, Method::POST, &request(), changed).is_err(),
+                "{}", content.to_string(),
+            );
+        }
+    }
+
+    #[test]
+    fn hash_functions_match_known_digests() {
+        assert_eq!(
+            eval("set_var(\"out\", sha256(\"abc\"));"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        );
+        assert_eq!(
+            eval(
+                "set_var(\"out\", hmac_sha256(\"key\", \
+                \"The quick brown fox jumps over the lazy dog\"));"
+            ),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8",
+        );
+        assert_eq!(
+            eval(
+                "set_var(\"out\", hmac_sha256_base64(\"key\", \
+                \"The quick brown fox jumps over the lazy dog\"));"
+            ),
+            "97yD9DBThCSxMpjmqm+xQ+9NWaFJRhdZl0edvC0aPNg=",
+        );
+    }
+
+    #[test]
+    fn base64_functions_round_trip() {
+        let encoded = eval("set_var(\"out\", base64_encode(\"user:pass\"));");
+        let decoded = eval(&format!("set_var(\"out\", base64_decode(\"{}\"));", encoded));
+
+        assert_eq!(encoded, "dXNlcjpwYXNz");
+        assert_eq!(decoded, "user:pass");
+    }
+
+    #[test]
+    fn base64_decode_fails_on_invalid_input() {
+        let result = run_post_response(
+            "base64_decode(\"not base64!\");",
+            &ScriptResponse { status: 200, headers: &[], body: "" },
+            &BTreeMap::new(),
+        );
+
+        assert!(result.is_err());
+    }
+}
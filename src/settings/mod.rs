@@ -0,0 +1,409 @@
+//! Settings of the HTTP client: timeout, redirects, proxy and TLS.
+//!
+//! The global settings are read from `settings.toml` in the working
+//! directory, every key is optional:
+//!
+//! ```toml
+//! timeout = 30                       # seconds, 0 waits forever
+//! redirects = 5                      # 0 doesn't follow redirects
+//! proxy = "socks5://localhost:1080"  # http://, https:// or socks5://
+//! no_proxy = "localhost,.internal.example.com"
+//! verify_tls = false
+//! ca_bundle = "certs/corporate.pem"
+//! user_agent = "Dakia/0.1.0"
+//!
+//! [headers]
+//! accept-language = "de-DE"
+//! ```
+//!
+//! The "Settings" tab of a request overrides them for that request.
+
+use std::{ collections::BTreeMap, error::Error, fs, path::Path, time::Duration };
+
+use reqwest::{
+    blocking::ClientBuilder, redirect::Policy, Certificate, NoProxy, Proxy,
+};
+use serde::Deserialize;
+
+/// File the global settings are read from, relative to the working
+/// directory.
+pub const DEFAULT_SETTINGS_FILE: &str = "settings.toml";
+
+pub const DEFAULT_TIMEOUT_SECS: u64 = 20;
+
+/// Redirects followed by default, the same as browsers.
+pub const DEFAULT_REDIRECTS: usize = 10;
+
+pub const DEFAULT_USER_AGENT: &str = "Dakia/0.1.0";
+
+const PEM_CERT_END: &str = "-----END CERTIFICATE-----";
+
+/// Settings of the client, `None` where the setting isn't set and the
+/// default or the global setting is used.
+#[derive(Clone, Default, Deserialize)]
+pub struct ClientSettings {
+    /// Seconds to wait for the whole response, 0 to wait forever.
+    timeout: Option<u64>,
+
+    /// Redirects to follow, 0 to return the redirect response.
+    redirects: Option<usize>,
+
+    proxy: Option<String>,
+
+    /// Comma separated hosts, domains and IP ranges reached without the
+    /// proxy.
+    no_proxy: Option<String>,
+
+    /// Whether the certificates and host names of the servers are checked.
+    verify_tls: Option<bool>,
+
+    /// PEM file with the certificates trusted in addition to the system
+    /// ones.
+    ca_bundle: Option<String>,
+
+    user_agent: Option<String>,
+
+    /// Headers sent with every request, replacing the built-in ones of the
+    /// same name.
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+}
+
+impl ClientSettings {
+    pub fn new(
+        timeout: Option<u64>,
+        redirects: Option<usize>,
+        proxy: Option<String>,
+        no_proxy: Option<String>,
+        verify_tls: Option<bool>,
+        ca_bundle: Option<String>,
+    ) -> Self {
+        Self {
+            timeout, redirects, proxy, no_proxy, verify_tls, ca_bundle,
+            user_agent: None,
+            headers: BTreeMap::new(),
+        }
+    }
+
+    pub fn timeout(&self) -> u64 {
+        self.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS)
+    }
+
+    pub fn redirects(&self) -> usize {
+        self.redirects.unwrap_or(DEFAULT_REDIRECTS)
+    }
+
+    pub fn proxy(&self) -> Option<&str> { self.proxy.as_deref() }
+    pub fn no_proxy(&self) -> Option<&str> { self.no_proxy.as_deref() }
+    pub fn verify_tls(&self) -> bool { self.verify_tls.unwrap_or(true) }
+    pub fn ca_bundle(&self) -> Option<&str> { self.ca_bundle.as_deref() }
+
+    pub fn user_agent(&self) -> &str {
+        self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT)
+    }
+
+    pub fn headers(&self) -> &BTreeMap<String, String> { &self.headers }
+
+    /// These settings with the ones they don't set taken from `global`.
+    pub fn merged(&self, global: &ClientSettings) -> ClientSettings {
+        ClientSettings {
+            timeout: self.timeout.or(global.timeout),
+            redirects: self.redirects.or(global.redirects),
+            proxy: self.proxy.clone().or_else(|| global.proxy.clone()),
+            no_proxy: self.no_proxy.clone().or_else(|| global.no_proxy.clone()),
+            verify_tls: self.verify_tls.or(global.verify_tls),
+            ca_bundle: self.ca_bundle.clone().or_else(|| global.ca_bundle.clone()),
+            user_agent: self.user_agent.clone().or_else(|| global.user_agent.clone()),
+            headers: global.headers.clone().into_iter()
+                .chain(self.headers.clone())
+                .collect(),
+        }
+    }
+
+    /// Sets up `builder` with these settings.
+    pub fn configure(&self, mut builder: ClientBuilder)
+        -> Result<ClientBuilder, Box<dyn Error>> {
+        builder = builder
+            .user_agent(self.user_agent())
+            .timeout(match self.timeout() {
+                0 => None,
+                secs => Some(Duration::from_secs(secs)),
+            })
+            .redirect(match self.redirects() {
+                0 => Policy::none(),
+                max => Policy::limited(max),
+            });
+
+        if let Some(proxy) = self.proxy() {
+            let proxy = Proxy::all(proxy)
+                .map_err(|e| format!("invalid proxy {}: {}", proxy, e))?;
+
+            builder = builder.proxy(
+                proxy.no_proxy(self.no_proxy().and_then(NoProxy::from_string))
+            );
+        }
+
+        if !self.verify_tls() {
+            // Skips the host name check as well
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        if let Some(path) = self.ca_bundle() {
+            for certificate in load_ca_bundle(Path::new(path))? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        Ok(builder)
+    }
+}
+
+/// Reads the global settings from `path`. A missing file means the defaults.
+pub fn load_settings(path: &Path) -> Result<ClientSettings, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(ClientSettings::default());
+    }
+
+    Ok(toml::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Reads the certificates of the PEM file at `path`. The TLS library reads
+/// one certificate at a time, so the bundle is split first.
+fn load_ca_bundle(path: &Path) -> Result<Vec<Certificate>, Box<dyn Error>> {
+    let pem = fs::read_to_string(path)
+        .map_err(|e| format!("couldn't read the CA bundle {}: {}", path.display(), e))?;
+
+    let certificates = pem.split_inclusive(PEM_CERT_END)
+        .filter(|block| block.contains(PEM_CERT_END))
+        .map(|block| Certificate::from_pem(block.trim().as_bytes()))
+        .collect::<Result<Vec<Certificate>, _>>()
+        .map_err(|e| format!("invalid CA bundle {}: {}", path.display(), e))?;
+
+    if certificates.is_empty() {
+        return Err(format!("no certificate in the CA bundle {}", path.display()).into());
+    }
+
+    Ok(certificates)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, process, thread,
+        io::{ Read, Write },
+        net::TcpListener,
+    };
+
+    use reqwest::blocking::Client;
+
+    use super::*;
+
+    /// Two self-signed certificates, of `dakia-test-a` and `dakia-test-b`.
+    const CERTIFICATES: &str = "\
+-----BEGIN CERTIFICATE-----\n\
+MIIBhDCCASugAwIBAgIUKx/pWrznzJTIp/AsaAoEE+cPMSYwCgYIKoZIzj0EAwIw\n\
+FzEVMBMGA1UEAwwMZGFraWEtdGVzdC1hMCAXDTI2MTAxNzExMDc1MloYDzIxMjYw\n\
+OTIzMTEwNzUyWjAXMRUwEwYDVQQDDAxkYWtpYS10ZXN0LWEwWTATBgcqhkjOPQIB\n\
+BggqhkjOPQMBBwNCAAS2v76gFBOrEYomQpwRPswCoOOTz+L1NdVe++CQmSS1OAEu\n\
+SW0Rv0HbZKDlMfyzry7kIOGhFf91UWM0YxLGE2EOo1MwUTAdBgNVHQ4EFgQU90It\n\
+nLf8VFqXA/16pt168wdIPQowHwYDVR0jBBgwFoAU90ItnLf8VFqXA/16pt168wdI\n\
+PQowDwYDVR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNHADBEAiBy6STV37sNU8uE\n\
+NVrgY7QW5fVZVLu2WGJdmbKmRdre2wIgDC+xGRedIDmVIqk2JETgGkZZkD38aTWO\n\
+wIYISdtBK2w=\n\
+-----END CERTIFICATE-----\n\
+-----BEGIN CERTIFICATE-----\n\
+MIIBhTCCASugAwIBAgIUcqRORsRh3DmABrZy3mR5s3awdV8wCgYIKoZIzj0EAwIw\n\
+FzEVMBMGA1UEAwwMZGFraWEtdGVzdC1iMCAXDTI2MTAxNzExMDc1MloYDzIxMjYw\n\
+OTIzMTEwNzUyWjAXMRUwEwYDVQQDDAxkYWtpYS10ZXN0LWIwWTATBgcqhkjOPQIB\n\
+BggqhkjOPQMBBwNCAAT1NU+xTN8nb1S61Td59StD+lTQLgOmYrqBFtMZPZMdrf/Q\n\
+CyjQ5BwD2MwBxdl8COrYrxN06xtTOggxdjVOZxD4o1MwUTAdBgNVHQ4EFgQUYaNg\n\
+ogoJz7i+yzRTW2VFyTa/cmEwHwYDVR0jBBgwFoAUYaNgogoJz7i+yzRTW2VFyTa/\n\
+cmEwDwYDVR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNIADBFAiEAt4BqjvSDdmZa\n\
+LprtMbbamAp52xOYrc+l47nC5D7kaN0CIFjmyxbcINQ+M8ZTTqPmRTalmSUSm+0f\n\
+KHA4v1BPmV65\n\
+-----END CERTIFICATE-----\n\
+";
+
+    fn settings(timeout: Option<u64>, redirects: Option<usize>) -> ClientSettings {
+        ClientSettings::new(timeout, redirects, None, None, None, None)
+    }
+
+    /// Answers every connection with a redirect to `/next`, for `count`
+    /// connections. Returns the url of the server.
+    fn redirecting(count: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            for _ in 0..count {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 1024];
+                let mut request = vec![];
+
+                while !request.ends_with(b"\r\n\r\n") {
+                    let read = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..read]);
+                }
+
+                stream.write_all(
+                    b"HTTP/1.1 302 Found\r\nLocation: /next\r\n\
+                    Content-Length: 0\r\nConnection: close\r\n\r\n"
+                ).unwrap();
+            }
+        });
+
+        url
+    }
+
+    fn client(settings: &ClientSettings) -> Client {
+        settings.configure(ClientBuilder::new()).unwrap().build().unwrap()
+    }
+
+    /// Writes `pem` to a temp file and reads it as a CA bundle.
+    fn ca_bundle(name: &str, pem: &str) -> Result<Vec<Certificate>, String> {
+        let path = env::temp_dir()
+            .join(format!("dakia-test-{}-{}.pem", process::id(), name));
+
+        fs::write(&path, pem).unwrap();
+
+        let certificates = load_ca_bundle(&path).map_err(|e| e.to_string());
+        fs::remove_file(&path).unwrap();
+
+        certificates
+    }
+
+    #[test]
+    fn merged_settings_override_the_global_ones() {
+        let mut global = ClientSettings::new(
+            Some(5), Some(3), Some(String::from("http://proxy:8080")),
+            Some(String::from("localhost")), Some(false), None,
+        );
+
+        global.headers.insert(String::from("x-team"), String::from("core"));
+        global.headers.insert(String::from("x-env"), String::from("global"));
+
+        let mut request = ClientSettings::new(
+            Some(0), None, None, Some(String::from("internal")), Some(true), None,
+        );
+
+        request.headers.insert(String::from("x-env"), String::from("request"));
+
+        let merged = request.merged(&global);
+
+        assert_eq!(merged.timeout(), 0);
+        assert_eq!(merged.redirects(), 3);
+        assert_eq!(merged.proxy(), Some("http://proxy:8080"));
+        assert_eq!(merged.no_proxy(), Some("internal"));
+        assert!(merged.verify_tls());
+        assert_eq!(merged.ca_bundle(), None);
+        assert_eq!(merged.headers()["x-team"], "core");
+        assert_eq!(merged.headers()["x-env"], "request");
+    }
+
+    #[test]
+    fn unset_settings_use_the_defaults() {
+        let merged = ClientSettings::default().merged(&ClientSettings::default());
+
+        assert_eq!(merged.timeout(), DEFAULT_TIMEOUT_SECS);
+        assert_eq!(merged.redirects(), DEFAULT_REDIRECTS);
+        assert_eq!(merged.user_agent(), DEFAULT_USER_AGENT);
+        assert!(merged.verify_tls());
+    }
+
+    #[test]
+    fn no_redirects_returns_the_redirect_response() {
+        let response = client(&settings(None, Some(0)))
+            .get(redirecting(1))
+            .send()
+            .unwrap();
+
+        assert_eq!(response.status().as_u16(), 302);
+    }
+
+    #[test]
+    fn redirects_are_followed_up_to_the_limit() {
+        let error = client(&settings(None, Some(2)))
+            .get(redirecting(3))
+            .send()
+            .unwrap_err();
+
+        assert!(error.is_redirect(), "{}", error);
+    }
+
+    #[test]
+    fn no_timeout_waits_for_a_slow_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).unwrap();
+
+            thread::sleep(Duration::from_millis(1200));
+
+            stream.write_all(
+                b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok"
+            ).unwrap();
+        });
+
+        // A timeout of 1 second would fail the same request
+        let response = client(&settings(Some(0), None)).get(url).send().unwrap();
+
+        assert_eq!(response.text().unwrap(), "ok");
+    }
+
+    #[test]
+    fn no_proxy_hosts_are_reached_without_the_proxy() {
+        // Nothing listens on the port of the proxy
+        let proxy = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let proxy = Some(format!("http://{}", proxy));
+
+        let direct = ClientSettings::new(
+            None, Some(0), proxy.clone(), Some(String::from("127.0.0.1")), None, None,
+        );
+        let proxied = ClientSettings::new(None, Some(0), proxy, None, None, None);
+
+        let url = redirecting(1);
+
+        assert!(client(&proxied).get(&url).send().is_err());
+        assert_eq!(client(&direct).get(&url).send().unwrap().status().as_u16(), 302);
+    }
+
+    #[test]
+    fn invalid_proxy_is_reported() {
+        let settings = ClientSettings::new(
+            None, None, Some(String::from("not a url")), None, None, None,
+        );
+
+        let error = settings.configure(ClientBuilder::new()).unwrap_err();
+
+        assert!(error.to_string().starts_with("invalid proxy not a url"), "{}", error);
+    }
+
+    #[test]
+    fn ca_bundle_is_split_into_its_certificates() {
+        assert_eq!(ca_bundle("two", CERTIFICATES).unwrap().len(), 2);
+
+        let with_text = format!("# corporate roots\n{}\ntrailing text\n", CERTIFICATES);
+
+        assert_eq!(ca_bundle("text", &with_text).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn ca_bundle_with_a_bad_certificate_is_rejected() {
+        let bad = format!(
+            "{}-----BEGIN CERTIFICATE-----\nbm90IGEgY2VydA==\n{}\n",
+            CERTIFICATES, PEM_CERT_END,
+        );
+
+        let error = ca_bundle("bad", &bad).unwrap_err();
+
+        assert!(error.starts_with("invalid CA bundle"), "{}", error);
+
+        let error = ca_bundle("empty", "no certificates here").unwrap_err();
+
+        assert!(error.starts_with("no certificate in the CA bundle"), "{}", error);
+        assert!(load_ca_bundle(Path::new("/nonexistent/dakia.pem")).is_err());
+    }
+}
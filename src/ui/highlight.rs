@@ -0,0 +1,628 @@
+//! Syntax highlighting of JSON, XML and HTML.
+//!
+//! The text is tokenized a line at a time. What a line starts in, e.g. a
+//! comment or the attributes of a tag, is carried over to the next line, so
+//! the lines must be tokenized in order.
+
+use tui::{ style::{ Color, Style }, text::Span };
+
+use crate::ui::state::body::BodyContent;
+
+/// The language of the text being highlighted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Syntax {
+    #[default]
+    Plain,
+    Json,
+    Xml,
+    Html,
+}
+
+impl Syntax {
+    /// The syntax of a request body of type `body_content`.
+    pub fn from_body_content(body_content: &BodyContent) -> Self {
+        match body_content {
+            BodyContent::Json => Syntax::Json,
+            BodyContent::Xml => Syntax::Xml,
+            BodyContent::Html => Syntax::Html,
+            _ => Syntax::Plain,
+        }
+    }
+
+    /// The syntax of a response with the `Content-Type` header
+    /// `content_type`, e.g. `application/problem+json`.
+    pub fn from_content_type(content_type: &str) -> Self {
+        let mime = content_type.split(';').next().unwrap_or_default()
+            .trim()
+            .to_lowercase();
+
+        if mime.ends_with("json") {
+            Syntax::Json
+        } else if mime.ends_with("html") {
+            Syntax::Html
+        } else if mime.ends_with("xml") {
+            Syntax::Xml
+        } else {
+            Syntax::Plain
+        }
+    }
+}
+
+/// What a character is part of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Token {
+    Text,
+
+    /// Brackets, commas, colons and the like.
+    Punctuation,
+
+    /// A key of a JSON object.
+    Key,
+    String,
+    Number,
+
+    /// `true`, `false` and `null` of JSON and the entities (`&amp;`) of XML
+    /// and HTML.
+    Literal,
+
+    /// Tags, including the `<` and `>` around their name.
+    Tag,
+    Attribute,
+    Comment,
+}
+
+impl Token {
+    /// `style` with the color of the token.
+    pub fn style(&self, style: Style) -> Style {
+        match self {
+            Token::Text => style,
+            Token::Punctuation => style.fg(Color::Gray),
+            Token::Key | Token::Attribute => style.fg(Color::Cyan),
+            Token::String => style.fg(Color::Green),
+            Token::Number => style.fg(Color::Yellow),
+            Token::Literal => style.fg(Color::Magenta),
+            Token::Tag => style.fg(Color::LightBlue),
+            Token::Comment => style.fg(Color::DarkGray),
+        }
+    }
+}
+
+/// The elements of HTML whose content isn't markup.
+#[derive(Clone, Copy, PartialEq)]
+enum RawElement {
+    Script,
+    Style,
+}
+
+impl RawElement {
+    fn from_name(name: &str) -> Option<Self> {
+        if name.eq_ignore_ascii_case("script") {
+            Some(RawElement::Script)
+        } else if name.eq_ignore_ascii_case("style") {
+            Some(RawElement::Style)
+        } else {
+            None
+        }
+    }
+
+    fn end_tag(&self) -> &'static str {
+        match self {
+            RawElement::Script => "</script",
+            RawElement::Style => "</style",
+        }
+    }
+}
+
+/// Where a line of XML or HTML starts.
+#[derive(Clone, Copy, PartialEq)]
+enum MarkupState {
+    Text,
+
+    /// Between the name of a tag and its `>`. `raw` is the element if the
+    /// tag opens a `<script>` or `<style>`.
+    Tag { raw: Option<RawElement> },
+
+    /// A quoted attribute value.
+    AttrValue { quote: char, raw: Option<RawElement> },
+    Comment,
+    CData,
+
+    /// `<!DOCTYPE ...>` or `<?xml ...?>`.
+    Declaration,
+
+    /// The content of a `<script>` or `<style>`.
+    Raw(RawElement),
+}
+
+/// Where a line of JSON starts: the arrays and objects it's in.
+#[derive(Clone, Copy, Default)]
+struct JsonState {
+    /// A bit for each nesting level, set for objects. Only the innermost 64
+    /// levels are kept.
+    containers: u64,
+    depth: usize,
+
+    /// Whether the next string is a key.
+    expect_key: bool,
+}
+
+impl JsonState {
+    fn in_object(&self) -> bool {
+        self.depth > 0 && self.containers & 1 == 1
+    }
+
+    fn open(&mut self, object: bool) {
+        self.containers = self.containers << 1 | object as u64;
+        self.depth += 1;
+        self.expect_key = object;
+    }
+
+    fn close(&mut self) {
+        self.containers >>= 1;
+        self.depth = self.depth.saturating_sub(1);
+        self.expect_key = false;
+    }
+}
+
+/// Tokenizes text line by line.
+pub struct Highlighter {
+    syntax: Syntax,
+    json: JsonState,
+    markup: MarkupState,
+}
+
+impl Highlighter {
+    pub fn new(syntax: Syntax) -> Self {
+        Self { syntax, json: JsonState::default(), markup: MarkupState::Text }
+    }
+
+    /// Tokenizes the next line, returning the token of each of its
+    /// characters. Plain text has no tokens.
+    pub fn line(&mut self, line: &str) -> Vec<Token> {
+        match self.syntax {
+            Syntax::Plain => vec![],
+            Syntax::Json => self.json_line(line),
+            Syntax::Xml | Syntax::Html => self.markup_line(line),
+        }
+    }
+
+    fn json_line(&mut self, line: &str) -> Vec<Token> {
+        let chars: Vec<char> = line.chars().collect();
+        let mut tokens = vec![Token::Text; chars.len()];
+        let state = &mut self.json;
+        let mut i = 0;
+
+        while i < chars.len() {
+            let start = i;
+
+            let token = match chars[i] {
+                '"' => {
+                    i += 1;
+
+                    while i < chars.len() && chars[i] != '"' {
+                        if chars[i] == '\\' {
+                            i += 1;
+                        }
+
+                        i += 1;
+                    }
+
+                    i += 1;
+
+                    if state.expect_key && state.in_object() {
+                        state.expect_key = false;
+                        Token::Key
+                    } else {
+                        Token::String
+                    }
+                }
+
+                '{' | '[' => {
+                    state.open(chars[i] == '{');
+                    i += 1;
+                    Token::Punctuation
+                }
+
+                '}' | ']' => {
+                    state.close();
+                    i += 1;
+                    Token::Punctuation
+                }
+
+                ',' => {
+                    state.expect_key = state.in_object();
+                    i += 1;
+                    Token::Punctuation
+                }
+
+                ':' => {
+                    state.expect_key = false;
+                    i += 1;
+                    Token::Punctuation
+                }
+
+                c if c == '-' || c.is_ascii_digit() => {
+                    while i < chars.len()
+                        && (chars[i].is_ascii_digit() || "+-.eE".contains(chars[i]))
+                    {
+                        i += 1;
+                    }
+
+                    Token::Number
+                }
+
+                c if c.is_alphabetic() => {
+                    while i < chars.len() && chars[i].is_alphanumeric() {
+                        i += 1;
+                    }
+
+                    Token::Literal
+                }
+
+                _ => {
+                    i += 1;
+                    Token::Text
+                }
+            };
+
+            let end = i.min(chars.len());
+
+            tokens[start..end].fill(token);
+        }
+
+        tokens
+    }
+
+    fn markup_line(&mut self, line: &str) -> Vec<Token> {
+        let chars: Vec<char> = line.chars().collect();
+        let mut tokens = vec![Token::Text; chars.len()];
+        let html = self.syntax == Syntax::Html;
+        let mut state = self.markup;
+        let mut i = 0;
+
+        while i < chars.len() {
+            let rest = &chars[i..];
+
+            match state {
+                MarkupState::Text => {
+                    if starts_with(rest, "<!--") {
+                        tokens[i..i + 4].fill(Token::Comment);
+                        i += 4;
+                        state = MarkupState::Comment;
+                    } else if starts_with(rest, "<![CDATA[") {
+                        tokens[i..i + 9].fill(Token::Tag);
+                        i += 9;
+                        state = MarkupState::CData;
+                    } else if starts_with(rest, "<!") || starts_with(rest, "<?") {
+                        tokens[i..i + 2].fill(Token::Tag);
+                        i += 2;
+                        state = MarkupState::Declaration;
+                    } else if rest[0] == '<'
+                        && rest.get(1).is_some_and(|c| is_name_char(*c) || *c == '/')
+                    {
+                        let closing = rest.get(1) == Some(&'/');
+                        let name_start = i + 1 + closing as usize;
+                        let mut end = name_start;
+
+                        while end < chars.len() && is_name_char(chars[end]) {
+                            end += 1;
+                        }
+
+                        let name: String = chars[name_start..end].iter().collect();
+                        let raw = match html && !closing {
+                            true => RawElement::from_name(&name),
+                            false => None,
+                        };
+
+                        tokens[i..end].fill(Token::Tag);
+                        i = end;
+                        state = MarkupState::Tag { raw };
+                    } else if rest[0] == '&' {
+                        let end = rest.iter().take(12).position(|c| *c == ';');
+
+                        match end {
+                            Some(end) if end > 1 && rest[1..end].iter()
+                                .all(|c| c.is_alphanumeric() || *c == '#') =>
+                            {
+                                tokens[i..=i + end].fill(Token::Literal);
+                                i += end + 1;
+                            }
+
+                            _ => { i += 1; }
+                        }
+                    } else {
+                        i += 1;
+                    }
+                }
+
+                MarkupState::Tag { raw } => {
+                    match rest[0] {
+                        '>' => {
+                            tokens[i] = Token::Tag;
+
+                            let self_closing = i > 0 && chars[i - 1] == '/';
+
+                            state = match raw {
+                                Some(raw) if !self_closing => MarkupState::Raw(raw),
+                                _ => MarkupState::Text,
+                            };
+                        }
+
+                        '/' | '?' => { tokens[i] = Token::Tag; }
+                        '=' => { tokens[i] = Token::Punctuation; }
+
+                        quote @ ('"' | '\'') => {
+                            tokens[i] = Token::String;
+                            state = MarkupState::AttrValue { quote, raw };
+                        }
+
+                        c if c.is_whitespace() => {}
+                        _ => { tokens[i] = Token::Attribute; }
+                    }
+
+                    i += 1;
+                }
+
+                MarkupState::AttrValue { quote, raw } => {
+                    tokens[i] = Token::String;
+
+                    if rest[0] == quote {
+                        state = MarkupState::Tag { raw };
+                    }
+
+                    i += 1;
+                }
+
+                MarkupState::Comment => {
+                    if starts_with(rest, "-->") {
+                        tokens[i..i + 3].fill(Token::Comment);
+                        i += 3;
+                        state = MarkupState::Text;
+                    } else {
+                        tokens[i] = Token::Comment;
+                        i += 1;
+                    }
+                }
+
+                MarkupState::CData => {
+                    if starts_with(rest, "]]>") {
+                        tokens[i..i + 3].fill(Token::Tag);
+                        i += 3;
+                        state = MarkupState::Text;
+                    } else {
+                        i += 1;
+                    }
+                }
+
+                MarkupState::Declaration => {
+                    tokens[i] = Token::Tag;
+
+                    if rest[0] == '>' {
+                        state = MarkupState::Text;
+                    }
+
+                    i += 1;
+                }
+
+                MarkupState::Raw(raw) => {
+                    let end_tag = raw.end_tag();
+                    let at_end = rest.len() >= end_tag.len()
+                        && rest.iter().zip(end_tag.chars())
+                            .all(|(a, b)| a.eq_ignore_ascii_case(&b));
+
+                    if at_end {
+                        // The closing tag is tokenized as markup
+                        state = MarkupState::Text;
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+        }
+
+        self.markup = state;
+
+        tokens
+    }
+}
+
+fn starts_with(chars: &[char], prefix: &str) -> bool {
+    chars.len() >= prefix.len()
+        && chars.iter().zip(prefix.chars()).all(|(a, b)| *a == b)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.')
+}
+
+/// Splits `text` into spans colored by the `tokens` of its characters, on
+/// top of `style`. Characters without a token are plain text.
+pub fn token_spans<'a>(text: &str, tokens: &[Token], style: Style) -> Vec<Span<'a>> {
+    if tokens.is_empty() {
+        return vec![Span::styled(String::from(text), style)];
+    }
+
+    let mut spans: Vec<Span> = vec![];
+    let mut current = String::new();
+    let mut current_token = Token::Text;
+
+    for (i, c) in text.chars().enumerate() {
+        let token = tokens.get(i).copied().unwrap_or(Token::Text);
+
+        if token != current_token && !current.is_empty() {
+            spans.push(Span::styled(
+                std::mem::take(&mut current),
+                current_token.style(style),
+            ));
+        }
+
+        current_token = token;
+        current.push(c);
+    }
+
+    if !current.is_empty() || spans.is_empty() {
+        spans.push(Span::styled(current, current_token.style(style)));
+    }
+
+    spans
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The runs of `token` in each of `lines`, tokenized in order.
+    fn runs(syntax: Syntax, lines: &[&str], token: Token) -> Vec<Vec<String>> {
+        let mut highlighter = Highlighter::new(syntax);
+
+        lines.iter()
+            .map(|line| {
+                let tokens = highlighter.line(line);
+                let mut runs: Vec<String> = vec![];
+                let mut last = None;
+
+                for (c, t) in line.chars().zip(tokens) {
+                    match (t == token, last == Some(token)) {
+                        (true, true) => { runs.last_mut().unwrap().push(c); }
+                        (true, false) => { runs.push(String::from(c)); }
+                        _ => {}
+                    }
+
+                    last = Some(t);
+                }
+
+                runs
+            })
+            .collect()
+    }
+
+    fn line_runs(syntax: Syntax, line: &str, token: Token) -> Vec<String> {
+        runs(syntax, &[line], token).remove(0)
+    }
+
+    #[test]
+    fn syntax_is_read_from_the_content_type() {
+        for (content_type, syntax) in [
+            ("application/json", Syntax::Json),
+            ("application/problem+json; charset=utf-8", Syntax::Json),
+            ("TEXT/HTML", Syntax::Html),
+            ("application/atom+xml", Syntax::Xml),
+            ("text/xml;charset=UTF-8", Syntax::Xml),
+            ("text/plain", Syntax::Plain),
+            ("", Syntax::Plain),
+        ] {
+            assert_eq!(
+                Syntax::from_content_type(content_type), syntax,
+                "{}", content_type,
+            );
+        }
+
+        assert_eq!(Syntax::from_body_content(&BodyContent::Json), Syntax::Json);
+        assert_eq!(Syntax::from_body_content(&BodyContent::Text), Syntax::Plain);
+    }
+
+    #[test]
+    fn json_keys_values_and_literals() {
+        let line = r#"{"id": 1, "name": "a\"b", "ok": true, "list": [null, -1.5e3]}"#;
+
+        assert_eq!(
+            line_runs(Syntax::Json, line, Token::Key),
+            ["\"id\"", "\"name\"", "\"ok\"", "\"list\""],
+        );
+        assert_eq!(line_runs(Syntax::Json, line, Token::String), [r#""a\"b""#]);
+        assert_eq!(line_runs(Syntax::Json, line, Token::Number), ["1", "-1.5e3"]);
+        assert_eq!(line_runs(Syntax::Json, line, Token::Literal), ["true", "null"]);
+    }
+
+    #[test]
+    fn json_nesting_is_carried_over_lines() {
+        let lines = [
+            "{",
+            "  \"a\": [",
+            "    \"in array\",",
+            "    {\"b\": \"value\"}",
+            "  ],",
+            "  \"c\": \"last\"",
+            "}",
+        ];
+
+        assert_eq!(runs(Syntax::Json, &lines, Token::Key), [
+            vec![], vec!["\"a\""], vec![], vec!["\"b\""], vec![], vec!["\"c\""], vec![],
+        ]);
+        assert_eq!(runs(Syntax::Json, &lines, Token::String)[2], ["\"in array\""]);
+    }
+
+    #[test]
+    fn xml_tags_attributes_entities_and_comments() {
+        let line = "<a href='x'>fish &amp; chips &no</a> <!-- note -->";
+
+        assert_eq!(line_runs(Syntax::Xml, line, Token::Tag), ["<a", ">", "</a>"]);
+        assert_eq!(line_runs(Syntax::Xml, line, Token::Attribute), ["href"]);
+        assert_eq!(line_runs(Syntax::Xml, line, Token::String), ["'x'"]);
+        assert_eq!(line_runs(Syntax::Xml, line, Token::Literal), ["&amp;"]);
+        assert_eq!(line_runs(Syntax::Xml, line, Token::Comment), ["<!-- note -->"]);
+        assert_eq!(
+            line_runs(Syntax::Xml, "<?xml version=\"1.0\"?>", Token::Tag),
+            ["<?xml version=\"1.0\"?>"],
+        );
+        assert_eq!(
+            line_runs(Syntax::Xml, "<![CDATA[x < y]]>", Token::Tag),
+            ["<![CDATA[", "]]>"],
+        );
+    }
+
+    #[test]
+    fn markup_comments_and_attributes_are_carried_over_lines() {
+        let lines = ["<!-- one", "two --> <b/>", "<a title=\"first", "second\" id=x>"];
+
+        assert_eq!(runs(Syntax::Xml, &lines, Token::Comment)[..2], [
+            vec!["<!-- one"], vec!["two -->"],
+        ]);
+        assert_eq!(runs(Syntax::Xml, &lines, Token::Tag)[1], ["<b/>"]);
+        assert_eq!(runs(Syntax::Xml, &lines, Token::String)[2..], [
+            vec!["\"first"], vec!["second\""],
+        ]);
+        assert_eq!(runs(Syntax::Xml, &lines, Token::Attribute)[3], ["id", "x"]);
+    }
+
+    #[test]
+    fn html_script_content_isnt_markup() {
+        let line = "<script>if (a < b) { x = \"<b>\"; }</script><b>";
+
+        assert_eq!(
+            line_runs(Syntax::Html, line, Token::Tag),
+            ["<script>", "</script><b>"],
+        );
+
+        // XML has no raw elements
+        assert_eq!(
+            line_runs(Syntax::Xml, line, Token::Tag),
+            ["<script>", "<b>", "</script><b>"],
+        );
+    }
+
+    #[test]
+    fn plain_text_is_one_span() {
+        let mut highlighter = Highlighter::new(Syntax::Plain);
+        let tokens = highlighter.line("{\"a\": 1}");
+
+        assert!(tokens.is_empty());
+        assert_eq!(token_spans("{\"a\": 1}", &tokens, Style::default()).len(), 1);
+    }
+
+    #[test]
+    fn token_spans_group_the_characters_of_a_token() {
+        let text = "{\"a\": 1}";
+        let tokens = Highlighter::new(Syntax::Json).line(text);
+        let spans = token_spans(text, &tokens, Style::default());
+
+        let contents: Vec<&str> = spans.iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+
+        assert_eq!(contents, ["{", "\"a\"", ":", " ", "1", "}"]);
+        assert_eq!(spans[1].style.fg, Some(Color::Cyan));
+        assert_eq!(spans[4].style.fg, Some(Color::Yellow));
+    }
+}
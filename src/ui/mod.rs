@@ -4,20 +4,41 @@ pub mod widgets;
 pub mod state;
 pub mod render;
 pub mod calc;
+pub mod highlight;
 
 use tui::{
     backend::Backend, style::{ Color, Style }, text::{ Span, Spans }, Frame,
-    widgets::{ BorderType, Block, Borders, Tabs, Paragraph },
-    layout::{ Layout, Constraint, Direction, Rect },
+    widgets::{ BorderType, Block, Borders, Tabs },
+    layout::{ Layout, Constraint, Direction },
 };
 
 use crate::ui::{
     state::{
-        UiState, UIElement, request_tabs::RequestTabs, app_status::AppStatus,
+        UiState, UIElement, EditorMode, request_tabs::RequestTabs,
+        app_status::AppStatus, response::format_size,
+        environment::ENV_SELECT_DISP_LEN, vim::field_cursor,
+        text_field::TextField,
+    },
+    widgets::{ text_input::TextInput, select::Select },
+    render::{
+        render_tab_content, sidebar::render_sidebar, response::render_response,
+        export::render_export_menu, history::render_history,
+        runner::render_runner, graphql::render_schema_browser,
+        cookies::render_cookie_manager,
+        vim::render_vim_status, vim_cursor,
     },
-    widgets::text_input::TextInput, render::render_tab_content,
-    calc::scrollbar_pos,
 };
+use crate::{ collection::request_name, websocket::is_websocket };
+
+/// Frames of the spinner shown in the status bar while a request is in
+/// flight.
+const SPINNER_FRAMES: [&str; 10] = [
+    "\u{280b}", "\u{2819}", "\u{2839}", "\u{2838}", "\u{283c}",
+    "\u{2834}", "\u{2826}", "\u{2827}", "\u{2807}", "\u{280f}",
+];
+
+/// Width of the collection sidebar.
+const SIDEBAR_WIDTH: u16 = 30;
 
 /// Main rendering function called whenever the ui has to be re-rendered.
 pub fn ui_func<B: Backend>(f: &mut Frame<B>, uistate: &mut UiState) {
@@ -32,11 +53,26 @@ pub fn ui_func<B: Backend>(f: &mut Frame<B>, uistate: &mut UiState) {
         ].as_ref())
         .split(window_size);
 
+    let mut main_area = outer_chunks[0];
+
+    if uistate.sidebar().visible() {
+        let sidebar_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(SIDEBAR_WIDTH),
+                Constraint::Min(10),
+            ].as_ref())
+            .split(outer_chunks[0]);
+
+        render_sidebar(f, uistate, sidebar_chunks[0]);
+        main_area = sidebar_chunks[1];
+    }
+
     // Divides the top portion of the window
     let inner_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(3), Constraint::Min(3)].as_ref())
-        .split(outer_chunks[0]);
+        .split(main_area);
 
     let mid_pane = Layout::default()
         .direction(Direction::Vertical)
@@ -50,6 +86,7 @@ pub fn ui_func<B: Backend>(f: &mut Frame<B>, uistate: &mut UiState) {
             Constraint::Length(11),
             Constraint::Min(10),
             Constraint::Length(8),
+            Constraint::Length(22),
         ].as_ref())
         .split(inner_chunks[0]);
 
@@ -71,19 +108,34 @@ pub fn ui_func<B: Backend>(f: &mut Frame<B>, uistate: &mut UiState) {
         method_border_style = method_border_style.fg(Color::Yellow);
     }
 
+    let method = match is_websocket(uistate) {
+        true => String::from("WS"),
+        false => String::from(uistate.method().as_str()),
+    };
+
     let method_input = TextInput::default()
         .label(String::from(" Method "))
         .borders(Borders::ALL)
         .border_style(method_border_style)
-        .text(String::from(uistate.method().as_str()));
+        .text(method);
 
     f.render_widget(method_input, top_bar_chunks[0]);
 
     ti_size.x = 12;
     ti_size.width -= 12;
 
+    // Shows the name of the request opened from the collection
+    let url_label = match uistate.current_request_path() {
+        Some(path) => format!(
+            " URL ({}) ",
+            request_name(uistate.sidebar().root(), path),
+        ),
+
+        None => String::from(" URL "),
+    };
+
     let url_input = TextInput::default()
-        .label(String::from(" URL "))
+        .label(url_label)
         .borders(Borders::ALL)
         .text(uistate.url())
         .multi_line(false)
@@ -91,7 +143,10 @@ pub fn ui_func<B: Backend>(f: &mut Frame<B>, uistate: &mut UiState) {
         .active_border_style(Style::default().fg(Color::Yellow))
         .width(top_bar_chunks[1].width)
         .cursor_pos(uistate.url_cursor_offset())
-        .active(uistate.active_element() == &UIElement::URL);
+        .active(uistate.active_element() == &UIElement::URL)
+        .variables(uistate.environments().variable_names());
+
+    let url_input = vim_cursor(url_input, field_cursor(uistate, TextField::Url));
 
     f.render_widget(url_input, top_bar_chunks[1]);
 
@@ -149,95 +204,7 @@ pub fn ui_func<B: Backend>(f: &mut Frame<B>, uistate: &mut UiState) {
 
     render_tab_content(f, uistate, tab_chunks[1]);
 
-    let mut response_style = Style::default().fg(Color::Gray);
-
-    if uistate.active_element() == &UIElement::ResponseArea {
-        response_style = response_style.fg(Color::Yellow);
-    }
-
-    let response = Block::default().borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .style(response_style)
-        .title(" Response ");
-
-    f.render_widget(response, mid_pane[1]);
-
-    if uistate.response().initialized() {
-        let rect = Rect::new(
-            mid_pane[1].x + 1,
-            mid_pane[1].y + 1,
-            mid_pane[1].width - 2,
-            mid_pane[1].height - 2,
-        );
-
-        let content_height = rect.height as usize;
-        let max_len = rect.width as usize;
-        let req_counter = uistate.response().cache_req_counter();
-
-        if uistate.request_counter() != req_counter {
-            let mut r_lines: Vec<String> = vec![];
-
-            for line in uistate.response().response().iter() {
-                if line.len() > max_len {
-                    let mut line_divided = string_chunks(line, max_len);
-
-                    r_lines.append(&mut line_divided);
-                } else {
-                    r_lines.push(line.clone());
-                }
-            }
-
-            uistate.response_mut().set_response(r_lines);
-            uistate.response_mut().set_cache_req_counter(req_counter);
-        }
-
-        let response_text = uistate.response().response();
-        let mut response_text_start: usize = 0;
-        let mut response_text_end: usize = response_text.len();
-
-        if response_text.len() > content_height {
-            response_text_start = uistate.response().scroll_pos() as usize;
-            response_text_end = response_text_start + content_height;
-
-            if response_text_end > response_text.len() {
-                response_text_end = response_text.len();
-            }
-
-            // Draw a scrollbar 
-            let scrollbar = Block::default()
-                .borders(Borders::NONE)
-                .style(Style::default().bg(Color::DarkGray));
-
-            let scrollbar_rect = Rect::new(rect.width, rect.y, 1, rect.height);
-
-            f.render_widget(scrollbar, scrollbar_rect);
-
-            let scrollbar_thumb = Block::default()
-                .borders(Borders::NONE)
-                .style(Style::default().bg(Color::White));
-
-            let (thumb_height, thumb_pos) = scrollbar_pos(
-                content_height as u16,
-                response_text_end as u16,
-                response_text.len() as u16,
-            );
-
-            f.render_widget(
-                scrollbar_thumb,
-                Rect::new(
-                    scrollbar_rect.x,
-                    scrollbar_rect.y + thumb_pos,
-                    1,
-                    thumb_height,
-            ));
-        }
-
-        let response_block = Paragraph::new(string_chunks_to_spans(
-            &response_text[response_text_start..response_text_end]
-        ));
-
-        f.render_widget(response_block, rect);
-    }
+    render_response(f, uistate, mid_pane[1]);
 
     let mut render_status = true;
     let mut status_style = Style::default();
@@ -246,19 +213,91 @@ pub fn ui_func<B: Backend>(f: &mut Frame<B>, uistate: &mut UiState) {
     match uistate.app_status() {
         AppStatus::PROCESSING => {
             status_style = status_style.bg(Color::Yellow).fg(Color::Black);
+
+            if let Some(in_flight) = uistate.in_flight() {
+                let elapsed = in_flight.elapsed();
+                let frame = SPINNER_FRAMES[
+                    (elapsed.as_millis() / 100) as usize % SPINNER_FRAMES.len()
+                ];
+
+                status_span = match uistate.response().stream() {
+                    Some(stream) if stream.request_id() == in_flight.id() => {
+                        Span::raw(format!(
+                            " {} Streaming: {} events \u{2502} {:.1}s (Esc to stop)",
+                            frame,
+                            stream.event_count(),
+                            elapsed.as_secs_f32(),
+                        ))
+                    }
+
+                    _ => match uistate.response().download() {
+                        Some(download) if download.request_id() == in_flight.id() => {
+                            Span::raw(format!(
+                                " {} Receiving: {} \u{2502} {}/s \u{2502} {:.1}s (Esc to cancel)",
+                                frame,
+                                format_size(download.received()),
+                                format_size(download.rate() as usize),
+                                elapsed.as_secs_f32(),
+                            ))
+                        }
+
+                        _ => Span::raw(format!(
+                            " {} {:.1}s (Esc to cancel)",
+                            frame,
+                            elapsed.as_secs_f32(),
+                        )),
+                    },
+                };
+            }
         },
         AppStatus::DONE => {
             match uistate.response_status_code() {
                 Some(req_status_code) => {
-                    status_span = Span::raw(
-                        format!(" Status: {}", req_status_code)
+                    let info = uistate.response().info();
+                    let tests = uistate.response().tests();
+
+                    let mut status = format!(
+                        " Status: {} {} \u{2502} {} ms",
+                        req_status_code,
+                        info.reason(),
+                        info.elapsed().as_millis(),
                     );
+
+                    if !tests.is_empty() {
+                        status.push_str(&format!(
+                            " \u{2502} Tests: {}/{} passed",
+                            tests.iter().filter(|t| t.passed).count(),
+                            tests.len(),
+                        ));
+                    }
+
+                    let graphql_errors = uistate.response().graphql_errors();
+
+                    if !graphql_errors.is_empty() {
+                        status.push_str(&format!(
+                            " \u{2502} GraphQL errors: {}",
+                            graphql_errors.len(),
+                        ));
+                    }
+
+                    if let Some(body_file) = info.body_file() {
+                        status.push_str(&format!(
+                            " \u{2502} Body in {}",
+                            body_file.display(),
+                        ));
+                    }
+
+                    status_span = Span::raw(status);
                 },
 
                 None => {},
             }
 
-            status_style = status_style.bg(Color::Green).fg(Color::Black);
+            // A response with GraphQL errors didn't fully succeed
+            status_style = match uistate.response().graphql_errors().is_empty() {
+                true => status_style.bg(Color::Green).fg(Color::Black),
+                false => status_style.bg(Color::Magenta).fg(Color::White),
+            };
         },
         AppStatus::ERROR => {
             status_style = status_style.bg(Color::Red).fg(Color::White);
@@ -270,6 +309,16 @@ pub fn ui_func<B: Backend>(f: &mut Frame<B>, uistate: &mut UiState) {
                 None => {},
             }
         },
+        AppStatus::CANCELLED => {
+            status_style = status_style.bg(Color::DarkGray).fg(Color::White);
+        },
+        AppStatus::INFO => {
+            status_style = status_style.bg(Color::Blue).fg(Color::White);
+
+            if let Some(message) = uistate.app_message() {
+                status_span = Span::raw(format!(" {}", message));
+            }
+        },
         AppStatus::STARTUP => { render_status = false; },
     }
 
@@ -285,6 +334,47 @@ pub fn ui_func<B: Backend>(f: &mut Frame<B>, uistate: &mut UiState) {
 
         f.render_widget(status_block, outer_chunks[1]);
     }
+
+    if uistate.editor_mode() == EditorMode::VIM {
+        render_vim_status(f, uistate, outer_chunks[1]);
+    }
+
+    // The environment select is rendered last so that its popup is drawn
+    // over the rest of the ui.
+    let environments = uistate.environments();
+
+    let env_select = Select::default()
+        .label(String::from(" Environment "))
+        .disp_content_length(ENV_SELECT_DISP_LEN)
+        .style(Style::default().fg(Color::White))
+        .active_style(Style::default().fg(Color::Yellow))
+        .scroll_offset(environments.scroll_offset())
+        .sel_index(environments.sel_index())
+        .options(environments.options())
+        .active(uistate.active_element() == &UIElement::Environment)
+        .opened(environments.opened());
+
+    f.render_widget(env_select, top_bar_chunks[3]);
+
+    if uistate.history().opened() {
+        render_history(f, uistate, main_area);
+    }
+
+    if uistate.runner().opened() {
+        render_runner(f, uistate, main_area);
+    }
+
+    if uistate.export_menu().opened() {
+        render_export_menu(f, uistate, main_area);
+    }
+
+    if uistate.graphql().browser().opened() {
+        render_schema_browser(f, uistate, main_area);
+    }
+
+    if uistate.cookies().opened() {
+        render_cookie_manager(f, uistate, main_area);
+    }
 }
 
 /// Returns vector of Strings with length upto `max_width`.
@@ -297,14 +387,3 @@ fn string_chunks(input: &String, max_width: usize) -> Vec<String> {
         .collect::<Vec<String>>()
 }
 
-/// Converts the string chunks to a vector of `Spans`.
-fn string_chunks_to_spans<'a>(input: &[String]) -> Vec<Spans<'a>> {
-    let mut spans: Vec<Spans> = vec![];
-
-    for chunk in input.iter() {
-        spans.push(Spans::from(chunk.clone()));
-    }
-
-    spans
-}
-
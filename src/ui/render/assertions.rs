@@ -0,0 +1,53 @@
+use tui::{
+    backend::Backend, style::{ Color, Style }, Frame,
+    widgets::{ Borders, Paragraph }, layout::Rect, text::Span,
+};
+
+use crate::ui::{
+    state::{ UiState, UIElement },
+    widgets::text_input::TextInput,
+};
+
+/// Syntax of the assertions, shown below the editor.
+const ASSERTION_HINT: &str = "status == 200 \u{2502} header <name> exists \u{2502} json <path> == <value> \u{2502} body contains <text> \u{2502} time < <ms>";
+
+/// Renders the editor of the assertions, one per line, with a reminder of
+/// their syntax.
+pub fn render_assertions<B: Backend>(
+    f: &mut Frame<B>, uistate: &mut UiState, rect: Rect
+) {
+    let mut editor_rect = rect;
+
+    if editor_rect.height > 3 {
+        editor_rect.height -= 1;
+
+        f.render_widget(
+            Paragraph::new(Span::styled(
+                ASSERTION_HINT, Style::default().fg(Color::DarkGray),
+            )),
+            Rect::new(rect.x + 1, rect.y + editor_rect.height, rect.width - 2, 1),
+        );
+    }
+
+    let editor = uistate.assertions();
+
+    let text_multi_line = TextInput::default()
+        .multi_line(true)
+        .label(String::from(" Assertions "))
+        .borders(Borders::ALL)
+        .active(uistate.active_element() == &UIElement::RequestTabsElem)
+        .border_style(Style::default().fg(Color::White))
+        .line_number(editor.line_number())
+        .cursor_pos(editor.cursor_pos())
+        .selecting(editor.selecting())
+        .sel_start_pos(editor.sel_start_pos())
+        .sel_end_pos(editor.sel_end_pos())
+        .text_vec(editor.text_vec())
+        .scroll_offset(editor.scroll_offset())
+        .variables(uistate.environments().variable_names());
+
+    uistate.assertions_mut()
+        .set_content_height(editor_rect.height.saturating_sub(2));
+
+    f.render_widget(text_multi_line, editor_rect);
+}
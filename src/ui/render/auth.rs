@@ -0,0 +1,189 @@
+use tui::{
+    backend::Backend, style::{ Color, Style }, Frame,
+    widgets::{ Borders, Paragraph },
+    layout::{ Layout, Constraint, Direction, Rect },
+};
+
+use crate::ui::{
+    state::{
+        UiState,
+        auth::{ AuthScheme, AuthUIElement, AUTH_SELECT_DISP_LEN },
+        vim::field_cursor, text_field::TextField,
+    },
+    widgets::{ text_input::TextInput, select::Select },
+    render::vim_cursor,
+};
+
+/// Renders the "Authorization" tab content.
+pub fn render_auth<B: Backend>(
+    f: &mut Frame<B>, uistate: &UiState, rect: Rect
+) {
+    let auth_content_rect = Layout::default()
+        .constraints([Constraint::Length(3), Constraint::Min(3)])
+        .direction(Direction::Vertical)
+        .split(rect);
+
+    let auth_top_rect = Layout::default()
+        .constraints([
+            Constraint::Percentage(34),
+            Constraint::Percentage(33),
+            Constraint::Percentage(33),
+        ])
+        .direction(Direction::Horizontal)
+        .split(auth_content_rect[0]);
+
+    let fields_rect = Rect::new(
+        auth_content_rect[1].x,
+        auth_content_rect[1].y,
+        auth_content_rect[1].width,
+        3.min(auth_content_rect[1].height),
+    );
+
+    let field_chunks = Layout::default()
+        .constraints([
+            Constraint::Percentage(50),
+            Constraint::Percentage(50),
+        ])
+        .direction(Direction::Horizontal)
+        .split(fields_rect);
+
+    let auth = uistate.auth();
+    let variables = uistate.environments().variable_names();
+
+    match auth.auth_scheme() {
+        AuthScheme::NoAuth => {
+            let hint = Paragraph::new(
+                String::from("This request does not use any authorization.")
+            ).style(Style::default().fg(Color::Gray));
+
+            f.render_widget(hint, auth_content_rect[1]);
+        }
+
+        AuthScheme::Basic => {
+            f.render_widget(
+                auth_field(
+                    " Username ",
+                    auth.username(),
+                    uistate,
+                    0,
+                    variables.clone(),
+                    field_chunks[0].width,
+                ),
+                field_chunks[0],
+            );
+
+            f.render_widget(
+                auth_field(
+                    " Password ",
+                    "*".repeat(auth.password().chars().count()),
+                    uistate,
+                    1,
+                    vec![],
+                    field_chunks[1].width,
+                ),
+                field_chunks[1],
+            );
+        }
+
+        AuthScheme::Bearer => {
+            f.render_widget(
+                auth_field(
+                    " Token ",
+                    auth.token(),
+                    uistate,
+                    0,
+                    variables.clone(),
+                    fields_rect.width,
+                ),
+                fields_rect,
+            );
+        }
+
+        AuthScheme::ApiKey => {
+            f.render_widget(
+                auth_field(
+                    " Key ",
+                    auth.api_key(),
+                    uistate,
+                    0,
+                    variables.clone(),
+                    field_chunks[0].width,
+                ),
+                field_chunks[0],
+            );
+
+            f.render_widget(
+                auth_field(
+                    " Value ",
+                    auth.api_key_value(),
+                    uistate,
+                    1,
+                    variables.clone(),
+                    field_chunks[1].width,
+                ),
+                field_chunks[1],
+            );
+
+            let mut location_select = Select::default()
+                .label(String::from(" Add To "))
+                .disp_content_length(AUTH_SELECT_DISP_LEN)
+                .style(Style::default().fg(Color::White))
+                .active_style(Style::default().fg(Color::Yellow))
+                .sel_index(auth.api_key_location_sel_index())
+                .options(auth.api_key_location_options());
+
+            if let AuthUIElement::ApiKeyLocation(opened) =
+                auth.active_auth_element()
+            {
+                location_select = location_select.active(true)
+                    .opened(*opened);
+            }
+
+            f.render_widget(location_select, auth_top_rect[1]);
+        }
+    }
+
+    let mut scheme_select = Select::default()
+        .label(String::from(" Auth Type "))
+        .default_index(0)
+        .disp_content_length(AUTH_SELECT_DISP_LEN)
+        .style(Style::default().fg(Color::White))
+        .scroll_offset(auth.auth_scheme_scroll_offset())
+        .active_style(Style::default().fg(Color::Yellow))
+        .sel_index(auth.auth_scheme_sel_index())
+        .options(auth.auth_scheme_options());
+
+    if let AuthUIElement::Scheme(opened) = auth.active_auth_element() {
+        scheme_select = scheme_select.active(true).opened(*opened);
+    }
+
+    f.render_widget(scheme_select, auth_top_rect[0]);
+}
+
+/// Builds the text input for the `field`th field of the selected scheme.
+/// `variables` are the variables defined in the active environment.
+fn auth_field(
+    label: &str,
+    text: String,
+    uistate: &UiState,
+    field: u8,
+    variables: Vec<String>,
+    width: u16,
+) -> TextInput {
+    let mut style = Style::default().fg(Color::Gray);
+
+    if *uistate.auth().active_auth_element() == AuthUIElement::Field(field) {
+        style = style.fg(Color::Yellow);
+    }
+
+    let input = TextInput::default()
+        .label(String::from(label))
+        .borders(Borders::ALL)
+        .text(text)
+        .border_style(style)
+        .active_border_style(style)
+        .width(width)
+        .variables(variables);
+
+    vim_cursor(input, field_cursor(uistate, TextField::Auth(field)))
+}
@@ -9,10 +9,15 @@ use tui::{
 use crate::ui::{
     state::{
         UiState, UIElement, request_tabs::RequestTabs, kv_tab_state::KVTabState,
-        body::{BodyContent, BodyUIElement},
+        body::{BodyContent, BodyUIElement}, kv_data::PartKind,
+        vim::field_cursor, text_field::TextField, search::SearchTarget,
     },
     widgets::{ text_input::TextInput, label::Label, select::Select },
-    render::render_kv_tab,
+    render::{
+        render_kv_tab, vim_cursor, search::{ search_bar, search_bar_height },
+        graphql::render_graphql_body,
+    },
+    highlight::Syntax,
 };
 
 pub fn render_body<B: Backend>(
@@ -33,39 +38,36 @@ pub fn render_body<B: Backend>(
         .split(body_content_rect[0]);
 
     match uistate.body().body_content() {
-        BodyContent::FormData | BodyContent::FormURLEncoded => {
+        BodyContent::FormData => {
+            render_form_data(f, uistate, body_content_rect[1]);
+        }
+
+        BodyContent::FormURLEncoded => {
             //info!("Rendering kv tab");
 
+            let params = uistate.body().kv_tab_state();
+            let cursor = field_cursor(uistate, TextField::BodyKV {
+                row: params.active_row(),
+                col: params.active_col(),
+            });
+
             render_kv_tab(
                 f,
-                uistate.body().kv_tab_state(),
+                params,
                 uistate.body().kv_data(),
+                uistate.environments().variable_names(),
+                cursor,
                 body_content_rect[1],
             );
         }
 
-        BodyContent::Text | BodyContent::Html | BodyContent::Xml => {
-            let text_data = uistate.body().text_data();
-
-            let text_multi_line = TextInput::default()
-                .multi_line(true)
-                .label(String::from(" Content "))
-                .borders(Borders::ALL)
-                .active(*uistate.body().active_body_element() == BodyUIElement::TextArea)
-                .border_style(Style::default().fg(Color::White))
-                .line_number(text_data.line_number())
-                .cursor_pos(text_data.cursor_pos())
-                .selecting(text_data.selecting())
-                .sel_start_pos(text_data.sel_start_pos())
-                .sel_end_pos(text_data.sel_end_pos())
-                .text_vec(text_data.text_vec())
-                .scroll_offset(text_data.scroll_offset());
-
-            uistate.body_mut().text_data_mut().set_content_height(
-                body_content_rect[1].height - 2
-            );
+        BodyContent::Text | BodyContent::Json | BodyContent::Html
+            | BodyContent::Xml => {
+            render_text_editor(f, uistate, body_content_rect[1], " Content ");
+        }
 
-            f.render_widget(text_multi_line, body_content_rect[1]);
+        BodyContent::GraphQL => {
+            render_graphql_body(f, uistate, body_content_rect[1], &body_top_rect);
         }
 
         _ => {}
@@ -92,3 +94,176 @@ pub fn render_body<B: Backend>(
     f.render_widget(body_content_select, body_top_rect[0]);
 }
 
+/// Renders the editor of the raw bodies, which is the query editor of the
+/// GraphQL body, with the search bar below it.
+pub fn render_text_editor<B: Backend>(
+    f: &mut Frame<B>, uistate: &mut UiState, rect: Rect, label: &str
+) {
+    let text_data = uistate.body().text_data();
+    let search = uistate.search();
+    let mut editor_rect = rect;
+
+    let searching = search.opened()
+        && search.target() == SearchTarget::Body;
+
+    // The search bar is below the editor
+    if searching && editor_rect.height > search_bar_height(search) + 2 {
+        let height = search_bar_height(search);
+
+        editor_rect.height -= height;
+
+        f.render_widget(
+            Paragraph::new(search_bar(search)),
+            Rect::new(
+                editor_rect.x + 1,
+                editor_rect.y + editor_rect.height,
+                editor_rect.width - 2,
+                height,
+            ),
+        );
+    }
+
+    let matches = match searching {
+        true => search.matches().clone(),
+        false => vec![],
+    };
+
+    let text_multi_line = TextInput::default()
+        .multi_line(true)
+        .label(String::from(label))
+        .borders(Borders::ALL)
+        .active(*uistate.body().active_body_element() == BodyUIElement::TextArea)
+        .border_style(Style::default().fg(Color::White))
+        .line_number(text_data.line_number())
+        .cursor_pos(text_data.cursor_pos())
+        .selecting(text_data.selecting())
+        .sel_start_pos(text_data.sel_start_pos())
+        .sel_end_pos(text_data.sel_end_pos())
+        .text_vec(text_data.text_vec())
+        .scroll_offset(text_data.scroll_offset())
+        .syntax(Syntax::from_body_content(uistate.body().body_content()))
+        .matches(matches, search.current_match())
+        .variables(uistate.environments().variable_names());
+
+    uistate.body_mut().text_data_mut().set_content_height(
+        editor_rect.height - 2
+    );
+
+    f.render_widget(text_multi_line, editor_rect);
+}
+
+/// Renders the rows of the "Form Data" body. Besides the key and value
+/// every row has the part type, file name and content type.
+fn render_form_data<B: Backend>(f: &mut Frame<B>, uistate: &UiState, rect: Rect) {
+    let params = uistate.body().kv_tab_state();
+    let variables = uistate.environments().variable_names();
+    let cursor = field_cursor(uistate, TextField::BodyKV {
+        row: params.active_row(),
+        col: params.active_col(),
+    });
+
+    let content_rect = Rect::new(
+        rect.x + 2,
+        rect.y + 1,
+        rect.width - 4,
+        3,
+    );
+
+    let content_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(10),
+            Constraint::Length(10),
+        ].as_ref())
+        .split(content_rect);
+
+    let param_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(22),
+            Constraint::Percentage(30),
+            Constraint::Length(8),
+            Constraint::Percentage(20),
+            Constraint::Min(8),
+        ].as_ref())
+        .split(content_chunks[0]);
+
+    let param_actions_chunk = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(50),
+            Constraint::Percentage(50),
+        ].as_ref())
+        .split(content_chunks[1]);
+
+    for (i, param) in uistate.body().kv_data().iter().enumerate() {
+        let row_active: bool = params.active_row() == (i as u16);
+        let y_offset = (3 * i) as u16;
+
+        let value_label = match param.part_kind() {
+            PartKind::Text => " Value ",
+            PartKind::File => " File Path ",
+        };
+
+        let fields = [
+            (" Key ", param.key()),
+            (value_label, param.value()),
+            (" Type ", String::from(param.part_kind().to_str())),
+            (" File Name ", param.filename()),
+            (" Content Type ", param.content_type()),
+        ];
+
+        for (col, (label, text)) in fields.into_iter().enumerate() {
+            let mut field_rect = param_chunks[col];
+            field_rect.y += y_offset;
+
+            let mut field_style = Style::default().fg(Color::Gray);
+
+            if row_active && params.active_col() == col as u8 {
+                field_style = field_style.fg(Color::Yellow);
+            }
+
+            let field = TextInput::default()
+                .label(String::from(label))
+                .borders(Borders::ALL)
+                .text(text)
+                .border_style(field_style)
+                .active_border_style(field_style)
+                .width(field_rect.width)
+                .variables(variables.clone());
+
+            let field = vim_cursor(
+                field,
+                cursor.filter(|_| row_active && params.active_col() == col as u8),
+            );
+
+            f.render_widget(field, field_rect);
+        }
+
+        let action_block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+
+        for (n, symbol) in ["+", "-"].into_iter().enumerate() {
+            let mut action_rect = param_actions_chunk[n];
+            action_rect.y += y_offset;
+
+            let mut action_style = Style::default().fg(Color::White);
+
+            if row_active && params.active_col() == 5 + n as u8 {
+                action_style = action_style.fg(Color::Cyan);
+            }
+
+            f.render_widget(action_block.clone().style(action_style), action_rect);
+
+            action_rect.x += 2;
+            action_rect.width -= 2;
+            action_rect.y += 1;
+
+            f.render_widget(
+                Label::default().text(symbol).style(action_style),
+                action_rect,
+            );
+        }
+    }
+}
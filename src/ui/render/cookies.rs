@@ -0,0 +1,205 @@
+use chrono::{ DateTime, Local };
+use tui::{
+    backend::Backend, style::{ Color, Modifier, Style }, text::{ Span, Spans },
+    Frame,
+    widgets::{ BorderType, Block, Borders, Clear, Paragraph },
+    layout::Rect,
+};
+
+use crate::{
+    cookie::Cookie,
+    ui::state::{ UiState, cookies::{ CookieEditor, EDITOR_FIELDS } },
+};
+
+/// Widths of the domain, path, name and expiry columns. The value takes the
+/// rest of the row.
+const COLUMN_WIDTHS: [usize; 4] = [24, 12, 20, 18];
+
+/// Renders the cookie manager as a popup over `rect`: the cookies of the jar
+/// of the active environment by domain and path, and the editor of the
+/// cookie being edited.
+pub fn render_cookie_manager<B: Backend>(
+    f: &mut Frame<B>, uistate: &mut UiState, rect: Rect
+) {
+    if rect.width < 40 || rect.height < 14 {
+        return;
+    }
+
+    let popup_rect = Rect::new(
+        rect.x + 2,
+        rect.y + 1,
+        rect.width - 4,
+        rect.height - 2,
+    );
+
+    f.render_widget(Clear, popup_rect);
+
+    let jar = uistate.cookie_jar();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().fg(Color::Yellow))
+        .title(format!(" Cookies \u{2502} {} ", jar));
+
+    f.render_widget(block, popup_rect);
+
+    let mut inner = Rect::new(
+        popup_rect.x + 1,
+        popup_rect.y + 1,
+        popup_rect.width - 2,
+        popup_rect.height - 2,
+    );
+
+    // The editor takes the rows above the key hints
+    if let Some(editor) = uistate.cookies().editor() {
+        let height = EDITOR_FIELDS.len() as u16 + 1;
+
+        inner.height -= height;
+
+        f.render_widget(
+            editor_paragraph(editor),
+            Rect::new(inner.x, inner.y + inner.height - 1, inner.width, height),
+        );
+    }
+
+    // Column heads, rows and the key hints
+    let list_rect = Rect::new(inner.x, inner.y + 1, inner.width, inner.height - 2);
+
+    uistate.cookies_mut().set_content_height(list_rect.height);
+
+    let cookies = uistate.cookies().jars().with_jar(&jar, |jar| jar.cookies().clone());
+    let manager = uistate.cookies();
+    let dimmed = Style::default().fg(Color::DarkGray);
+
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            row_text(["Domain", "Path", "Name", "Expires", "Value"], inner.width),
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        )),
+        Rect::new(inner.x, inner.y, inner.width, 1),
+    );
+
+    let mut lines: Vec<Spans> = vec![];
+
+    if cookies.is_empty() {
+        lines.push(Spans::from(Span::styled(
+            " The jar is empty. Responses setting cookies fill it.",
+            dimmed,
+        )));
+    }
+
+    for (i, cookie) in cookies.iter()
+        .enumerate()
+        .skip(manager.scroll_offset())
+        .take(list_rect.height as usize)
+    {
+        let mut style = Style::default().fg(Color::White);
+
+        if i == manager.sel_index() {
+            style = style.fg(Color::Black).bg(Color::Cyan);
+        }
+
+        lines.push(Spans::from(Span::styled(cookie_row(cookie, inner.width), style)));
+    }
+
+    f.render_widget(Paragraph::new(lines), list_rect);
+
+    let hints = match manager.editor() {
+        Some(_) => {
+            " Tab: next field \u{2502} Enter: save \u{2502} Esc: cancel \u{2502} \
+            Expires: RFC 3339 date, empty for a session cookie"
+        }
+        None => {
+            " Enter: edit \u{2502} n: new \u{2502} d: delete \u{2502} Esc: close"
+        }
+    };
+
+    f.render_widget(
+        Paragraph::new(Span::styled(hints, dimmed)),
+        Rect::new(
+            popup_rect.x + 1,
+            popup_rect.y + popup_rect.height - 2,
+            popup_rect.width - 2,
+            1,
+        ),
+    );
+}
+
+/// A row of the cookie list, with the flags of the cookie after its value.
+fn cookie_row(cookie: &Cookie, width: u16) -> String {
+    let expires = match DateTime::parse_from_rfc3339(cookie.expires()) {
+        Ok(expires) => expires.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(),
+        Err(_) => String::from("session"),
+    };
+
+    let mut value = String::from(cookie.value());
+
+    for (set, flag) in [(cookie.secure(), "Secure"), (cookie.http_only(), "HttpOnly")] {
+        if set {
+            value.push_str(&format!("  [{}]", flag));
+        }
+    }
+
+    let domain = match cookie.host_only() {
+        true => String::from(cookie.domain()),
+        false => format!(".{}", cookie.domain()),
+    };
+
+    row_text([&domain, cookie.path(), cookie.name(), &expires, &value], width)
+}
+
+/// The columns of a row cut to their widths.
+fn row_text(columns: [&str; 5], width: u16) -> String {
+    let mut row = String::from(" ");
+
+    for (text, column_width) in columns.iter().zip(COLUMN_WIDTHS) {
+        let text: String = text.chars().take(column_width - 1).collect();
+        row.push_str(&format!("{:width$}", text, width = column_width));
+    }
+
+    let value_width = (width as usize).saturating_sub(row.chars().count() + 1);
+    let value: String = columns[4].chars().take(value_width).collect();
+
+    row.push_str(&value);
+    row
+}
+
+/// The fields of the cookie being edited, the active one with the cursor.
+fn editor_paragraph<'a>(editor: &CookieEditor) -> Paragraph<'a> {
+    let title = match editor.index() {
+        Some(_) => " Edit cookie ",
+        None => " New cookie ",
+    };
+
+    let lines: Vec<Spans> = EDITOR_FIELDS.iter()
+        .zip(editor.fields().iter())
+        .enumerate()
+        .map(|(i, (label, text))| {
+            let active = i == editor.active_field();
+
+            let label_style = match active {
+                true => Style::default().fg(Color::Yellow),
+                false => Style::default().fg(Color::Cyan),
+            };
+
+            let mut text = text.clone();
+
+            if active {
+                text.push('\u{2588}');
+            }
+
+            Spans::from(vec![
+                Span::styled(format!(" {:9}", label), label_style),
+                Span::styled(text, Style::default().fg(Color::White)),
+            ])
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::TOP)
+        .style(Style::default().fg(Color::Yellow))
+        .title(title);
+
+    Paragraph::new(lines).block(block)
+}
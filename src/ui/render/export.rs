@@ -0,0 +1,57 @@
+use tui::{
+    backend::Backend, style::{ Color, Style }, text::{ Span, Spans }, Frame,
+    widgets::{ BorderType, Block, Borders, Clear, Paragraph },
+    layout::Rect,
+};
+
+use crate::{ ui::state::UiState, export::SnippetFormat };
+
+/// Width of the export menu popup.
+const EXPORT_MENU_WIDTH: u16 = 28;
+
+/// Renders the export menu as a popup in the middle of `rect`.
+pub fn render_export_menu<B: Backend>(
+    f: &mut Frame<B>, uistate: &UiState, rect: Rect
+) {
+    let height = SnippetFormat::iter().len() as u16 + 2;
+
+    if rect.width < EXPORT_MENU_WIDTH || rect.height < height {
+        return;
+    }
+
+    let popup_rect = Rect::new(
+        rect.x + (rect.width - EXPORT_MENU_WIDTH) / 2,
+        rect.y + (rect.height - height) / 2,
+        EXPORT_MENU_WIDTH,
+        height,
+    );
+
+    f.render_widget(Clear, popup_rect);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().fg(Color::Yellow))
+        .title(" Copy as ");
+
+    let sel_index = uistate.export_menu().sel_index() as usize;
+    let width = (EXPORT_MENU_WIDTH - 2) as usize;
+
+    let lines: Vec<Spans> = SnippetFormat::iter()
+        .enumerate()
+        .map(|(i, format)| {
+            let mut style = Style::default().fg(Color::White);
+
+            if i == sel_index {
+                style = style.fg(Color::Black).bg(Color::Cyan);
+            }
+
+            Spans::from(Span::styled(
+                format!(" {:width$}", format.to_str(), width = width - 1),
+                style,
+            ))
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines).block(block), popup_rect);
+}
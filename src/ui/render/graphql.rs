@@ -0,0 +1,306 @@
+use tui::{
+    backend::Backend, style::{ Color, Modifier, Style }, text::{ Span, Spans },
+    Frame,
+    widgets::{ BorderType, Block, Borders, Clear, Paragraph, Wrap },
+    layout::{ Layout, Constraint, Direction, Rect },
+};
+
+use crate::{
+    api::resolved_url,
+    ui::{
+        state::{
+            UiState, body::BodyUIElement,
+            graphql::{ CompletionMenu, COMPLETION_DISP_LEN },
+        },
+        widgets::{ text_input::TextInput, select::Select },
+        render::body::render_text_editor,
+        highlight::Syntax,
+    },
+};
+
+/// Renders the query and variables editors of the GraphQL body, with the
+/// operation select and the state of the schema next to the "Content Type"
+/// select in `top`.
+pub fn render_graphql_body<B: Backend>(
+    f: &mut Frame<B>, uistate: &mut UiState, rect: Rect, top: &[Rect]
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Percentage(35)])
+        .split(rect);
+
+    render_text_editor(f, uistate, chunks[0], " Query ");
+
+    let variables = uistate.body().variables();
+
+    let variables_input = TextInput::default()
+        .multi_line(true)
+        .label(String::from(" Variables "))
+        .borders(Borders::ALL)
+        .active(*uistate.body().active_body_element() == BodyUIElement::Variables)
+        .border_style(Style::default().fg(Color::White))
+        .line_number(variables.line_number())
+        .cursor_pos(variables.cursor_pos())
+        .selecting(variables.selecting())
+        .sel_start_pos(variables.sel_start_pos())
+        .sel_end_pos(variables.sel_end_pos())
+        .text_vec(variables.text_vec())
+        .scroll_offset(variables.scroll_offset())
+        .syntax(Syntax::Json)
+        .variables(uistate.environments().variable_names());
+
+    uistate.body_mut().variables_mut()
+        .set_content_height(chunks[1].height.saturating_sub(2));
+
+    f.render_widget(variables_input, chunks[1]);
+
+    if let Some(menu) = uistate.graphql().completion() {
+        render_completion(f, uistate, menu, chunks[0]);
+    }
+
+    render_schema_status(f, uistate, top[2]);
+
+    let body = uistate.body();
+    let mut options: Vec<String> = body.operations().iter()
+        .map(|operation| operation.label())
+        .collect();
+
+    if options.is_empty() {
+        options.push(String::from("(none)"));
+    }
+
+    let mut operation_select = Select::default()
+        .label(String::from(" Operation "))
+        .disp_content_length(5)
+        .style(Style::default().fg(Color::White))
+        .active_style(Style::default().fg(Color::Yellow))
+        .options(options);
+
+    operation_select = match body.active_body_element() {
+        BodyUIElement::Operation(true) => operation_select
+            .active(true)
+            .opened(true)
+            .sel_index(body.operation_sel_index())
+            .scroll_offset(body.operation_scroll_offset()),
+
+        element => operation_select
+            .active(*element == BodyUIElement::Operation(false))
+            .sel_index(body.operation_index() as u8),
+    };
+
+    f.render_widget(operation_select, top[1]);
+}
+
+/// Renders whether the schema of the url is read.
+fn render_schema_status<B: Backend>(f: &mut Frame<B>, uistate: &UiState, rect: Rect) {
+    let url = resolved_url(uistate);
+    let graphql = uistate.graphql();
+
+    let (text, color) = match graphql.schema(&url) {
+        _ if graphql.fetching() == Some(url.as_str()) => {
+            (String::from(" reading..."), Color::Yellow)
+        }
+
+        Some(schema) => (format!(" {} types (Ctrl+G)", schema.types().len()), Color::Green),
+        None => (String::from(" not read (Ctrl+G)"), Color::DarkGray),
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().fg(Color::White))
+        .title(" Schema ");
+
+    let status = Paragraph::new(Span::styled(text, Style::default().fg(color)))
+        .block(block);
+
+    f.render_widget(status, rect);
+}
+
+/// Renders the completion menu below the cursor of the query editor in
+/// `rect`, or above it when there is no room below.
+fn render_completion<B: Backend>(
+    f: &mut Frame<B>, uistate: &UiState, menu: &CompletionMenu, rect: Rect
+) {
+    let text_data = uistate.body().text_data();
+    let frame = f.size();
+
+    let label_width = menu.items().iter()
+        .map(|item| item.label().chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let detail_width = menu.items().iter()
+        .map(|item| item.detail().chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let width = ((label_width + detail_width + 5) as u16).clamp(20, frame.width);
+    let height = (menu.items().len().min(COMPLETION_DISP_LEN) + 2) as u16;
+
+    let cursor_x = rect.x + 2 + text_data.cursor_pos();
+    let cursor_y = rect.y + 1 + text_data.line_number()
+        .saturating_sub(text_data.scroll_offset());
+
+    let x = cursor_x.min(frame.width.saturating_sub(width));
+    let y = match cursor_y + 1 + height <= frame.height {
+        true => cursor_y + 1,
+        false => cursor_y.saturating_sub(height),
+    };
+
+    let popup_rect = Rect::new(x, y, width, height.min(frame.height));
+
+    f.render_widget(Clear, popup_rect);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().fg(Color::Yellow));
+
+    let lines: Vec<Spans> = menu.items().iter()
+        .enumerate()
+        .skip(menu.scroll_offset())
+        .take(COMPLETION_DISP_LEN)
+        .map(|(i, item)| {
+            let mut style = Style::default().fg(Color::White);
+            let mut detail_style = Style::default().fg(Color::DarkGray);
+
+            if i == menu.sel_index() {
+                style = style.fg(Color::Black).bg(Color::Cyan);
+                detail_style = detail_style.fg(Color::Black).bg(Color::Cyan);
+            }
+
+            Spans::from(vec![
+                Span::styled(
+                    format!(" {:width$} ", item.label(), width = label_width),
+                    style,
+                ),
+                Span::styled(
+                    format!("{:width$} ", item.detail(), width = detail_width),
+                    detail_style,
+                ),
+            ])
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines).block(block), popup_rect);
+}
+
+/// Renders the schema browser as a popup over `rect`: the types of the
+/// schema, or the fields of the type opened, with the description of the
+/// selected row.
+pub fn render_schema_browser<B: Backend>(
+    f: &mut Frame<B>, uistate: &mut UiState, rect: Rect
+) {
+    if rect.width < 20 || rect.height < 10 {
+        return;
+    }
+
+    let popup_rect = Rect::new(
+        rect.x + 2,
+        rect.y + 1,
+        rect.width - 4,
+        rect.height - 2,
+    );
+
+    f.render_widget(Clear, popup_rect);
+
+    let url = resolved_url(uistate);
+    let browser = uistate.graphql().browser();
+
+    let title = match browser.path().is_empty() {
+        true => format!(" Schema \u{2502} {} ", url),
+        false => format!(" Schema \u{2502} {} ", browser.path().join(" \u{203a} ")),
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().fg(Color::Yellow))
+        .title(title);
+
+    f.render_widget(block, popup_rect);
+
+    let inner = Rect::new(
+        popup_rect.x + 1,
+        popup_rect.y + 1,
+        popup_rect.width - 2,
+        popup_rect.height - 2,
+    );
+
+    // Rows, the description of the selected row and the key hints
+    let list_rect = Rect::new(inner.x, inner.y, inner.width, inner.height - 4);
+
+    uistate.graphql_mut().browser_mut().set_content_height(list_rect.height);
+
+    let graphql = uistate.graphql();
+    let browser = graphql.browser();
+    let dimmed = Style::default().fg(Color::DarkGray);
+
+    let rows = match graphql.schema(&url) {
+        Some(schema) => browser.rows(schema),
+        None => vec![],
+    };
+
+    let mut lines: Vec<Spans> = vec![];
+
+    if rows.is_empty() {
+        let message = match graphql.schema(&url) {
+            _ if graphql.fetching() == Some(url.as_str()) => " Reading the schema...",
+            Some(_) => " The type has no fields.",
+            None => " The schema isn't read. Press r to read it.",
+        };
+
+        lines.push(Spans::from(Span::styled(message, dimmed)));
+    }
+
+    let label_width = rows.iter()
+        .map(|row| row.label().chars().count())
+        .max()
+        .unwrap_or_default()
+        .min(inner.width as usize / 2);
+
+    for (i, row) in rows.iter()
+        .enumerate()
+        .skip(browser.scroll_offset())
+        .take(list_rect.height as usize)
+    {
+        let mut style = Style::default().fg(Color::White);
+        let mut detail_style = Style::default().fg(Color::Cyan);
+
+        if i == browser.sel_index() {
+            style = style.fg(Color::Black).bg(Color::Cyan);
+            detail_style = detail_style.fg(Color::Black).bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD);
+        }
+
+        let label: String = row.label().chars().take(label_width).collect();
+
+        lines.push(Spans::from(vec![
+            Span::styled(format!(" {:width$}  ", label, width = label_width), style),
+            Span::styled(format!("{} ", row.detail()), detail_style),
+        ]));
+    }
+
+    f.render_widget(Paragraph::new(lines), list_rect);
+
+    let description = rows.get(browser.sel_index())
+        .map(|row| String::from(row.description()))
+        .unwrap_or_default();
+
+    f.render_widget(
+        Paragraph::new(Span::styled(description, dimmed))
+            .wrap(Wrap { trim: true }),
+        Rect::new(inner.x + 1, inner.y + inner.height - 3, inner.width - 2, 2),
+    );
+
+    let hints = Paragraph::new(Span::styled(
+        " Enter: open type \u{2502} Backspace: back \u{2502} r: read again \u{2502} Esc: close",
+        dimmed,
+    ));
+
+    f.render_widget(
+        hints,
+        Rect::new(inner.x, inner.y + inner.height - 1, inner.width, 1),
+    );
+}
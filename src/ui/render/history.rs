@@ -0,0 +1,124 @@
+use tui::{
+    backend::Backend, style::{ Color, Modifier, Style }, text::{ Span, Spans },
+    Frame,
+    widgets::{ BorderType, Block, Borders, Clear, Paragraph },
+    layout::Rect,
+};
+
+use crate::{ ui::state::UiState, history::HistoryEntry };
+
+/// Renders the history panel as a popup over `rect`: the search query, the
+/// matching entries and the keys.
+pub fn render_history<B: Backend>(
+    f: &mut Frame<B>, uistate: &mut UiState, rect: Rect
+) {
+    if rect.width < 20 || rect.height < 8 {
+        return;
+    }
+
+    let popup_rect = Rect::new(
+        rect.x + 2,
+        rect.y + 1,
+        rect.width - 4,
+        rect.height - 2,
+    );
+
+    f.render_widget(Clear, popup_rect);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().fg(Color::Yellow))
+        .title(" History ");
+
+    f.render_widget(block, popup_rect);
+
+    let inner = Rect::new(
+        popup_rect.x + 1,
+        popup_rect.y + 1,
+        popup_rect.width - 2,
+        popup_rect.height - 2,
+    );
+
+    // Search row, entries and the key hints
+    let search = Paragraph::new(Spans::from(vec![
+        Span::styled(" Search: ", Style::default().fg(Color::Cyan)),
+        Span::styled(
+            format!("{}\u{2588}", uistate.history().query()),
+            Style::default().fg(Color::White),
+        ),
+    ]));
+
+    f.render_widget(search, Rect::new(inner.x, inner.y, inner.width, 1));
+
+    let list_rect = Rect::new(inner.x, inner.y + 2, inner.width, inner.height - 3);
+
+    uistate.history_mut().set_content_height(list_rect.height);
+
+    let history = uistate.history();
+    let shown = history.shown();
+    let mut lines: Vec<Spans> = vec![];
+
+    if shown.is_empty() {
+        lines.push(Spans::from(Span::styled(
+            " No requests sent yet.",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    for (i, entry) in shown.iter()
+        .enumerate()
+        .skip(history.scroll_offset())
+        .take(list_rect.height as usize)
+    {
+        lines.push(entry_line(entry, i == history.sel_index(), list_rect.width));
+    }
+
+    f.render_widget(Paragraph::new(lines), list_rect);
+
+    let hints = Paragraph::new(Span::styled(
+        " Enter: open \u{2502} F5: resend \u{2502} Esc: close",
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    f.render_widget(
+        hints,
+        Rect::new(inner.x, inner.y + inner.height - 1, inner.width, 1),
+    );
+}
+
+/// A row of the history list: time, method, status and url.
+fn entry_line<'a>(entry: &HistoryEntry, selected: bool, width: u16) -> Spans<'a> {
+    let time = entry.sent_at()
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| String::from("-"));
+
+    let (status, status_color) = match entry.status_code() {
+        Some(code) if code < 300 => (code.to_string(), Color::Green),
+        Some(code) if code < 400 => (code.to_string(), Color::Cyan),
+        Some(code) if code < 500 => (code.to_string(), Color::Yellow),
+        Some(code) => (code.to_string(), Color::Red),
+        None => (String::from("ERR"), Color::Red),
+    };
+
+    let mut style = Style::default().fg(Color::White);
+    let mut status_style = Style::default()
+        .fg(status_color)
+        .add_modifier(Modifier::BOLD);
+
+    if selected {
+        style = style.fg(Color::Black).bg(Color::Cyan);
+        status_style = status_style.bg(Color::Cyan);
+    }
+
+    let prefix = format!(" {}  {:7} ", time, entry.method());
+    let url_width = (width as usize).saturating_sub(prefix.chars().count() + 5);
+
+    let url: String = entry.url().chars().take(url_width).collect();
+
+    Spans::from(vec![
+        Span::styled(prefix, style),
+        Span::styled(format!("{:4} ", status), status_style),
+        Span::styled(format!("{:width$}", url, width = url_width), style),
+    ])
+}
@@ -1,18 +1,36 @@
 pub mod body;
+pub mod auth;
+pub mod sidebar;
+pub mod response;
+pub mod export;
+pub mod history;
+pub mod vim;
+pub mod search;
+pub mod scripts;
+pub mod assertions;
+pub mod runner;
+pub mod websocket;
+pub mod graphql;
+pub mod cookies;
+pub mod settings;
 
 use tui::{
     backend::Backend, style::{ Color, Style }, Frame,
-    widgets::{ BorderType, Block, Borders, Paragraph },
+    widgets::{ BorderType, Block, Borders },
     layout::{ Layout, Constraint, Direction, Rect },
 };
 
 use crate::ui::{
     state::{
         UiState, UIElement, request_tabs::RequestTabs, kv_tab_state::KVTabState,
-        kv_data::KVData,
+        kv_data::KVData, vim::{ FieldCursor, field_cursor },
+        text_field::TextField,
     },
     widgets::{ text_input::TextInput, label::Label },
-    render::body::render_body,
+    render::{
+        body::render_body, auth::render_auth, scripts::render_scripts,
+        assertions::render_assertions, settings::render_settings,
+    },
 };
 
 /// Renders tab content
@@ -42,28 +60,52 @@ pub fn render_tab_content<B: Backend>(
     match uistate.active_request_tab() {
         RequestTabs::UrlParams => {
             //render_kv_tab(f, uistate, RequestTabs::UrlParams, rect);
+            let params = uistate.query_params_ui();
+            let cursor = field_cursor(uistate, TextField::UrlParam {
+                row: params.active_row(),
+                col: params.active_col(),
+            });
+
             render_kv_tab(
                 f,
-                uistate.query_params_ui(),
+                params,
                 uistate.url_deconst().query_params(),
+                uistate.environments().variable_names(),
+                cursor,
                 rect
             );
         },
         RequestTabs::Authorization => {
-            let content = Paragraph::new(String::from("Authorization"));
-            f.render_widget(content, rect_inset);
+            render_auth(f, uistate, rect_inset);
         },
         RequestTabs::Headers => {
             //render_kv_tab(f, uistate, RequestTabs::Headers, rect);
+            let headers = uistate.request_headers_ui();
+            let cursor = field_cursor(uistate, TextField::Header {
+                row: headers.active_row(),
+                col: headers.active_col(),
+            });
+
             render_kv_tab(f,
-                uistate.request_headers_ui(),
+                headers,
                 uistate.request_headers(),
+                uistate.environments().variable_names(),
+                cursor,
                 rect
             );
         },
         RequestTabs::Body => {
             render_body(f, uistate, rect_inset);
         },
+        RequestTabs::Scripts => {
+            render_scripts(f, uistate, rect_inset);
+        },
+        RequestTabs::Assertions => {
+            render_assertions(f, uistate, rect_inset);
+        },
+        RequestTabs::Settings => {
+            render_settings(f, uistate, rect_inset);
+        },
     }
 }
 
@@ -71,6 +113,8 @@ pub fn render_kv_tab<B: Backend>(
     f: &mut Frame<B>,
     params: KVTabState,
     kv_data: &Vec<KVData>,
+    variables: Vec<String>,
+    cursor: Option<FieldCursor>,
     //render_tab: RequestTabs,
     rect: Rect
 ) {
@@ -144,7 +188,15 @@ pub fn render_kv_tab<B: Backend>(
             .label(String::from(" Key "))
             .borders(Borders::ALL)
             .text(param.key())
-            .border_style(param_name_style);
+            .border_style(param_name_style)
+            .active_border_style(param_name_style)
+            .width(name_rect.width)
+            .variables(variables.clone());
+
+        let param_name = vim_cursor(
+            param_name,
+            cursor.filter(|_| row_active && params.active_col() == 0),
+        );
 
         f.render_widget(param_name, name_rect);
 
@@ -158,7 +210,15 @@ pub fn render_kv_tab<B: Backend>(
             .label(String::from(" Value "))
             .borders(Borders::ALL)
             .text(param.value())
-            .border_style(param_value_style);
+            .border_style(param_value_style)
+            .active_border_style(param_value_style)
+            .width(value_rect.width)
+            .variables(variables.clone());
+
+        let param_value = vim_cursor(
+            param_value,
+            cursor.filter(|_| row_active && params.active_col() == 1),
+        );
 
         f.render_widget(param_value, value_rect);
 
@@ -205,3 +265,19 @@ pub fn render_kv_tab<B: Backend>(
     }
 }
 
+
+/// Shows the cursor and the `VISUAL` selection of the VIM editor mode in a
+/// single line field.
+pub fn vim_cursor(input: TextInput, cursor: Option<FieldCursor>) -> TextInput {
+    let Some(cursor) = cursor else { return input; };
+
+    let input = input.active(true).cursor_pos(cursor.cursor);
+
+    match cursor.selection {
+        Some((start, end)) => input.selecting(true)
+            .sel_start_pos((0, start))
+            .sel_end_pos((0, end)),
+
+        None => input,
+    }
+}
@@ -0,0 +1,677 @@
+use tui::{
+    backend::Backend, style::{ Color, Modifier, Style }, text::{ Span, Spans },
+    Frame,
+    widgets::{ BorderType, Block, Borders, Tabs, Paragraph },
+    layout::Rect,
+};
+
+use crate::{
+    assertion::TestResult,
+    api::sse::StreamItem,
+    websocket::is_websocket,
+    graphql::GraphQLError,
+    ui::{
+        state::{
+            UiState, UIElement,
+            response::{ ResponseInfo, EventStream },
+            response_tabs::ResponseTabs, response_filter::JQ_SYNTAX,
+            json_tree::{ JsonTree, JsonKey, JsonNodeKind }, search::SearchTarget,
+        },
+        highlight::{ Syntax, Token, Highlighter, token_spans },
+        render::{
+            search::{ search_bar, search_bar_height },
+            websocket::render_websocket,
+        },
+        widgets::highlights::Highlights,
+        calc::scrollbar_pos, string_chunks,
+    },
+};
+
+/// Errors of a GraphQL response listed above the body.
+const GRAPHQL_ERROR_ROWS: usize = 3;
+
+/// Renders the response area: the tab heads and the content of the active
+/// response tab.
+pub fn render_response<B: Backend>(
+    f: &mut Frame<B>, uistate: &mut UiState, rect: Rect
+) {
+    // A WebSocket request shows the messages of its connection instead
+    if is_websocket(uistate) {
+        render_websocket(f, uistate, rect);
+        return;
+    }
+
+    let mut response_style = Style::default().fg(Color::Gray);
+    let active = uistate.active_element() == &UIElement::ResponseArea;
+
+    if active {
+        response_style = response_style.fg(Color::Yellow);
+    }
+
+    let response = Block::default().borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(response_style)
+        .title(" Response ");
+
+    f.render_widget(response, rect);
+
+    if !uistate.response().initialized() || rect.height < 4 {
+        return;
+    }
+
+    // Tab Titles
+    let tab_titles: Vec<Spans> = ResponseTabs::iter()
+        .map(|tab| Spans::from(vec![Span::raw(tab.get_str_label())]))
+        .collect();
+
+    let mut tab_head_active_style = Style::default().fg(Color::Yellow);
+
+    if active {
+        tab_head_active_style = tab_head_active_style.fg(Color::Black)
+            .bg(Color::Cyan);
+    }
+
+    let tab_head = Tabs::new(tab_titles)
+        .block(Block::default().borders(Borders::NONE))
+        .select(uistate.response().active_tab().get_val() as usize)
+        .divider("│")
+        .style(Style::default().fg(Color::Gray))
+        .highlight_style(tab_head_active_style);
+
+    f.render_widget(
+        tab_head,
+        Rect::new(rect.x + 1, rect.y + 1, rect.width - 2, 1),
+    );
+
+    let mut content_rect = Rect::new(
+        rect.x + 1,
+        rect.y + 2,
+        rect.width - 2,
+        rect.height - 3,
+    );
+
+    let body_shown = uistate.response().active_tab() == ResponseTabs::Body;
+
+    // The "Body" tab of an event stream lists its events
+    let stream_shown = body_shown && uistate.response().stream().is_some();
+
+    // A body that is still arriving can't be searched, filtered or shown
+    // as a tree yet
+    let live = stream_shown
+        || (body_shown && uistate.response().download().is_some());
+
+    // The errors of a GraphQL response take the first rows of the body
+    let graphql_errors = uistate.response().graphql_errors();
+
+    if body_shown && !live && !graphql_errors.is_empty() {
+        let lines = graphql_error_lines(graphql_errors, GRAPHQL_ERROR_ROWS);
+        let height = (lines.len() as u16).min(content_rect.height / 2);
+
+        f.render_widget(
+            Paragraph::new(lines),
+            Rect::new(content_rect.x, content_rect.y, content_rect.width, height),
+        );
+
+        content_rect.y += height;
+        content_rect.height -= height;
+    }
+
+    let search = uistate.search();
+    let searching = body_shown && !live && search.opened()
+        && search.target() == SearchTarget::Response;
+
+    // The search bar and the filter bar take the last rows of the body
+    if searching && content_rect.height > search_bar_height(search) {
+        let height = search_bar_height(search);
+
+        content_rect.height -= height;
+
+        f.render_widget(
+            Paragraph::new(search_bar(search)),
+            Rect::new(
+                content_rect.x,
+                content_rect.y + content_rect.height,
+                content_rect.width,
+                height,
+            ),
+        );
+    }
+
+    if uistate.response().filter().shown() && content_rect.height > 1
+        && body_shown && !live
+    {
+        content_rect.height -= 1;
+
+        f.render_widget(
+            Paragraph::new(filter_bar(uistate)),
+            Rect::new(
+                content_rect.x,
+                content_rect.y + content_rect.height,
+                content_rect.width,
+                1,
+            ),
+        );
+    }
+
+    let content_height = content_rect.height as usize;
+
+    // The tree view scrolls with its cursor
+    let tree_shown = uistate.response().tree_view() && body_shown && !live;
+
+    if let Some(tree) = uistate.response_mut().tree_mut().filter(|_| tree_shown) {
+        tree.scroll_to_cursor(content_height);
+    }
+
+    // The spans of the body are only made for the lines shown
+    let lines: Option<Vec<Spans>> = match uistate.response().active_tab() {
+        ResponseTabs::Body if stream_shown => {
+            uistate.response().stream().map(event_lines)
+        }
+
+        ResponseTabs::Body if tree_shown => None,
+        ResponseTabs::Body => {
+            cache_body_lines(uistate, content_rect.width as usize);
+            None
+        }
+
+        ResponseTabs::Headers => Some(header_lines(uistate.response().info())),
+        ResponseTabs::Cookies => Some(cookie_lines(uistate.response().info())),
+        ResponseTabs::Info => Some(info_lines(uistate.response().info())),
+        ResponseTabs::Tests => Some(test_lines(uistate.response().tests())),
+    };
+
+    let line_count = match &lines {
+        Some(lines) => lines.len(),
+        None => uistate.response().line_count(),
+    };
+
+    // The body and the event list follow the text as it arrives
+    if body_shown && !tree_shown && (live || uistate.response().follow()) {
+        let first = uistate.response_mut().first_row(content_height);
+        uistate.response_mut().set_scroll_pos(first as u16);
+    }
+
+    let mut start: usize = 0;
+    let mut end: usize = line_count;
+
+    if line_count > content_height {
+        start = match uistate.response().tree() {
+            Some(tree) if tree_shown => tree.scroll_offset(),
+            _ => uistate.response().scroll_pos() as usize,
+        }.min(line_count - content_height);
+        end = start + content_height;
+
+        // Draw a scrollbar
+        let scrollbar = Block::default()
+            .borders(Borders::NONE)
+            .style(Style::default().bg(Color::DarkGray));
+
+        let scrollbar_rect = Rect::new(
+            content_rect.x + content_rect.width - 1,
+            content_rect.y,
+            1,
+            content_rect.height,
+        );
+
+        f.render_widget(scrollbar, scrollbar_rect);
+
+        let scrollbar_thumb = Block::default()
+            .borders(Borders::NONE)
+            .style(Style::default().bg(Color::White));
+
+        let (thumb_height, thumb_pos) = scrollbar_pos(
+            content_height as u16,
+            end as u16,
+            line_count as u16,
+        );
+
+        f.render_widget(
+            scrollbar_thumb,
+            Rect::new(
+                scrollbar_rect.x,
+                scrollbar_rect.y + thumb_pos,
+                1,
+                thumb_height,
+        ));
+    }
+
+    let lines = match (lines, uistate.response().tree()) {
+        (Some(lines), _) => lines[start..end].to_vec(),
+        (None, Some(tree)) if tree_shown => {
+            tree_lines(tree, start, end, active)
+        }
+        (None, _) => body_lines(uistate, start, end),
+    };
+
+    f.render_widget(Paragraph::new(lines), content_rect);
+
+    if searching && !tree_shown {
+        let response = uistate.response();
+        let search = uistate.search();
+
+        f.render_widget(
+            Highlights::matches(
+                &response.line_starts()[start..end],
+                response.wrap_width(),
+                search.matches(),
+                search.current_match(),
+            ),
+            content_rect,
+        );
+    }
+}
+
+/// Wraps the response body to `max_len` and tokenizes it for the syntax
+/// highlighting, going by the `Content-Type` of the response. The wrapped
+/// lines are cached until the next request or until the width changes.
+fn cache_body_lines(uistate: &mut UiState, max_len: usize) {
+    let req_counter = uistate.request_counter();
+    let response = uistate.response();
+
+    if max_len == 0 || (response.cache_req_counter() == req_counter
+        && response.wrap_width() == max_len)
+    {
+        return;
+    }
+
+    // The filtered body is always JSON
+    let syntax = match uistate.response().filtered() {
+        true => Syntax::Json,
+        false => Syntax::from_content_type(
+            &uistate.response().info().content_type()
+        ),
+    };
+
+    let mut highlighter = Highlighter::new(syntax);
+    let mut r_lines: Vec<String> = vec![];
+    let mut r_tokens: Vec<Vec<Token>> = vec![];
+    let mut r_starts: Vec<(usize, usize)> = vec![];
+
+    for (n, line) in uistate.response().body_lines().iter().enumerate() {
+        let tokens = highlighter.line(line);
+
+        if line.len() > max_len {
+            let line_divided = string_chunks(line, max_len);
+
+            for (i, chunk) in line_divided.into_iter().enumerate() {
+                let chunk_tokens = tokens.iter()
+                    .skip(i * max_len)
+                    .take(max_len)
+                    .copied()
+                    .collect();
+
+                r_lines.push(chunk);
+                r_tokens.push(chunk_tokens);
+                r_starts.push((n, i * max_len));
+            }
+        } else {
+            r_lines.push(line.clone());
+            r_tokens.push(tokens);
+            r_starts.push((n, 0));
+        }
+    }
+
+    uistate.response_mut().set_wrapped(r_lines, r_tokens, r_starts, max_len);
+    uistate.response_mut().set_cache_req_counter(req_counter);
+}
+
+/// A row per event with its time, type, id and retry, followed by the lines
+/// of its data. The reconnects are listed between the events.
+fn event_lines<'a>(stream: &EventStream) -> Vec<Spans<'a>> {
+    let time_style = Style::default().fg(Color::DarkGray);
+    let mut lines = vec![];
+
+    for item in stream.items() {
+        match item {
+            StreamItem::Event(event) => {
+                let mut spans = vec![
+                    Span::styled(
+                        format!(" {} ", event.at().format("%H:%M:%S%.3f")),
+                        time_style,
+                    ),
+                    Span::styled(
+                        String::from(event.event()),
+                        Style::default().fg(Color::Cyan)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                ];
+
+                if !event.id().is_empty() {
+                    spans.push(Span::styled(
+                        format!("  id: {}", event.id()),
+                        Style::default().fg(Color::Yellow),
+                    ));
+                }
+
+                if let Some(retry) = event.retry() {
+                    spans.push(Span::styled(
+                        format!("  retry: {} ms", retry),
+                        Style::default().fg(Color::Magenta),
+                    ));
+                }
+
+                lines.push(Spans::from(spans));
+
+                for line in event.data().lines() {
+                    lines.push(Spans::from(Span::styled(
+                        format!("{:14}{}", "", line),
+                        Style::default().fg(Color::White),
+                    )));
+                }
+            }
+
+            StreamItem::Reconnect { at, reason, retry, last_event_id } => {
+                let resume = match last_event_id.is_empty() {
+                    true => String::new(),
+                    false => format!(" from id {}", last_event_id),
+                };
+
+                lines.push(Spans::from(vec![
+                    Span::styled(
+                        format!(" {} ", at.format("%H:%M:%S%.3f")),
+                        time_style,
+                    ),
+                    Span::styled(
+                        format!(
+                            "\u{21bb} {}, reconnecting in {:.1} s{}",
+                            reason,
+                            retry.as_secs_f32(),
+                            resume,
+                        ),
+                        Style::default().fg(Color::Red),
+                    ),
+                ]));
+            }
+        }
+    }
+
+    lines
+}
+
+/// The highlighted lines `start` to `end` of the wrapped response body.
+fn body_lines<'a>(uistate: &UiState, start: usize, end: usize) -> Vec<Spans<'a>> {
+    let response = uistate.response();
+
+    response.wrapped()[start..end].iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let tokens = response.tokens().get(start + i)
+                .map(|tokens| tokens.as_slice())
+                .unwrap_or_default();
+
+            Spans::from(token_spans(line, tokens, Style::default()))
+        })
+        .collect()
+}
+
+/// The rows `start` to `end` of the visible nodes of the JSON tree. The row
+/// of the cursor is highlighted.
+fn tree_lines<'a>(
+    tree: &JsonTree, start: usize, end: usize, active: bool
+) -> Vec<Spans<'a>> {
+    let cursor_style = match active {
+        true => Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD),
+        false => Style::default().bg(Color::DarkGray),
+    };
+
+    tree.visible()[start..end].iter()
+        .map(|index| {
+            let node = tree.node(*index);
+            let style = match *index == tree.cursor() {
+                true => cursor_style,
+                false => Style::default(),
+            };
+
+            let marker = match (node.expandable(), node.expanded()) {
+                (false, _) => "  ",
+                (true, true) => "\u{25be} ",
+                (true, false) => "\u{25b8} ",
+            };
+
+            let mut spans = vec![Span::styled(
+                format!("{}{}", "  ".repeat(node.depth()), marker),
+                Token::Punctuation.style(style),
+            )];
+
+            match node.key() {
+                JsonKey::Root => {}
+
+                JsonKey::Name(name) => {
+                    spans.push(Span::styled(
+                        serde_json::Value::String(name.clone()).to_string(),
+                        Token::Key.style(style),
+                    ));
+                    spans.push(Span::styled(": ", Token::Punctuation.style(style)));
+                }
+
+                JsonKey::Index(i) => {
+                    spans.push(Span::styled(
+                        format!("[{}]: ", i),
+                        Token::Punctuation.style(style),
+                    ));
+                }
+            }
+
+            let (brackets, count) = match node.kind() {
+                JsonNodeKind::Value(value) => {
+                    let token = match value.chars().next() {
+                        Some('"') => Token::String,
+                        Some(c) if c == '-' || c.is_ascii_digit() => Token::Number,
+                        _ => Token::Literal,
+                    };
+
+                    spans.push(Span::styled(value.clone(), token.style(style)));
+
+                    return Spans::from(spans);
+                }
+
+                JsonNodeKind::Object(n) => (("{", "}"), plural(*n, "key")),
+                JsonNodeKind::Array(n) => (("[", "]"), plural(*n, "item")),
+            };
+
+            // Collapsed nodes show what they hold
+            let text = match node.expanded() {
+                true => String::from(brackets.0),
+                false if node.expandable() => {
+                    format!("{}\u{2026}{}", brackets.0, brackets.1)
+                }
+                false => format!("{}{}", brackets.0, brackets.1),
+            };
+
+            spans.push(Span::styled(text, Token::Punctuation.style(style)));
+
+            if !node.expanded() && node.expandable() {
+                spans.push(Span::styled(
+                    format!("  {}", count),
+                    style.fg(Color::DarkGray),
+                ));
+            }
+
+            Spans::from(spans)
+        })
+        .collect()
+}
+
+/// The expression of the filter followed by its error, or by the keys of
+/// the input while it is edited.
+fn filter_bar<'a>(uistate: &UiState) -> Spans<'a> {
+    let filter = uistate.response().filter();
+
+    let mut expression = String::from(filter.expression());
+
+    if filter.editing() {
+        expression.push('\u{2588}');
+    }
+
+    let mut spans = vec![
+        Span::styled("Filter: ", Style::default().fg(Color::Cyan)),
+        Span::styled(expression, Style::default().fg(Color::White)),
+    ];
+
+    match filter.error() {
+        Some(error) => {
+            spans.push(Span::styled(
+                format!("  {}", error),
+                Style::default().fg(Color::Red),
+            ));
+        }
+
+        None if filter.editing() => {
+            spans.push(Span::styled(
+                format!(
+                    "  $JSONPath or jq: {} \u{2502} Enter: keep \u{2502} Esc: \
+                    clear \u{2502} \u{2191}\u{2193}: history",
+                    JQ_SYNTAX,
+                ),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+
+        None => {}
+    }
+
+    Spans::from(spans)
+}
+
+/// `n` followed by `noun`, e.g. `1 key` or `3 keys`.
+fn plural(n: usize, noun: &str) -> String {
+    match n {
+        1 => format!("1 {}", noun),
+        _ => format!("{} {}s", n, noun),
+    }
+}
+
+fn header_lines<'a>(info: &ResponseInfo) -> Vec<Spans<'a>> {
+    info.headers().iter()
+        .map(|header| Spans::from(vec![
+            Span::styled(header.key(), Style::default().fg(Color::Cyan)),
+            Span::raw(": "),
+            Span::raw(header.value()),
+        ]))
+        .collect()
+}
+
+fn cookie_lines<'a>(info: &ResponseInfo) -> Vec<Spans<'a>> {
+    if info.cookies().is_empty() {
+        return vec![Spans::from(Span::styled(
+            "The response doesn't set any cookies.",
+            Style::default().fg(Color::DarkGray),
+        ))];
+    }
+
+    info.cookies().iter()
+        .map(|cookie| {
+            let attributes = cookie.attributes().iter()
+                .map(|attr| attr.to_string())
+                .collect::<Vec<String>>()
+                .join("; ");
+
+            Spans::from(vec![
+                Span::styled(
+                    String::from(cookie.name()),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::raw(" = "),
+                Span::raw(String::from(cookie.value())),
+                Span::styled(
+                    format!("  {}", attributes),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ])
+        })
+        .collect()
+}
+
+fn info_lines<'a>(info: &ResponseInfo) -> Vec<Spans<'a>> {
+    info.lines().into_iter()
+        .map(|(label, value)| Spans::from(vec![
+            Span::styled(
+                format!("{:20}", label),
+                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(value),
+        ]))
+        .collect()
+}
+
+/// The header and a row per error of a GraphQL response, with the path of
+/// the field and the position in the query. At most `max_rows` errors are
+/// listed.
+fn graphql_error_lines<'a>(errors: &[GraphQLError], max_rows: usize) -> Vec<Spans<'a>> {
+    let error_style = Style::default().fg(Color::Magenta);
+
+    let mut lines = vec![Spans::from(Span::styled(
+        format!(" GraphQL errors ({})", errors.len()),
+        error_style.add_modifier(Modifier::BOLD),
+    ))];
+
+    for error in errors.iter().take(max_rows) {
+        let mut spans = vec![Span::styled(
+            format!(" \u{2022} {}", error.message()),
+            error_style,
+        )];
+
+        if !error.path().is_empty() {
+            spans.push(Span::styled(
+                format!("  at {}", error.path()),
+                Style::default().fg(Color::Gray),
+            ));
+        }
+
+        if let Some((line, column)) = error.locations().first() {
+            spans.push(Span::styled(
+                format!(" ({}:{})", line, column),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+
+        lines.push(Spans::from(spans));
+    }
+
+    if errors.len() > max_rows {
+        lines.push(Spans::from(Span::styled(
+            format!("   and {} more", errors.len() - max_rows),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    lines
+}
+
+/// A row per test with its result. The failed tests show why they failed.
+fn test_lines<'a>(tests: &[TestResult]) -> Vec<Spans<'a>> {
+    if tests.is_empty() {
+        return vec![Spans::from(Span::styled(
+            "No tests. Add assertions in the \"Assertions\" tab or call test() in the post-response script.",
+            Style::default().fg(Color::DarkGray),
+        ))];
+    }
+
+    tests.iter()
+        .map(|test| {
+            let mut spans = match test.passed {
+                true => vec![
+                    Span::styled("\u{2713} PASS ", Style::default().fg(Color::Green)),
+                    Span::raw(test.name.clone()),
+                ],
+
+                false => vec![
+                    Span::styled(
+                        "\u{2717} FAIL ",
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(test.name.clone()),
+                ],
+            };
+
+            if !test.detail.is_empty() {
+                spans.push(Span::styled(
+                    format!("  {}", test.detail),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+
+            Spans::from(spans)
+        })
+        .collect()
+}
@@ -0,0 +1,260 @@
+use tui::{
+    backend::Backend, style::{ Color, Modifier, Style }, text::{ Span, Spans },
+    Frame,
+    widgets::{ BorderType, Block, Borders, Clear, Paragraph },
+    layout::Rect,
+};
+
+use crate::{
+    runner::{ RequestResult, folder_name },
+    ui::state::{ UiState, runner::{ Runner, RunnerField } },
+};
+
+/// Renders the runner as a popup over `rect`: the options of the run, the
+/// result of every request and the progress of the run.
+pub fn render_runner<B: Backend>(
+    f: &mut Frame<B>, uistate: &mut UiState, rect: Rect
+) {
+    if rect.width < 30 || rect.height < 10 {
+        return;
+    }
+
+    let popup_rect = Rect::new(
+        rect.x + 2,
+        rect.y + 1,
+        rect.width - 4,
+        rect.height - 2,
+    );
+
+    f.render_widget(Clear, popup_rect);
+
+    let title = match uistate.runner().folder() {
+        Some(folder) => format!(
+            " Runner: {} ",
+            folder_name(uistate.sidebar().root(), folder),
+        ),
+        None => String::from(" Runner "),
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().fg(Color::Yellow))
+        .title(title);
+
+    f.render_widget(block, popup_rect);
+
+    let inner = Rect::new(
+        popup_rect.x + 1,
+        popup_rect.y + 1,
+        popup_rect.width - 2,
+        popup_rect.height - 2,
+    );
+
+    // Options, results, progress and the key hints
+    f.render_widget(
+        Paragraph::new(options_line(uistate.runner())),
+        Rect::new(inner.x, inner.y, inner.width, 1),
+    );
+
+    let list_rect = Rect::new(inner.x, inner.y + 2, inner.width, inner.height - 5);
+    let rows = result_rows(uistate.runner());
+
+    uistate.runner_mut()
+        .clamp_scroll(rows.len(), list_rect.height as usize);
+
+    let runner = uistate.runner();
+
+    let lines: Vec<Spans> = match rows.is_empty() && !runner.running() {
+        true => vec![Spans::from(Span::styled(
+            " Enter runs the requests of the folder in order.",
+            Style::default().fg(Color::DarkGray),
+        ))],
+
+        false => rows.into_iter()
+            .skip(runner.scroll_offset())
+            .take(list_rect.height as usize)
+            .collect(),
+    };
+
+    f.render_widget(Paragraph::new(lines), list_rect);
+
+    f.render_widget(
+        Paragraph::new(progress_line(runner)),
+        Rect::new(inner.x, inner.y + inner.height - 2, inner.width, 1),
+    );
+
+    let hints = Paragraph::new(Span::styled(
+        " Enter: run \u{2502} Tab: next option \u{2502} Space: toggle \u{2502} Ctrl+X: export reports \u{2502} Esc: stop/close",
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    f.render_widget(
+        hints,
+        Rect::new(inner.x, inner.y + inner.height - 1, inner.width, 1),
+    );
+}
+
+/// The options of the run, the active one highlighted.
+fn options_line<'a>(runner: &Runner) -> Spans<'a> {
+    let label_style = Style::default().fg(Color::Cyan);
+
+    let value_style = |field: RunnerField| match runner.active_field() == field {
+        true => Style::default().fg(Color::Black).bg(Color::Yellow),
+        false => Style::default().fg(Color::White).bg(Color::DarkGray),
+    };
+
+    let checkbox = match runner.stop_on_failure() {
+        true => "[x]",
+        false => "[ ]",
+    };
+
+    Spans::from(vec![
+        Span::styled(" Data file: ", label_style),
+        Span::styled(
+            format!(" {:24} ", runner.data_file()),
+            value_style(RunnerField::DataFile),
+        ),
+        Span::styled("  Delay (ms): ", label_style),
+        Span::styled(
+            format!(" {:6} ", runner.delay()),
+            value_style(RunnerField::Delay),
+        ),
+        Span::raw("  "),
+        Span::styled(checkbox, value_style(RunnerField::StopOnFailure)),
+        Span::styled(" Stop on failure", label_style),
+    ])
+}
+
+/// A row per request, followed by its failed tests or its error.
+fn result_rows<'a>(runner: &Runner) -> Vec<Spans<'a>> {
+    let iterations = runner.results().iter()
+        .map(|result| result.iteration())
+        .max()
+        .unwrap_or(1);
+
+    let mut rows = vec![];
+
+    for result in runner.results() {
+        rows.push(result_line(result, iterations > 1));
+
+        let detail_style = Style::default().fg(Color::Red);
+
+        if let Some(error) = result.error() {
+            rows.push(Spans::from(Span::styled(
+                format!("       {}", error),
+                detail_style,
+            )));
+        }
+
+        for test in result.tests().iter().filter(|test| !test.passed) {
+            let text = match test.detail.is_empty() {
+                true => format!("       \u{2717} {}", test.name),
+                false => format!("       \u{2717} {}  {}", test.name, test.detail),
+            };
+
+            rows.push(Spans::from(Span::styled(text, detail_style)));
+        }
+    }
+
+    rows
+}
+
+/// Mark, iteration, method, name, status, time and tests of a request.
+fn result_line<'a>(result: &RequestResult, show_iteration: bool) -> Spans<'a> {
+    let (mark, mark_color) = match result.passed() {
+        true => ("\u{2713}", Color::Green),
+        false => ("\u{2717}", Color::Red),
+    };
+
+    let (status, status_color) = match result.status() {
+        Some(code) if code < 300 => (code.to_string(), Color::Green),
+        Some(code) if code < 400 => (code.to_string(), Color::Cyan),
+        Some(code) if code < 500 => (code.to_string(), Color::Yellow),
+        Some(code) => (code.to_string(), Color::Red),
+        None => (String::from("ERR"), Color::Red),
+    };
+
+    let iteration = match show_iteration {
+        true => format!("#{:<3} ", result.iteration()),
+        false => String::new(),
+    };
+
+    let tests = match result.tests().len() {
+        0 => String::new(),
+        count => format!(
+            "{}/{} tests",
+            result.tests().iter().filter(|test| test.passed).count(),
+            count,
+        ),
+    };
+
+    Spans::from(vec![
+        Span::styled(
+            format!(" {} ", mark),
+            Style::default().fg(mark_color).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!("{}{:7} {:32} ", iteration, result.method(), result.name()),
+            Style::default().fg(Color::White),
+        ),
+        Span::styled(
+            format!("{:4} ", status),
+            Style::default().fg(status_color).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!("{:>7} ms  ", result.elapsed().as_millis()),
+            Style::default().fg(Color::White),
+        ),
+        Span::styled(tests, Style::default().fg(Color::DarkGray)),
+    ])
+}
+
+/// How far the run is, or the summary of the last run.
+fn progress_line<'a>(runner: &Runner) -> Spans<'a> {
+    let done = runner.results().len();
+    let failed = runner.results().iter().filter(|r| !r.passed()).count();
+
+    let counts = format!(
+        "{} passed, {} failed",
+        done - failed,
+        failed,
+    );
+
+    let (text, color) = match (runner.active(), runner.report()) {
+        (Some(active), _) => (
+            format!(
+                " Running {}/{} \u{2502} {} \u{2502} {:.1} s",
+                done,
+                runner.total(),
+                counts,
+                active.elapsed().as_secs_f32(),
+            ),
+            Color::Yellow,
+        ),
+
+        (None, Some(report)) => {
+            let stopped = match done < report.total() {
+                true => format!(" \u{2502} stopped after {} of {}", done, report.total()),
+                false => String::new(),
+            };
+
+            (
+                format!(
+                    " Done \u{2502} {}{} \u{2502} {:.1} s",
+                    counts,
+                    stopped,
+                    report.duration().as_secs_f32(),
+                ),
+                if failed == 0 { Color::Green } else { Color::Red },
+            )
+        }
+
+        (None, None) => (String::new(), Color::White),
+    };
+
+    Spans::from(Span::styled(
+        text,
+        Style::default().fg(color).add_modifier(Modifier::BOLD),
+    ))
+}
@@ -0,0 +1,53 @@
+use tui::{
+    backend::Backend, style::{ Color, Style }, Frame,
+    widgets::Borders, layout::{ Layout, Constraint, Direction, Rect },
+};
+
+use crate::ui::{
+    state::{ UiState, UIElement, scripts::ScriptKind },
+    widgets::text_input::TextInput,
+};
+
+/// Renders the editors of the pre-request and post-response scripts, one
+/// above the other.
+pub fn render_scripts<B: Backend>(
+    f: &mut Frame<B>, uistate: &mut UiState, rect: Rect
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rect);
+
+    let tab_active = uistate.active_element() == &UIElement::RequestTabsElem;
+
+    for (kind, chunk) in ScriptKind::iter().zip(chunks.iter()) {
+        let active = tab_active && uistate.scripts().active() == *kind;
+        let editor = uistate.scripts().editor(*kind);
+
+        let border_style = match active {
+            true => Style::default().fg(Color::Yellow),
+            false => Style::default().fg(Color::White),
+        };
+
+        let text_multi_line = TextInput::default()
+            .multi_line(true)
+            .label(format!(" {} Script ", kind.to_str()))
+            .borders(Borders::ALL)
+            .active(active)
+            .border_style(border_style)
+            .active_border_style(border_style)
+            .line_number(editor.line_number())
+            .cursor_pos(editor.cursor_pos())
+            .selecting(editor.selecting())
+            .sel_start_pos(editor.sel_start_pos())
+            .sel_end_pos(editor.sel_end_pos())
+            .text_vec(editor.text_vec())
+            .scroll_offset(editor.scroll_offset())
+            .variables(uistate.environments().variable_names());
+
+        uistate.scripts_mut().editor_mut(*kind)
+            .set_content_height(chunk.height.saturating_sub(2));
+
+        f.render_widget(text_multi_line, *chunk);
+    }
+}
@@ -0,0 +1,84 @@
+use tui::{
+    style::{ Color, Modifier, Style }, text::{ Span, Spans },
+};
+
+use crate::ui::state::search::Search;
+
+/// Number of rows of the search bar.
+pub fn search_bar_height(search: &Search) -> u16 {
+    match search.replacing() {
+        true => 2,
+        false => 1,
+    }
+}
+
+/// The rows of the search bar: the query with the toggles and the match
+/// count, then the replacement if it is shown.
+pub fn search_bar<'a>(search: &Search) -> Vec<Spans<'a>> {
+    let label_style = Style::default().fg(Color::Cyan);
+    let hint_style = Style::default().fg(Color::DarkGray);
+
+    let input = |text: &str, focused: bool| {
+        let mut text = String::from(text);
+
+        if focused {
+            text.push('\u{2588}');
+        }
+
+        Span::styled(text, Style::default().fg(Color::White))
+    };
+
+    let toggle = |label: &'static str, on: bool| {
+        let style = match on {
+            true => Style::default().fg(Color::Black).bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+            false => hint_style,
+        };
+
+        Span::styled(label, style)
+    };
+
+    let count = match (search.error(), search.matches().len()) {
+        (Some(error), _) => {
+            Span::styled(error.clone(), Style::default().fg(Color::Red))
+        }
+
+        (None, 0) if !search.query().is_empty() => {
+            Span::styled("No matches", Style::default().fg(Color::Red))
+        }
+
+        (None, 0) => Span::raw(""),
+        (None, n) => Span::raw(format!("{}/{}", search.current() + 1, n)),
+    };
+
+    let mut rows = vec![Spans::from(vec![
+        Span::styled(match search.replacing() {
+            true => "Find:    ",
+            false => "Find: ",
+        }, label_style),
+        input(search.query(), !search.replacement_focused()),
+        Span::raw("  "),
+        toggle(" Aa ", search.case_sensitive()),
+        Span::raw(" "),
+        toggle(" .* ", search.regex()),
+        Span::raw("  "),
+        count,
+        Span::styled(
+            "  Enter/\u{2193}: next \u{2502} \u{2191}: previous \u{2502} Alt+C: case \u{2502} Alt+R: regex \u{2502} Esc: close",
+            hint_style,
+        ),
+    ])];
+
+    if search.replacing() {
+        rows.push(Spans::from(vec![
+            Span::styled("Replace: ", label_style),
+            input(search.replacement(), search.replacement_focused()),
+            Span::styled(
+                "  Tab: switch \u{2502} Enter: replace \u{2502} Alt+A: replace all",
+                hint_style,
+            ),
+        ]));
+    }
+
+    rows
+}
@@ -0,0 +1,136 @@
+use tui::{
+    backend::Backend, style::{ Color, Style }, Frame,
+    widgets::{ Borders, Paragraph, Wrap },
+    layout::{ Layout, Constraint, Direction, Rect },
+};
+
+use crate::{
+    settings::{ ClientSettings, DEFAULT_SETTINGS_FILE },
+    ui::{
+        state::{
+            UiState,
+            settings::{ SettingsUIElement, SETTINGS_FIELDS, SETTINGS_SELECT_DISP_LEN },
+            vim::field_cursor, text_field::TextField,
+        },
+        widgets::{ text_input::TextInput, select::Select },
+        render::vim_cursor,
+    },
+};
+
+/// Renders the "Settings" tab content: the overrides of the request and the
+/// global settings they fall back to.
+pub fn render_settings<B: Backend>(
+    f: &mut Frame<B>, uistate: &UiState, rect: Rect
+) {
+    let rows = Layout::default()
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(0),
+        ])
+        .direction(Direction::Vertical)
+        .split(rect);
+
+    let top_row = Layout::default()
+        .constraints([
+            Constraint::Percentage(34),
+            Constraint::Percentage(33),
+            Constraint::Percentage(33),
+        ])
+        .direction(Direction::Horizontal)
+        .split(rows[0]);
+
+    let proxy_row = Layout::default()
+        .constraints([
+            Constraint::Percentage(50),
+            Constraint::Percentage(50),
+        ])
+        .direction(Direction::Horizontal)
+        .split(rows[1]);
+
+    let field_rects = [top_row[0], top_row[1], proxy_row[0], proxy_row[1], rows[2]];
+
+    for (field, field_rect) in field_rects.iter().enumerate() {
+        f.render_widget(
+            settings_field(uistate, field as u8, field_rect.width),
+            *field_rect,
+        );
+    }
+
+    let global = Paragraph::new(global_summary(uistate.settings().global()))
+        .style(Style::default().fg(Color::Gray))
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(global, rows[3]);
+
+    let settings = uistate.settings();
+
+    let mut verify_select = Select::default()
+        .label(String::from(" Verify TLS "))
+        .disp_content_length(SETTINGS_SELECT_DISP_LEN)
+        .style(Style::default().fg(Color::White))
+        .active_style(Style::default().fg(Color::Yellow))
+        .sel_index(settings.tls_verification_sel_index())
+        .options(settings.tls_verification_options());
+
+    if let SettingsUIElement::VerifyTls(opened) =
+        settings.active_settings_element()
+    {
+        verify_select = verify_select.active(true).opened(*opened);
+    }
+
+    f.render_widget(verify_select, top_row[2]);
+}
+
+/// Builds the text input for the `field`th field, see [SETTINGS_FIELDS].
+fn settings_field(uistate: &UiState, field: u8, width: u16) -> TextInput {
+    let settings = uistate.settings();
+    let mut style = Style::default().fg(Color::Gray);
+
+    if *settings.active_settings_element() == SettingsUIElement::Field(field) {
+        style = style.fg(Color::Yellow);
+    }
+
+    let input = TextInput::default()
+        .label(format!(" {} ", SETTINGS_FIELDS[field as usize]))
+        .borders(Borders::ALL)
+        .text(settings.field(field).unwrap_or_default())
+        .border_style(style)
+        .active_border_style(style)
+        .width(width)
+        .variables(uistate.environments().variable_names());
+
+    vim_cursor(input, field_cursor(uistate, TextField::Setting(field)))
+}
+
+/// The global settings the empty fields fall back to.
+fn global_summary(global: &ClientSettings) -> String {
+    let timeout = match global.timeout() {
+        0 => String::from("none"),
+        secs => format!("{} s", secs),
+    };
+
+    let redirects = match global.redirects() {
+        0 => String::from("not followed"),
+        max => format!("up to {}", max),
+    };
+
+    let proxy = match (global.proxy(), global.no_proxy()) {
+        (Some(proxy), Some(no_proxy)) => format!("{} (not for {})", proxy, no_proxy),
+        (Some(proxy), None) => String::from(proxy),
+        (None, _) => String::from("from the environment"),
+    };
+
+    let tls = match (global.verify_tls(), global.ca_bundle()) {
+        (false, _) => String::from("not verified"),
+        (true, Some(ca_bundle)) => format!("verified, also against {}", ca_bundle),
+        (true, None) => String::from("verified"),
+    };
+
+    format!(
+        "Empty fields use the global settings of {}: timeout {}, redirects \
+        {}, proxy {}, certificates {}.",
+        DEFAULT_SETTINGS_FILE, timeout, redirects, proxy, tls,
+    )
+}
@@ -0,0 +1,132 @@
+use tui::{
+    backend::Backend, style::{ Color, Style }, text::{ Span, Spans }, Frame,
+    widgets::{ BorderType, Block, Borders, Paragraph },
+    layout::Rect,
+};
+
+use crate::ui::{
+    state::{ UiState, sidebar::SidebarPromptKind },
+    widgets::text_input::TextInput,
+};
+
+/// Renders the collection sidebar.
+pub fn render_sidebar<B: Backend>(
+    f: &mut Frame<B>, uistate: &mut UiState, rect: Rect
+) {
+    let focused = uistate.sidebar().focused();
+    let mut sidebar_style = Style::default().fg(Color::Gray);
+
+    if focused {
+        sidebar_style = sidebar_style.fg(Color::Yellow);
+    }
+
+    let sidebar_block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(sidebar_style)
+        .title(" Collection ");
+
+    f.render_widget(sidebar_block, rect);
+
+    if rect.width < 3 || rect.height < 3 {
+        return;
+    }
+
+    let prompt_height: u16 = if uistate.sidebar().prompt().is_some() {
+        3
+    } else {
+        0
+    };
+
+    let list_rect = Rect::new(
+        rect.x + 1,
+        rect.y + 1,
+        rect.width - 2,
+        (rect.height - 2).saturating_sub(prompt_height),
+    );
+
+    uistate.sidebar_mut().set_content_height(list_rect.height);
+
+    let sidebar = uistate.sidebar();
+    let mut lines: Vec<Spans> = vec![];
+
+    if sidebar.items().is_empty() {
+        lines.push(Spans::from(Span::styled(
+            "Empty. Ctrl+S saves the request.",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    for (i, item) in sidebar.items().iter()
+        .enumerate()
+        .skip(sidebar.scroll_offset())
+        .take(list_rect.height as usize)
+    {
+        let marker = if !item.is_folder() {
+            "  "
+        } else if sidebar.is_expanded(item.path()) {
+            "\u{25be} "
+        } else {
+            "\u{25b8} "
+        };
+
+        let mut item_style = Style::default().fg(Color::White);
+
+        if item.is_folder() {
+            item_style = item_style.fg(Color::Cyan);
+        }
+
+        if i == sidebar.sel_index() {
+            item_style = if focused {
+                item_style.fg(Color::Black).bg(Color::Yellow)
+            } else {
+                item_style.bg(Color::DarkGray)
+            };
+        }
+
+        let text = format!(
+            "{}{}{}",
+            "  ".repeat(item.depth() as usize),
+            marker,
+            item.name(),
+        );
+
+        lines.push(Spans::from(Span::styled(
+            format!("{:width$}", text, width = list_rect.width as usize),
+            item_style,
+        )));
+    }
+
+    f.render_widget(Paragraph::new(lines), list_rect);
+
+    if let Some(prompt) = sidebar.prompt() {
+        let prompt_rect = Rect::new(
+            list_rect.x,
+            list_rect.y + list_rect.height,
+            list_rect.width,
+            prompt_height.min(rect.height - 2),
+        );
+
+        let (label, text) = match prompt.kind() {
+            SidebarPromptKind::SaveAs => {
+                (" Save As ", String::from(prompt.input()))
+            }
+
+            SidebarPromptKind::Rename => {
+                (" Rename ", String::from(prompt.input()))
+            }
+
+            SidebarPromptKind::Delete => {
+                (" Delete ", String::from("Press y to confirm"))
+            }
+        };
+
+        let prompt_input = TextInput::default()
+            .label(String::from(label))
+            .borders(Borders::ALL)
+            .text(text)
+            .border_style(Style::default().fg(Color::Yellow));
+
+        f.render_widget(prompt_input, prompt_rect);
+    }
+}
@@ -0,0 +1,65 @@
+use crossterm::event::KeyCode;
+use tui::{
+    backend::Backend, style::{ Color, Modifier, Style }, text::{ Span, Spans },
+    Frame, widgets::{ Clear, Paragraph }, layout::{ Alignment, Rect },
+};
+
+use crate::ui::state::{ UiState, InputMode };
+
+/// Renders the mode of the VIM editor mode and the keys of the command being
+/// typed at the right end of the status bar. The `:` command line takes the
+/// whole status bar.
+pub fn render_vim_status<B: Backend>(
+    f: &mut Frame<B>, uistate: &UiState, rect: Rect
+) {
+    let vim = uistate.vim();
+
+    if let Some(command) = vim.command_line() {
+        f.render_widget(Clear, rect);
+        f.render_widget(
+            Paragraph::new(format!(":{}\u{2502}", command)),
+            rect,
+        );
+
+        return;
+    }
+
+    let (mode, color) = match uistate.input_mode() {
+        InputMode::Normal => ("NORMAL", Color::Cyan),
+        InputMode::INSERT => ("INSERT", Color::LightGreen),
+        InputMode::VISUAL if vim.visual_line() => ("V-LINE", Color::Magenta),
+        InputMode::VISUAL => ("VISUAL", Color::Magenta),
+    };
+
+    let pending: String = vim.pending().iter()
+        .filter_map(|key| match key.code {
+            KeyCode::Char(c) => Some(c),
+            _ => None,
+        })
+        .collect();
+
+    let indicator = Paragraph::new(Spans::from(vec![
+        Span::raw(format!("{} ", pending)),
+        Span::styled(
+            format!(" {} ", mode),
+            Style::default().fg(Color::Black).bg(color)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ])).alignment(Alignment::Right);
+
+    let width = (mode.len() + pending.len() + 3) as u16;
+
+    if rect.width < width {
+        return;
+    }
+
+    let indicator_rect = Rect::new(
+        rect.x + rect.width - width,
+        rect.y,
+        width,
+        1,
+    );
+
+    f.render_widget(Clear, indicator_rect);
+    f.render_widget(indicator, indicator_rect);
+}
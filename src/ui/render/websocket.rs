@@ -0,0 +1,136 @@
+use tui::{
+    backend::Backend, style::{ Color, Modifier, Style }, text::{ Span, Spans },
+    Frame,
+    widgets::{ BorderType, Block, Borders, Paragraph },
+    layout::Rect,
+};
+
+use crate::{
+    websocket::{ Direction, LogEntry, LogEvent },
+    ui::state::{ UiState, UIElement, websocket::WebSocket },
+};
+
+/// Renders the message log of the WebSocket connection in place of the
+/// response.
+pub fn render_websocket<B: Backend>(
+    f: &mut Frame<B>, uistate: &mut UiState, rect: Rect
+) {
+    let mut style = Style::default().fg(Color::Gray);
+
+    if uistate.active_element() == &UIElement::ResponseArea {
+        style = style.fg(Color::Yellow);
+    }
+
+    let block = Block::default().borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(style)
+        .title(title(uistate.websocket()));
+
+    f.render_widget(block, rect);
+
+    if rect.height < 4 || rect.width < 4 {
+        return;
+    }
+
+    let log_rect = Rect::new(rect.x + 1, rect.y + 1, rect.width - 2, rect.height - 3);
+    let rows: Vec<Spans> = uistate.websocket().log().iter()
+        .flat_map(log_rows)
+        .collect();
+
+    uistate.websocket_mut()
+        .clamp_scroll(rows.len(), log_rect.height as usize);
+
+    let lines: Vec<Spans> = match rows.is_empty() {
+        true => vec![Spans::from(Span::styled(
+            " Send connects, then sends the body as a frame.",
+            Style::default().fg(Color::DarkGray),
+        ))],
+
+        false => rows.into_iter()
+            .skip(uistate.websocket().scroll_offset())
+            .take(log_rect.height as usize)
+            .collect(),
+    };
+
+    f.render_widget(Paragraph::new(lines), log_rect);
+
+    let hints = Paragraph::new(Span::styled(
+        " Enter: connect/send \u{2502} b: text/binary \u{2502} c: clear \u{2502} Esc: close",
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    f.render_widget(
+        hints,
+        Rect::new(log_rect.x, rect.y + rect.height - 2, log_rect.width, 1),
+    );
+}
+
+/// The state of the connection and the kind of the frames sent.
+fn title(websocket: &WebSocket) -> String {
+    let status = match websocket.connection() {
+        Some(connection) if connection.connected() => {
+            format!("connected to {}", connection.url())
+        }
+
+        Some(connection) => format!("connecting to {}", connection.url()),
+        None => String::from("disconnected"),
+    };
+
+    format!(
+        " WebSocket \u{2502} {} \u{2502} {} frames ",
+        status,
+        websocket.frame_kind().to_str(),
+    )
+}
+
+/// The rows of a log entry: the time, the direction and the first line of
+/// the message, then its other lines.
+fn log_rows<'a>(entry: &LogEntry) -> Vec<Spans<'a>> {
+    let time = Span::styled(
+        format!(" {} ", entry.at().format("%H:%M:%S%.3f")),
+        Style::default().fg(Color::DarkGray),
+    );
+
+    let (mark, color, text) = match entry.event() {
+        LogEvent::Connected(url) => (
+            "\u{2713}", Color::Green, format!("connected to {}", url),
+        ),
+
+        LogEvent::Message(Direction::Sent, kind, text) => (
+            "\u{2192}", Color::Cyan, format!("{:6} {}", kind.to_str(), text),
+        ),
+
+        LogEvent::Message(Direction::Received, kind, text) => (
+            "\u{2190}", Color::White, format!("{:6} {}", kind.to_str(), text),
+        ),
+
+        LogEvent::Closed(code, reason) => (
+            "\u{2715}", Color::Yellow, format!("closed {} {}", code, reason),
+        ),
+
+        LogEvent::Error(e) => ("!", Color::Red, e.clone()),
+    };
+
+    let mut lines = text.lines();
+    let mut rows = vec![Spans::from(vec![
+        time,
+        Span::styled(
+            format!("{} ", mark),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            String::from(lines.next().unwrap_or_default()),
+            Style::default().fg(color),
+        ),
+    ])];
+
+    // Lines after the first one are indented under the message
+    for line in lines {
+        rows.push(Spans::from(Span::styled(
+            format!("{:23}{}", "", line),
+            Style::default().fg(color),
+        )));
+    }
+
+    rows
+}
@@ -1,10 +1,18 @@
 #[derive(Clone)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum AppStatus {
     /// Denotes the startup state - no request have been made
     STARTUP,
     PROCESSING,
     DONE,
     ERROR,
+
+    /// The user aborted the request that was in flight.
+    CANCELLED,
+
+    /// Something other than a request finished, e.g. a snippet was copied.
+    /// The message is in `UiState::app_message`.
+    INFO,
 }
 
 impl Default for AppStatus {
@@ -18,6 +26,8 @@ impl AppStatus {
             Self::PROCESSING => "Processing",
             Self::DONE => "Done",
             Self::ERROR => "Error",
+            Self::CANCELLED => "Cancelled",
+            Self::INFO => "Info",
         }
     }
 }
@@ -0,0 +1,323 @@
+//! State of the "Authorization" request tab.
+
+use std::slice::Iter;
+
+/// Number of options displayed at once in the auth select widgets.
+pub const AUTH_SELECT_DISP_LEN: u8 = 5;
+
+/// The UI elements in the authorization tab.
+#[derive(Clone, PartialEq)]
+pub enum AuthUIElement {
+    /// The "Auth Type" select widget.
+    /// params
+    /// - bool - whether the Select widget is open
+    Scheme(bool),
+
+    /// The "Add To" select widget shown for the API key scheme.
+    /// params
+    /// - bool - whether the Select widget is open
+    ApiKeyLocation(bool),
+
+    /// A text field of the selected scheme, e.g. `Field(1)` is the password
+    /// field for basic auth.
+    Field(u8),
+}
+
+/// The authorization scheme applied to the request.
+#[derive(Clone, Default, PartialEq)]
+pub enum AuthScheme {
+    #[default]
+    NoAuth,
+    Basic,
+    Bearer,
+    ApiKey,
+}
+
+/// Where the API key is sent.
+#[derive(Clone, Default, PartialEq)]
+pub enum ApiKeyLocation {
+    #[default]
+    Header,
+    QueryParam,
+}
+
+#[derive(Clone)]
+pub struct Auth {
+    active_auth_element: AuthUIElement,
+
+    /// The selected authorization scheme.
+    auth_scheme: AuthScheme,
+
+    /// The index of the element selected in the "Auth Type" select.
+    auth_scheme_sel_index: u8,
+    auth_scheme_options: Vec<String>,
+    auth_scheme_scroll_offset: u8,
+
+    /// Basic auth user name.
+    username: String,
+
+    /// Basic auth password.
+    password: String,
+
+    /// Bearer token.
+    token: String,
+
+    /// Name of the API key header or query param.
+    api_key: String,
+
+    /// Value of the API key.
+    api_key_value: String,
+
+    api_key_location: ApiKeyLocation,
+
+    /// The index of the element selected in the "Add To" select.
+    api_key_location_sel_index: u8,
+    api_key_location_options: Vec<String>,
+}
+
+impl Default for AuthUIElement {
+    fn default() -> Self { Self::Scheme(false) }
+}
+
+impl Default for Auth {
+    fn default() -> Self {
+        Self {
+            active_auth_element: AuthUIElement::default(),
+            auth_scheme: AuthScheme::default(),
+            auth_scheme_sel_index: 0,
+            auth_scheme_options: AuthScheme::as_string_vec(),
+            auth_scheme_scroll_offset: 0,
+            username: String::default(),
+            password: String::default(),
+            token: String::default(),
+            api_key: String::default(),
+            api_key_value: String::default(),
+            api_key_location: ApiKeyLocation::default(),
+            api_key_location_sel_index: 0,
+            api_key_location_options: ApiKeyLocation::as_string_vec(),
+        }
+    }
+}
+
+impl Auth {
+    pub fn active_auth_element(&self) -> &AuthUIElement {
+        &self.active_auth_element
+    }
+    pub fn set_active_auth_element(&mut self, auth_el: AuthUIElement) {
+        self.active_auth_element = auth_el;
+    }
+
+    pub fn auth_scheme(&self) -> &AuthScheme { &self.auth_scheme }
+    pub fn set_auth_scheme(&mut self, auth_scheme: AuthScheme) {
+        self.auth_scheme_sel_index = AuthScheme::iter()
+            .position(|s| *s == auth_scheme)
+            .unwrap_or(0) as u8;
+
+        self.auth_scheme = auth_scheme;
+    }
+
+    pub fn auth_scheme_sel_index(&self) -> u8 { self.auth_scheme_sel_index }
+    pub fn set_auth_scheme_sel_index(&mut self, index: u8) {
+        self.auth_scheme_sel_index = index;
+    }
+
+    pub fn auth_scheme_options(&self) -> Vec<String> {
+        self.auth_scheme_options.clone()
+    }
+
+    pub fn auth_scheme_scroll_offset(&self) -> u8 {
+        self.auth_scheme_scroll_offset
+    }
+    pub fn set_auth_scheme_scroll_offset(&mut self, offset: u8) {
+        self.auth_scheme_scroll_offset = offset;
+    }
+
+    pub fn username(&self) -> String { self.username.clone() }
+    pub fn set_username(&mut self, username: String) {
+        self.username = username;
+    }
+
+    pub fn password(&self) -> String { self.password.clone() }
+    pub fn set_password(&mut self, password: String) {
+        self.password = password;
+    }
+
+    pub fn token(&self) -> String { self.token.clone() }
+    pub fn set_token(&mut self, token: String) { self.token = token; }
+
+    pub fn api_key(&self) -> String { self.api_key.clone() }
+    pub fn set_api_key(&mut self, api_key: String) { self.api_key = api_key; }
+
+    pub fn api_key_value(&self) -> String { self.api_key_value.clone() }
+    pub fn set_api_key_value(&mut self, api_key_value: String) {
+        self.api_key_value = api_key_value;
+    }
+
+    pub fn api_key_location(&self) -> &ApiKeyLocation {
+        &self.api_key_location
+    }
+    pub fn set_api_key_location(&mut self, location: ApiKeyLocation) {
+        self.api_key_location_sel_index = ApiKeyLocation::iter()
+            .position(|l| *l == location)
+            .unwrap_or(0) as u8;
+
+        self.api_key_location = location;
+    }
+
+    pub fn api_key_location_sel_index(&self) -> u8 {
+        self.api_key_location_sel_index
+    }
+    pub fn set_api_key_location_sel_index(&mut self, index: u8) {
+        self.api_key_location_sel_index = index;
+    }
+
+    pub fn api_key_location_options(&self) -> Vec<String> {
+        self.api_key_location_options.clone()
+    }
+
+    /// Returns the UI elements of the selected scheme in navigation order.
+    pub fn elements(&self) -> Vec<AuthUIElement> {
+        match self.auth_scheme {
+            AuthScheme::NoAuth => vec![AuthUIElement::Scheme(false)],
+
+            AuthScheme::Basic => vec![
+                AuthUIElement::Scheme(false),
+                AuthUIElement::Field(0),
+                AuthUIElement::Field(1),
+            ],
+
+            AuthScheme::Bearer => vec![
+                AuthUIElement::Scheme(false),
+                AuthUIElement::Field(0),
+            ],
+
+            AuthScheme::ApiKey => vec![
+                AuthUIElement::Scheme(false),
+                AuthUIElement::ApiKeyLocation(false),
+                AuthUIElement::Field(0),
+                AuthUIElement::Field(1),
+            ],
+        }
+    }
+
+    /// Position of the active element in [Auth::elements].
+    fn active_element_index(&self) -> usize {
+        let active = match self.active_auth_element {
+            AuthUIElement::Scheme(_) => AuthUIElement::Scheme(false),
+            AuthUIElement::ApiKeyLocation(_) => {
+                AuthUIElement::ApiKeyLocation(false)
+            }
+            AuthUIElement::Field(n) => AuthUIElement::Field(n),
+        };
+
+        self.elements().iter().position(|e| *e == active).unwrap_or(0)
+    }
+
+    /// Activates the next element of the selected scheme.
+    pub fn activate_next_element(&mut self) {
+        let elements = self.elements();
+        let next = self.active_element_index() + 1;
+
+        if next < elements.len() {
+            self.active_auth_element = elements[next].clone();
+        }
+    }
+
+    /// Activates the previous element of the selected scheme.
+    pub fn activate_previous_element(&mut self) {
+        let current = self.active_element_index();
+
+        if current > 0 {
+            self.active_auth_element = self.elements()[current - 1].clone();
+        }
+    }
+
+    /// Returns the text of field `field` of the selected scheme.
+    pub fn field(&self, field: u8) -> Option<String> {
+        match (&self.auth_scheme, field) {
+            (AuthScheme::Basic, 0) => Some(self.username()),
+            (AuthScheme::Basic, 1) => Some(self.password()),
+            (AuthScheme::Bearer, 0) => Some(self.token()),
+            (AuthScheme::ApiKey, 0) => Some(self.api_key()),
+            (AuthScheme::ApiKey, 1) => Some(self.api_key_value()),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the text of field `field` of the
+    /// selected scheme.
+    pub fn field_mut(&mut self, field: u8) -> Option<&mut String> {
+        match (&self.auth_scheme, field) {
+            (AuthScheme::Basic, 0) => Some(&mut self.username),
+            (AuthScheme::Basic, 1) => Some(&mut self.password),
+            (AuthScheme::Bearer, 0) => Some(&mut self.token),
+            (AuthScheme::ApiKey, 0) => Some(&mut self.api_key),
+            (AuthScheme::ApiKey, 1) => Some(&mut self.api_key_value),
+            _ => None,
+        }
+    }
+}
+
+impl AuthScheme {
+    pub fn to_str(&self) -> &str {
+        match self {
+            AuthScheme::NoAuth => "No Auth",
+            AuthScheme::Basic => "Basic Auth",
+            AuthScheme::Bearer => "Bearer Token",
+            AuthScheme::ApiKey => "API Key",
+        }
+    }
+
+    pub fn from_str(input: &str) -> Self {
+        match input {
+            "Basic Auth" => AuthScheme::Basic,
+            "Bearer Token" => AuthScheme::Bearer,
+            "API Key" => AuthScheme::ApiKey,
+            _ => AuthScheme::NoAuth,
+        }
+    }
+
+    pub fn iter() -> Iter<'static, AuthScheme> {
+        static AUTH_SCHEMES: [AuthScheme; 4] = [
+            AuthScheme::NoAuth,
+            AuthScheme::Basic,
+            AuthScheme::Bearer,
+            AuthScheme::ApiKey,
+        ];
+
+        AUTH_SCHEMES.iter()
+    }
+
+    pub fn as_string_vec() -> Vec<String> {
+        Self::iter().map(|s| String::from(s.to_str())).collect()
+    }
+}
+
+impl ApiKeyLocation {
+    pub fn to_str(&self) -> &str {
+        match self {
+            ApiKeyLocation::Header => "Header",
+            ApiKeyLocation::QueryParam => "Query Params",
+        }
+    }
+
+    pub fn from_str(input: &str) -> Self {
+        match input {
+            "Query Params" => ApiKeyLocation::QueryParam,
+            _ => ApiKeyLocation::Header,
+        }
+    }
+
+    pub fn iter() -> Iter<'static, ApiKeyLocation> {
+        static API_KEY_LOCATIONS: [ApiKeyLocation; 2] = [
+            ApiKeyLocation::Header,
+            ApiKeyLocation::QueryParam,
+        ];
+
+        API_KEY_LOCATIONS.iter()
+    }
+
+    pub fn as_string_vec() -> Vec<String> {
+        Self::iter().map(|l| String::from(l.to_str())).collect()
+    }
+}
@@ -1,5 +1,6 @@
 use std::slice::Iter;
 use super::{ kv_data::KVData, kv_tab_state::KVTabState, text_edit::TextEditState };
+use crate::graphql::{ Operation, operations };
 
 /// The UI elements in the body tab (e.g. The "Content Type" selection).
 /// **Note:** the `bool` value in the variant defines whether the body ui
@@ -19,6 +20,14 @@ pub enum BodyUIElement {
     TextArea,
 
     KVArea,
+
+    /// The variables editor of the GraphQL body.
+    Variables,
+
+    /// The "Operation" select of the GraphQL body.
+    /// params
+    /// - bool - whether the Select widget is open
+    Operation(bool),
 }
 
 #[derive(Clone, PartialEq)]
@@ -30,6 +39,7 @@ pub enum BodyContent {
     Json,
     Html,
     Xml,
+    GraphQL,
 //    Raw(RawBodyContentType),
 }
 
@@ -71,9 +81,19 @@ pub struct Body {
     kv_data: Vec<KVData>,
     kv_tab_state: KVTabState,
 
-    /// Text data for the body
+    /// Text data for the body, the query of the GraphQL body.
     text_data: TextEditState,
 
+    /// The JSON variables of the GraphQL body.
+    variables: TextEditState,
+
+    /// Name of the GraphQL operation to run. Empty to run the first one.
+    operation: String,
+
+    /// The index of the element selected in the Operation select.
+    operation_sel_index: u8,
+    operation_scroll_offset: u8,
+
     // /// The length of the select widget popup content length.
     // disp_content_len: u8,
 }
@@ -93,6 +113,10 @@ impl Default for Body {
             kv_data: vec![KVData::default()],
             kv_tab_state: KVTabState::default(),
             text_data: TextEditState::default(),
+            variables: TextEditState::default(),
+            operation: String::new(),
+            operation_sel_index: 0,
+            operation_scroll_offset: 0,
             //disp_content_len: 5,
         }
     }
@@ -125,6 +149,20 @@ impl Body {
     pub fn body_content(&self) -> &BodyContent { &self.body_content }
     pub fn set_body_content(&mut self, body_content: BodyContent) {
         self.body_content = body_content;
+
+        // The content types have different form columns
+        self.kv_tab_state.set_active_col(0);
+    }
+
+    /// Sets the body content and moves the "Content Type" select to it.
+    pub fn select_body_content(&mut self, body_content: BodyContent) {
+        let index = BodyContent::iter()
+            .position(|c| *c == body_content)
+            .unwrap_or(0) as u8;
+
+        self.body_content_sel_index = index;
+        self.body_content_scroll_offset = index.saturating_sub(4);
+        self.set_body_content(body_content);
     }
 
     pub fn body_content_sel_index(&self) -> &u8 { &self.body_content_sel_index }
@@ -172,6 +210,35 @@ impl Body {
     pub fn text_data(&self) -> &TextEditState { &self.text_data }
     pub fn text_data_mut(&mut self) -> &mut TextEditState { &mut self.text_data }
 
+    pub fn variables(&self) -> &TextEditState { &self.variables }
+    pub fn variables_mut(&mut self) -> &mut TextEditState { &mut self.variables }
+
+    pub fn operation(&self) -> &str { &self.operation }
+    pub fn set_operation(&mut self, operation: String) { self.operation = operation; }
+
+    pub fn operation_sel_index(&self) -> u8 { self.operation_sel_index }
+    pub fn set_operation_sel_index(&mut self, sel_index: u8) {
+        self.operation_sel_index = sel_index;
+    }
+
+    pub fn operation_scroll_offset(&self) -> u8 { self.operation_scroll_offset }
+    pub fn set_operation_scroll_offset(&mut self, offset: u8) {
+        self.operation_scroll_offset = offset;
+    }
+
+    /// The operations of the GraphQL query.
+    pub fn operations(&self) -> Vec<Operation> {
+        operations(&self.text_data.text())
+    }
+
+    /// Index of the selected operation in [Body::operations], the first one
+    /// when the selected operation isn't in the query anymore.
+    pub fn operation_index(&self) -> usize {
+        self.operations().iter()
+            .position(|operation| operation.name() == Some(self.operation.as_str()))
+            .unwrap_or(0)
+    }
+
     pub fn kv_data(&self) -> &Vec<KVData> { &self.kv_data }
     pub fn kv_data_mut(&mut self) -> &mut Vec<KVData> { &mut self.kv_data }
 
@@ -199,6 +266,7 @@ impl BodyContent {
             BodyContent::Json => String::from("Json"),
             BodyContent::Html => String::from("Html"),
             BodyContent::Xml => String::from("Xml"),
+            BodyContent::GraphQL => String::from("GraphQL"),
             // BodyContent::Raw(_a) => String::from("Raw"),
         }
     }
@@ -216,13 +284,14 @@ impl BodyContent {
             "Json" => BodyContent::Json,
             "Html" => BodyContent::Html,
             "Xml" => BodyContent::Xml,
+            "GraphQL" => BodyContent::GraphQL,
             //"Raw" => BodyContent::Raw(RawBodyContentType::default()),
             _ => BodyContent::default(),
         }
     }
 
     pub fn iter() -> Iter<'static, BodyContent> {
-        static BODY_CONTENT_TYPES: [BodyContent; 7] = [
+        static BODY_CONTENT_TYPES: [BodyContent; 8] = [
             BodyContent::NONE,
             BodyContent::FormData,
             BodyContent::FormURLEncoded,
@@ -230,6 +299,7 @@ impl BodyContent {
             BodyContent::Text,
             BodyContent::Html,
             BodyContent::Xml,
+            BodyContent::GraphQL,
             // BodyContent::Raw(RawBodyContentType::Text),
         ];
 
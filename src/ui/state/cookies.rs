@@ -0,0 +1,125 @@
+//! State of the cookie manager, which lists the cookies of the jar of the
+//! active environment.
+
+use std::error::Error;
+
+use crate::cookie::{ Cookie, CookieJars };
+
+/// Labels of the fields of the cookie editor.
+pub const EDITOR_FIELDS: [&str; 5] = ["Name", "Value", "Domain", "Path", "Expires"];
+
+#[derive(Clone, Default)]
+pub struct Cookies {
+    /// The jars of all the environments, shared with the requests.
+    jars: CookieJars,
+    opened: bool,
+
+    /// Index of the cookies of the jar that is selected.
+    sel_index: usize,
+    scroll_offset: usize,
+
+    /// Number of rows that fit in the manager. Set while rendering.
+    content_height: u16,
+
+    /// The cookie being edited or added, `None` while the list is shown.
+    editor: Option<CookieEditor>,
+}
+
+/// The fields of a cookie being edited in the cookie manager.
+#[derive(Clone)]
+pub struct CookieEditor {
+    /// Index of the cookie in the jar, `None` for a new cookie.
+    index: Option<usize>,
+
+    /// The cookie before the edit, keeping the flags that aren't edited.
+    cookie: Cookie,
+
+    /// Text of the fields, in the order of [EDITOR_FIELDS].
+    fields: [String; 5],
+    active_field: usize,
+}
+
+impl Cookies {
+    pub fn jars(&self) -> &CookieJars { &self.jars }
+    pub fn set_jars(&mut self, jars: CookieJars) { self.jars = jars; }
+
+    pub fn opened(&self) -> bool { self.opened }
+    pub fn set_opened(&mut self, opened: bool) {
+        self.opened = opened;
+        self.editor = None;
+    }
+
+    pub fn sel_index(&self) -> usize { self.sel_index }
+    pub fn scroll_offset(&self) -> usize { self.scroll_offset }
+
+    pub fn set_content_height(&mut self, height: u16) {
+        self.content_height = height;
+    }
+
+    /// Moves the selection by `delta` of the `count` cookies, scrolling to
+    /// keep it visible.
+    pub fn move_selection(&mut self, delta: i32, count: usize) {
+        if count == 0 {
+            self.sel_index = 0;
+            self.scroll_offset = 0;
+            return;
+        }
+
+        let height = self.content_height as usize;
+
+        let max = (count - 1) as i32;
+        self.sel_index = (self.sel_index as i32 + delta).clamp(0, max) as usize;
+
+        if self.sel_index < self.scroll_offset {
+            self.scroll_offset = self.sel_index;
+        } else if height > 0 && self.sel_index >= self.scroll_offset + height {
+            self.scroll_offset = self.sel_index + 1 - height;
+        }
+    }
+
+    pub fn editor(&self) -> Option<&CookieEditor> { self.editor.as_ref() }
+    pub fn editor_mut(&mut self) -> Option<&mut CookieEditor> {
+        self.editor.as_mut()
+    }
+    pub fn set_editor(&mut self, editor: Option<CookieEditor>) {
+        self.editor = editor;
+    }
+}
+
+impl CookieEditor {
+    /// Edits `cookie`, the cookie at `index` of the jar or a new one.
+    pub fn new(index: Option<usize>, cookie: Cookie) -> Self {
+        let fields = [
+            String::from(cookie.name()),
+            String::from(cookie.value()),
+            String::from(cookie.domain()),
+            String::from(cookie.path()),
+            String::from(cookie.expires()),
+        ];
+
+        Self { index, cookie, fields, active_field: 0 }
+    }
+
+    pub fn index(&self) -> Option<usize> { self.index }
+    pub fn fields(&self) -> &[String; 5] { &self.fields }
+
+    pub fn active_field(&self) -> usize { self.active_field }
+    /// Moves to the field `delta` fields away, wrapping around.
+    pub fn move_field(&mut self, delta: i32) {
+        let count = self.fields.len() as i32;
+        self.active_field = (self.active_field as i32 + delta).rem_euclid(count) as usize;
+    }
+
+    pub fn push(&mut self, c: char) { self.fields[self.active_field].push(c); }
+    pub fn pop(&mut self) { self.fields[self.active_field].pop(); }
+
+    /// The cookie with the edited fields.
+    pub fn edited(&self) -> Result<Cookie, Box<dyn Error>> {
+        let [name, value, domain, path, expires] = self.fields.clone();
+        let mut cookie = self.cookie.clone();
+
+        cookie.edit(name, value, domain, path, expires)?;
+
+        Ok(cookie)
+    }
+}
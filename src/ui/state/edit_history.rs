@@ -0,0 +1,436 @@
+//! The undo history of the text fields.
+
+use crate::ui::state::text_field::{ TextField, FieldState };
+
+/// Number of edits that can be undone.
+const UNDO_LEVELS: usize = 200;
+
+/// What an edit did. Consecutive edits of the same kind, other than
+/// `Other`, are undone at once.
+#[derive(Clone, Copy, PartialEq)]
+pub enum EditKind {
+    /// Typing characters.
+    Insert,
+
+    /// Deleting characters with Backspace or Delete.
+    Delete,
+
+    /// A change of the VIM editor mode, from its command to the end of the
+    /// `INSERT` mode.
+    VimChange,
+
+    /// Anything else, like pasting or replacing the selection.
+    Other,
+}
+
+/// Cursor and selection of a field, see [FieldState].
+#[derive(Clone, PartialEq)]
+struct Marks {
+    cursor: (usize, usize),
+    selection: Option<((u16, u16), (u16, u16))>,
+}
+
+/// An edit of a text field. Only the changed text is kept: `removed` was
+/// replaced by `inserted` at the byte `at` of the text, its lines joined by
+/// `\n`.
+#[derive(Clone)]
+struct Edit {
+    field: TextField,
+    at: usize,
+    removed: String,
+    inserted: String,
+    before: Marks,
+    after: Marks,
+    kind: EditKind,
+
+    /// Whether the next edit of the same kind is merged into this one.
+    open: bool,
+}
+
+/// The edits that can be undone and redone. Every text field has its own
+/// history within the journal: undoing in a field only undoes the edits of
+/// that field.
+#[derive(Clone, Default)]
+pub struct EditHistory {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+}
+
+impl EditHistory {
+    /// Records an edit of `field`. A run of typed or deleted characters
+    /// becomes a single edit, as long as the cursor isn't moved in between.
+    /// A new edit can't be redone over, so the edits undone in any field
+    /// are dropped.
+    pub fn record(
+        &mut self, field: TextField, before: FieldState, after: FieldState,
+        kind: EditKind,
+    ) {
+        self.redo.clear();
+
+        let old = before.lines.join("\n");
+        let new = after.lines.join("\n");
+        let (at, removed, inserted) = diff(&old, &new);
+
+        let edit = Edit {
+            field,
+            at,
+            removed: String::from(removed),
+            inserted: String::from(inserted),
+            before: Marks { cursor: before.cursor, selection: before.selection },
+            after: Marks { cursor: after.cursor, selection: after.selection },
+            kind,
+            open: kind != EditKind::Other,
+        };
+
+        if let Some(last) = self.undo.last_mut() {
+            let continues = last.open && last.field == field && last.kind == kind
+                && last.after == edit.before
+                && old.get(last.at..last.at + last.inserted.len())
+                    == Some(last.inserted.as_str());
+
+            if continues && last.merge(&edit) {
+                return;
+            }
+
+            last.open = false;
+        }
+
+        self.undo.push(edit);
+
+        if self.undo.len() > UNDO_LEVELS {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Ends the current run of typed or deleted characters, so that the next
+    /// edit is undone on its own.
+    pub fn break_run(&mut self) {
+        if let Some(last) = self.undo.last_mut() {
+            last.open = false;
+        }
+    }
+
+    /// Takes the last edit of `field`, returning the state before it.
+    /// `current` is the state of the field now. If the field was changed in
+    /// another way since, e.g. by removing its row, its history is dropped.
+    pub fn undo(
+        &mut self, field: TextField, current: &FieldState
+    ) -> Option<FieldState> {
+        self.break_run();
+
+        let index = self.undo.iter().rposition(|edit| edit.field == field)?;
+        let edit = self.undo.remove(index);
+
+        let Some(lines) = replace(current, edit.at, &edit.inserted, &edit.removed)
+        else {
+            self.undo.retain(|edit| edit.field != field);
+            self.redo.retain(|edit| edit.field != field);
+
+            return None;
+        };
+
+        let before = FieldState {
+            lines,
+            cursor: edit.before.cursor,
+            selection: edit.before.selection,
+        };
+
+        self.redo.push(edit);
+
+        Some(before)
+    }
+
+    /// Takes the last undone edit of `field`, returning the state after it.
+    pub fn redo(
+        &mut self, field: TextField, current: &FieldState
+    ) -> Option<FieldState> {
+        let index = self.redo.iter().rposition(|edit| edit.field == field)?;
+        let edit = self.redo.remove(index);
+
+        let Some(lines) = replace(current, edit.at, &edit.removed, &edit.inserted)
+        else {
+            self.redo.retain(|edit| edit.field != field);
+            return None;
+        };
+
+        let after = FieldState {
+            lines,
+            cursor: edit.after.cursor,
+            selection: edit.after.selection,
+        };
+
+        self.undo.push(edit);
+
+        Some(after)
+    }
+}
+
+impl Edit {
+    /// Merges `next`, made right after this edit, into it. Returns `false`
+    /// if `next` doesn't touch the text this edit inserted.
+    fn merge(&mut self, next: &Edit) -> bool {
+        let (start, end) = (self.at, self.at + self.inserted.len());
+        let (next_start, next_end) = (next.at, next.at + next.removed.len());
+
+        if next_start > end || next_end < start {
+            return false;
+        }
+
+        // The text `next` removed around the inserted one was there before
+        let mut removed = String::new();
+
+        if next_start < start {
+            removed.push_str(&next.removed[..start - next_start]);
+        }
+
+        removed.push_str(&self.removed);
+
+        if next_end > end {
+            removed.push_str(&next.removed[next.removed.len() - (next_end - end)..]);
+        }
+
+        let mut inserted = String::new();
+
+        if next_start > start {
+            inserted.push_str(&self.inserted[..next_start - start]);
+        }
+
+        inserted.push_str(&next.inserted);
+
+        if next_end < end {
+            inserted.push_str(&self.inserted[next_end - start..]);
+        }
+
+        self.at = start.min(next_start);
+        self.removed = removed;
+        self.inserted = inserted;
+        self.after = next.after.clone();
+
+        true
+    }
+}
+
+/// Where `old` and `new` differ: the byte they differ from, the text of
+/// `old` that was replaced and the text of `new` that replaced it.
+fn diff<'a>(old: &'a str, new: &'a str) -> (usize, &'a str, &'a str) {
+    let mut prefix = old.bytes().zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+
+    let mut suffix = old[prefix..].bytes().rev().zip(new[prefix..].bytes().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    while !old.is_char_boundary(old.len() - suffix)
+        || !new.is_char_boundary(new.len() - suffix)
+    {
+        suffix -= 1;
+    }
+
+    (prefix, &old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix])
+}
+
+/// The lines of `state` with `from` at the byte `at` replaced by `to`.
+/// `None` if `from` isn't there, i.e. the text was changed in another way.
+fn replace(state: &FieldState, at: usize, from: &str, to: &str) -> Option<Vec<String>> {
+    let text = state.lines.join("\n");
+
+    if text.get(at..at + from.len()) != Some(from) {
+        return None;
+    }
+
+    let text = format!("{}{}{}", &text[..at], to, &text[at + from.len()..]);
+
+    Some(text.split('\n').map(String::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELD: TextField = TextField::Url;
+    const OTHER: TextField = TextField::Assertions;
+
+    fn state(text: &str, col: usize) -> FieldState {
+        FieldState {
+            lines: text.split('\n').map(String::from).collect(),
+            cursor: (0, col),
+            selection: None,
+        }
+    }
+
+    /// Types `text` at the end of `start` in `field`, one key at a time.
+    fn type_text(history: &mut EditHistory, field: TextField, start: &str, text: &str) {
+        let mut current = String::from(start);
+
+        for c in text.chars() {
+            let before = state(&current, current.chars().count());
+            current.push(c);
+            let after = state(&current, current.chars().count());
+
+            history.record(field, before, after, EditKind::Insert);
+        }
+    }
+
+    fn text(state: &FieldState) -> String {
+        state.lines.join("\n")
+    }
+
+    #[test]
+    fn diff_finds_the_changed_text() {
+        assert_eq!(diff("abc", "abXc"), (2, "", "X"));
+        assert_eq!(diff("abc", "ac"), (1, "b", ""));
+        assert_eq!(diff("abc", "abc"), (3, "", ""));
+        assert_eq!(diff("aaa", "aa"), (2, "a", ""));
+        assert_eq!(diff("héllo", "hèllo"), (1, "é", "è"));
+        assert_eq!(diff("a\nb", "a\n\nb"), (2, "", "\n"));
+    }
+
+    #[test]
+    fn typed_characters_are_undone_at_once() {
+        let mut history = EditHistory::default();
+
+        type_text(&mut history, FIELD, "", "héllo");
+
+        let before = history.undo(FIELD, &state("héllo", 5)).unwrap();
+
+        assert_eq!(text(&before), "");
+        assert_eq!(before.cursor, (0, 0));
+        assert!(history.undo(FIELD, &before).is_none());
+    }
+
+    #[test]
+    fn deleted_characters_are_undone_at_once() {
+        let mut history = EditHistory::default();
+
+        // Backspace twice, then Delete twice, from "ab|cd"
+        for (before, after) in [("abcd", "acd"), ("acd", "cd")] {
+            let col = before.find('c').unwrap();
+
+            let (before, after) = (state(before, col), state(after, col - 1));
+
+            history.record(FIELD, before, after, EditKind::Delete);
+        }
+
+        for (before, after) in [("cd", "d"), ("d", "")] {
+            history.record(FIELD, state(before, 0), state(after, 0), EditKind::Delete);
+        }
+
+        let before = history.undo(FIELD, &state("", 0)).unwrap();
+
+        assert_eq!(text(&before), "abcd");
+        assert_eq!(before.cursor, (0, 2));
+    }
+
+    #[test]
+    fn moving_the_cursor_ends_the_run() {
+        let mut history = EditHistory::default();
+
+        type_text(&mut history, FIELD, "", "ab");
+
+        // Typed at the start after moving the cursor there
+        history.record(FIELD, state("ab", 0), state("Xab", 1), EditKind::Insert);
+
+        let undone = history.undo(FIELD, &state("Xab", 1)).unwrap();
+        assert_eq!(text(&undone), "ab");
+
+        let undone = history.undo(FIELD, &undone).unwrap();
+        assert_eq!(text(&undone), "");
+    }
+
+    #[test]
+    fn break_run_and_other_edits_are_undone_on_their_own() {
+        let mut history = EditHistory::default();
+
+        type_text(&mut history, FIELD, "", "a");
+        history.break_run();
+        type_text(&mut history, FIELD, "a", "b");
+        history.record(FIELD, state("ab", 2), state("abcd", 4), EditKind::Other);
+        type_text(&mut history, FIELD, "abcd", "e");
+
+        let mut current = state("abcde", 5);
+
+        for expected in ["abcd", "ab", "a", ""] {
+            current = history.undo(FIELD, &current).unwrap();
+            assert_eq!(text(&current), expected);
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_keep_each_field_apart() {
+        let mut history = EditHistory::default();
+
+        type_text(&mut history, FIELD, "", "url");
+        history.break_run();
+        type_text(&mut history, OTHER, "", "x\ny");
+        history.break_run();
+        type_text(&mut history, FIELD, "url", "/a");
+
+        let url = history.undo(FIELD, &state("url/a", 5)).unwrap();
+        assert_eq!(text(&url), "url");
+
+        let other = history.undo(OTHER, &state("x\ny", 3)).unwrap();
+        assert_eq!(text(&other), "");
+
+        let other = history.redo(OTHER, &other).unwrap();
+        assert_eq!(text(&other), "x\ny");
+
+        let url = history.redo(FIELD, &url).unwrap();
+        assert_eq!(text(&url), "url/a");
+        assert!(history.redo(FIELD, &url).is_none());
+    }
+
+    #[test]
+    fn a_new_edit_drops_the_undone_edits_of_every_field() {
+        let mut history = EditHistory::default();
+
+        type_text(&mut history, FIELD, "", "a");
+        history.break_run();
+        type_text(&mut history, OTHER, "", "b");
+
+        history.undo(OTHER, &state("b", 1)).unwrap();
+        history.undo(FIELD, &state("a", 1)).unwrap();
+
+        type_text(&mut history, FIELD, "", "c");
+
+        assert!(history.redo(OTHER, &state("", 0)).is_none());
+        assert!(history.redo(FIELD, &state("c", 1)).is_none());
+    }
+
+    #[test]
+    fn a_field_changed_in_another_way_loses_its_history() {
+        let mut history = EditHistory::default();
+
+        type_text(&mut history, FIELD, "", "abc");
+
+        assert!(history.undo(FIELD, &state("xyz", 3)).is_none());
+        assert!(history.undo(FIELD, &state("abc", 3)).is_none());
+    }
+
+    #[test]
+    fn only_the_last_edits_can_be_undone() {
+        let mut history = EditHistory::default();
+        let mut current = String::new();
+
+        for _ in 0..UNDO_LEVELS + 10 {
+            history.break_run();
+            type_text(&mut history, FIELD, &current, "x");
+            current.push('x');
+        }
+
+        let mut state = state(&current, current.len());
+        let mut undone = 0;
+
+        while let Some(before) = history.undo(FIELD, &state) {
+            state = before;
+            undone += 1;
+        }
+
+        assert_eq!(undone, UNDO_LEVELS);
+        assert_eq!(text(&state), "x".repeat(10));
+    }
+}
@@ -0,0 +1,180 @@
+//! State of the active environment selector.
+
+use std::{ collections::BTreeMap, error::Error, path::PathBuf };
+
+use crate::environment::{
+    Environment, load_environments, DEFAULT_ENVIRONMENT_DIR,
+};
+
+/// Number of options displayed at once in the environment select widget.
+pub const ENV_SELECT_DISP_LEN: u8 = 5;
+
+/// Label of the select option for sending requests without an environment.
+const NO_ENVIRONMENT: &str = "No Environment";
+
+#[derive(Clone)]
+pub struct Environments {
+    /// The folder the environments are read from.
+    dir: PathBuf,
+    environments: Vec<Environment>,
+
+    /// Index of `environments` that is active, `None` when no environment
+    /// is used.
+    active: Option<usize>,
+
+    /// Variables set by the scripts. They override the variables of the
+    /// environments until dakia exits.
+    script_variables: BTreeMap<String, String>,
+
+    /// The active environment with the `script_variables`, which the
+    /// requests are resolved from.
+    resolved: Option<Environment>,
+
+    /// Whether the select widget is open.
+    opened: bool,
+
+    /// The index of the element selected in the select widget. `0` is
+    /// "No Environment".
+    sel_index: u8,
+    scroll_offset: u8,
+}
+
+impl Default for Environments {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from(DEFAULT_ENVIRONMENT_DIR),
+            environments: vec![],
+            active: None,
+            script_variables: BTreeMap::new(),
+            resolved: None,
+            opened: false,
+            sel_index: 0,
+            scroll_offset: 0,
+        }
+    }
+}
+
+impl Environments {
+    /// The environment selected in the select widget.
+    pub fn active(&self) -> Option<&Environment> {
+        self.active.and_then(|i| self.environments.get(i))
+    }
+
+    /// The environment whose variables are substituted in the request: the
+    /// active environment with the variables set by the scripts.
+    pub fn resolved(&self) -> Option<&Environment> { self.resolved.as_ref() }
+
+    /// Sets a variable from a script. It's used by the requests whichever
+    /// environment is active.
+    pub fn set_variable(&mut self, name: String, value: String) {
+        self.script_variables.insert(name, value);
+        self.update_resolved();
+    }
+
+    /// Merges the variables of the active environment and the scripts into
+    /// [Self::resolved].
+    fn update_resolved(&mut self) {
+        if self.active.is_none() && self.script_variables.is_empty() {
+            self.resolved = None;
+            return;
+        }
+
+        let mut resolved = self.active().cloned().unwrap_or_default();
+
+        for (name, value) in self.script_variables.iter() {
+            resolved.set(name.clone(), value.clone());
+        }
+
+        self.resolved = Some(resolved);
+    }
+
+    /// Activates the environment at `index` of the select options, `0`
+    /// deactivating the environment.
+    pub fn set_active(&mut self, index: u8) {
+        self.active = match index {
+            0 => None,
+            i if (i as usize) <= self.environments.len() => {
+                Some(i as usize - 1)
+            }
+            _ => None,
+        };
+
+        self.sel_index = self.active.map(|i| i as u8 + 1).unwrap_or(0);
+        self.update_resolved();
+    }
+
+    /// Activates the environment named `name`.
+    /// Returns `false` if there is no such environment.
+    pub fn set_active_by_name(&mut self, name: &str) -> bool {
+        match self.environments.iter().position(|e| e.name() == name) {
+            Some(i) => {
+                self.set_active(i as u8 + 1);
+                true
+            }
+
+            None => false,
+        }
+    }
+
+    pub fn opened(&self) -> bool { self.opened }
+    /// Opens or closes the select. Closing it without [Self::set_active]
+    /// puts the selection back on the active environment.
+    pub fn set_opened(&mut self, opened: bool) {
+        self.opened = opened;
+
+        if !opened {
+            self.sel_index = self.active.map(|i| i as u8 + 1).unwrap_or(0);
+        }
+    }
+
+    pub fn sel_index(&self) -> u8 { self.sel_index }
+    pub fn set_sel_index(&mut self, index: u8) { self.sel_index = index; }
+
+    pub fn scroll_offset(&self) -> u8 { self.scroll_offset }
+    pub fn set_scroll_offset(&mut self, offset: u8) {
+        self.scroll_offset = offset;
+    }
+
+    /// The options of the select widget: "No Environment" followed by the
+    /// environment names.
+    pub fn options(&self) -> Vec<String> {
+        let mut options = vec![String::from(NO_ENVIRONMENT)];
+
+        for environment in self.environments.iter() {
+            options.push(String::from(environment.name()));
+        }
+
+        options
+    }
+
+    /// Names of the variables defined in the active environment or by the
+    /// scripts.
+    pub fn variable_names(&self) -> Vec<String> {
+        match self.resolved() {
+            Some(environment) => {
+                environment.variables().keys().cloned().collect()
+            }
+
+            None => vec![],
+        }
+    }
+
+    /// Re-reads the environments from disk, keeping the active environment
+    /// when it still exists.
+    pub fn reload(&mut self) -> Result<(), Box<dyn Error>> {
+        let active_name = self.active().map(|e| String::from(e.name()));
+
+        self.environments = load_environments(&self.dir)?;
+        self.active = None;
+        self.sel_index = 0;
+        self.scroll_offset = 0;
+
+        if let Some(name) = active_name {
+            self.set_active_by_name(&name);
+        }
+
+        self.update_resolved();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+//! State of the export menu, which copies the request as a code snippet.
+
+use crate::export::SnippetFormat;
+
+#[derive(Clone, Default)]
+pub struct ExportMenu {
+    opened: bool,
+
+    /// Index of the selected [SnippetFormat].
+    sel_index: u8,
+}
+
+impl ExportMenu {
+    pub fn opened(&self) -> bool { self.opened }
+    pub fn set_opened(&mut self, opened: bool) { self.opened = opened; }
+
+    pub fn sel_index(&self) -> u8 { self.sel_index }
+    pub fn set_sel_index(&mut self, index: u8) { self.sel_index = index; }
+
+    /// The [SnippetFormat] that is selected.
+    pub fn selected(&self) -> SnippetFormat {
+        SnippetFormat::iter()
+            .nth(self.sel_index as usize)
+            .cloned()
+            .unwrap_or(SnippetFormat::Curl)
+    }
+}
@@ -0,0 +1,257 @@
+//! State of the GraphQL body: the schemas read from the endpoints, the
+//! completion menu of the query editor and the schema browser.
+
+use std::{ collections::HashMap, sync::mpsc::Sender };
+
+use crate::graphql::{ Completion, schema::Schema, worker::SchemaEvent };
+
+/// Rows of the completion menu shown at once.
+pub const COMPLETION_DISP_LEN: usize = 8;
+
+#[derive(Clone, Default)]
+pub struct GraphQL {
+    /// The schemas by the url they were read from.
+    schemas: HashMap<String, Schema>,
+
+    /// The url whose schema is being read.
+    fetching: Option<String>,
+
+    /// Sends the schemas from the worker thread back to the ui loop. `None`
+    /// until the ui loop sets it up.
+    sender: Option<Sender<SchemaEvent>>,
+
+    /// The completion menu of the query editor, `None` when it's closed.
+    completion: Option<CompletionMenu>,
+    browser: SchemaBrowser,
+}
+
+/// The completions of the word at the cursor of the query editor.
+#[derive(Clone)]
+pub struct CompletionMenu {
+    /// The part of the word typed already, replaced by the completion.
+    prefix: String,
+    items: Vec<Completion>,
+    sel_index: usize,
+    scroll_offset: usize,
+}
+
+#[derive(Clone, Default)]
+pub struct SchemaBrowser {
+    opened: bool,
+
+    /// The types followed from the type list, the last one is shown. The
+    /// type list is shown when it's empty.
+    path: Vec<String>,
+
+    sel_index: usize,
+    scroll_offset: usize,
+
+    /// Number of rows that fit in the browser. Set while rendering.
+    content_height: u16,
+}
+
+/// A row of the schema browser: a type of the schema, or a field, argument
+/// or value of the type shown.
+pub struct BrowserRow {
+    label: String,
+
+    /// The type of the field, the kind of the type.
+    detail: String,
+    description: String,
+
+    /// The type opened with Enter.
+    target: Option<String>,
+}
+
+impl GraphQL {
+    pub fn schema(&self, url: &str) -> Option<&Schema> { self.schemas.get(url) }
+    pub fn set_schema(&mut self, url: String, schema: Schema) {
+        self.schemas.insert(url, schema);
+    }
+
+    pub fn fetching(&self) -> Option<&str> { self.fetching.as_deref() }
+    pub fn set_fetching(&mut self, fetching: Option<String>) {
+        self.fetching = fetching;
+    }
+
+    pub fn sender(&self) -> &Option<Sender<SchemaEvent>> { &self.sender }
+    pub fn set_sender(&mut self, sender: Sender<SchemaEvent>) {
+        self.sender = Some(sender);
+    }
+
+    pub fn completion(&self) -> Option<&CompletionMenu> { self.completion.as_ref() }
+    pub fn completion_mut(&mut self) -> Option<&mut CompletionMenu> {
+        self.completion.as_mut()
+    }
+
+    /// Shows the completions in the menu, closing it when there are none.
+    pub fn set_completions(&mut self, prefix: String, items: Vec<Completion>) {
+        self.completion = match items.is_empty() {
+            true => None,
+            false => Some(CompletionMenu {
+                prefix,
+                items,
+                sel_index: 0,
+                scroll_offset: 0,
+            }),
+        };
+    }
+
+    pub fn close_completion(&mut self) { self.completion = None; }
+
+    pub fn browser(&self) -> &SchemaBrowser { &self.browser }
+    pub fn browser_mut(&mut self) -> &mut SchemaBrowser { &mut self.browser }
+}
+
+impl CompletionMenu {
+    pub fn prefix(&self) -> &str { &self.prefix }
+    pub fn items(&self) -> &Vec<Completion> { &self.items }
+    pub fn sel_index(&self) -> usize { self.sel_index }
+    pub fn scroll_offset(&self) -> usize { self.scroll_offset }
+
+    pub fn selected(&self) -> Option<&Completion> { self.items.get(self.sel_index) }
+
+    /// Moves the selection by `delta` rows, scrolling to keep it visible.
+    pub fn move_selection(&mut self, delta: i32) {
+        let max = self.items.len().saturating_sub(1) as i32;
+        self.sel_index = (self.sel_index as i32 + delta).clamp(0, max) as usize;
+
+        if self.sel_index < self.scroll_offset {
+            self.scroll_offset = self.sel_index;
+        } else if self.sel_index >= self.scroll_offset + COMPLETION_DISP_LEN {
+            self.scroll_offset = self.sel_index + 1 - COMPLETION_DISP_LEN;
+        }
+    }
+}
+
+impl SchemaBrowser {
+    pub fn opened(&self) -> bool { self.opened }
+    pub fn set_opened(&mut self, opened: bool) { self.opened = opened; }
+
+    pub fn path(&self) -> &Vec<String> { &self.path }
+
+    /// The type shown, `None` for the type list.
+    pub fn current(&self) -> Option<&str> { self.path.last().map(String::as_str) }
+
+    pub fn sel_index(&self) -> usize { self.sel_index }
+    pub fn scroll_offset(&self) -> usize { self.scroll_offset }
+
+    pub fn set_content_height(&mut self, height: u16) {
+        self.content_height = height;
+    }
+
+    /// Shows the type `name`.
+    pub fn open_type(&mut self, name: String) {
+        self.path.push(name);
+        self.sel_index = 0;
+        self.scroll_offset = 0;
+    }
+
+    /// Goes back to the type shown before, or to the type list.
+    pub fn back(&mut self) {
+        self.path.pop();
+        self.sel_index = 0;
+        self.scroll_offset = 0;
+    }
+
+    /// Moves the selection by `delta` of the `count` rows, scrolling to keep
+    /// it visible.
+    pub fn move_selection(&mut self, delta: i32, count: usize) {
+        if count == 0 {
+            return;
+        }
+
+        let height = self.content_height as usize;
+
+        let max = (count - 1) as i32;
+        self.sel_index = (self.sel_index as i32 + delta).clamp(0, max) as usize;
+
+        if self.sel_index < self.scroll_offset {
+            self.scroll_offset = self.sel_index;
+        } else if height > 0 && self.sel_index >= self.scroll_offset + height {
+            self.scroll_offset = self.sel_index + 1 - height;
+        }
+    }
+
+    /// The rows of the type shown in `schema`.
+    pub fn rows(&self, schema: &Schema) -> Vec<BrowserRow> {
+        let schema_type = match self.current() {
+            Some(name) => match schema.type_named(name) {
+                Some(schema_type) => schema_type,
+                None => { return vec![]; }
+            },
+
+            None => {
+                return schema.types().into_iter()
+                    .map(|t| BrowserRow {
+                        label: String::from(t.name()),
+                        detail: String::from(t.kind()),
+                        description: String::from(t.description()),
+                        target: Some(String::from(t.name())),
+                    })
+                    .collect();
+            }
+        };
+
+        let mut rows: Vec<BrowserRow> = vec![];
+
+        for field in schema_type.fields() {
+            let args: Vec<String> = field.args().iter()
+                .map(|arg| format!("{}: {}", arg.name(), arg.type_ref()))
+                .collect();
+
+            let label = match args.is_empty() {
+                true => String::from(field.name()),
+                false => format!("{}({})", field.name(), args.join(", ")),
+            };
+
+            rows.push(BrowserRow {
+                label,
+                detail: field.type_ref().to_string(),
+                description: String::from(field.description()),
+                target: Some(String::from(field.type_ref().named())),
+            });
+        }
+
+        for input in schema_type.input_fields() {
+            let detail = match input.default_value() {
+                Some(default) => format!("{} = {}", input.type_ref(), default),
+                None => input.type_ref().to_string(),
+            };
+
+            rows.push(BrowserRow {
+                label: String::from(input.name()),
+                detail,
+                description: String::from(input.description()),
+                target: Some(String::from(input.type_ref().named())),
+            });
+        }
+
+        for value in schema_type.enum_values() {
+            rows.push(BrowserRow {
+                label: String::from(value.name()),
+                detail: String::new(),
+                description: String::from(value.description()),
+                target: None,
+            });
+        }
+
+        for possible in schema_type.possible_types() {
+            rows.push(BrowserRow {
+                label: String::from(possible.named()),
+                detail: String::from("OBJECT"),
+                description: String::new(),
+                target: Some(String::from(possible.named())),
+            });
+        }
+
+        rows
+    }
+}
+
+impl BrowserRow {
+    pub fn label(&self) -> &str { &self.label }
+    pub fn detail(&self) -> &str { &self.detail }
+    pub fn description(&self) -> &str { &self.description }
+    pub fn target(&self) -> Option<&str> { self.target.as_deref() }
+}
@@ -0,0 +1,106 @@
+//! State of the history panel.
+
+use std::{ error::Error, path::PathBuf };
+
+use crate::history::{
+    HistoryEntry, load_history, DEFAULT_HISTORY_DIR, MAX_HISTORY_ENTRIES,
+};
+
+#[derive(Clone)]
+pub struct History {
+    /// The folder the history is read from.
+    dir: PathBuf,
+
+    /// The recorded requests, newest first.
+    entries: Vec<HistoryEntry>,
+    opened: bool,
+
+    /// Words the shown entries are filtered by.
+    query: String,
+
+    /// Index of the shown (filtered) entries that is selected.
+    sel_index: usize,
+    scroll_offset: usize,
+
+    /// Number of rows that fit in the panel. Set while rendering.
+    content_height: u16,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from(DEFAULT_HISTORY_DIR),
+            entries: vec![],
+            opened: false,
+            query: String::new(),
+            sel_index: 0,
+            scroll_offset: 0,
+            content_height: 0,
+        }
+    }
+}
+
+impl History {
+    pub fn opened(&self) -> bool { self.opened }
+    pub fn set_opened(&mut self, opened: bool) { self.opened = opened; }
+
+    pub fn query(&self) -> &str { &self.query }
+    /// Filters the entries by `query`, selecting the first match.
+    pub fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.sel_index = 0;
+        self.scroll_offset = 0;
+    }
+
+    pub fn sel_index(&self) -> usize { self.sel_index }
+    pub fn scroll_offset(&self) -> usize { self.scroll_offset }
+
+    pub fn set_content_height(&mut self, height: u16) {
+        self.content_height = height;
+    }
+
+    /// Adds a request that was just sent.
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.entries.insert(0, entry);
+        self.entries.truncate(MAX_HISTORY_ENTRIES);
+    }
+
+    /// The entries matching the query, newest first.
+    pub fn shown(&self) -> Vec<&HistoryEntry> {
+        self.entries.iter()
+            .filter(|entry| entry.matches(&self.query))
+            .collect()
+    }
+
+    pub fn selected(&self) -> Option<&HistoryEntry> {
+        self.shown().get(self.sel_index).copied()
+    }
+
+    /// Moves the selection by `delta` rows, scrolling to keep it visible.
+    pub fn move_selection(&mut self, delta: i32) {
+        let count = self.shown().len();
+
+        if count == 0 {
+            return;
+        }
+
+        let height = self.content_height as usize;
+
+        let max = (count - 1) as i32;
+        self.sel_index = (self.sel_index as i32 + delta).clamp(0, max) as usize;
+
+        if self.sel_index < self.scroll_offset {
+            self.scroll_offset = self.sel_index;
+        } else if height > 0 && self.sel_index >= self.scroll_offset + height {
+            self.scroll_offset = self.sel_index + 1 - height;
+        }
+    }
+
+    /// Reads the history again from the disk.
+    pub fn reload(&mut self) -> Result<(), Box<dyn Error>> {
+        self.entries = load_history(&self.dir)?;
+        self.move_selection(0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,410 @@
+//! State of the tree view of JSON responses.
+//!
+//! The JSON is flattened into its nodes in document order, so the nodes of a
+//! subtree follow the node they belong to. Collapsing a node hides the nodes
+//! up to the end of its subtree.
+
+use serde_json::Value;
+
+/// How a node is reached from its parent.
+#[derive(Clone)]
+pub enum JsonKey {
+    Root,
+
+    /// The key of an object member.
+    Name(String),
+
+    /// The index of an array element.
+    Index(usize),
+}
+
+/// What a node holds.
+#[derive(Clone)]
+pub enum JsonNodeKind {
+    /// An object with the number of its members.
+    Object(usize),
+
+    /// An array with the number of its elements.
+    Array(usize),
+
+    /// A string, number, boolean or `null`, as JSON.
+    Value(String),
+}
+
+#[derive(Clone)]
+pub struct JsonNode {
+    key: JsonKey,
+    kind: JsonNodeKind,
+    depth: usize,
+    parent: Option<usize>,
+
+    /// Index of the first node after the subtree of this node.
+    end: usize,
+    expanded: bool,
+}
+
+#[derive(Clone)]
+pub struct JsonTree {
+    root: Value,
+    nodes: Vec<JsonNode>,
+
+    /// Indices of the nodes whose ancestors are all expanded, in order.
+    visible: Vec<usize>,
+
+    /// Index of the node under the cursor.
+    cursor: usize,
+
+    /// Index of the first visible node shown.
+    scroll_offset: usize,
+}
+
+impl JsonNode {
+    pub fn key(&self) -> &JsonKey { &self.key }
+    pub fn kind(&self) -> &JsonNodeKind { &self.kind }
+    pub fn depth(&self) -> usize { self.depth }
+    pub fn expanded(&self) -> bool { self.expanded }
+
+    /// Whether the node is an object or array with anything in it.
+    pub fn expandable(&self) -> bool {
+        matches!(self.kind, JsonNodeKind::Object(n) | JsonNodeKind::Array(n) if n > 0)
+    }
+}
+
+impl JsonTree {
+    /// Parses `text` into a tree with the members of the root shown.
+    pub fn parse(text: &str) -> Result<Self, serde_json::Error> {
+        let root: Value = serde_json::from_str(text)?;
+        let mut nodes = vec![];
+
+        flatten(&root, JsonKey::Root, 0, None, &mut nodes);
+
+        let mut tree = Self {
+            root,
+            nodes,
+            visible: vec![],
+            cursor: 0,
+            scroll_offset: 0,
+        };
+
+        tree.expand_to_depth(1);
+
+        Ok(tree)
+    }
+
+    pub fn node(&self, index: usize) -> &JsonNode { &self.nodes[index] }
+    pub fn visible(&self) -> &Vec<usize> { &self.visible }
+    pub fn cursor(&self) -> usize { self.cursor }
+    pub fn scroll_offset(&self) -> usize { self.scroll_offset }
+
+    /// Row of the cursor among the visible nodes.
+    pub fn cursor_row(&self) -> usize {
+        self.visible.binary_search(&self.cursor).unwrap_or_default()
+    }
+
+    /// Moves the cursor by `rows` visible nodes, up for negative `rows`.
+    pub fn move_cursor(&mut self, rows: isize) {
+        let row = (self.cursor_row() as isize + rows)
+            .clamp(0, self.visible.len() as isize - 1);
+
+        self.cursor = self.visible[row as usize];
+    }
+
+    /// Scrolls so that the cursor is within the `height` rows shown.
+    pub fn scroll_to_cursor(&mut self, height: usize) {
+        let row = self.cursor_row();
+
+        if row < self.scroll_offset {
+            self.scroll_offset = row;
+        } else if height > 0 && row >= self.scroll_offset + height {
+            self.scroll_offset = row + 1 - height;
+        }
+
+        self.scroll_offset = self.scroll_offset
+            .min(self.visible.len().saturating_sub(height));
+    }
+
+    /// Expands or collapses the node under the cursor.
+    pub fn toggle(&mut self) {
+        let node = &mut self.nodes[self.cursor];
+
+        if node.expandable() {
+            node.expanded = !node.expanded;
+            self.update_visible();
+        }
+    }
+
+    pub fn expand_all(&mut self) { self.expand_to_depth(usize::MAX); }
+    pub fn collapse_all(&mut self) { self.expand_to_depth(0); }
+
+    /// Expands the nodes less than `depth` levels deep and collapses the
+    /// rest. The root is at depth `0`.
+    pub fn expand_to_depth(&mut self, depth: usize) {
+        for node in self.nodes.iter_mut() {
+            node.expanded = node.expandable() && node.depth < depth;
+        }
+
+        self.update_visible();
+    }
+
+    /// JSONPath of the node at `index`, e.g. `$.items[0]["content-type"]`.
+    pub fn path(&self, index: usize) -> String {
+        let mut path = String::new();
+
+        for node in self.ancestry(index) {
+            match &node.key {
+                JsonKey::Root => { path.push('$'); }
+
+                JsonKey::Name(name) if is_identifier(name) => {
+                    path.push('.');
+                    path.push_str(name);
+                }
+
+                JsonKey::Name(name) => {
+                    path.push('[');
+                    path.push_str(&Value::String(name.clone()).to_string());
+                    path.push(']');
+                }
+
+                JsonKey::Index(i) => { path.push_str(&format!("[{}]", i)); }
+            }
+        }
+
+        path
+    }
+
+    /// The value of the node at `index` as pretty-printed JSON.
+    pub fn value(&self, index: usize) -> String {
+        let pointer: String = self.ancestry(index).iter()
+            .map(|node| match &node.key {
+                JsonKey::Root => String::new(),
+                JsonKey::Name(name) => {
+                    format!("/{}", name.replace('~', "~0").replace('/', "~1"))
+                }
+                JsonKey::Index(i) => format!("/{}", i),
+            })
+            .collect();
+
+        self.root.pointer(&pointer)
+            .and_then(|value| serde_json::to_string_pretty(value).ok())
+            .unwrap_or_default()
+    }
+
+    /// The nodes from the root down to the node at `index`.
+    fn ancestry(&self, index: usize) -> Vec<&JsonNode> {
+        let mut nodes = vec![];
+        let mut current = Some(index);
+
+        while let Some(i) = current {
+            nodes.push(&self.nodes[i]);
+            current = self.nodes[i].parent;
+        }
+
+        nodes.reverse();
+        nodes
+    }
+
+    /// Lists the nodes that are shown, moving the cursor out of collapsed
+    /// nodes.
+    fn update_visible(&mut self) {
+        self.visible.clear();
+
+        let mut i = 0;
+
+        while i < self.nodes.len() {
+            self.visible.push(i);
+
+            let node = &self.nodes[i];
+
+            i = match node.expanded {
+                true => i + 1,
+                false => node.end,
+            };
+        }
+
+        while self.visible.binary_search(&self.cursor).is_err() {
+            match self.nodes[self.cursor].parent {
+                Some(parent) => { self.cursor = parent; }
+                None => { break; }
+            }
+        }
+    }
+}
+
+/// Appends `value` and everything in it to `nodes`, returning its index.
+fn flatten(
+    value: &Value,
+    key: JsonKey,
+    depth: usize,
+    parent: Option<usize>,
+    nodes: &mut Vec<JsonNode>,
+) -> usize {
+    let index = nodes.len();
+
+    let kind = match value {
+        Value::Object(map) => JsonNodeKind::Object(map.len()),
+        Value::Array(items) => JsonNodeKind::Array(items.len()),
+        _ => JsonNodeKind::Value(value.to_string()),
+    };
+
+    nodes.push(JsonNode {
+        key,
+        kind,
+        depth,
+        parent,
+        end: index + 1,
+        expanded: false,
+    });
+
+    match value {
+        Value::Object(map) => {
+            for (name, child) in map {
+                let key = JsonKey::Name(name.clone());
+                flatten(child, key, depth + 1, Some(index), nodes);
+            }
+        }
+
+        Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                flatten(child, JsonKey::Index(i), depth + 1, Some(index), nodes);
+            }
+        }
+
+        _ => {}
+    }
+
+    nodes[index].end = nodes.len();
+
+    index
+}
+
+/// Whether `name` can follow a `.` in a JSONPath.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str = r#"{"a": {"b": [1, {"c/d": null}]}, "e": "x", "f g": []}"#;
+
+    // The nodes in document order
+    const ROOT: usize = 0;
+    const A: usize = 1;
+    const B: usize = 2;
+    const B_1: usize = 4;
+    const C_D: usize = 5;
+    const E: usize = 6;
+    const F_G: usize = 7;
+
+    #[test]
+    fn parse_flattens_in_document_order_and_shows_the_root_members() {
+        let tree = JsonTree::parse(JSON).unwrap();
+
+        assert_eq!(tree.visible(), &vec![ROOT, A, E, F_G]);
+        assert!(tree.node(ROOT).expanded() && !tree.node(A).expanded());
+        assert!(matches!(tree.node(B).kind(), JsonNodeKind::Array(2)));
+        assert!(matches!(tree.node(C_D).key(), JsonKey::Name(name) if name == "c/d"));
+        assert!(matches!(tree.node(B_1).key(), JsonKey::Index(1)));
+        assert_eq!(tree.node(C_D).depth(), 4);
+
+        // An empty array can't be expanded
+        assert!(!tree.node(F_G).expandable());
+        assert!(JsonTree::parse("{").is_err());
+    }
+
+    #[test]
+    fn expand_and_collapse_to_a_depth() {
+        let mut tree = JsonTree::parse(JSON).unwrap();
+
+        tree.expand_all();
+        assert_eq!(tree.visible().len(), 8);
+
+        tree.expand_to_depth(2);
+        assert_eq!(tree.visible(), &vec![ROOT, A, B, E, F_G]);
+
+        tree.collapse_all();
+        assert_eq!(tree.visible(), &vec![ROOT]);
+    }
+
+    #[test]
+    fn collapsing_moves_the_cursor_out_of_the_hidden_nodes() {
+        let mut tree = JsonTree::parse(JSON).unwrap();
+
+        tree.expand_all();
+        tree.move_cursor(5);
+        assert_eq!(tree.cursor(), C_D);
+
+        tree.expand_to_depth(2);
+        assert_eq!(tree.cursor(), B);
+
+        tree.collapse_all();
+        assert_eq!(tree.cursor(), ROOT);
+    }
+
+    #[test]
+    fn toggle_expands_and_collapses_the_node_under_the_cursor() {
+        let mut tree = JsonTree::parse(JSON).unwrap();
+
+        tree.move_cursor(1);
+        tree.toggle();
+        assert_eq!(tree.visible(), &vec![ROOT, A, B, E, F_G]);
+
+        tree.toggle();
+        assert_eq!(tree.visible(), &vec![ROOT, A, E, F_G]);
+
+        // A value has nothing to toggle
+        tree.move_cursor(1);
+        tree.toggle();
+        assert_eq!(tree.visible().len(), 4);
+    }
+
+    #[test]
+    fn cursor_moves_between_the_visible_nodes() {
+        let mut tree = JsonTree::parse(JSON).unwrap();
+
+        tree.move_cursor(2);
+        assert_eq!((tree.cursor(), tree.cursor_row()), (E, 2));
+
+        tree.move_cursor(10);
+        assert_eq!(tree.cursor(), F_G);
+
+        tree.move_cursor(-10);
+        assert_eq!(tree.cursor(), ROOT);
+    }
+
+    #[test]
+    fn scroll_keeps_the_cursor_shown() {
+        let mut tree = JsonTree::parse(JSON).unwrap();
+
+        tree.expand_all();
+        tree.move_cursor(6);
+        tree.scroll_to_cursor(3);
+        assert_eq!(tree.scroll_offset(), 4);
+
+        tree.move_cursor(-5);
+        tree.scroll_to_cursor(3);
+        assert_eq!(tree.scroll_offset(), 1);
+
+        // Collapsing leaves fewer rows than the scroll offset
+        tree.collapse_all();
+        tree.scroll_to_cursor(3);
+        assert_eq!(tree.scroll_offset(), 0);
+    }
+
+    #[test]
+    fn paths_and_values_of_the_nodes() {
+        let tree = JsonTree::parse(JSON).unwrap();
+
+        assert_eq!(tree.path(ROOT), "$");
+        assert_eq!(tree.path(C_D), "$.a.b[1][\"c/d\"]");
+        assert_eq!(tree.path(F_G), "$[\"f g\"]");
+
+        assert_eq!(tree.value(C_D), "null");
+        assert_eq!(tree.value(E), "\"x\"");
+        assert_eq!(tree.value(B_1), "{\n  \"c/d\": null\n}");
+    }
+}
@@ -1,17 +1,47 @@
 const EMPTY_STRING: String = String::new();
 
+/// What the value of a multipart form row is.
+#[derive(Clone, Default, PartialEq)]
+pub enum PartKind {
+    /// The value is sent as it is.
+    #[default]
+    Text,
+
+    /// The value is the path of the file to upload.
+    File,
+}
+
 #[derive(Clone, Default, PartialEq)]
 pub struct KVData {
     key: String,
     value: String,
+
+    /// Only used by the rows of a multipart form body.
+    part_kind: PartKind,
+
+    /// File name of the multipart part. Defaults to the name of the file for
+    /// file parts.
+    filename: String,
+
+    /// Content type of the multipart part. Defaults to `text/plain` for text
+    /// parts and is guessed from the extension for file parts.
+    content_type: String,
 }
 
 impl KVData {
     pub const fn default_const() -> Self {
-        Self { key: EMPTY_STRING, value: EMPTY_STRING }
+        Self {
+            key: EMPTY_STRING,
+            value: EMPTY_STRING,
+            part_kind: PartKind::Text,
+            filename: EMPTY_STRING,
+            content_type: EMPTY_STRING,
+        }
     }
 
-    pub fn new(key: String, value: String) -> Self { KVData { key, value } }
+    pub fn new(key: String, value: String) -> Self {
+        KVData { key, value, ..Default::default() }
+    }
 
     pub fn from_string(input: String) -> Self { Self::from_str(&input) }
 
@@ -29,7 +59,7 @@ impl KVData {
             value.push_str(inp_vec[1]);
         }
 
-        KVData { key, value }
+        KVData { key, value, ..Default::default() }
     }
 
     pub fn to_string(&self) -> String {
@@ -49,5 +79,66 @@ impl KVData {
 
     pub fn value(&self) -> String { self.value.clone() }
     pub fn set_value(&mut self, value: String) { self.value = value; }
+
+    pub fn part_kind(&self) -> &PartKind { &self.part_kind }
+    pub fn set_part_kind(&mut self, part_kind: PartKind) {
+        self.part_kind = part_kind;
+    }
+
+    pub fn filename(&self) -> String { self.filename.clone() }
+    pub fn set_filename(&mut self, filename: String) {
+        self.filename = filename;
+    }
+
+    pub fn content_type(&self) -> String { self.content_type.clone() }
+    pub fn set_content_type(&mut self, content_type: String) {
+        self.content_type = content_type;
+    }
+
+    /// Text of the form column `col`. The "Form Data" body has the file name
+    /// and content type columns after the value and type columns.
+    pub fn field(&self, col: u8, is_form_data: bool) -> String {
+        match col {
+            0 => self.key(),
+            1 => self.value(),
+            3 if is_form_data => self.filename(),
+            4 if is_form_data => self.content_type(),
+            _ => String::new(),
+        }
+    }
+
+    /// Sets the text of the form column `col`, see [KVData::field].
+    pub fn set_field(&mut self, col: u8, is_form_data: bool, text: String) {
+        match col {
+            0 => self.set_key(text),
+            1 => self.set_value(text),
+            3 if is_form_data => self.set_filename(text),
+            4 if is_form_data => self.set_content_type(text),
+            _ => {}
+        }
+    }
 }
 
+impl PartKind {
+    pub fn to_str(&self) -> &str {
+        match self {
+            PartKind::Text => "Text",
+            PartKind::File => "File",
+        }
+    }
+
+    pub fn from_str(input: &str) -> Self {
+        match input {
+            "File" => PartKind::File,
+            _ => PartKind::Text,
+        }
+    }
+
+    /// The other kind, for toggling it in the form.
+    pub fn toggled(&self) -> Self {
+        match self {
+            PartKind::Text => PartKind::File,
+            PartKind::File => PartKind::Text,
+        }
+    }
+}
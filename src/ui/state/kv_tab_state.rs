@@ -1,6 +1,18 @@
 //! State of the tab that shows and sets key-value data.
 //! Used in "URL Params" and "Headers" tabs.
 //! Keeps concerns separate from `QueryParams` and `HeaderMap`.
+
+/// Number of columns of a key-value tab, see [KVTabState::active_col].
+pub const KV_TAB_COLUMNS: u8 = 4;
+
+/// Number of columns of the "Form Data" body, which has the part type, file
+/// name and content type columns after the value:
+/// * 2 - the part type (text or file)
+/// * 3 - the file name of the part
+/// * 4 - the content type of the part
+/// * 5 - the add new param button
+/// * 6 - the remove param button
+pub const FORM_DATA_COLUMNS: u8 = 7;
 #[derive(Clone, PartialEq)]
 pub struct KVTabState {
     /// The param being edited/active (index of `params`)
@@ -12,6 +24,8 @@ pub struct KVTabState {
     /// * 1 - the "value" of the param is highlighted or being edited
     /// * 2 - the add new param button is highlighted
     /// * 3 - the remove param button is highlighted
+    ///
+    /// The "Form Data" body has more columns, see [FORM_DATA_COLUMNS].
     active_col: u8,
 
     scroll_pos: u16,
@@ -5,19 +5,61 @@ pub mod kv_data;
 pub mod kv_tab_state;
 pub mod url;
 pub mod response;
+pub mod response_tabs;
 pub mod app_status;
 pub mod body;
 pub mod text_edit;
+pub mod auth;
+pub mod sidebar;
+pub mod environment;
+pub mod export;
+pub mod history;
+pub mod vim;
+pub mod text_field;
+pub mod edit_history;
+pub mod json_tree;
+pub mod response_filter;
+pub mod search;
+pub mod scripts;
+pub mod runner;
+pub mod websocket;
+pub mod graphql;
+pub mod cookies;
+pub mod settings;
 
 //use std::fmt::{ Display, Formatter, Result as FResult };
+use std::{ path::PathBuf, sync::mpsc::Sender };
 use reqwest::Method;
 use request_tabs::RequestTabs;
 use kv_tab_state::KVTabState;
 use kv_data::KVData;
 use url::Url;
 use body::Body;
+use auth::Auth;
+use sidebar::Sidebar;
+use environment::Environments;
+use export::ExportMenu;
+use history::History;
+use vim::VimState;
+use edit_history::EditHistory;
+use search::Search;
+use scripts::Scripts;
+use runner::Runner;
+use websocket::WebSocket;
+use graphql::GraphQL;
+use cookies::Cookies;
+use settings::Settings;
+use text_edit::TextEditState;
 
 use self::{response::Response, app_status::AppStatus};
+use crate::{
+    api::{
+        download::DEFAULT_MEMORY_CAP,
+        worker::{ ApiResult, InFlightRequest },
+    },
+    environment::Environment,
+    cookie::jar_name,
+};
 
 const METHOD_ALLOWED_CHARS: &str = "GPUDHOCAT";
 
@@ -35,6 +77,9 @@ pub struct UiState {
     /// The state of the "Body" tab in request section.
     body: Body,
 
+    /// The state of the "Authorization" tab in request section.
+    auth: Auth,
+
     /// The current [EditorMode].
     editor_mode: EditorMode,
 
@@ -64,11 +109,74 @@ pub struct UiState {
     /// Current [InputMode].
     input_mode: InputMode,
 
+    /// Pending keys and register of the VIM editor mode.
+    vim: VimState,
+
+    /// The edits of the text fields that can be undone.
+    edit_history: EditHistory,
+
     /// HTTP url query parameters
     query_params_ui: KVTabState,
 
     app_status: AppStatus,
     app_error: Option<String>,
+
+    /// Message shown in the status bar with [AppStatus::INFO].
+    app_message: Option<String>,
+
+    /// Sends the results of requests from the worker thread back to the ui
+    /// loop. `None` until the ui loop sets it up.
+    api_sender: Option<Sender<ApiResult>>,
+
+    /// The request currently being processed by the worker thread.
+    in_flight: Option<InFlightRequest>,
+
+    /// Id given to the next dispatched request.
+    next_request_id: u64,
+
+    /// Bytes of a response body kept in memory. Bigger bodies are written
+    /// to a temp file and only their end is shown.
+    body_memory_cap: usize,
+
+    /// The collection sidebar.
+    sidebar: Sidebar,
+
+    /// File of the collection request being edited. `None` until the
+    /// request is saved or opened from the collection.
+    current_request_path: Option<PathBuf>,
+
+    /// The environments and the active environment selector.
+    environments: Environments,
+
+    /// The menu to copy the request as a code snippet.
+    export_menu: ExportMenu,
+
+    /// The sent requests and the history panel.
+    history: History,
+
+    /// The search bar of the response and the body editor.
+    search: Search,
+
+    /// The state of the "Scripts" tab in request section.
+    scripts: Scripts,
+
+    /// The editor of the "Assertions" tab in request section.
+    assertions: TextEditState,
+
+    /// The runner of collection folders.
+    runner: Runner,
+
+    /// The WebSocket connection and its message log.
+    websocket: WebSocket,
+
+    /// The GraphQL schemas, the completion menu and the schema browser.
+    graphql: GraphQL,
+
+    /// The cookie jars and the cookie manager.
+    cookies: Cookies,
+
+    /// The global client settings and the overrides of the "Settings" tab.
+    settings: Settings,
 }
 
 /// An enum representing all the ui elements that can be seen on the screen.
@@ -77,9 +185,10 @@ pub enum UIElement {
     Method = 0,
     URL = 1,
     SendButton = 2,
-    RequestTabsHead = 3,
-    RequestTabsElem = 4,
-    ResponseArea = 5,
+    Environment = 3,
+    RequestTabsHead = 4,
+    RequestTabsElem = 5,
+    ResponseArea = 6,
 }
 
 impl Default for UIElement {
@@ -93,9 +202,10 @@ impl UIElement {
             0 => UIElement::Method,
             1 => UIElement::URL,
             2 => UIElement::SendButton,
-            3 => UIElement::RequestTabsHead,
-            4 => UIElement::RequestTabsElem,
-            5 => UIElement::ResponseArea,
+            3 => UIElement::Environment,
+            4 => UIElement::RequestTabsHead,
+            5 => UIElement::RequestTabsElem,
+            6 => UIElement::ResponseArea,
             _ => UIElement::ResponseArea,
         }
     }
@@ -104,16 +214,13 @@ impl UIElement {
 /// Represents the editor mode.
 /// There can be 2 editor modes:
 /// * **Normal** - This is the default editor mode. The user uses the tab and
-/// arrow keys on their keyboard to move around. Once a field is selected, it
-/// can be edited directly.
-///
-/// * **\*VIM** - This is a future editor mode with vim movements and modes like
-/// `NORMAL`, `INSERT` etc.
+///   arrow keys on their keyboard to move around. Once a field is selected,
+///   it can be edited directly.
 ///
-/// **Notes**
-/// * **\*** - VIM editor mode is yet to be implemented! It doesn't work as
-/// of now.
-#[derive(Clone, PartialEq)]
+/// * **VIM** - The text fields are edited with vim motions and operators in
+///   the `NORMAL`, `INSERT` and `VISUAL` modes (see [InputMode]). Toggled
+///   with F2.
+#[derive(Clone, Copy, PartialEq)]
 pub enum EditorMode {
     Normal,
     VIM,
@@ -123,6 +230,8 @@ impl Default for EditorMode {
     fn default() -> Self { EditorMode::Normal }
 }
 
+/// The mode of the VIM editor mode. Always `Normal` in the normal editor
+/// mode.
 #[derive(Clone, Copy, PartialEq)]
 pub enum InputMode {
     Normal,
@@ -141,6 +250,7 @@ impl Default for UiState {
             url_deconst: Url::default(),
             url_cursor_offset: 0,
             body: Body::default(),
+            auth: Auth::default(),
             editor_mode: EditorMode::default(),
             method: Method::default(),
             active_request_tab: RequestTabs::default(),
@@ -151,9 +261,29 @@ impl Default for UiState {
             response_status_code: None,
             active_element: UIElement::default(),
             input_mode: InputMode::default(),
+            vim: VimState::default(),
+            edit_history: EditHistory::default(),
             query_params_ui: KVTabState::default(),
             app_status: AppStatus::default(),
             app_error: None,
+            app_message: None,
+            api_sender: None,
+            in_flight: None,
+            next_request_id: 0,
+            body_memory_cap: DEFAULT_MEMORY_CAP,
+            sidebar: Sidebar::default(),
+            current_request_path: None,
+            environments: Environments::default(),
+            export_menu: ExportMenu::default(),
+            history: History::default(),
+            search: Search::default(),
+            scripts: Scripts::default(),
+            assertions: TextEditState::default(),
+            runner: Runner::default(),
+            websocket: WebSocket::default(),
+            graphql: GraphQL::default(),
+            cookies: Cookies::default(),
+            settings: Settings::default(),
         }
     }
 }
@@ -163,6 +293,14 @@ impl UiState {
     pub fn url(&self) -> String { self.url.clone() }
     /// Sets the URL
     pub fn set_url(&mut self, url: String) { self.url = url; }
+    /// Replaces the URL, parsing it from scratch and moving the cursor to
+    /// its end.
+    pub fn load_url(&mut self, url: String) {
+        self.url_deconst = Url::default();
+        self.url_deconst.update(url.clone());
+        self.url_cursor_offset = url.len() as u16;
+        self.url = url;
+    }
     /// Appends `chr` at the end of the URL.
     pub fn append_url(&mut self, chr: char) {
         let cursor_offset = self.url_cursor_offset as usize;
@@ -220,7 +358,10 @@ impl UiState {
             self.url_cursor_offset += 1;
         }
     }
-    pub fn url_cursor_offset(&mut self) -> u16 { self.url_cursor_offset }
+    pub fn url_cursor_offset(&self) -> u16 { self.url_cursor_offset }
+    pub fn set_url_cursor_offset(&mut self, offset: u16) {
+        self.url_cursor_offset = offset.min(self.url.chars().count() as u16);
+    }
 
     pub fn url_deconst(&self) -> &Url { &self.url_deconst }
     pub fn url_deconst_mut(&mut self) -> &mut Url { &mut self.url_deconst }
@@ -228,6 +369,9 @@ impl UiState {
     pub fn body(&self) -> &Body { &self.body }
     pub fn body_mut(&mut self) -> &mut Body { &mut self.body }
 
+    pub fn auth(&self) -> &Auth { &self.auth }
+    pub fn auth_mut(&mut self) -> &mut Auth { &mut self.auth }
+
     /// Gets the current [Method].
     pub fn method(&self) -> Method { self.method.clone() }
     /// Sets the current [Method].
@@ -269,7 +413,7 @@ impl UiState {
     }
 
     /// Gets the current [EditorMode].
-    pub fn editor_mode(self) -> EditorMode { self.editor_mode }
+    pub fn editor_mode(&self) -> EditorMode { self.editor_mode }
     /// Sets the current [EditorMode].
     pub fn set_editor_mode(&mut self, editor_mode: EditorMode) {
         self.editor_mode = editor_mode;
@@ -282,6 +426,13 @@ impl UiState {
         self.input_mode = input_mode;
     }
 
+    pub fn vim(&self) -> &VimState { &self.vim }
+    pub fn vim_mut(&mut self) -> &mut VimState { &mut self.vim }
+
+    pub fn edit_history_mut(&mut self) -> &mut EditHistory {
+        &mut self.edit_history
+    }
+
     /// Gets the active [UIElement].
     pub fn active_element(&self) -> &UIElement { &self.active_element }
     /// Sets the active [UIElement].
@@ -312,7 +463,9 @@ impl UiState {
     pub fn response_mut(&mut self) -> &mut Response { &mut self.response }
 
     pub fn request_counter(&self) -> u8 { self.request_counter }
-    pub fn increment_request_counter(&mut self) { self.request_counter += 1 }
+    pub fn increment_request_counter(&mut self) {
+        self.request_counter = self.request_counter.wrapping_add(1);
+    }
 
     pub fn response_status_code(&self) -> &Option<u16> {
         &self.response_status_code
@@ -371,6 +524,93 @@ impl UiState {
         self.app_error = Some(error_str);
     }
 
+    pub fn app_message(&self) -> &Option<String> { &self.app_message }
+    pub fn set_app_message(&mut self, message: String) {
+        self.app_message = Some(message);
+    }
+
+    pub fn api_sender(&self) -> &Option<Sender<ApiResult>> {
+        &self.api_sender
+    }
+    pub fn set_api_sender(&mut self, sender: Sender<ApiResult>) {
+        self.api_sender = Some(sender);
+    }
+
+    pub fn in_flight(&self) -> &Option<InFlightRequest> { &self.in_flight }
+    pub fn set_in_flight(&mut self, in_flight: Option<InFlightRequest>) {
+        self.in_flight = in_flight;
+    }
+
+    /// Returns a new id for a request that is about to be dispatched.
+    pub fn next_request_id(&mut self) -> u64 {
+        self.next_request_id += 1;
+        self.next_request_id
+    }
+
+    pub fn body_memory_cap(&self) -> usize { self.body_memory_cap }
+    pub fn set_body_memory_cap(&mut self, body_memory_cap: usize) {
+        self.body_memory_cap = body_memory_cap;
+    }
+
+    pub fn sidebar(&self) -> &Sidebar { &self.sidebar }
+    pub fn sidebar_mut(&mut self) -> &mut Sidebar { &mut self.sidebar }
+
+    pub fn current_request_path(&self) -> &Option<PathBuf> {
+        &self.current_request_path
+    }
+    pub fn set_current_request_path(&mut self, path: Option<PathBuf>) {
+        self.current_request_path = path;
+    }
+
+    pub fn environments(&self) -> &Environments { &self.environments }
+    pub fn environments_mut(&mut self) -> &mut Environments {
+        &mut self.environments
+    }
+
+    pub fn export_menu(&self) -> &ExportMenu { &self.export_menu }
+    pub fn export_menu_mut(&mut self) -> &mut ExportMenu {
+        &mut self.export_menu
+    }
+
+    pub fn history(&self) -> &History { &self.history }
+    pub fn history_mut(&mut self) -> &mut History { &mut self.history }
+
+    pub fn search(&self) -> &Search { &self.search }
+    pub fn search_mut(&mut self) -> &mut Search { &mut self.search }
+
+    pub fn scripts(&self) -> &Scripts { &self.scripts }
+    pub fn scripts_mut(&mut self) -> &mut Scripts { &mut self.scripts }
+
+    pub fn assertions(&self) -> &TextEditState { &self.assertions }
+    pub fn assertions_mut(&mut self) -> &mut TextEditState {
+        &mut self.assertions
+    }
+
+    pub fn runner(&self) -> &Runner { &self.runner }
+    pub fn runner_mut(&mut self) -> &mut Runner { &mut self.runner }
+
+    pub fn websocket(&self) -> &WebSocket { &self.websocket }
+    pub fn websocket_mut(&mut self) -> &mut WebSocket { &mut self.websocket }
+
+    pub fn graphql(&self) -> &GraphQL { &self.graphql }
+    pub fn graphql_mut(&mut self) -> &mut GraphQL { &mut self.graphql }
+
+    pub fn cookies(&self) -> &Cookies { &self.cookies }
+    pub fn cookies_mut(&mut self) -> &mut Cookies { &mut self.cookies }
+
+    pub fn settings(&self) -> &Settings { &self.settings }
+    pub fn settings_mut(&mut self) -> &mut Settings { &mut self.settings }
+
+    /// Name of the cookie jar of the environment selected.
+    pub fn cookie_jar(&self) -> String {
+        jar_name(self.environments.active())
+    }
+
+    /// The environment whose variables are substituted in the request.
+    pub fn active_environment(&self) -> Option<&Environment> {
+        self.environments.resolved()
+    }
+
     pub fn insert_url_param(&mut self, pos: u16, param: KVData) {
         self.url_deconst.insert_param(pos, param);
     }
@@ -6,6 +6,9 @@ pub enum RequestTabs {
     Authorization = 1,
     Headers = 2,
     Body = 3,
+    Scripts = 4,
+    Assertions = 5,
+    Settings = 6,
 }
 
 impl Default for RequestTabs {
@@ -19,6 +22,9 @@ impl RequestTabs {
             RequestTabs::Authorization => String::from("Authorization"),
             RequestTabs::Headers => String::from("Headers"),
             RequestTabs::Body => String::from("Body"),
+            RequestTabs::Scripts => String::from("Scripts"),
+            RequestTabs::Assertions => String::from("Assertions"),
+            RequestTabs::Settings => String::from("Settings"),
         }
     }
 
@@ -30,6 +36,9 @@ impl RequestTabs {
             1 => RequestTabs::Authorization,
             2 => RequestTabs::Headers,
             3 => RequestTabs::Body,
+            4 => RequestTabs::Scripts,
+            5 => RequestTabs::Assertions,
+            6 => RequestTabs::Settings,
             _ => RequestTabs::UrlParams,
         }
     }
@@ -37,6 +46,7 @@ impl RequestTabs {
     pub fn iter() -> impl Iterator<Item = RequestTabs> {
         [
             Self::UrlParams, Self::Authorization, Self::Headers, Self::Body,
+            Self::Scripts, Self::Assertions, Self::Settings,
         ].iter().copied()
     }
 }
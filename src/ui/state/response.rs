@@ -1,3 +1,25 @@
+use std::{ fs, path::PathBuf, time::{ Duration, Instant } };
+
+use log::info;
+use serde_json::Value;
+
+use reqwest::{
+    blocking::Response as HttpResponse, header::{ CONTENT_TYPE, SET_COOKIE },
+};
+
+use crate::{
+    assertion::TestResult,
+    api::sse::StreamItem,
+    graphql::GraphQLError,
+    ui::{
+        highlight::Token,
+        state::{
+            kv_data::KVData, response_tabs::ResponseTabs, json_tree::JsonTree,
+            response_filter::{ ResponseFilter, filter_json },
+        },
+    },
+};
+
 #[derive(Clone, Default)]
 
 /// Handles the content shown in the Response Area
@@ -5,8 +27,131 @@ pub struct Response {
     /// The state of the request counter when the request was cached.
     cache_req_counter: u8,
     response: Vec<String>,
+
+    /// `response` wrapped to the width of the response area.
+    wrapped: Vec<String>,
+
+    /// Tokens of the characters of each line of `wrapped`, for the syntax
+    /// highlighting.
+    tokens: Vec<Vec<Token>>,
+
+    /// (line, first character) of each line of `wrapped`.
+    line_starts: Vec<(usize, usize)>,
+
+    /// The width the lines were wrapped to.
+    wrap_width: usize,
     initialized: bool,
     scroll_pos: u16,
+
+    /// The response tab that is shown.
+    active_tab: ResponseTabs,
+
+    /// Whether the "Body" tab shows the JSON of the response as a tree.
+    tree_view: bool,
+
+    /// The JSON of the response, parsed when the tree view is shown.
+    tree: Option<JsonTree>,
+
+    /// The filter of the body and its input.
+    filter: ResponseFilter,
+
+    /// The body parsed as JSON for the filter. Parsed when the body is first
+    /// filtered.
+    json: Option<Value>,
+
+    /// The lines of the filtered body. `None` when the body isn't filtered.
+    filtered: Option<Vec<String>>,
+
+    /// Everything about the response except the body.
+    info: ResponseInfo,
+
+    /// Results of the assertions and of the tests of the post-response
+    /// script.
+    tests: Vec<TestResult>,
+
+    /// The errors of the `errors` array of a GraphQL response, shown apart
+    /// from the body.
+    graphql_errors: Vec<GraphQLError>,
+
+    /// The events of an event stream response, shown in the "Body" tab.
+    /// `None` for the other responses.
+    stream: Option<EventStream>,
+
+    /// The body being received, shown as it arrives. `None` once the whole
+    /// response is read.
+    download: Option<Download>,
+
+    /// Whether the "Body" tab scrolls to its last line as the body or the
+    /// events arrive.
+    follow: bool,
+}
+
+/// The events received from an event stream.
+#[derive(Clone)]
+pub struct EventStream {
+    /// Id of the request whose response is the stream.
+    request_id: u64,
+
+    /// The events and the reconnects, oldest first.
+    items: Vec<StreamItem>,
+}
+
+/// A body that is being received.
+#[derive(Clone)]
+pub struct Download {
+    /// Id of the request whose response is received.
+    request_id: u64,
+
+    /// When the first bytes of the body were expected, for the rate.
+    started: Instant,
+
+    /// Bytes received so far, before decompressing them.
+    received: usize,
+
+    /// Size of the text kept in memory.
+    size: usize,
+
+    /// The file the body is written to once it is bigger than `cap`, after
+    /// which only the last lines fitting in `cap` are kept.
+    file: Option<PathBuf>,
+    cap: usize,
+}
+
+/// Details of a response shown in the "Headers", "Cookies" and "Info" tabs.
+#[derive(Clone, Default)]
+pub struct ResponseInfo {
+    status_code: u16,
+
+    /// Reason phrase of the status code, e.g. "Not Found".
+    reason: String,
+
+    /// HTTP version, e.g. "HTTP/1.1".
+    version: String,
+    remote_addr: Option<String>,
+
+    /// Time from sending the request to receiving the whole body.
+    elapsed: Duration,
+
+    /// Size of the body as received, before decompressing it.
+    compressed_size: usize,
+
+    /// Size of the decompressed body.
+    size: usize,
+
+    /// The file with the whole body, when it was too big to keep in memory.
+    body_file: Option<PathBuf>,
+    headers: Vec<KVData>,
+    cookies: Vec<ResponseCookie>,
+}
+
+/// A cookie set by the response with a `Set-Cookie` header.
+#[derive(Clone, Default)]
+pub struct ResponseCookie {
+    name: String,
+    value: String,
+
+    /// Attributes like `Path` or `HttpOnly`. Flags have an empty value.
+    attributes: Vec<KVData>,
 }
 
 impl Response {
@@ -15,19 +160,510 @@ impl Response {
         self.cache_req_counter = cache_req_counter;
     }
 
-    pub fn response(&self) -> &Vec<String> { &self.response }
-    pub fn set_response(&mut self, response: Vec<String>) {
-        self.response = response;
-        self.initialized = true;
-    }
     pub fn from_str(&mut self, resp_text: String) {
         self.response = resp_text.split('\n').map(|s| s.to_string()).collect();
+        self.json = None;
+        self.graphql_errors = vec![];
+        self.stream = None;
+        self.download = None;
+        self.follow = false;
         self.initialized = true;
+        self.apply_filter();
+    }
+
+    /// The lines of the body that are shown: the filtered body if it is
+    /// filtered, the whole body otherwise.
+    pub fn body_lines(&self) -> &Vec<String> {
+        self.filtered.as_ref().unwrap_or(&self.response)
+    }
+
+    pub fn filter(&self) -> &ResponseFilter { &self.filter }
+    pub fn filter_mut(&mut self) -> &mut ResponseFilter { &mut self.filter }
+
+    /// Whether the body shown is filtered.
+    pub fn filtered(&self) -> bool { self.filtered.is_some() }
+
+    /// Filters the body with the expression of the filter. The whole body is
+    /// shown if the expression is empty or invalid, with the error of an
+    /// invalid one set in the filter.
+    pub fn apply_filter(&mut self) {
+        let expression = self.filter.expression().trim().to_string();
+
+        let result = match expression.is_empty() {
+            true => Ok(None),
+            false => self.filter_body(&expression).map(Some),
+        };
+
+        match result {
+            Ok(filtered) => {
+                self.filtered = filtered;
+                self.filter.set_error(None);
+            }
+
+            Err(e) => {
+                self.filtered = None;
+                self.filter.set_error(Some(e));
+            }
+        }
+
+        self.wrapped = vec![];
+        self.tokens = vec![];
+        self.line_starts = vec![];
+        self.wrap_width = 0;
+        self.scroll_pos = 0;
+        self.tree = None;
+
+        // A body that isn't JSON is shown as text
+        if self.tree_view && self.build_tree().is_err() {
+            self.tree_view = false;
+        }
+    }
+
+    fn filter_body(&mut self, expression: &str) -> Result<Vec<String>, String> {
+        if self.json.is_none() {
+            let json = serde_json::from_str(&self.response.join("\n"))
+                .map_err(|e| format!("The response isn't JSON: {}", e))?;
+
+            self.json = Some(json);
+        }
+
+        match &self.json {
+            Some(json) => filter_json(json, expression),
+            None => Ok(vec![]),
+        }
+    }
+
+    pub fn wrapped(&self) -> &Vec<String> { &self.wrapped }
+    pub fn tokens(&self) -> &Vec<Vec<Token>> { &self.tokens }
+    pub fn line_starts(&self) -> &Vec<(usize, usize)> { &self.line_starts }
+    pub fn wrap_width(&self) -> usize { self.wrap_width }
+    /// Sets the lines of the response wrapped to `width`, along with their
+    /// tokens and where they start.
+    pub fn set_wrapped(
+        &mut self,
+        wrapped: Vec<String>,
+        tokens: Vec<Vec<Token>>,
+        line_starts: Vec<(usize, usize)>,
+        width: usize,
+    ) {
+        self.wrapped = wrapped;
+        self.tokens = tokens;
+        self.line_starts = line_starts;
+        self.wrap_width = width;
+    }
+
+    /// Scrolls to the wrapped line with the character `index` of `line`.
+    pub fn scroll_to(&mut self, line: usize, index: usize) {
+        let row = self.line_starts
+            .partition_point(|start| *start <= (line, index))
+            .saturating_sub(1);
+
+        self.scroll_pos = row as u16;
     }
 
     pub fn initialized(&self) -> bool { self.initialized }
 
+    pub fn tree_view(&self) -> bool { self.tree_view }
+    /// Switches the "Body" tab between the text and the tree view. The tree
+    /// view is only shown if the body is JSON.
+    pub fn set_tree_view(&mut self, tree_view: bool) -> Result<(), String> {
+        if tree_view {
+            self.build_tree()?;
+        }
+
+        self.tree_view = tree_view;
+
+        Ok(())
+    }
+
+    pub fn tree(&self) -> Option<&JsonTree> { self.tree.as_ref() }
+    pub fn tree_mut(&mut self) -> Option<&mut JsonTree> { self.tree.as_mut() }
+
+    /// Parses the body into the tree unless it is parsed already.
+    fn build_tree(&mut self) -> Result<(), String> {
+        if self.tree.is_none() {
+            let tree = JsonTree::parse(&self.body_lines().join("\n"))
+                .map_err(|e| format!("The response isn't JSON: {}", e))?;
+
+            self.tree = Some(tree);
+        }
+
+        Ok(())
+    }
+
     pub fn scroll_pos(&self) -> u16 { self.scroll_pos }
     pub fn set_scroll_pos(&mut self, pos: u16) { self.scroll_pos = pos; }
+
+    pub fn active_tab(&self) -> ResponseTabs { self.active_tab }
+    /// Shows the `tab`, scrolling back to its top.
+    pub fn set_active_tab(&mut self, tab: ResponseTabs) {
+        self.active_tab = tab;
+        self.scroll_pos = 0;
+    }
+
+    pub fn activate_next_tab(&mut self) {
+        let n = self.active_tab.get_val();
+        self.set_active_tab(ResponseTabs::from_val(n + 1));
+    }
+    pub fn activate_previous_tab(&mut self) {
+        let n = self.active_tab.get_val();
+
+        if n == 0 {
+            return;
+        }
+
+        self.set_active_tab(ResponseTabs::from_val(n - 1));
+    }
+
+    pub fn info(&self) -> &ResponseInfo { &self.info }
+
+    /// Replaces the details of the response. The file of the body replaced,
+    /// if any, is deleted.
+    pub fn set_info(&mut self, info: ResponseInfo) {
+        if self.info.body_file != info.body_file {
+            self.remove_body_file();
+        }
+
+        self.info = info;
+    }
+
+    /// Deletes the file the body is written to, if it was too big to keep
+    /// in memory.
+    pub fn remove_body_file(&mut self) {
+        let download_file = self.download.as_mut().and_then(|d| d.file.take());
+
+        for file in self.info.body_file.take().into_iter().chain(download_file) {
+            if let Err(e) = fs::remove_file(&file) {
+                info!("Couldn't delete {}: {}", file.display(), e);
+            }
+        }
+    }
+
+    pub fn tests(&self) -> &Vec<TestResult> { &self.tests }
+    pub fn set_tests(&mut self, tests: Vec<TestResult>) { self.tests = tests; }
+
+    pub fn graphql_errors(&self) -> &Vec<GraphQLError> { &self.graphql_errors }
+    pub fn set_graphql_errors(&mut self, errors: Vec<GraphQLError>) {
+        self.graphql_errors = errors;
+    }
+
+    pub fn stream(&self) -> Option<&EventStream> { self.stream.as_ref() }
+    pub fn stream_mut(&mut self) -> Option<&mut EventStream> {
+        self.stream.as_mut()
+    }
+
+    /// Shows the events of the stream of the request `request_id` in place
+    /// of the body.
+    pub fn start_stream(&mut self, request_id: u64) {
+        self.stream = Some(EventStream { request_id, items: vec![] });
+        self.follow = true;
+        self.active_tab = ResponseTabs::Body;
+    }
+
+    pub fn download(&self) -> Option<&Download> { self.download.as_ref() }
+
+    /// Empties the body to show the body of the request `request_id` as it
+    /// arrives.
+    pub fn start_download(&mut self, request_id: u64) {
+        self.response = vec![String::new()];
+        self.json = None;
+        self.filtered = None;
+        self.tree = None;
+        self.stream = None;
+        self.wrapped = vec![];
+        self.tokens = vec![];
+        self.line_starts = vec![];
+        self.wrap_width = 0;
+        self.scroll_pos = 0;
+        self.follow = true;
+        self.initialized = true;
+
+        self.download = Some(Download {
+            request_id,
+            started: Instant::now(),
+            received: 0,
+            size: 0,
+            file: None,
+            cap: 0,
+        });
+    }
+
+    /// Adds `text` to the body being received. Once the body is written to
+    /// a file, the first lines are dropped to keep it under the cap.
+    pub fn append(&mut self, text: &str, received: usize) {
+        let download = match self.download.as_mut() {
+            Some(download) => download,
+            None => { return; }
+        };
+
+        download.received = received;
+        download.size += text.len();
+
+        let mut lines = text.split('\n');
+
+        if let (Some(first), Some(last)) = (lines.next(), self.response.last_mut()) {
+            last.push_str(first);
+        }
+
+        self.response.extend(lines.map(String::from));
+
+        if download.file.is_some() {
+            let mut dropped = 0;
+
+            while download.size > download.cap && dropped + 1 < self.response.len() {
+                download.size -= self.response[dropped].len() + 1;
+                dropped += 1;
+            }
+
+            self.response.drain(..dropped);
+        }
+
+        // Wrapped again when the body is next rendered
+        self.wrap_width = 0;
+    }
+
+    /// The body being received is written to `file` from now on, and only
+    /// its last `cap` bytes are kept.
+    pub fn spill(&mut self, file: PathBuf, cap: usize) {
+        if let Some(download) = self.download.as_mut() {
+            download.file = Some(file);
+            download.cap = cap;
+        }
+    }
+
+    /// Stops showing the body as it arrives. The body received so far stays.
+    pub fn end_download(&mut self) {
+        self.download = None;
+    }
+
+    pub fn follow(&self) -> bool { self.follow }
+    pub fn set_follow(&mut self, follow: bool) { self.follow = follow; }
+
+    /// The first row of the active tab shown when `height` rows are shown.
+    /// While following, that is the row showing the last `height` rows.
+    /// Scrolling down to the last row of a body or a stream that is still
+    /// arriving follows it again.
+    pub fn first_row(&mut self, height: usize) -> usize {
+        let max = self.line_count().saturating_sub(height);
+        let live = self.stream.is_some() || self.download.is_some();
+
+        if self.follow || (live && self.scroll_pos as usize >= max) {
+            self.follow = true;
+            return max;
+        }
+
+        self.scroll_pos as usize
+    }
+
+    /// Number of lines of the active tab, used to limit the scrolling.
+    pub fn line_count(&self) -> usize {
+        match self.active_tab {
+            ResponseTabs::Body => match (&self.stream, &self.tree) {
+                (Some(stream), _) => stream.line_count(),
+                (None, Some(tree)) if self.tree_view => tree.visible().len(),
+                _ => self.wrapped.len(),
+            },
+            ResponseTabs::Headers => self.info.headers.len(),
+            ResponseTabs::Cookies => self.info.cookies.len(),
+            ResponseTabs::Info => self.info.lines().len(),
+            ResponseTabs::Tests => self.tests.len(),
+        }
+    }
+}
+
+impl EventStream {
+    pub fn request_id(&self) -> u64 { self.request_id }
+
+    pub fn items(&self) -> &Vec<StreamItem> { &self.items }
+    pub fn push(&mut self, item: StreamItem) { self.items.push(item); }
+
+    /// The number of events, without the reconnects.
+    pub fn event_count(&self) -> usize {
+        self.items.iter()
+            .filter(|item| matches!(item, StreamItem::Event(_)))
+            .count()
+    }
+
+    /// Number of rows of the event list: a row per item and a row per line
+    /// of the data of the events.
+    pub fn line_count(&self) -> usize {
+        self.items.iter()
+            .map(|item| match item {
+                StreamItem::Event(event) => 1 + event.data().lines().count(),
+                StreamItem::Reconnect { .. } => 1,
+            })
+            .sum()
+    }
+
 }
 
+impl Download {
+    pub fn request_id(&self) -> u64 { self.request_id }
+    pub fn received(&self) -> usize { self.received }
+
+    /// The bytes received per second.
+    pub fn rate(&self) -> f64 {
+        let secs = self.started.elapsed().as_secs_f64();
+
+        match secs > 0.0 {
+            true => self.received as f64 / secs,
+            false => 0.0,
+        }
+    }
+}
+
+impl ResponseInfo {
+    /// Reads the status, version, remote address, headers and cookies of
+    /// `response`. The timing and sizes are set once the body is read.
+    pub fn from_response(response: &HttpResponse) -> Self {
+        let status = response.status();
+
+        let headers = response.headers().iter()
+            .map(|(name, value)| KVData::new(
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).to_string(),
+            ))
+            .collect();
+
+        let cookies = response.headers().get_all(SET_COOKIE).iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(ResponseCookie::parse)
+            .collect();
+
+        Self {
+            status_code: status.as_u16(),
+            reason: String::from(status.canonical_reason().unwrap_or_default()),
+            version: format!("{:?}", response.version()),
+            remote_addr: response.remote_addr().map(|addr| addr.to_string()),
+            elapsed: Duration::default(),
+            compressed_size: 0,
+            size: 0,
+            body_file: None,
+            headers,
+            cookies,
+        }
+    }
+
+    /// Restores the details of a response read earlier, e.g. from the
+    /// history. The cookies are read from the `Set-Cookie` headers.
+    pub fn restore(
+        status_code: u16, reason: String, version: String, headers: Vec<KVData>
+    ) -> Self {
+        let cookies = headers.iter()
+            .filter(|h| h.key().eq_ignore_ascii_case(SET_COOKIE.as_str()))
+            .filter_map(|h| ResponseCookie::parse(&h.value()))
+            .collect();
+
+        Self {
+            status_code,
+            reason,
+            version,
+            headers,
+            cookies,
+            ..Default::default()
+        }
+    }
+
+    pub fn status_code(&self) -> u16 { self.status_code }
+    pub fn reason(&self) -> &str { &self.reason }
+    pub fn version(&self) -> &str { &self.version }
+
+    pub fn elapsed(&self) -> Duration { self.elapsed }
+    pub fn set_elapsed(&mut self, elapsed: Duration) { self.elapsed = elapsed; }
+
+    pub fn set_sizes(&mut self, compressed_size: usize, size: usize) {
+        self.compressed_size = compressed_size;
+        self.size = size;
+    }
+
+    /// Size of the decompressed body.
+    pub fn size(&self) -> usize { self.size }
+    pub fn compressed_size(&self) -> usize { self.compressed_size }
+
+    pub fn body_file(&self) -> Option<&PathBuf> { self.body_file.as_ref() }
+    pub fn set_body_file(&mut self, body_file: Option<PathBuf>) {
+        self.body_file = body_file;
+    }
+
+    pub fn headers(&self) -> &Vec<KVData> { &self.headers }
+
+    /// Value of the `Content-Type` header, empty if there is none.
+    pub fn content_type(&self) -> String {
+        self.headers.iter()
+            .find(|h| h.key().eq_ignore_ascii_case(CONTENT_TYPE.as_str()))
+            .map(|h| h.value())
+            .unwrap_or_default()
+    }
+
+    pub fn cookies(&self) -> &Vec<ResponseCookie> { &self.cookies }
+
+    /// The lines shown in the "Info" tab.
+    pub fn lines(&self) -> Vec<(&str, String)> {
+        let mut lines = vec![
+            ("Status", format!("{} {}", self.status_code, self.reason)),
+            ("Version", self.version.clone()),
+            (
+                "Remote Address",
+                self.remote_addr.clone().unwrap_or_else(|| String::from("-")),
+            ),
+            ("Time", format!("{} ms", self.elapsed.as_millis())),
+            ("Size (compressed)", format_size(self.compressed_size)),
+            ("Size (decompressed)", format_size(self.size)),
+        ];
+
+        if let Some(body_file) = &self.body_file {
+            lines.push(("Body File", body_file.display().to_string()));
+        }
+
+        lines
+    }
+}
+
+impl ResponseCookie {
+    /// Parses the value of a `Set-Cookie` header.
+    pub fn parse(header: &str) -> Option<Self> {
+        let mut parts = header.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+
+        let name = name.trim();
+
+        if name.is_empty() {
+            return None;
+        }
+
+        let attributes = parts
+            .map(|attr| match attr.split_once('=') {
+                Some((k, v)) => {
+                    KVData::new(String::from(k.trim()), String::from(v.trim()))
+                }
+                None => KVData::new(String::from(attr.trim()), String::new()),
+            })
+            .filter(|attr| !attr.key().is_empty())
+            .collect();
+
+        Some(Self {
+            name: String::from(name),
+            value: String::from(value.trim()),
+            attributes,
+        })
+    }
+
+    pub fn name(&self) -> &str { &self.name }
+    pub fn value(&self) -> &str { &self.value }
+    pub fn attributes(&self) -> &Vec<KVData> { &self.attributes }
+}
+
+/// Formats a number of bytes for display, e.g. `1.5 KB`.
+pub fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let kb = bytes as f64 / 1024.0;
+
+    if kb < 1024.0 {
+        return format!("{:.1} KB", kb);
+    }
+
+    format!("{:.1} MB", kb / 1024.0)
+}
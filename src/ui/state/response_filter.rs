@@ -0,0 +1,343 @@
+//! Filtering of JSON responses with JSONPath or jq-like expressions.
+//!
+//! Expressions starting with `$` are JSONPath, e.g. `$.items[*].id`. Other
+//! expressions are a subset of jq made of paths and pipes, e.g.
+//! `.items[] | .id`, which is translated to JSONPath. The subset is
+//! [JQ_SYNTAX]:
+//!
+//! - `.key` for keys made of letters, digits and `_`, `."any key"` and
+//!   `.["any key"]` for the others, e.g. `."content-type"`
+//! - `.[0]` and `.[-1]` for an element, `.[]` for all, `.[1:3]` for a slice
+//! - `.` for the input and `|` to chain paths
+//! - `?` is accepted and does nothing, a missing key matches nothing anyway
+//!
+//! Functions, operators, `,` and the like are not supported.
+
+use serde_json::Value;
+
+/// The jq syntax supported, shown while a filter is typed.
+pub const JQ_SYNTAX: &str = ".key .\"any key\" .[0] .[-1] .[] .[1:3] ? |";
+
+/// Number of filters kept in the history.
+const MAX_FILTER_HISTORY: usize = 20;
+
+#[derive(Clone, Default)]
+pub struct ResponseFilter {
+    expression: String,
+
+    /// Whether the filter input has the focus.
+    editing: bool,
+
+    /// The filters applied recently, newest first.
+    history: Vec<String>,
+
+    /// The entry of the history shown in the input while going through it.
+    history_index: Option<usize>,
+
+    /// Why the expression couldn't be applied.
+    error: Option<String>,
+}
+
+impl ResponseFilter {
+    pub fn expression(&self) -> &str { &self.expression }
+    pub fn set_expression(&mut self, expression: String) {
+        self.expression = expression;
+        self.history_index = None;
+    }
+
+    pub fn editing(&self) -> bool { self.editing }
+    pub fn set_editing(&mut self, editing: bool) {
+        self.editing = editing;
+        self.history_index = None;
+    }
+
+    pub fn error(&self) -> Option<&String> { self.error.as_ref() }
+    pub fn set_error(&mut self, error: Option<String>) { self.error = error; }
+
+    /// Whether the filter bar is shown below the response.
+    pub fn shown(&self) -> bool { self.editing || !self.expression.is_empty() }
+
+    /// Adds the expression to the history unless it is empty or invalid.
+    pub fn remember(&mut self) {
+        if self.expression.is_empty() || self.error.is_some() {
+            return;
+        }
+
+        self.history.retain(|e| e != &self.expression);
+        self.history.insert(0, self.expression.clone());
+        self.history.truncate(MAX_FILTER_HISTORY);
+    }
+
+    /// Puts the next older filter of the history in the input.
+    pub fn history_back(&mut self) {
+        let index = match self.history_index {
+            Some(i) => i + 1,
+            None => 0,
+        };
+
+        if let Some(expression) = self.history.get(index) {
+            self.expression = expression.clone();
+            self.history_index = Some(index);
+        }
+    }
+
+    /// Puts the next newer filter of the history in the input, clearing it
+    /// after the newest.
+    pub fn history_forward(&mut self) {
+        match self.history_index {
+            Some(0) => {
+                self.expression.clear();
+                self.history_index = None;
+            }
+
+            Some(i) => {
+                self.expression = self.history[i - 1].clone();
+                self.history_index = Some(i - 1);
+            }
+
+            None => {}
+        }
+    }
+}
+
+/// Applies `expression` to `json`, returning the matches as pretty-printed
+/// JSON lines. A single match is shown as is, no or several matches as an
+/// array.
+pub fn filter_json(json: &Value, expression: &str) -> Result<Vec<String>, String> {
+    let matches = select_json(json, expression)?;
+
+    let text = match matches.as_slice() {
+        [value] => serde_json::to_string_pretty(value),
+        _ => serde_json::to_string_pretty(&matches),
+    }.map_err(|e| e.to_string())?;
+
+    Ok(text.split('\n').map(String::from).collect())
+}
+
+/// The values of `json` matched by the JSONPath or jq-like `expression`.
+pub fn select_json<'a>(json: &'a Value, expression: &str)
+    -> Result<Vec<&'a Value>, String> {
+    let path = match expression.trim_start().starts_with('$') {
+        true => String::from(expression.trim()),
+        false => jq_to_json_path(expression)?,
+    };
+
+    // The errors of the JSONPath parser span several lines
+    jsonpath_lib::select(json, &path).map_err(|e| {
+        e.to_string().split_whitespace().collect::<Vec<&str>>().join(" ")
+    })
+}
+
+/// Translates a jq path like `.items[0].name` or `.items[] | .id` to
+/// JSONPath.
+fn jq_to_json_path(expression: &str) -> Result<String, String> {
+    let mut path = String::from("$");
+
+    for filter in expression.split('|').map(str::trim) {
+        if filter.is_empty() {
+            return Err(String::from("Empty filter between pipes"));
+        }
+
+        if filter == "." {
+            continue;
+        }
+
+        let chars: Vec<char> = filter.chars().collect();
+        let mut i = 0;
+
+        while i < chars.len() {
+            match chars[i] {
+                '.' if chars.get(i + 1) == Some(&'[') => { i += 1; }
+
+                '.' if chars.get(i + 1) == Some(&'"') => {
+                    let (name, end) = quoted(&chars, i + 1)?;
+
+                    path.push_str(&bracket_name(&name));
+                    i = end;
+                }
+
+                '.' => {
+                    let start = i + 1;
+                    let mut end = start;
+
+                    while end < chars.len()
+                        && (chars[end].is_alphanumeric() || chars[end] == '_')
+                    {
+                        end += 1;
+                    }
+
+                    if end == start {
+                        return Err(format!("Expected a key after `.` in `{}`", filter));
+                    }
+
+                    // jq reads `.foo-bar` as `.foo - bar`
+                    if chars.get(end) == Some(&'-') {
+                        let key: String = chars[start..].iter()
+                            .take_while(|c| !matches!(c, '.' | '[' | '?' | '|'))
+                            .collect();
+
+                        return Err(format!(
+                            "Write the key `{}` as .\"{}\"", key.trim(), key.trim()
+                        ));
+                    }
+
+                    let name: String = chars[start..end].iter().collect();
+
+                    path.push_str(&bracket_name(&name));
+                    i = end;
+                }
+
+                '[' if chars.get(i + 1) == Some(&'"') => {
+                    let (name, end) = quoted(&chars, i + 1)?;
+
+                    if chars.get(end) != Some(&']') {
+                        return Err(format!("Expected `]` in `{}`", filter));
+                    }
+
+                    path.push_str(&bracket_name(&name));
+                    i = end + 1;
+                }
+
+                '[' => {
+                    let end = chars[i..].iter().position(|c| *c == ']')
+                        .map(|p| i + p)
+                        .ok_or_else(|| format!("Expected `]` in `{}`", filter))?;
+
+                    let inner: String = chars[i + 1..end].iter().collect();
+                    let inner = inner.trim();
+
+                    let valid = inner.split(':')
+                        .all(|n| n.is_empty() || n.parse::<i64>().is_ok());
+
+                    match inner {
+                        "" => { path.push_str("[*]"); }
+                        _ if valid => { path.push_str(&format!("[{}]", inner)); }
+                        _ => {
+                            return Err(format!("Invalid index `{}` in `{}`", inner, filter));
+                        }
+                    }
+
+                    i = end + 1;
+                }
+
+                // `?` suppresses errors in jq, which JSONPath doesn't raise
+                '?' => { i += 1; }
+
+                c => {
+                    return Err(format!(
+                        "Unsupported `{}` in `{}`, jq filters can only be {}",
+                        c, filter, JQ_SYNTAX,
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(path)
+}
+
+/// Reads the string quoted at `start` of `chars`, returning it and the index
+/// after its closing quote.
+fn quoted(chars: &[char], start: usize) -> Result<(String, usize), String> {
+    let mut name = String::new();
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '"' => { return Ok((name, i + 1)); }
+
+            '\\' if i + 1 < chars.len() => {
+                name.push(chars[i + 1]);
+                i += 2;
+            }
+
+            c => {
+                name.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    Err(String::from("Unterminated string"))
+}
+
+/// `name` as a JSONPath member in brackets, e.g. `['content-type']`. The
+/// names aren't escaped, so a name with `'` is put in double quotes.
+fn bracket_name(name: &str) -> String {
+    match name.contains('\'') {
+        true => format!("[\"{}\"]", name),
+        false => format!("['{}']", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jq_paths_translate_to_json_path() {
+        let table = [
+            (".", "$"),
+            (".a", "$['a']"),
+            (".a.b_2", "$['a']['b_2']"),
+            (".\"content-type\"", "$['content-type']"),
+            (".[\"a b\"]", "$['a b']"),
+            (".[\"it's\"]", "$[\"it's\"]"),
+            (".a[0]", "$['a'][0]"),
+            (".a.[0]", "$['a'][0]"),
+            (".a[-1]", "$['a'][-1]"),
+            (".a[]", "$['a'][*]"),
+            (".a[ ]", "$['a'][*]"),
+            (".a[1:3]", "$['a'][1:3]"),
+            (".a[:2]", "$['a'][:2]"),
+            (".a[].id?", "$['a'][*]['id']"),
+            (".a[] | .id", "$['a'][*]['id']"),
+            (". | .a", "$['a']"),
+            (".a.\"x\\\"y\"", "$['a']['x\"y']"),
+        ];
+
+        for (jq, json_path) in table {
+            assert_eq!(jq_to_json_path(jq).as_deref(), Ok(json_path), "{}", jq);
+        }
+    }
+
+    #[test]
+    fn jq_outside_the_subset_is_rejected() {
+        for jq in [
+            "", ".a |", "a", ".a[x]", ".a[0", ".\"a", ".a, .b", "keys",
+            ".a | length", ".a + 1",
+        ] {
+            assert!(jq_to_json_path(jq).is_err(), "{}", jq);
+        }
+    }
+
+    #[test]
+    fn jq_keys_with_a_dash_need_quotes() {
+        assert_eq!(
+            jq_to_json_path(".foo-bar.baz"),
+            Err(String::from("Write the key `foo-bar` as .\"foo-bar\"")),
+        );
+    }
+
+    #[test]
+    fn unsupported_jq_lists_the_syntax() {
+        let error = jq_to_json_path("map(.id)").unwrap_err();
+
+        assert!(error.contains(JQ_SYNTAX), "{}", error);
+    }
+
+    #[test]
+    fn select_json_reads_both_syntaxes() {
+        let json: Value = serde_json::from_str(
+            r#"{"items": [{"id": 1}, {"id": 2}, {"name": "x"}]}"#
+        ).unwrap();
+
+        let ids = |expression| select_json(&json, expression).unwrap()
+            .into_iter().cloned().collect::<Vec<Value>>();
+
+        assert_eq!(ids(".items[].id"), vec![Value::from(1), Value::from(2)]);
+        assert_eq!(ids("$.items[*].id"), vec![Value::from(1), Value::from(2)]);
+        assert_eq!(ids(".items[-1].name"), vec![Value::from("x")]);
+        assert!(ids(".missing?").is_empty());
+    }
+}
@@ -0,0 +1,43 @@
+use std::iter::Iterator;
+
+/// The tabs of the response area.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum ResponseTabs {
+    #[default]
+    Body = 0,
+    Headers = 1,
+    Cookies = 2,
+    Info = 3,
+    Tests = 4,
+}
+
+impl ResponseTabs {
+    pub fn get_str_label(self) -> String {
+        match self {
+            ResponseTabs::Body => String::from("Body"),
+            ResponseTabs::Headers => String::from("Headers"),
+            ResponseTabs::Cookies => String::from("Cookies"),
+            ResponseTabs::Info => String::from("Info"),
+            ResponseTabs::Tests => String::from("Tests"),
+        }
+    }
+
+    pub fn get_val(&self) -> u8 { *self as u8 }
+
+    pub fn from_val(val: u8) -> Self {
+        match val {
+            0 => ResponseTabs::Body,
+            1 => ResponseTabs::Headers,
+            2 => ResponseTabs::Cookies,
+            3 => ResponseTabs::Info,
+            4 => ResponseTabs::Tests,
+            _ => ResponseTabs::Body,
+        }
+    }
+
+    pub fn iter() -> impl Iterator<Item = ResponseTabs> {
+        [
+            Self::Body, Self::Headers, Self::Cookies, Self::Info, Self::Tests,
+        ].iter().copied()
+    }
+}
@@ -0,0 +1,203 @@
+//! State of the runner, which runs the requests of a collection folder.
+
+use std::{
+    path::{ Path, PathBuf },
+    sync::{ Arc, atomic::{ AtomicBool, Ordering }, mpsc::Sender },
+    time::{ Duration, Instant },
+};
+
+use crate::runner::{
+    RequestResult, RunReport, worker::RunEvent,
+};
+
+/// The options of the runner that can be edited.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum RunnerField {
+    /// Path of the CSV or JSON file with the variables of the iterations.
+    #[default]
+    DataFile,
+
+    /// Milliseconds to wait between two requests.
+    Delay,
+
+    StopOnFailure,
+}
+
+impl RunnerField {
+    pub fn next(self) -> Self {
+        match self {
+            Self::DataFile => Self::Delay,
+            Self::Delay => Self::StopOnFailure,
+            Self::StopOnFailure => Self::DataFile,
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        match self {
+            Self::DataFile => Self::StopOnFailure,
+            Self::Delay => Self::DataFile,
+            Self::StopOnFailure => Self::Delay,
+        }
+    }
+}
+
+/// A run that has been started and hasn't finished yet.
+#[derive(Clone)]
+pub struct ActiveRun {
+    id: u64,
+    started: Instant,
+
+    /// Set when the user stops the run. The worker thread checks it before
+    /// every request.
+    cancelled: Arc<AtomicBool>,
+}
+
+#[derive(Clone, Default)]
+pub struct Runner {
+    opened: bool,
+
+    /// The collection folder to run.
+    folder: Option<PathBuf>,
+
+    active_field: RunnerField,
+    data_file: String,
+    delay: String,
+    stop_on_failure: bool,
+
+    /// Sends the results from the worker thread back to the ui loop. `None`
+    /// until the ui loop sets it up.
+    sender: Option<Sender<RunEvent>>,
+
+    /// The run in progress.
+    active: Option<ActiveRun>,
+
+    /// Id given to the next run.
+    next_run_id: u64,
+
+    /// Number of requests the current run sends.
+    total: usize,
+
+    /// Results of the current or last run, in the order they were sent.
+    results: Vec<RequestResult>,
+
+    /// The report of the last run once it has finished.
+    report: Option<RunReport>,
+
+    /// First row of the results shown.
+    scroll_offset: usize,
+
+    /// Whether the results scroll to the last one as they arrive.
+    follow: bool,
+}
+
+impl ActiveRun {
+    pub fn id(&self) -> u64 { self.id }
+
+    /// Time since the run started.
+    pub fn elapsed(&self) -> Duration { self.started.elapsed() }
+
+    pub fn cancelled(&self) -> Arc<AtomicBool> { self.cancelled.clone() }
+    pub fn cancel(&self) { self.cancelled.store(true, Ordering::SeqCst); }
+}
+
+impl Runner {
+    pub fn opened(&self) -> bool { self.opened }
+    pub fn set_opened(&mut self, opened: bool) { self.opened = opened; }
+
+    pub fn folder(&self) -> Option<&Path> { self.folder.as_deref() }
+    /// Sets the folder to run. The results of another folder are cleared.
+    pub fn set_folder(&mut self, folder: PathBuf) {
+        if self.folder.as_ref() != Some(&folder) && self.active.is_none() {
+            self.results.clear();
+            self.report = None;
+            self.total = 0;
+            self.scroll_offset = 0;
+        }
+
+        self.folder = Some(folder);
+    }
+
+    pub fn active_field(&self) -> RunnerField { self.active_field }
+    pub fn set_active_field(&mut self, field: RunnerField) {
+        self.active_field = field;
+    }
+
+    pub fn data_file(&self) -> &str { &self.data_file }
+    pub fn data_file_mut(&mut self) -> &mut String { &mut self.data_file }
+
+    pub fn delay(&self) -> &str { &self.delay }
+    pub fn delay_mut(&mut self) -> &mut String { &mut self.delay }
+
+    pub fn stop_on_failure(&self) -> bool { self.stop_on_failure }
+    pub fn set_stop_on_failure(&mut self, stop_on_failure: bool) {
+        self.stop_on_failure = stop_on_failure;
+    }
+
+    pub fn sender(&self) -> &Option<Sender<RunEvent>> { &self.sender }
+    pub fn set_sender(&mut self, sender: Sender<RunEvent>) {
+        self.sender = Some(sender);
+    }
+
+    pub fn active(&self) -> &Option<ActiveRun> { &self.active }
+    pub fn running(&self) -> bool { self.active.is_some() }
+
+    /// Starts showing a new run of `total` requests. Returns the run.
+    pub fn start(&mut self, total: usize) -> ActiveRun {
+        self.next_run_id += 1;
+
+        let run = ActiveRun {
+            id: self.next_run_id,
+            started: Instant::now(),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        };
+
+        self.active = Some(run.clone());
+        self.total = total;
+        self.results.clear();
+        self.report = None;
+        self.scroll_offset = 0;
+        self.follow = true;
+
+        run
+    }
+
+    /// Ends the current run with its `report`.
+    pub fn finish(&mut self, report: RunReport) {
+        self.active = None;
+        self.results = report.results().clone();
+        self.report = Some(report);
+    }
+
+    pub fn total(&self) -> usize { self.total }
+
+    pub fn results(&self) -> &Vec<RequestResult> { &self.results }
+    pub fn push_result(&mut self, result: RequestResult) {
+        self.results.push(result);
+    }
+
+    pub fn report(&self) -> Option<&RunReport> { self.report.as_ref() }
+
+    pub fn scroll_offset(&self) -> usize { self.scroll_offset }
+
+    /// Scrolls the results by `delta` rows. Scrolling up stops following the
+    /// results as they arrive.
+    pub fn scroll(&mut self, delta: i32) {
+        self.scroll_offset = (self.scroll_offset as i32 + delta).max(0) as usize;
+
+        if delta < 0 {
+            self.follow = false;
+        }
+    }
+
+    /// Keeps the scroll offset within the `row_count` rows of the results,
+    /// `height` of which are shown. Scrolling down to the last row follows
+    /// the results again.
+    pub fn clamp_scroll(&mut self, row_count: usize, height: usize) {
+        let max = row_count.saturating_sub(height);
+
+        if self.follow || self.scroll_offset >= max {
+            self.scroll_offset = max;
+            self.follow = true;
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! State of the "Scripts" tab: the pre-request and post-response scripts of
+//! the request.
+
+use std::slice::Iter;
+
+use super::text_edit::TextEditState;
+
+/// When a script runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ScriptKind {
+    /// Runs before the request is built and can change it.
+    #[default]
+    PreRequest,
+
+    /// Runs after the response is received and can check it.
+    PostResponse,
+}
+
+impl ScriptKind {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Self::PreRequest => "Pre-request",
+            Self::PostResponse => "Post-response",
+        }
+    }
+
+    pub fn iter() -> Iter<'static, ScriptKind> {
+        static SCRIPT_KINDS: [ScriptKind; 2] = [
+            ScriptKind::PreRequest,
+            ScriptKind::PostResponse,
+        ];
+
+        SCRIPT_KINDS.iter()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Scripts {
+    /// The script editor that has the focus.
+    active: ScriptKind,
+    pre_request: TextEditState,
+    post_response: TextEditState,
+}
+
+impl Scripts {
+    pub fn active(&self) -> ScriptKind { self.active }
+    pub fn set_active(&mut self, active: ScriptKind) { self.active = active; }
+
+    /// The editor of the `kind` script.
+    pub fn editor(&self, kind: ScriptKind) -> &TextEditState {
+        match kind {
+            ScriptKind::PreRequest => &self.pre_request,
+            ScriptKind::PostResponse => &self.post_response,
+        }
+    }
+    pub fn editor_mut(&mut self, kind: ScriptKind) -> &mut TextEditState {
+        match kind {
+            ScriptKind::PreRequest => &mut self.pre_request,
+            ScriptKind::PostResponse => &mut self.post_response,
+        }
+    }
+
+    /// The text of the `kind` script.
+    pub fn script(&self, kind: ScriptKind) -> String {
+        self.editor(kind).text()
+    }
+
+    /// Whether the `kind` script has anything to run.
+    pub fn has_script(&self, kind: ScriptKind) -> bool {
+        !self.script(kind).trim().is_empty()
+    }
+}
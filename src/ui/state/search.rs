@@ -0,0 +1,373 @@
+//! State of the search in the response and in the body editor.
+
+use regex::{ Regex, RegexBuilder };
+
+/// The text being searched.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum SearchTarget {
+    /// The body of the response, filtered if a filter is applied.
+    #[default]
+    Response,
+
+    /// The text of the body editor.
+    Body,
+}
+
+/// A match on a line. The positions are characters of the line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Clone, Default)]
+pub struct Search {
+    /// Whether the search bar is shown. It has the focus while it is shown.
+    opened: bool,
+    target: SearchTarget,
+    query: String,
+    replacement: String,
+
+    /// Whether the replacement input is shown, only for the body editor.
+    replacing: bool,
+
+    /// Whether the replacement input has the focus rather than the query.
+    replacement_focused: bool,
+    case_sensitive: bool,
+
+    /// Whether the query is a regular expression.
+    regex: bool,
+
+    /// The matches in the order of the text.
+    matches: Vec<SearchMatch>,
+
+    /// Index of the match that is jumped to.
+    current: usize,
+
+    /// Why the query isn't a valid regular expression.
+    error: Option<String>,
+}
+
+impl Search {
+    pub fn opened(&self) -> bool { self.opened }
+    /// Shows the search bar for `target`, with the replacement input if
+    /// `replacing`.
+    pub fn open(&mut self, target: SearchTarget, replacing: bool) {
+        if self.target != target {
+            self.matches.clear();
+        }
+
+        self.opened = true;
+        self.target = target;
+        self.replacing = replacing && target == SearchTarget::Body;
+        self.replacement_focused = false;
+    }
+
+    /// Hides the search bar and the highlighting of the matches.
+    pub fn close(&mut self) {
+        self.opened = false;
+        self.matches.clear();
+    }
+
+    pub fn target(&self) -> SearchTarget { self.target }
+
+    pub fn query(&self) -> &str { &self.query }
+    pub fn replacement(&self) -> &str { &self.replacement }
+
+    /// Text of the input that has the focus.
+    pub fn input_mut(&mut self) -> &mut String {
+        match self.replacement_focused {
+            true => &mut self.replacement,
+            false => &mut self.query,
+        }
+    }
+
+    pub fn replacing(&self) -> bool { self.replacing }
+    pub fn replacement_focused(&self) -> bool { self.replacement_focused }
+    /// Moves the focus between the query and the replacement.
+    pub fn toggle_focus(&mut self) {
+        self.replacement_focused = self.replacing && !self.replacement_focused;
+    }
+
+    pub fn case_sensitive(&self) -> bool { self.case_sensitive }
+    pub fn toggle_case_sensitive(&mut self) {
+        self.case_sensitive = !self.case_sensitive;
+    }
+
+    pub fn regex(&self) -> bool { self.regex }
+    pub fn toggle_regex(&mut self) { self.regex = !self.regex; }
+
+    pub fn matches(&self) -> &Vec<SearchMatch> { &self.matches }
+    pub fn current(&self) -> usize { self.current }
+    pub fn error(&self) -> Option<&String> { self.error.as_ref() }
+
+    /// The match that is jumped to.
+    pub fn current_match(&self) -> Option<SearchMatch> {
+        self.matches.get(self.current).copied()
+    }
+
+    /// The query as a regular expression. `None` if the query is empty.
+    pub fn pattern(&self) -> Result<Option<Regex>, String> {
+        if self.query.is_empty() {
+            return Ok(None);
+        }
+
+        let pattern = match self.regex {
+            true => self.query.clone(),
+            false => regex::escape(&self.query),
+        };
+
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+            .map(Some)
+            .map_err(|e| match e {
+                regex::Error::Syntax(_) => String::from("Invalid regex"),
+                e => e.to_string(),
+            })
+    }
+
+    /// Finds the matches in `lines`, making the first match at or after
+    /// `from` (line, character) the current one.
+    pub fn find(&mut self, lines: &[String], from: (usize, usize)) {
+        self.matches.clear();
+        self.current = 0;
+
+        let pattern = match self.pattern() {
+            Ok(pattern) => {
+                self.error = None;
+                pattern
+            }
+
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        };
+
+        let Some(pattern) = pattern else { return; };
+
+        for (i, line) in lines.iter().enumerate() {
+            for found in pattern.find_iter(line) {
+                // Empty matches can't be highlighted
+                if found.start() == found.end() {
+                    continue;
+                }
+
+                self.matches.push(SearchMatch {
+                    line: i,
+                    start: line[..found.start()].chars().count(),
+                    end: line[..found.end()].chars().count(),
+                });
+            }
+        }
+
+        self.current = self.matches.iter()
+            .position(|m| (m.line, m.start) >= from)
+            .unwrap_or_default();
+    }
+
+    /// Makes the next match the current one, going back to the first after
+    /// the last.
+    pub fn next(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+        }
+    }
+
+    /// Makes the previous match the current one, going to the last before
+    /// the first.
+    pub fn previous(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = match self.current {
+                0 => self.matches.len() - 1,
+                n => n - 1,
+            };
+        }
+    }
+
+    /// `lines` with the current match replaced. The replacement of a regular
+    /// expression can refer to its groups, e.g. `$1`.
+    pub fn replace_current(&self, lines: &[String]) -> Option<Vec<String>> {
+        let found = self.current_match()?;
+        let pattern = self.pattern().ok()??;
+
+        let mut lines = lines.to_vec();
+        let line = lines.get(found.line)?;
+
+        let start = char_to_byte(line, found.start);
+        let end = char_to_byte(line, found.end);
+        let replaced = pattern.replace(&line[start..end], self.expansion());
+
+        lines[found.line] = format!("{}{}{}", &line[..start], replaced, &line[end..]);
+
+        Some(lines)
+    }
+
+    /// `lines` with all matches replaced.
+    pub fn replace_all(&self, lines: &[String]) -> Option<Vec<String>> {
+        let pattern = self.pattern().ok()??;
+
+        Some(lines.iter()
+            .map(|line| pattern.replace_all(line, self.expansion()).to_string())
+            .collect())
+    }
+
+    /// The replacement as given to the regex. Without the regex toggle, `$`
+    /// is taken literally.
+    fn expansion(&self) -> String {
+        match self.regex {
+            true => self.replacement.clone(),
+            false => self.replacement.replace('$', "$$"),
+        }
+    }
+}
+
+/// Byte index of the character `index` of `line`.
+fn char_to_byte(line: &str, index: usize) -> usize {
+    line.char_indices().nth(index).map(|(i, _)| i).unwrap_or(line.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split('\n').map(String::from).collect()
+    }
+
+    fn search(query: &str) -> Search {
+        let mut search = Search::default();
+
+        search.open(SearchTarget::Body, true);
+        search.input_mut().push_str(query);
+        search
+    }
+
+    fn found(line: usize, start: usize, end: usize) -> SearchMatch {
+        SearchMatch { line, start, end }
+    }
+
+    #[test]
+    fn find_ignores_case_unless_asked() {
+        let text = lines("Foo foo\nFOO");
+        let mut search = search("foo");
+
+        search.find(&text, (0, 0));
+
+        assert_eq!(
+            search.matches(),
+            &vec![found(0, 0, 3), found(0, 4, 7), found(1, 0, 3)],
+        );
+
+        search.toggle_case_sensitive();
+        search.find(&text, (0, 0));
+
+        assert_eq!(search.matches(), &vec![found(0, 4, 7)]);
+    }
+
+    #[test]
+    fn find_counts_characters_not_bytes() {
+        let mut search = search("ü");
+
+        search.find(&lines("aéü ü"), (0, 0));
+
+        assert_eq!(search.matches(), &vec![found(0, 2, 3), found(0, 4, 5)]);
+    }
+
+    #[test]
+    fn find_escapes_the_query_unless_it_is_a_regex() {
+        let text = lines("a.c abc");
+        let mut search = search("a.c");
+
+        search.find(&text, (0, 0));
+        assert_eq!(search.matches().len(), 1);
+
+        search.toggle_regex();
+        search.find(&text, (0, 0));
+        assert_eq!(search.matches().len(), 2);
+    }
+
+    #[test]
+    fn find_skips_empty_matches_and_reports_invalid_regexes() {
+        let mut search = search("x*");
+
+        search.toggle_regex();
+        search.find(&lines("abc xx"), (0, 0));
+
+        assert_eq!(search.matches(), &vec![found(0, 4, 6)]);
+        assert_eq!(search.error(), None);
+
+        search.input_mut().push('(');
+        search.find(&lines("abc"), (0, 0));
+
+        assert!(search.matches().is_empty());
+        assert_eq!(search.error().map(String::as_str), Some("Invalid regex"));
+    }
+
+    #[test]
+    fn find_starts_at_the_first_match_from_the_cursor() {
+        let text = lines("a a\na");
+        let mut search = search("a");
+
+        search.find(&text, (0, 1));
+        assert_eq!(search.current_match(), Some(found(0, 2, 3)));
+
+        search.find(&text, (1, 1));
+        assert_eq!(search.current_match(), Some(found(0, 0, 1)));
+    }
+
+    #[test]
+    fn next_and_previous_wrap_around() {
+        let mut search = search("a");
+
+        search.find(&lines("aaa"), (0, 0));
+        search.previous();
+        assert_eq!(search.current(), 2);
+
+        search.next();
+        assert_eq!(search.current(), 0);
+
+        let mut empty = Search::default();
+
+        empty.next();
+        empty.previous();
+        assert_eq!(empty.current(), 0);
+    }
+
+    #[test]
+    fn replace_current_replaces_only_the_current_match() {
+        let text = lines("cat cat\ncat");
+        let mut search = search("cat");
+
+        search.find(&text, (0, 1));
+        search.toggle_focus();
+        search.input_mut().push_str("dog$1");
+
+        assert_eq!(search.replace_current(&text), Some(lines("cat dog$1\ncat")));
+    }
+
+    #[test]
+    fn replace_all_expands_groups_of_regexes() {
+        let text = lines("id=1\nid=22");
+        let mut search = search(r"id=(\d+)");
+
+        search.toggle_regex();
+        search.toggle_focus();
+        search.input_mut().push_str("${1}_id");
+
+        assert_eq!(search.replace_all(&text), Some(lines("1_id\n22_id")));
+    }
+
+    #[test]
+    fn the_replacement_is_only_for_the_body() {
+        let mut search = Search::default();
+
+        search.open(SearchTarget::Response, true);
+        search.toggle_focus();
+
+        assert!(!search.replacing());
+        assert!(!search.replacement_focused());
+    }
+}
@@ -0,0 +1,235 @@
+//! State of the "Settings" request tab, which overrides the global client
+//! settings for the request.
+
+use std::{ error::Error, path::PathBuf, slice::Iter };
+
+use crate::settings::{ ClientSettings, DEFAULT_SETTINGS_FILE, load_settings };
+
+/// Number of options displayed at once in the "Verify TLS" select.
+pub const SETTINGS_SELECT_DISP_LEN: u8 = 3;
+
+/// Labels of the text fields, in the order of their indexes.
+pub const SETTINGS_FIELDS: [&str; 5] = [
+    "Timeout (s)", "Max Redirects", "Proxy", "No Proxy", "CA Bundle",
+];
+
+/// The UI elements in the settings tab.
+#[derive(Clone, PartialEq)]
+pub enum SettingsUIElement {
+    /// The "Verify TLS" select widget.
+    /// params
+    /// - bool - whether the Select widget is open
+    VerifyTls(bool),
+
+    /// A text field, e.g. `Field(2)` is the proxy, see [SETTINGS_FIELDS].
+    Field(u8),
+}
+
+/// Whether the certificates of the servers are checked.
+#[derive(Clone, Default, PartialEq)]
+pub enum TlsVerification {
+    /// As the global settings say.
+    #[default]
+    Global,
+    Verify,
+    Skip,
+}
+
+#[derive(Clone)]
+pub struct Settings {
+    /// The settings of `settings.toml`.
+    global: ClientSettings,
+    file: PathBuf,
+
+    active_settings_element: SettingsUIElement,
+
+    /// Text of the fields, in the order of [SETTINGS_FIELDS]. Empty fields
+    /// use the global settings.
+    fields: [String; 5],
+
+    tls_verification: TlsVerification,
+
+    /// The index of the element selected in the "Verify TLS" select.
+    tls_verification_sel_index: u8,
+    tls_verification_options: Vec<String>,
+}
+
+impl Default for SettingsUIElement {
+    fn default() -> Self { Self::Field(0) }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            global: ClientSettings::default(),
+            file: PathBuf::from(DEFAULT_SETTINGS_FILE),
+            active_settings_element: SettingsUIElement::default(),
+            fields: Default::default(),
+            tls_verification: TlsVerification::default(),
+            tls_verification_sel_index: 0,
+            tls_verification_options: TlsVerification::as_string_vec(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn global(&self) -> &ClientSettings { &self.global }
+    pub fn set_global(&mut self, global: ClientSettings) {
+        self.global = global;
+    }
+
+    /// Reads the global settings again from their file.
+    pub fn reload(&mut self) -> Result<(), Box<dyn Error>> {
+        self.global = load_settings(&self.file)
+            .map_err(|e| format!("{}: {}", self.file.display(), e))?;
+
+        Ok(())
+    }
+
+    pub fn active_settings_element(&self) -> &SettingsUIElement {
+        &self.active_settings_element
+    }
+    pub fn set_active_settings_element(&mut self, element: SettingsUIElement) {
+        self.active_settings_element = element;
+    }
+
+    pub fn tls_verification(&self) -> &TlsVerification {
+        &self.tls_verification
+    }
+    pub fn set_tls_verification(&mut self, verification: TlsVerification) {
+        self.tls_verification_sel_index = TlsVerification::iter()
+            .position(|v| *v == verification)
+            .unwrap_or(0) as u8;
+
+        self.tls_verification = verification;
+    }
+
+    pub fn tls_verification_sel_index(&self) -> u8 {
+        self.tls_verification_sel_index
+    }
+    pub fn set_tls_verification_sel_index(&mut self, index: u8) {
+        self.tls_verification_sel_index = index;
+    }
+
+    pub fn tls_verification_options(&self) -> Vec<String> {
+        self.tls_verification_options.clone()
+    }
+
+    /// Returns the UI elements in navigation order.
+    pub fn elements(&self) -> Vec<SettingsUIElement> {
+        vec![
+            SettingsUIElement::Field(0),
+            SettingsUIElement::Field(1),
+            SettingsUIElement::VerifyTls(false),
+            SettingsUIElement::Field(2),
+            SettingsUIElement::Field(3),
+            SettingsUIElement::Field(4),
+        ]
+    }
+
+    /// Position of the active element in [Settings::elements].
+    fn active_element_index(&self) -> usize {
+        let active = match self.active_settings_element {
+            SettingsUIElement::VerifyTls(_) => SettingsUIElement::VerifyTls(false),
+            SettingsUIElement::Field(n) => SettingsUIElement::Field(n),
+        };
+
+        self.elements().iter().position(|e| *e == active).unwrap_or(0)
+    }
+
+    /// Activates the next element.
+    pub fn activate_next_element(&mut self) {
+        let elements = self.elements();
+        let next = self.active_element_index() + 1;
+
+        if next < elements.len() {
+            self.active_settings_element = elements[next].clone();
+        }
+    }
+
+    /// Activates the previous element.
+    pub fn activate_previous_element(&mut self) {
+        let current = self.active_element_index();
+
+        if current > 0 {
+            self.active_settings_element = self.elements()[current - 1].clone();
+        }
+    }
+
+    /// Returns the text of field `field`.
+    pub fn field(&self, field: u8) -> Option<String> {
+        self.fields.get(field as usize).cloned()
+    }
+
+    /// Returns a mutable reference to the text of field `field`.
+    pub fn field_mut(&mut self, field: u8) -> Option<&mut String> {
+        self.fields.get_mut(field as usize)
+    }
+
+    pub fn fields(&self) -> &[String; 5] { &self.fields }
+    pub fn set_fields(&mut self, fields: [String; 5]) { self.fields = fields; }
+
+    /// The settings of the request, with the `{{variables}}` of the fields
+    /// resolved by `var`. Fails on a timeout or redirect count that isn't a
+    /// number.
+    pub fn overrides(&self, var: impl Fn(String) -> String)
+        -> Result<ClientSettings, Box<dyn Error>> {
+        let [timeout, redirects, proxy, no_proxy, ca_bundle] = self.fields.clone()
+            .map(|field| Some(var(field).trim().to_string()).filter(|f| !f.is_empty()));
+
+        let timeout = timeout
+            .map(|t| t.parse::<u64>().map_err(|_| format!("invalid timeout {}", t)))
+            .transpose()?;
+
+        let redirects = redirects
+            .map(|r| r.parse::<usize>().map_err(|_| format!("invalid max redirects {}", r)))
+            .transpose()?;
+
+        let verify_tls = match self.tls_verification {
+            TlsVerification::Global => None,
+            TlsVerification::Verify => Some(true),
+            TlsVerification::Skip => Some(false),
+        };
+
+        Ok(ClientSettings::new(timeout, redirects, proxy, no_proxy, verify_tls, ca_bundle))
+    }
+
+    /// The settings the request is sent with: the overrides of the tab over
+    /// the global settings.
+    pub fn effective(&self, var: impl Fn(String) -> String)
+        -> Result<ClientSettings, Box<dyn Error>> {
+        Ok(self.overrides(var)?.merged(&self.global))
+    }
+}
+
+impl TlsVerification {
+    pub fn to_str(&self) -> &str {
+        match self {
+            TlsVerification::Global => "Global",
+            TlsVerification::Verify => "Verify",
+            TlsVerification::Skip => "Don't Verify",
+        }
+    }
+
+    pub fn from_str(input: &str) -> Self {
+        match input {
+            "Verify" => TlsVerification::Verify,
+            "Don't Verify" => TlsVerification::Skip,
+            _ => TlsVerification::Global,
+        }
+    }
+
+    pub fn iter() -> Iter<'static, TlsVerification> {
+        static TLS_VERIFICATIONS: [TlsVerification; 3] = [
+            TlsVerification::Global,
+            TlsVerification::Verify,
+            TlsVerification::Skip,
+        ];
+
+        TLS_VERIFICATIONS.iter()
+    }
+
+    pub fn as_string_vec() -> Vec<String> {
+        Self::iter().map(|v| String::from(v.to_str())).collect()
+    }
+}
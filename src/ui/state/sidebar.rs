@@ -0,0 +1,240 @@
+//! State of the collection sidebar.
+
+use std::{ collections::HashSet, path::{ Path, PathBuf } };
+
+use crate::collection::{ CollectionEntry, load_tree, DEFAULT_COLLECTION_DIR };
+
+/// A row of the sidebar: a folder or a request of the collection tree.
+#[derive(Clone)]
+pub struct SidebarItem {
+    name: String,
+    path: PathBuf,
+
+    /// Nesting level, `0` for the entries directly in the collection root.
+    depth: u16,
+    is_folder: bool,
+}
+
+/// The action the sidebar prompt is asking input for.
+#[derive(Clone, PartialEq)]
+pub enum SidebarPromptKind {
+    /// Name (which can contain `/` for folders) to save the request as.
+    SaveAs,
+
+    /// New name of the selected entry.
+    Rename,
+
+    /// Confirmation (`y`) to delete the selected entry.
+    Delete,
+}
+
+#[derive(Clone)]
+pub struct SidebarPrompt {
+    kind: SidebarPromptKind,
+    input: String,
+}
+
+#[derive(Clone)]
+pub struct Sidebar {
+    visible: bool,
+
+    /// Whether the key presses go to the sidebar.
+    focused: bool,
+
+    /// The folder the collection is read from.
+    root: PathBuf,
+
+    /// The visible rows, i.e. the tree flattened with collapsed folders
+    /// left out.
+    items: Vec<SidebarItem>,
+
+    /// Folders that are expanded.
+    expanded: HashSet<PathBuf>,
+
+    /// Index of `items` that is selected.
+    sel_index: usize,
+    scroll_offset: usize,
+
+    /// Number of rows that fit in the sidebar. Set while rendering.
+    content_height: u16,
+    prompt: Option<SidebarPrompt>,
+}
+
+impl Default for Sidebar {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            focused: false,
+            root: PathBuf::from(DEFAULT_COLLECTION_DIR),
+            items: vec![],
+            expanded: HashSet::new(),
+            sel_index: 0,
+            scroll_offset: 0,
+            content_height: 0,
+            prompt: None,
+        }
+    }
+}
+
+impl SidebarItem {
+    pub fn name(&self) -> &str { &self.name }
+    pub fn path(&self) -> &Path { &self.path }
+    pub fn depth(&self) -> u16 { self.depth }
+    pub fn is_folder(&self) -> bool { self.is_folder }
+}
+
+impl SidebarPrompt {
+    pub fn new(kind: SidebarPromptKind, input: String) -> Self {
+        Self { kind, input }
+    }
+
+    pub fn kind(&self) -> &SidebarPromptKind { &self.kind }
+
+    pub fn input(&self) -> &str { &self.input }
+    pub fn input_mut(&mut self) -> &mut String { &mut self.input }
+}
+
+impl Sidebar {
+    pub fn visible(&self) -> bool { self.visible }
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+
+        if !visible {
+            self.focused = false;
+            self.prompt = None;
+        }
+    }
+
+    pub fn focused(&self) -> bool { self.focused }
+    pub fn set_focused(&mut self, focused: bool) { self.focused = focused; }
+
+    pub fn root(&self) -> &Path { &self.root }
+
+    pub fn items(&self) -> &Vec<SidebarItem> { &self.items }
+
+    pub fn sel_index(&self) -> usize { self.sel_index }
+
+    /// The selected row, `None` when the collection is empty.
+    pub fn selected(&self) -> Option<&SidebarItem> {
+        self.items.get(self.sel_index)
+    }
+
+    pub fn scroll_offset(&self) -> usize { self.scroll_offset }
+
+    pub fn set_content_height(&mut self, content_height: u16) {
+        self.content_height = content_height;
+    }
+
+    pub fn prompt(&self) -> &Option<SidebarPrompt> { &self.prompt }
+    pub fn prompt_mut(&mut self) -> &mut Option<SidebarPrompt> {
+        &mut self.prompt
+    }
+    pub fn set_prompt(&mut self, prompt: Option<SidebarPrompt>) {
+        self.prompt = prompt;
+    }
+
+    /// Re-reads the collection from disk, keeping the selection on the same
+    /// path when it still exists.
+    pub fn reload(&mut self) -> std::io::Result<()> {
+        let selected = self.selected().map(|item| item.path.clone());
+        let tree = load_tree(&self.root)?;
+
+        self.items = vec![];
+        self.flatten(&tree, 0);
+
+        self.sel_index = selected
+            .and_then(|path| self.items.iter().position(|i| i.path == path))
+            .unwrap_or(0)
+            .min(self.items.len().saturating_sub(1));
+
+        self.move_selection(0);
+
+        Ok(())
+    }
+
+    fn flatten(&mut self, entries: &[CollectionEntry], depth: u16) {
+        for entry in entries {
+            match entry {
+                CollectionEntry::Folder { name, path, children } => {
+                    self.items.push(SidebarItem {
+                        name: name.clone(),
+                        path: path.clone(),
+                        depth,
+                        is_folder: true,
+                    });
+
+                    if self.expanded.contains(path) {
+                        self.flatten(children, depth + 1);
+                    }
+                }
+
+                CollectionEntry::Request { name, path } => {
+                    self.items.push(SidebarItem {
+                        name: name.clone(),
+                        path: path.clone(),
+                        depth,
+                        is_folder: false,
+                    });
+                }
+            }
+        }
+    }
+
+    pub fn is_expanded(&self, path: &Path) -> bool {
+        self.expanded.contains(path)
+    }
+
+    /// Expands or collapses the folder at `path`.
+    pub fn set_expanded(&mut self, path: &Path, expanded: bool) {
+        if expanded {
+            self.expanded.insert(path.to_path_buf());
+        } else {
+            self.expanded.remove(path);
+        }
+    }
+
+    /// Moves the selection by `delta` rows, scrolling to keep it visible.
+    pub fn move_selection(&mut self, delta: i32) {
+        if self.items.is_empty() {
+            return;
+        }
+
+        let height = self.content_height as usize;
+
+        let max = (self.items.len() - 1) as i32;
+        self.sel_index = (self.sel_index as i32 + delta).clamp(0, max) as usize;
+
+        if self.sel_index < self.scroll_offset {
+            self.scroll_offset = self.sel_index;
+        } else if height > 0 && self.sel_index >= self.scroll_offset + height {
+            self.scroll_offset = self.sel_index + 1 - height;
+        }
+    }
+
+    /// Selects the row of `path`, if it is visible.
+    pub fn select_path(&mut self, path: &Path) {
+        if let Some(index) = self.items.iter().position(|i| i.path == path) {
+            self.sel_index = index;
+        }
+    }
+
+    /// The collection folder the selected row is in (or is), relative to the
+    /// root and ending with `/`. Empty for the root.
+    pub fn selected_folder_name(&self) -> String {
+        let folder = match self.selected() {
+            Some(item) if item.is_folder => item.path.clone(),
+            Some(item) => match item.path.parent() {
+                Some(parent) => parent.to_path_buf(),
+                None => { return String::default(); }
+            },
+            None => { return String::default(); }
+        };
+
+        match folder.strip_prefix(&self.root) {
+            Ok(relative) if !relative.as_os_str().is_empty() => {
+                format!("{}/", relative.to_string_lossy())
+            }
+            _ => String::default(),
+        }
+    }
+}
@@ -48,6 +48,16 @@ impl TextEditState {
     pub fn text_vec(&self) -> Vec<String> { self.text.clone() }
     pub fn set_text(&mut self, text: Vec<String>) { self.text = text; }
 
+    /// Replaces the whole text, moving the cursor to the start.
+    pub fn load_text(&mut self, text: &str) {
+        self.text = text.replace('\r', "").split('\n')
+            .map(str::to_owned).collect();
+        self.cursor_pos = 0;
+        self.line_number = 0;
+        self.scroll_offset = 0;
+        self.reset_selection();
+    }
+
     pub fn cursor_pos(&self) -> u16 { self.cursor_pos.clone() }
     pub fn set_cursor_pos(&mut self, cursor_pos: u16) {
         self.cursor_pos = cursor_pos;
@@ -390,6 +400,17 @@ impl TextEditState {
         self.scroll_offset = scroll_offset;
     }
 
+    /// Scrolls just enough for the line with the cursor to be visible.
+    pub fn scroll_to_cursor(&mut self) {
+        if self.line_number < self.scroll_offset {
+            self.scroll_offset = self.line_number;
+        } else if self.content_height > 0
+            && self.line_number >= self.scroll_offset + self.content_height
+        {
+            self.scroll_offset = self.line_number + 1 - self.content_height;
+        }
+    }
+
     pub fn content_height(&self) -> u16 { self.content_height.clone() }
     pub fn set_content_height(&mut self, content_height: u16) {
         self.content_height = content_height;
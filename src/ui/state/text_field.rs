@@ -0,0 +1,342 @@
+//! The text fields of the ui, for the features that work the same on all of
+//! them: the VIM editor mode and the undo history.
+
+use crate::ui::state::{
+    UiState, UIElement, EditorMode, request_tabs::RequestTabs,
+    body::{ BodyUIElement, BodyContent }, auth::AuthUIElement,
+    text_edit::TextEditState, scripts::ScriptKind,
+    settings::SettingsUIElement,
+};
+
+/// A text field that can be edited.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TextField {
+    Url,
+
+    /// A cell of the "URL Params" tab.
+    UrlParam { row: u16, col: u8 },
+
+    /// A cell of the "Headers" tab.
+    Header { row: u16, col: u8 },
+
+    /// A cell of the form bodies.
+    BodyKV { row: u16, col: u8 },
+
+    /// The text area of the raw bodies, the query editor of the GraphQL
+    /// body.
+    BodyText,
+
+    /// The variables editor of the GraphQL body.
+    BodyVariables,
+
+    /// A field of the "Authorization" tab, see [Auth::field](super::auth::Auth::field).
+    Auth(u8),
+
+    /// A script editor of the "Scripts" tab.
+    Script(ScriptKind),
+
+    /// The editor of the "Assertions" tab.
+    Assertions,
+
+    /// A field of the "Settings" tab, see [SETTINGS_FIELDS](super::settings::SETTINGS_FIELDS).
+    Setting(u8),
+}
+
+impl TextField {
+    /// Whether the field is a text area with several lines.
+    pub fn multi_line(&self) -> bool {
+        matches!(
+            self,
+            Self::BodyText | Self::BodyVariables | Self::Script(_) | Self::Assertions
+        )
+    }
+}
+
+/// Text, cursor and selection of a text field.
+#[derive(Clone, PartialEq)]
+pub struct FieldState {
+    /// The single line fields have one line.
+    pub lines: Vec<String>,
+
+    /// (line, character) position of the cursor.
+    pub cursor: (usize, usize),
+
+    /// Start and end (line, character) positions of the selection in the
+    /// body text area.
+    pub selection: Option<((u16, u16), (u16, u16))>,
+}
+
+/// The text field being edited, `None` if the active element isn't a text
+/// field.
+pub fn active_text_field(uistate: &UiState) -> Option<TextField> {
+    match uistate.active_element() {
+        UIElement::URL => Some(TextField::Url),
+
+        UIElement::RequestTabsElem => match uistate.active_request_tab() {
+            RequestTabs::UrlParams => {
+                let params = uistate.query_params_ui();
+                let (row, col) = (params.active_row(), params.active_col());
+
+                (col < 2).then_some(TextField::UrlParam { row, col })
+            }
+
+            RequestTabs::Headers => {
+                let headers = uistate.request_headers_ui();
+                let (row, col) = (headers.active_row(), headers.active_col());
+
+                (col < 2).then_some(TextField::Header { row, col })
+            }
+
+            RequestTabs::Body => {
+                let body = uistate.body();
+                let row = body.kv_tab_state().active_row();
+                let col = body.kv_tab_state().active_col();
+
+                match (body.active_body_element(), body.body_content()) {
+                    (BodyUIElement::TextArea, BodyContent::Text)
+                        | (BodyUIElement::TextArea, BodyContent::Json)
+                        | (BodyUIElement::TextArea, BodyContent::Html)
+                        | (BodyUIElement::TextArea, BodyContent::Xml)
+                        | (BodyUIElement::TextArea, BodyContent::GraphQL) => {
+                        Some(TextField::BodyText)
+                    }
+
+                    (BodyUIElement::Variables, BodyContent::GraphQL) => {
+                        Some(TextField::BodyVariables)
+                    }
+
+                    (BodyUIElement::KVArea, BodyContent::FormData) => {
+                        matches!(col, 0 | 1 | 3 | 4)
+                            .then_some(TextField::BodyKV { row, col })
+                    }
+
+                    (BodyUIElement::KVArea, BodyContent::FormURLEncoded) => {
+                        (col < 2).then_some(TextField::BodyKV { row, col })
+                    }
+
+                    _ => None,
+                }
+            }
+
+            RequestTabs::Authorization => {
+                match uistate.auth().active_auth_element() {
+                    AuthUIElement::Field(field) => uistate.auth()
+                        .field(*field)
+                        .map(|_| TextField::Auth(*field)),
+
+                    _ => None,
+                }
+            }
+
+            RequestTabs::Scripts => {
+                Some(TextField::Script(uistate.scripts().active()))
+            }
+
+            RequestTabs::Assertions => Some(TextField::Assertions),
+
+            RequestTabs::Settings => {
+                match uistate.settings().active_settings_element() {
+                    SettingsUIElement::Field(field) => uistate.settings()
+                        .field(*field)
+                        .map(|_| TextField::Setting(*field)),
+
+                    _ => None,
+                }
+            }
+        },
+
+        _ => None,
+    }
+}
+
+/// Lines of the text in `field`.
+pub fn field_text(uistate: &UiState, field: TextField) -> Vec<String> {
+    let text = match field {
+        TextField::Url => uistate.url(),
+
+        TextField::UrlParam { row, col } => uistate.url_deconst()
+            .query_params().get(row as usize)
+            .map(|param| param.field(col, false))
+            .unwrap_or_default(),
+
+        TextField::Header { row, col } => uistate.request_headers()
+            .get(row as usize)
+            .map(|header| header.field(col, false))
+            .unwrap_or_default(),
+
+        TextField::BodyKV { row, col } => {
+            let is_form_data =
+                *uistate.body().body_content() == BodyContent::FormData;
+
+            uistate.body().kv_data().get(row as usize)
+                .map(|kv_data| kv_data.field(col, is_form_data))
+                .unwrap_or_default()
+        }
+
+        TextField::BodyText | TextField::BodyVariables | TextField::Script(_)
+            | TextField::Assertions => {
+            return text_edit(uistate, field)
+                .map(TextEditState::text_vec)
+                .unwrap_or_default();
+        }
+
+        TextField::Auth(field) => uistate.auth().field(field).unwrap_or_default(),
+
+        TextField::Setting(field) => {
+            uistate.settings().field(field).unwrap_or_default()
+        }
+    };
+
+    vec![text]
+}
+
+/// The editor of a multi-line `field`.
+pub fn text_edit(uistate: &UiState, field: TextField) -> Option<&TextEditState> {
+    match field {
+        TextField::BodyText => Some(uistate.body().text_data()),
+        TextField::BodyVariables => Some(uistate.body().variables()),
+        TextField::Script(kind) => Some(uistate.scripts().editor(kind)),
+        TextField::Assertions => Some(uistate.assertions()),
+        _ => None,
+    }
+}
+
+pub fn text_edit_mut(uistate: &mut UiState, field: TextField)
+    -> Option<&mut TextEditState> {
+    match field {
+        TextField::BodyText => Some(uistate.body_mut().text_data_mut()),
+        TextField::BodyVariables => Some(uistate.body_mut().variables_mut()),
+        TextField::Script(kind) => Some(uistate.scripts_mut().editor_mut(kind)),
+        TextField::Assertions => Some(uistate.assertions_mut()),
+        _ => None,
+    }
+}
+
+/// Reads the text, cursor and selection of `field`. The single line fields
+/// other than the url keep their cursor only in the VIM editor mode, it's at
+/// the end of the text otherwise.
+pub fn field_state(uistate: &UiState, field: TextField) -> FieldState {
+    let lines = field_text(uistate, field);
+
+    let (cursor, selection) = match field {
+        TextField::Url => ((0, uistate.url_cursor_offset() as usize), None),
+
+        _ if field.multi_line() => {
+            let Some(text_data) = text_edit(uistate, field) else {
+                return FieldState { lines, cursor: (0, 0), selection: None };
+            };
+
+            let selection = text_data.selecting()
+                .then(|| (text_data.sel_start_pos(), text_data.sel_end_pos()));
+
+            (
+                (text_data.line_number() as usize, text_data.cursor_pos() as usize),
+                selection,
+            )
+        }
+
+        _ if uistate.editor_mode() == EditorMode::VIM
+            && uistate.vim().last_target() == Some(field) =>
+        {
+            ((0, uistate.vim().field_cursor()), None)
+        }
+
+        _ => ((0, lines[0].chars().count()), None),
+    };
+
+    FieldState { lines, cursor, selection }
+}
+
+/// Replaces the text, cursor and selection of `field`.
+pub fn set_field_state(uistate: &mut UiState, field: TextField, state: &FieldState) {
+    let mut lines = state.lines.clone();
+
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+
+    match field {
+        TextField::Url => {
+            let url = lines.remove(0);
+
+            if url != uistate.url() {
+                uistate.load_url(url);
+            }
+
+            uistate.set_url_cursor_offset(state.cursor.1 as u16);
+            return;
+        }
+
+        _ if field.multi_line() => {
+            let Some(text_data) = text_edit_mut(uistate, field) else { return; };
+
+            text_data.set_text(lines);
+            text_data.set_line_number(state.cursor.0 as u16);
+            text_data.set_cursor_pos(state.cursor.1 as u16);
+
+            match state.selection {
+                Some((start, end)) => {
+                    text_data.set_selecting(true);
+                    text_data.set_sel_start_pos(start);
+                    text_data.set_sel_end_pos(end);
+                }
+
+                None => { text_data.reset_selection(); }
+            }
+
+            text_data.scroll_to_cursor();
+            return;
+        }
+
+        _ => {}
+    }
+
+    let text = lines.remove(0);
+    let vim = uistate.vim_mut();
+
+    vim.set_last_target(Some(field));
+    vim.set_field_cursor(state.cursor.1);
+
+    match field {
+        TextField::UrlParam { row, col } => {
+            if let Some(param) = uistate.url_deconst_mut().get_param(row) {
+                param.set_field(col, false, text);
+            }
+
+            uistate.set_url(uistate.url_deconst().to_string());
+        }
+
+        TextField::Header { row, col } => {
+            if let Some(header) = uistate.request_headers_mut()
+                .get_mut(row as usize)
+            {
+                header.set_field(col, false, text);
+            }
+        }
+
+        TextField::BodyKV { row, col } => {
+            let is_form_data =
+                *uistate.body().body_content() == BodyContent::FormData;
+
+            if let Some(kv_data) = uistate.body_mut().kv_data_mut()
+                .get_mut(row as usize)
+            {
+                kv_data.set_field(col, is_form_data, text);
+            }
+        }
+
+        TextField::Auth(field) => {
+            if let Some(field_text) = uistate.auth_mut().field_mut(field) {
+                *field_text = text;
+            }
+        }
+
+        TextField::Setting(field) => {
+            if let Some(field_text) = uistate.settings_mut().field_mut(field) {
+                *field_text = text;
+            }
+        }
+
+        _ => {}
+    }
+}
@@ -12,13 +12,24 @@ pub struct Url {
 }
 
 #[derive(Clone, PartialEq, Eq)]
-enum Protocol { HTTP, HTTPS }
+#[allow(clippy::upper_case_acronyms)]
+enum Protocol { HTTP, HTTPS, WS, WSS }
 
 impl Protocol {
     pub fn to_str(&self) -> &str {
         match self {
             Protocol::HTTP => "http",
             Protocol::HTTPS => "https",
+            Protocol::WS => "ws",
+            Protocol::WSS => "wss",
+        }
+    }
+
+    /// The port used when the url doesn't have one.
+    pub fn default_port(&self) -> u16 {
+        match self {
+            Protocol::HTTP | Protocol::WS => 80,
+            Protocol::HTTPS | Protocol::WSS => 443,
         }
     }
 }
@@ -105,6 +116,16 @@ impl Url {
                 self.port = 443;
             },
 
+            "ws" => {
+                self.protocol = Protocol::WS;
+                self.port = 80;
+            },
+
+            "wss" => {
+                self.protocol = Protocol::WSS;
+                self.port = 443;
+            },
+
             _ => {
                 self.protocol = Protocol::HTTP;
                 url_contains_protocol = false;
@@ -186,9 +207,7 @@ impl Url {
         url_string.push_str("://");
         url_string.push_str(&self.host);
 
-        if self.protocol == Protocol::HTTP && self.port != 80
-            || self.protocol == Protocol::HTTPS && self.port != 443
-        {
+        if self.port != self.protocol.default_port() {
             url_string.push(':');
             url_string.push_str(&self.port.to_string());
         }
@@ -244,6 +263,10 @@ impl Url {
     }
 
     pub fn protocol(&self) -> Protocol { self.protocol.clone() }
+    /// Whether the url has the `ws://` or `wss://` scheme.
+    pub fn is_websocket(&self) -> bool {
+        matches!(self.protocol, Protocol::WS | Protocol::WSS)
+    }
     pub fn host(&self) -> String { self.host.clone() }
     pub fn port(&self) -> u16 { self.port }
     pub fn path(&self) -> String { self.path.clone() }
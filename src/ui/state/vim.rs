@@ -0,0 +1,147 @@
+//! State of the VIM editor mode. The mode itself (`NORMAL`, `INSERT` or
+//! `VISUAL`) is the [InputMode](super::InputMode) of the `UiState`.
+
+use crossterm::event::KeyEvent;
+
+use crate::ui::state::{
+    UiState, EditorMode, InputMode,
+    text_field::{ TextField, active_text_field, field_text },
+};
+
+/// Cursor and visual selection of a single line field, for rendering.
+#[derive(Clone, Copy, PartialEq)]
+pub struct FieldCursor {
+    pub cursor: u16,
+
+    /// First and last selected characters.
+    pub selection: Option<(u16, u16)>,
+}
+
+#[derive(Clone, Default)]
+pub struct VimState {
+    /// Keys of the command being typed, e.g. `2d` while waiting for the
+    /// motion.
+    pending: Vec<KeyEvent>,
+
+    /// Whether the `VISUAL` mode selects whole lines (`V`).
+    visual_line: bool,
+
+    /// (line, character) where the visual selection started.
+    visual_start: (usize, usize),
+
+    /// Text that was deleted or yanked last.
+    register: String,
+
+    /// Whether `register` holds whole lines.
+    register_linewise: bool,
+
+    /// The field the keys went to last. The pending keys and selection are
+    /// dropped when it changes.
+    last_target: Option<TextField>,
+
+    /// Cursor in the key-value fields, which don't keep one themselves.
+    field_cursor: usize,
+
+    /// Keys of the last change, including the text typed in `INSERT` mode,
+    /// repeated by `.`.
+    last_change: Vec<KeyEvent>,
+
+    /// Whether the keys typed in `INSERT` mode are added to `last_change`.
+    recording: bool,
+
+    /// The `:` command being typed. `None` when the command line is closed.
+    command_line: Option<String>,
+}
+
+impl VimState {
+    pub fn pending(&self) -> &Vec<KeyEvent> { &self.pending }
+    pub fn pending_mut(&mut self) -> &mut Vec<KeyEvent> { &mut self.pending }
+
+    pub fn visual_line(&self) -> bool { self.visual_line }
+    pub fn visual_start(&self) -> (usize, usize) { self.visual_start }
+    pub fn start_visual(&mut self, pos: (usize, usize), line: bool) {
+        self.visual_start = pos;
+        self.visual_line = line;
+    }
+
+    pub fn register(&self) -> (&str, bool) {
+        (&self.register, self.register_linewise)
+    }
+    pub fn set_register(&mut self, text: String, linewise: bool) {
+        self.register = text;
+        self.register_linewise = linewise;
+    }
+
+    pub fn last_target(&self) -> Option<TextField> { self.last_target }
+    pub fn set_last_target(&mut self, target: Option<TextField>) {
+        self.last_target = target;
+    }
+
+    pub fn field_cursor(&self) -> usize { self.field_cursor }
+    pub fn set_field_cursor(&mut self, cursor: usize) {
+        self.field_cursor = cursor;
+    }
+
+    pub fn last_change(&self) -> &Vec<KeyEvent> { &self.last_change }
+    pub fn set_last_change(&mut self, keys: Vec<KeyEvent>) {
+        self.last_change = keys;
+    }
+
+    pub fn set_recording(&mut self, recording: bool) {
+        self.recording = recording;
+    }
+
+    /// Adds a key typed in `INSERT` mode to the last change.
+    pub fn record(&mut self, key: KeyEvent) {
+        if self.recording {
+            self.last_change.push(key);
+        }
+    }
+
+    pub fn command_line(&self) -> &Option<String> { &self.command_line }
+    pub fn set_command_line(&mut self, command_line: Option<String>) {
+        self.command_line = command_line;
+    }
+}
+/// Cursor and selection of the single line `field` when it has the
+/// Cursor and selection of the single line field `field` when it has the
+/// VIM cursor.
+pub fn field_cursor(uistate: &UiState, field: TextField) -> Option<FieldCursor> {
+    if uistate.editor_mode() != EditorMode::VIM
+        || active_text_field(uistate) != Some(field)
+    {
+        return None;
+    }
+
+    let vim = uistate.vim();
+    let len = field_text(uistate, field)[0].chars().count();
+
+    let mut cursor = match field {
+        TextField::Url => uistate.url_cursor_offset() as usize,
+        _ if vim.last_target() == Some(field) => vim.field_cursor(),
+        _ => len,
+    };
+
+    if uistate.input_mode() != InputMode::INSERT {
+        cursor = cursor.min(len.saturating_sub(1));
+    }
+
+    let selection = match uistate.input_mode() {
+        InputMode::VISUAL if len > 0 => {
+            if vim.visual_line() {
+                Some((0, len - 1))
+            } else {
+                let start = vim.visual_start().1.min(len - 1);
+
+                Some((start.min(cursor), start.max(cursor)))
+            }
+        }
+
+        _ => None,
+    };
+
+    Some(FieldCursor {
+        cursor: cursor as u16,
+        selection: selection.map(|(s, e)| (s as u16, e as u16)),
+    })
+}
@@ -0,0 +1,135 @@
+//! State of the WebSocket mode: the connection and its message log.
+
+use std::sync::mpsc::{ SendError, Sender };
+
+use crate::websocket::{
+    FrameKind, LogEntry, worker::{ Command, WsEvent },
+};
+
+/// A connection that has been opened and hasn't ended yet.
+#[derive(Clone)]
+pub struct Connection {
+    id: u64,
+
+    /// The url of the handshake, with the variables resolved.
+    url: String,
+
+    /// Whether the handshake completed.
+    connected: bool,
+
+    /// Sends the frames to write to the worker thread.
+    commands: Sender<Command>,
+}
+
+#[derive(Clone, Default)]
+pub struct WebSocket {
+    /// The switch of the method box that sends `http` urls over WebSocket.
+    enabled: bool,
+
+    /// How the body editor is sent.
+    frame_kind: FrameKind,
+
+    /// Sends the events from the worker thread back to the ui loop. `None`
+    /// until the ui loop sets it up.
+    sender: Option<Sender<WsEvent>>,
+
+    connection: Option<Connection>,
+
+    /// Id given to the next connection.
+    next_connection_id: u64,
+
+    /// The messages of the connections, oldest first.
+    log: Vec<LogEntry>,
+
+    /// First row of the log shown.
+    scroll_offset: usize,
+
+    /// Whether the log scrolls to the last message as they arrive.
+    follow: bool,
+}
+
+impl Connection {
+    pub fn id(&self) -> u64 { self.id }
+    pub fn url(&self) -> &str { &self.url }
+    pub fn connected(&self) -> bool { self.connected }
+
+    /// Hands `command` over to the worker thread.
+    pub fn command(&self, command: Command) -> Result<(), SendError<Command>> {
+        self.commands.send(command)
+    }
+}
+
+impl WebSocket {
+    pub fn enabled(&self) -> bool { self.enabled }
+    pub fn set_enabled(&mut self, enabled: bool) { self.enabled = enabled; }
+
+    pub fn frame_kind(&self) -> FrameKind { self.frame_kind }
+    pub fn set_frame_kind(&mut self, frame_kind: FrameKind) {
+        self.frame_kind = frame_kind;
+    }
+
+    pub fn sender(&self) -> &Option<Sender<WsEvent>> { &self.sender }
+    pub fn set_sender(&mut self, sender: Sender<WsEvent>) {
+        self.sender = Some(sender);
+    }
+
+    pub fn connection(&self) -> Option<&Connection> { self.connection.as_ref() }
+
+    /// Starts showing a new connection to `url`. Returns its id.
+    pub fn open(&mut self, url: String, commands: Sender<Command>) -> u64 {
+        self.next_connection_id += 1;
+
+        self.connection = Some(Connection {
+            id: self.next_connection_id,
+            url,
+            connected: false,
+            commands,
+        });
+
+        self.follow = true;
+
+        self.next_connection_id
+    }
+
+    /// The handshake of the connection completed.
+    pub fn set_connected(&mut self) {
+        if let Some(connection) = self.connection.as_mut() {
+            connection.connected = true;
+        }
+    }
+
+    /// Forgets the connection once it has ended.
+    pub fn end(&mut self) { self.connection = None; }
+
+    pub fn log(&self) -> &Vec<LogEntry> { &self.log }
+    pub fn push_log(&mut self, entry: LogEntry) { self.log.push(entry); }
+    pub fn clear_log(&mut self) {
+        self.log.clear();
+        self.scroll_offset = 0;
+        self.follow = true;
+    }
+
+    pub fn scroll_offset(&self) -> usize { self.scroll_offset }
+
+    /// Scrolls the log by `delta` rows. Scrolling up stops following the
+    /// messages as they arrive.
+    pub fn scroll(&mut self, delta: i32) {
+        self.scroll_offset = (self.scroll_offset as i32 + delta).max(0) as usize;
+
+        if delta < 0 {
+            self.follow = false;
+        }
+    }
+
+    /// Keeps the scroll offset within the `row_count` rows of the log,
+    /// `height` of which are shown. Scrolling down to the last row follows
+    /// the log again.
+    pub fn clamp_scroll(&mut self, row_count: usize, height: usize) {
+        let max = row_count.saturating_sub(height);
+
+        if self.follow || self.scroll_offset >= max {
+            self.scroll_offset = max;
+            self.follow = true;
+        }
+    }
+}
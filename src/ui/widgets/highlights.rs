@@ -0,0 +1,75 @@
+use tui::{
+    layout::Rect,
+    buffer::Buffer,
+    style::{ Color, Style },
+    widgets::Widget,
+};
+
+use crate::ui::state::search::SearchMatch;
+
+/// Styles ranges of cells over what is already rendered in the area, e.g.
+/// the matches of a search.
+#[derive(Default)]
+pub struct Highlights {
+    /// (row, column, width, style) of each range.
+    ranges: Vec<(u16, u16, u16, Style)>,
+}
+
+impl Highlights {
+    /// Highlights the `matches`, in order, on wrapped lines. `rows` has the
+    /// (line, first character) of each row of the area and `width` is the
+    /// width the lines are wrapped to.
+    pub fn matches(
+        rows: &[(usize, usize)],
+        width: usize,
+        matches: &[SearchMatch],
+        current: Option<SearchMatch>,
+    ) -> Self {
+        let mut ranges = vec![];
+
+        for (row, (line, first)) in rows.iter().enumerate() {
+            let row_end = first + width;
+
+            // The matches are in the order of the lines
+            let line_start = matches.partition_point(|m| m.line < *line);
+
+            for m in matches[line_start..].iter().take_while(|m| m.line == *line) {
+                let start = m.start.max(*first);
+                let end = m.end.min(row_end);
+
+                if start < end {
+                    ranges.push((
+                        row as u16,
+                        (start - first) as u16,
+                        (end - start) as u16,
+                        match_style(current == Some(*m)),
+                    ));
+                }
+            }
+        }
+
+        Self { ranges }
+    }
+}
+
+impl Widget for Highlights {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for (row, col, width, style) in self.ranges {
+            if row >= area.height || col >= area.width {
+                continue;
+            }
+
+            let width = width.min(area.width - col);
+
+            buf.set_style(Rect::new(area.x + col, area.y + row, width, 1), style);
+        }
+    }
+}
+
+/// Style of a match of the search, `current` for the one jumped to.
+fn match_style(current: bool) -> Style {
+    match current {
+        true => Style::default().fg(Color::Black).bg(Color::LightMagenta),
+        false => Style::default().fg(Color::Black).bg(Color::Cyan),
+    }
+}
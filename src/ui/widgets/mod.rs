@@ -2,3 +2,4 @@ pub mod label;
 pub mod text_input;
 pub mod select;
 
+pub mod highlights;
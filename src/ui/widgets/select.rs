@@ -108,7 +108,7 @@ impl Widget for Select {
                 let mut text_rect = Rect::new(
                     area.x + 2,
                     area.y + 1,
-                    area.width,
+                    area.width.saturating_sub(4),
                     1,
                 );
 
@@ -246,7 +246,7 @@ impl Widget for Select {
                         Span::styled(String::from("\u{eb71}"), style)
                     );
 
-                    text_rect.x += text_rect.width - 5;
+                    text_rect.x += text_rect.width - 1;
                     text_rect.width = 1;
                     triangle_up.render(text_rect, buf);
                 } else {
@@ -254,7 +254,7 @@ impl Widget for Select {
                         Span::styled(String::from("\u{eb6e}"), style)
                     );
 
-                    text_rect.x += text_rect.width - 5;
+                    text_rect.x += text_rect.width - 1;
                     text_rect.width = 1;
                     triangle_down.render(text_rect, buf);
                 }
@@ -3,11 +3,18 @@ use tui::{
     buffer::Buffer,
     layout::{ Alignment, Layout, Constraint, Direction, Rect },
     widgets::{ BorderType, Borders, Block, Widget, Paragraph },
-    text::Span,
+    text::{ Span, Spans },
     style::{ Color, Style },
 };
 
-use crate::ui::{ string_chunks, string_chunks_to_spans, calc::scrollbar_pos };
+use crate::{
+    ui::{
+        calc::scrollbar_pos,
+        highlight::{ Syntax, Token, Highlighter, token_spans },
+        state::search::SearchMatch, widgets::highlights::Highlights,
+    },
+    environment::find_variables,
+};
 
 /// A Widget that combines Block with text.
 /// Used as a text input field.
@@ -31,6 +38,17 @@ pub struct TextInput {
     selecting: bool,
     sel_start_pos: (u16, u16),
     sel_end_pos: (u16, u16),
+
+    /// Names of the variables defined in the active environment. When set,
+    /// `{{variables}}` that are not in it are highlighted.
+    variables: Option<Vec<String>>,
+
+    /// Language of the multi-line text, for the syntax highlighting.
+    syntax: Syntax,
+
+    /// Matches of the search highlighted in the multi-line text.
+    matches: Vec<SearchMatch>,
+    current_match: Option<SearchMatch>,
 }
 
 impl Default for TextInput {
@@ -54,6 +72,10 @@ impl Default for TextInput {
             selecting: false,
             sel_start_pos: (0, 0),
             sel_end_pos: (0, 0),
+            variables: None,
+            syntax: Syntax::default(),
+            matches: vec![],
+            current_match: None,
         }
     }
 }
@@ -112,33 +134,54 @@ impl Widget for TextInput {
 
         // Text
         if self.is_multi_line() {
-            let mut lines: Vec<String> = vec![];
+            let text_vec = self.get_text_vec();
+            let width = (text_area.width as usize).max(1);
+
+            // (line, first character) of each line after wrapping
+            let mut lines: Vec<(usize, usize)> = vec![];
             let mut text_start: usize = 0;
             let mut text_end: usize;
 
-            for line in self.get_text_vec() {
-                if line.len() > text_area.width as usize {
-                    let mut line_divided = string_chunks(line, text_area.width as usize);
+            for (i, line) in text_vec.iter().enumerate() {
+                let len = line.chars().count();
 
-                    lines.append(&mut line_divided);
-                } else {
-                    lines.push(line.clone());
-                }
+                lines.push((i, 0));
+                lines.extend((width..len).step_by(width).map(|start| (i, start)));
             }
 
             text_end = lines.len();
 
             if text_end > text_area.height as usize {
                 text_start = self.get_scroll_offset() as usize;
-                text_end = text_start + text_area.height as usize;
+                text_end = (text_start + text_area.height as usize).min(lines.len());
             }
 
+            let shown = &lines[text_start.min(text_end)..text_end];
+            let tokens = self.line_tokens(shown);
+
             let text = Paragraph::new(
-                string_chunks_to_spans(&lines[text_start..text_end])
+                shown.iter()
+                    .map(|&(i, start)| {
+                        let chunk = text_vec[i].chars().skip(start).take(width)
+                            .collect();
+
+                        let chunk_tokens = tokens.get(i - shown[0].0)
+                            .and_then(|tokens| tokens.get(start..))
+                            .unwrap_or_default();
+
+                        self.variable_spans(chunk, chunk_tokens, Style::default())
+                    })
+                    .collect::<Vec<Spans>>()
             );
 
             text.render(text_area, buf);
 
+            if !self.matches.is_empty() {
+                Highlights::matches(
+                    shown, width, &self.matches, self.current_match,
+                ).render(text_area, buf);
+            }
+
             if self.is_active() {
                 let cursor_block = Block::default()
                     .style(Style::default().fg(Color::Black).bg(Color::Yellow))
@@ -175,6 +218,9 @@ impl Widget for TextInput {
 
             let text_par: Paragraph;
 
+            // Index of the first character shown
+            let mut first_char = 0;
+
             if self.is_active() {
                 if let Some(txt) = self.get_text() {
                     let cursor_pos = self.get_cursor_pos() as usize;
@@ -191,9 +237,11 @@ impl Widget for TextInput {
 
                         if cursor_pos > skip_chars {
                             text = txt.chars().skip(skip_chars).collect();
+                            first_char = skip_chars;
                         } else {
                             text = txt.chars().skip(cursor_pos)
                                 .take(width).collect();
+                            first_char = cursor_pos;
                         }
                     }
 
@@ -224,7 +272,7 @@ impl Widget for TextInput {
                 }
 
                 text_par = Paragraph::new(
-                    Span::styled(text, self.get_active_border_style())
+                    self.variable_spans(text, &[], self.get_active_border_style())
                 );
             } else {
                 if let Some(txt) = self.get_text() {
@@ -233,11 +281,16 @@ impl Widget for TextInput {
                     text = String::default();
                 }
 
-                text_par = Paragraph::new(text)
-                    .style(*self.get_border_style());
+                text_par = Paragraph::new(
+                    self.variable_spans(text, &[], Style::default())
+                ).style(*self.get_border_style());
             }
 
             text_par.render(text_area, buf);
+
+            if self.is_active() && self.is_selecting() {
+                self.render_line_selection(text_area, first_char, buf);
+            }
         }
     }
 }
@@ -379,6 +432,109 @@ impl TextInput {
         self
     }
 
+    pub fn get_variables(&self) -> &Option<Vec<String>> { &self.variables }
+    pub fn variables(mut self, variables: Vec<String>) -> TextInput {
+        self.variables = Some(variables);
+        self
+    }
+
+    pub fn get_syntax(&self) -> Syntax { self.syntax }
+    pub fn syntax(mut self, syntax: Syntax) -> TextInput {
+        self.syntax = syntax;
+        self
+    }
+
+    /// Highlights the `matches` of a search, `current` being the one jumped
+    /// to.
+    pub fn matches(
+        mut self, matches: Vec<SearchMatch>, current: Option<SearchMatch>
+    ) -> TextInput {
+        self.matches = matches;
+        self.current_match = current;
+        self
+    }
+
+    /// Tokens of the characters of the lines in `shown`, which are
+    /// (line, first character) pairs of the wrapped lines. The lines above
+    /// are tokenized as well, since a line can continue e.g. a comment.
+    fn line_tokens(&self, shown: &[(usize, usize)]) -> Vec<Vec<Token>> {
+        let (Some(first), Some(last)) = (shown.first(), shown.last()) else {
+            return vec![];
+        };
+
+        if self.get_syntax() == Syntax::Plain {
+            return vec![];
+        }
+
+        let mut highlighter = Highlighter::new(self.get_syntax());
+        let mut tokens: Vec<Vec<Token>> = vec![];
+
+        for (i, line) in self.get_text_vec()[..=last.0].iter().enumerate() {
+            let line_tokens = highlighter.line(line);
+
+            if i >= first.0 {
+                tokens.push(line_tokens);
+            }
+        }
+
+        tokens
+    }
+
+    /// Splits `text` into spans styled with `style` and colored by the
+    /// `tokens` of its characters, highlighting the `{{variables}}` that
+    /// can't be resolved.
+    fn variable_spans<'a>(
+        &self, text: String, tokens: &[Token], style: Style
+    ) -> Spans<'a> {
+        let variables = match self.get_variables() {
+            Some(variables) => variables,
+            None => { return Spans::from(token_spans(&text, tokens, style)); }
+        };
+
+        let unresolved_style = style.fg(Color::White).bg(Color::Red);
+        let mut spans: Vec<Span> = vec![];
+        let mut pos = 0;
+
+        // Index of the character at `pos`, for the tokens
+        let mut char_pos = 0;
+
+        for var in find_variables(&text) {
+            if variables.iter().any(|v| v == var.name) {
+                continue;
+            }
+
+            if var.start > pos {
+                let before = &text[pos..var.start];
+
+                spans.extend(token_spans(
+                    before,
+                    tokens.get(char_pos..).unwrap_or_default(),
+                    style,
+                ));
+
+                char_pos += before.chars().count();
+            }
+
+            spans.push(Span::styled(
+                String::from(&text[var.start..var.end]),
+                unresolved_style,
+            ));
+
+            char_pos += text[var.start..var.end].chars().count();
+            pos = var.end;
+        }
+
+        if pos < text.len() || spans.is_empty() {
+            spans.extend(token_spans(
+                &text[pos..],
+                tokens.get(char_pos..).unwrap_or_default(),
+                style,
+            ));
+        }
+
+        Spans::from(spans)
+    }
+
     /// Renders text selection whenever textarea is displayed
     fn render_selection(&self, area: Rect, buf: &mut Buffer) {
         let start_pos = self.get_sel_start_pos();
@@ -418,6 +574,30 @@ impl TextInput {
         }
     }
 
+    /// Renders the text selection of the single line input. The cursor is
+    /// drawn as a character before the character under it.
+    fn render_line_selection(&self, area: Rect, first_char: usize, buf: &mut Buffer) {
+        let cursor_pos = self.get_cursor_pos() as usize;
+        let sel_style = Style::default().fg(Color::White).bg(Color::DarkGray);
+
+        let start = self.get_sel_start_pos().1 as usize;
+        let end = self.get_sel_end_pos().1 as usize;
+
+        for i in start.max(first_char)..=end {
+            let mut x = i - first_char;
+
+            if i >= cursor_pos {
+                x += 1;
+            }
+
+            if x >= area.width as usize {
+                break;
+            }
+
+            buf.get_mut(area.x + x as u16, area.y).set_style(sel_style);
+        }
+    }
+
     fn render_sel_multiline_ends(&self, area: Rect, buf: &mut Buffer) {
         let start_pos = self.get_sel_start_pos();
         let end_pos = self.get_sel_end_pos();
@@ -0,0 +1,140 @@
+use crossterm::event::{ KeyCode, KeyModifiers, KeyEvent };
+
+use crate::ui::state::auth::{
+    Auth, AuthScheme, AuthUIElement, ApiKeyLocation, AUTH_SELECT_DISP_LEN,
+};
+
+/// Processes the key events when the "Authorization" tab content is active.
+pub fn process_auth_input(key: KeyEvent, auth: &mut Auth) {
+    let ctrl_down = key.modifiers == KeyModifiers::CONTROL;
+
+    match auth.active_auth_element().clone() {
+        AuthUIElement::Scheme(opened) => {
+            match key.code {
+                KeyCode::Enter => {
+                    if opened {
+                        let index = auth.auth_scheme_sel_index() as usize;
+                        let selected_option = auth.auth_scheme_options()[index]
+                            .clone();
+
+                        auth.set_auth_scheme(
+                            AuthScheme::from_str(selected_option.as_str())
+                        );
+                    }
+
+                    auth.set_active_auth_element(
+                        AuthUIElement::Scheme(!opened)
+                    );
+                }
+
+                KeyCode::Up if opened => {
+                    let s = auth.auth_scheme_sel_index();
+                    let offset = auth.auth_scheme_scroll_offset();
+
+                    if s > 0 {
+                        if offset == s {
+                            auth.set_auth_scheme_scroll_offset(offset - 1);
+                        }
+
+                        auth.set_auth_scheme_sel_index(s - 1);
+                    }
+                }
+
+                KeyCode::Down if opened => {
+                    let current_selection = auth.auth_scheme_sel_index();
+                    let offset = auth.auth_scheme_scroll_offset();
+                    let selection_at_bottom = current_selection - offset
+                        == AUTH_SELECT_DISP_LEN - 1;
+
+                    if ((current_selection + 1) as usize)
+                        < auth.auth_scheme_options().len()
+                    {
+                        if selection_at_bottom {
+                            auth.set_auth_scheme_scroll_offset(offset + 1);
+                        }
+
+                        auth.set_auth_scheme_sel_index(current_selection + 1);
+                    }
+                }
+
+                KeyCode::Down | KeyCode::Right if ctrl_down => {
+                    auth.activate_next_element();
+                }
+
+                _ => {}
+            }
+        }
+
+        AuthUIElement::ApiKeyLocation(opened) => {
+            match key.code {
+                KeyCode::Enter => {
+                    if opened {
+                        let index = auth.api_key_location_sel_index() as usize;
+                        let selected_option = auth.api_key_location_options()
+                            [index].clone();
+
+                        auth.set_api_key_location(
+                            ApiKeyLocation::from_str(selected_option.as_str())
+                        );
+                    }
+
+                    auth.set_active_auth_element(
+                        AuthUIElement::ApiKeyLocation(!opened)
+                    );
+                }
+
+                KeyCode::Up if opened => {
+                    let s = auth.api_key_location_sel_index();
+
+                    if s > 0 {
+                        auth.set_api_key_location_sel_index(s - 1);
+                    }
+                }
+
+                KeyCode::Down if opened => {
+                    let s = auth.api_key_location_sel_index() + 1;
+
+                    if (s as usize) < auth.api_key_location_options().len() {
+                        auth.set_api_key_location_sel_index(s);
+                    }
+                }
+
+                KeyCode::Down | KeyCode::Right if ctrl_down => {
+                    auth.activate_next_element();
+                }
+
+                KeyCode::Up | KeyCode::Left if ctrl_down => {
+                    auth.activate_previous_element();
+                }
+
+                _ => {}
+            }
+        }
+
+        AuthUIElement::Field(field) => {
+            match key.code {
+                KeyCode::Down | KeyCode::Right if ctrl_down => {
+                    auth.activate_next_element();
+                }
+
+                KeyCode::Up | KeyCode::Left if ctrl_down => {
+                    auth.activate_previous_element();
+                }
+
+                KeyCode::Char(c) => {
+                    if let Some(text) = auth.field_mut(field) {
+                        text.push(c);
+                    }
+                }
+
+                KeyCode::Backspace => {
+                    if let Some(text) = auth.field_mut(field) {
+                        text.pop();
+                    }
+                }
+
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crossterm::event::{ KeyCode, KeyEvent };
+use log::info;
+use reqwest::Url;
+
+use crate::{
+    api::resolved_url,
+    cookie::{ Cookie, CookieJar },
+    ui::state::{ UiState, app_status::AppStatus, cookies::CookieEditor },
+};
+
+/// Opens the cookie manager, or closes it if it's already open.
+pub fn toggle_cookie_manager(uistate: &mut UiState) {
+    let opened = uistate.cookies().opened();
+
+    uistate.cookies_mut().set_opened(!opened);
+
+    // The jar may have changed since the manager was last open
+    move_selection(uistate, 0);
+}
+
+/// Processes the key events while the cookie manager is open.
+pub fn process_cookie_manager_input(key: KeyEvent, uistate: &mut UiState) {
+    if uistate.cookies().editor().is_some() {
+        process_editor_input(key, uistate);
+        return;
+    }
+
+    match key.code {
+        KeyCode::Up => { move_selection(uistate, -1); }
+        KeyCode::Down => { move_selection(uistate, 1); }
+        KeyCode::PageUp => { move_selection(uistate, -10); }
+        KeyCode::PageDown => { move_selection(uistate, 10); }
+
+        KeyCode::Enter | KeyCode::Char('e') => {
+            let index = uistate.cookies().sel_index();
+
+            if let Some(cookie) = with_jar(uistate, |jar| jar.cookies().get(index).cloned()) {
+                uistate.cookies_mut()
+                    .set_editor(Some(CookieEditor::new(Some(index), cookie)));
+            }
+        }
+
+        // A new cookie for the host of the request
+        KeyCode::Char('n') => {
+            let host = Url::parse(&resolved_url(uistate)).ok()
+                .and_then(|url| url.host_str().map(String::from))
+                .unwrap_or_default();
+
+            let cookie = Cookie::new(
+                String::new(), String::new(), host, String::from("/"),
+            );
+
+            uistate.cookies_mut()
+                .set_editor(Some(CookieEditor::new(None, cookie)));
+        }
+
+        KeyCode::Char('d') | KeyCode::Delete => {
+            let index = uistate.cookies().sel_index();
+
+            with_jar(uistate, |jar| jar.remove(index));
+            save(uistate);
+            move_selection(uistate, 0);
+        }
+
+        KeyCode::Esc => { uistate.cookies_mut().set_opened(false); }
+
+        _ => {}
+    }
+}
+
+fn process_editor_input(key: KeyEvent, uistate: &mut UiState) {
+    let editor = match uistate.cookies_mut().editor_mut() {
+        Some(editor) => editor,
+        None => { return; }
+    };
+
+    match key.code {
+        KeyCode::Tab | KeyCode::Down => { editor.move_field(1); }
+        KeyCode::BackTab | KeyCode::Up => { editor.move_field(-1); }
+        KeyCode::Char(c) => { editor.push(c); }
+        KeyCode::Backspace => { editor.pop(); }
+        KeyCode::Esc => { uistate.cookies_mut().set_editor(None); }
+
+        KeyCode::Enter => {
+            let index = editor.index();
+
+            let cookie = match editor.edited() {
+                Ok(cookie) => cookie,
+                Err(e) => {
+                    uistate.set_app_error(format!("Couldn't save the cookie: {}", e));
+                    uistate.set_app_status(AppStatus::ERROR);
+                    return;
+                }
+            };
+
+            uistate.cookies_mut().set_editor(None);
+
+            with_jar(uistate, |jar| match index {
+                Some(index) => jar.replace(index, cookie),
+                None => jar.insert(cookie),
+            });
+
+            save(uistate);
+            move_selection(uistate, 0);
+        }
+
+        _ => {}
+    }
+}
+
+/// Runs `f` with the jar of the active environment.
+fn with_jar<T>(uistate: &UiState, f: impl FnOnce(&mut CookieJar) -> T) -> T {
+    uistate.cookies().jars().with_jar(&uistate.cookie_jar(), f)
+}
+
+/// Moves the selection of the cookie list, keeping it on a cookie after
+/// cookies were deleted.
+fn move_selection(uistate: &mut UiState, delta: i32) {
+    let count = with_jar(uistate, |jar| jar.cookies().len());
+
+    uistate.cookies_mut().move_selection(delta, count);
+}
+
+/// Writes the jar of the active environment to its file.
+fn save(uistate: &mut UiState) {
+    let name = uistate.cookie_jar();
+
+    if let Err(e) = uistate.cookies().jars().save(&name) {
+        info!("{}", e);
+
+        uistate.set_app_error(format!("Couldn't save the cookie jar {}: {}", name, e));
+        uistate.set_app_status(AppStatus::ERROR);
+    }
+}
@@ -0,0 +1,80 @@
+use crossterm::event::{ KeyCode, KeyEvent };
+use log::info;
+
+use crate::ui::state::{
+    UiState, app_status::AppStatus, environment::ENV_SELECT_DISP_LEN,
+};
+
+/// Processes the key events for the active environment select.
+pub fn process_environment_input(key: KeyEvent, uistate: &mut UiState) {
+    let opened = uistate.environments().opened();
+
+    match key.code {
+        KeyCode::Enter => {
+            let environments = uistate.environments_mut();
+
+            if opened {
+                let index = environments.sel_index();
+
+                environments.set_active(index);
+                environments.set_opened(false);
+
+                return;
+            }
+
+            // Picks up the environment files edited since the last time
+            if let Err(e) = environments.reload() {
+                info!("{}", e);
+
+                uistate.set_app_error(
+                    format!("Couldn't read the environments: {}", e)
+                );
+                uistate.set_app_status(AppStatus::ERROR);
+
+                return;
+            }
+
+            let environments = uistate.environments_mut();
+            let sel_index = environments.sel_index();
+
+            environments.set_scroll_offset(
+                sel_index.saturating_sub(ENV_SELECT_DISP_LEN - 1)
+            );
+            environments.set_opened(true);
+        }
+
+        KeyCode::Up if opened => {
+            let environments = uistate.environments_mut();
+            let s = environments.sel_index();
+            let offset = environments.scroll_offset();
+
+            if s > 0 {
+                if offset == s {
+                    environments.set_scroll_offset(offset - 1);
+                }
+
+                environments.set_sel_index(s - 1);
+            }
+        }
+
+        KeyCode::Down if opened => {
+            let environments = uistate.environments_mut();
+            let current_selection = environments.sel_index();
+            let offset = environments.scroll_offset();
+            let selection_at_bottom = current_selection - offset
+                == ENV_SELECT_DISP_LEN - 1;
+
+            if ((current_selection + 1) as usize)
+                < environments.options().len()
+            {
+                if selection_at_bottom {
+                    environments.set_scroll_offset(offset + 1);
+                }
+
+                environments.set_sel_index(current_selection + 1);
+            }
+        }
+
+        _ => { uistate.environments_mut().set_opened(false); }
+    }
+}
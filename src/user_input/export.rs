@@ -0,0 +1,66 @@
+use crossterm::event::{ KeyCode, KeyEvent };
+use copypasta::{ ClipboardContext, ClipboardProvider };
+use log::info;
+
+use crate::{
+    ui::state::{ UiState, app_status::AppStatus },
+    export::{ SnippetFormat, snippet },
+};
+
+/// Opens the export menu, or closes it if it's already open.
+pub fn toggle_export_menu(uistate: &mut UiState) {
+    let opened = uistate.export_menu().opened();
+
+    uistate.export_menu_mut().set_opened(!opened);
+}
+
+/// Processes the key events while the export menu is open.
+pub fn process_export_input(key: KeyEvent, uistate: &mut UiState) {
+    let menu = uistate.export_menu_mut();
+    let s = menu.sel_index();
+
+    match key.code {
+        KeyCode::Up if s > 0 => { menu.set_sel_index(s - 1); }
+
+        KeyCode::Down if (s as usize) < SnippetFormat::iter().len() - 1 => {
+            menu.set_sel_index(s + 1);
+        }
+
+        KeyCode::Enter => {
+            let format = menu.selected();
+
+            menu.set_opened(false);
+            copy_snippet(uistate, &format);
+        }
+
+        KeyCode::Esc => { menu.set_opened(false); }
+
+        _ => {}
+    }
+}
+
+/// Copies the snippet of the current request in `format` to the clipboard.
+fn copy_snippet(uistate: &mut UiState, format: &SnippetFormat) {
+    let code = snippet(format, uistate);
+
+    let result = ClipboardContext::new()
+        .and_then(|mut ctx| ctx.set_contents(code));
+
+    match result {
+        Ok(()) => {
+            uistate.set_app_message(
+                format!("Copied the {} snippet to the clipboard", format.to_str())
+            );
+            uistate.set_app_status(AppStatus::INFO);
+        }
+
+        Err(e) => {
+            info!("Couldn't copy the snippet: {}", e);
+
+            uistate.set_app_error(
+                format!("Couldn't write to the clipboard: {}", e)
+            );
+            uistate.set_app_status(AppStatus::ERROR);
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//! Keys of the GraphQL body: the operation select, the completion menu of
+//! the query editor and the schema browser.
+
+use crossterm::event::{ KeyCode, KeyEvent, KeyModifiers };
+
+use crate::{
+    api::resolved_url,
+    graphql::{ completions, worker::fetch },
+    ui::state::{
+        UiState, app_status::AppStatus,
+        body::{ BodyUIElement, BodyContent },
+        edit_history::EditKind,
+        text_edit::TextEditState,
+        text_field::{ TextField, active_text_field, field_state },
+    },
+};
+
+/// Rows of the operation select shown at once.
+const OPERATION_DISP_LEN: u8 = 5;
+
+/// Processes the key events of the "Operation" select.
+pub fn process_operation_input(key: KeyEvent, uistate: &mut UiState, opened: bool) {
+    let operations = uistate.body().operations();
+    let body = uistate.body_mut();
+
+    match key.code {
+        KeyCode::Enter if opened => {
+            let name = operations.get(body.operation_sel_index() as usize)
+                .and_then(|operation| operation.name())
+                .map(String::from)
+                .unwrap_or_default();
+
+            body.set_operation(name);
+            body.set_active_body_element(BodyUIElement::Operation(false));
+        }
+
+        KeyCode::Enter => {
+            let index = body.operation_index() as u8;
+
+            body.set_operation_sel_index(index);
+            body.set_operation_scroll_offset(
+                index.saturating_sub(OPERATION_DISP_LEN - 1)
+            );
+            body.set_active_body_element(BodyUIElement::Operation(true));
+        }
+
+        KeyCode::Up if opened => {
+            let s = body.operation_sel_index();
+            let offset = body.operation_scroll_offset();
+
+            if s > 0 {
+                if offset == s {
+                    body.set_operation_scroll_offset(offset - 1);
+                }
+
+                body.set_operation_sel_index(s - 1);
+            }
+        }
+
+        KeyCode::Down if opened => {
+            let s = body.operation_sel_index();
+            let offset = body.operation_scroll_offset();
+
+            if ((s + 1) as usize) < operations.len() {
+                if s - offset == OPERATION_DISP_LEN - 1 {
+                    body.set_operation_scroll_offset(offset + 1);
+                }
+
+                body.set_operation_sel_index(s + 1);
+            }
+        }
+
+        KeyCode::Left if key.modifiers == KeyModifiers::CONTROL => {
+            body.set_active_body_element(BodyUIElement::ContentType(false));
+        }
+
+        KeyCode::Down if key.modifiers == KeyModifiers::CONTROL => {
+            body.set_active_body_element(BodyUIElement::TextArea);
+        }
+
+        _ => {}
+    }
+}
+
+/// Whether the query editor of the GraphQL body is being edited.
+fn editing_query(uistate: &UiState) -> bool {
+    *uistate.body().body_content() == BodyContent::GraphQL
+        && active_text_field(uistate) == Some(TextField::BodyText)
+}
+
+/// Processes the keys of the completion menu: Ctrl+Space opens it in the
+/// query editor, and while it's open the arrows select a completion that
+/// Enter or Tab inserts. Returns `false` for the keys left to the editor.
+pub fn process_completion_input(key: KeyEvent, uistate: &mut UiState) -> bool {
+    if key.code == KeyCode::Char(' ') && key.modifiers == KeyModifiers::CONTROL {
+        if !editing_query(uistate) {
+            return false;
+        }
+
+        open_completion(uistate);
+        return true;
+    }
+
+    let menu = match uistate.graphql_mut().completion_mut() {
+        Some(menu) => menu,
+        None => { return false; }
+    };
+
+    match key.code {
+        KeyCode::Up => { menu.move_selection(-1); }
+        KeyCode::Down => { menu.move_selection(1); }
+        KeyCode::PageUp => { menu.move_selection(-8); }
+        KeyCode::PageDown => { menu.move_selection(8); }
+        KeyCode::Enter | KeyCode::Tab => { accept_completion(uistate); }
+        KeyCode::Esc => { uistate.graphql_mut().close_completion(); }
+        _ => { return false; }
+    }
+
+    true
+}
+
+/// Shows the completions of the word at the cursor of the query editor.
+/// The schema is read first if it wasn't yet.
+fn open_completion(uistate: &mut UiState) {
+    let url = resolved_url(uistate);
+
+    let schema = match uistate.graphql().schema(&url) {
+        Some(schema) => schema,
+        None => {
+            fetch(uistate);
+            return;
+        }
+    };
+
+    let (prefix, items) = completions(
+        schema,
+        &text_before_cursor(uistate.body().text_data()),
+    );
+
+    if items.is_empty() {
+        uistate.set_app_message(String::from("No completions"));
+        uistate.set_app_status(AppStatus::INFO);
+    }
+
+    uistate.graphql_mut().set_completions(prefix, items);
+}
+
+/// Updates the completion menu after a key edited the query or moved its
+/// cursor. The menu closes when the cursor leaves the word.
+pub fn refresh_completion(uistate: &mut UiState) {
+    if uistate.graphql().completion().is_none() {
+        return;
+    }
+
+    let url = resolved_url(uistate);
+
+    let completions = match (editing_query(uistate), uistate.graphql().schema(&url)) {
+        (true, Some(schema)) => Some(completions(
+            schema,
+            &text_before_cursor(uistate.body().text_data()),
+        )),
+
+        _ => None,
+    };
+
+    match completions {
+        Some((prefix, items)) if !prefix.is_empty() => {
+            uistate.graphql_mut().set_completions(prefix, items);
+        }
+
+        _ => { uistate.graphql_mut().close_completion(); }
+    }
+}
+
+/// Replaces the word at the cursor with the selected completion.
+fn accept_completion(uistate: &mut UiState) {
+    let (prefix, label) = match uistate.graphql().completion() {
+        Some(menu) => match menu.selected() {
+            Some(item) => (String::from(menu.prefix()), String::from(item.label())),
+            None => { return; }
+        },
+
+        None => { return; }
+    };
+
+    uistate.graphql_mut().close_completion();
+
+    let before = field_state(uistate, TextField::BodyText);
+    let text_data = uistate.body_mut().text_data_mut();
+
+    text_data.reset_selection();
+
+    for _ in prefix.chars() {
+        text_data.delete_char();
+    }
+
+    for c in label.chars() {
+        text_data.insert_char(c);
+    }
+
+    let after = field_state(uistate, TextField::BodyText);
+
+    uistate.edit_history_mut()
+        .record(TextField::BodyText, before, after, EditKind::Other);
+}
+
+/// The text of the editor before the cursor.
+fn text_before_cursor(text_data: &TextEditState) -> String {
+    let lines = text_data.text_vec();
+    let line_number = (text_data.line_number() as usize).min(lines.len() - 1);
+
+    let mut before = lines[..line_number].join("\n");
+
+    if line_number > 0 {
+        before.push('\n');
+    }
+
+    before.extend(
+        lines[line_number].chars().take(text_data.cursor_pos() as usize)
+    );
+
+    before
+}
+
+/// Opens the schema browser, or closes it if it's already open. The schema
+/// is read when the browser opens without it.
+pub fn toggle_schema_browser(uistate: &mut UiState) {
+    let opened = uistate.graphql().browser().opened();
+
+    uistate.graphql_mut().browser_mut().set_opened(!opened);
+
+    if !opened && uistate.graphql().schema(&resolved_url(uistate)).is_none() {
+        fetch(uistate);
+    }
+}
+
+/// Processes the key events while the schema browser is open. Enter opens
+/// the type of the selected row and Backspace goes back.
+pub fn process_schema_browser_input(key: KeyEvent, uistate: &mut UiState) {
+    let url = resolved_url(uistate);
+
+    if key.code == KeyCode::Char('r') {
+        fetch(uistate);
+        return;
+    }
+
+    if key.code == KeyCode::Esc {
+        uistate.graphql_mut().browser_mut().set_opened(false);
+        return;
+    }
+
+    let graphql = uistate.graphql_mut();
+
+    let rows = match graphql.schema(&url) {
+        Some(schema) => graphql.browser().rows(schema),
+        None => vec![],
+    };
+
+    let browser = graphql.browser_mut();
+
+    match key.code {
+        KeyCode::Up => { browser.move_selection(-1, rows.len()); }
+        KeyCode::Down => { browser.move_selection(1, rows.len()); }
+        KeyCode::PageUp => { browser.move_selection(-10, rows.len()); }
+        KeyCode::PageDown => { browser.move_selection(10, rows.len()); }
+
+        KeyCode::Enter | KeyCode::Right => {
+            let target = rows.get(browser.sel_index())
+                .and_then(|row| row.target())
+                .map(String::from);
+
+            if let Some(target) = target {
+                browser.open_type(target);
+            }
+        }
+
+        KeyCode::Backspace | KeyCode::Left => { browser.back(); }
+
+        _ => {}
+    }
+}
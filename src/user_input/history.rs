@@ -0,0 +1,98 @@
+use crossterm::event::{ KeyCode, KeyEvent };
+use log::info;
+
+use crate::{
+    ui::state::{ UiState, app_status::AppStatus },
+    api::apply_response,
+};
+
+/// Opens the history panel, or closes it if it's already open.
+pub fn toggle_history(uistate: &mut UiState) {
+    let opened = uistate.history().opened();
+
+    uistate.history_mut().set_opened(!opened);
+}
+
+/// Processes the key events while the history panel is open. Typing filters
+/// the entries by their method, url and status.
+pub fn process_history_input(key: KeyEvent, uistate: &mut UiState) {
+    let history = uistate.history_mut();
+
+    match key.code {
+        KeyCode::Up => { history.move_selection(-1); }
+        KeyCode::Down => { history.move_selection(1); }
+        KeyCode::PageUp => { history.move_selection(-10); }
+        KeyCode::PageDown => { history.move_selection(10); }
+
+        KeyCode::Char(c) => {
+            let mut query = String::from(history.query());
+
+            query.push(c);
+            history.set_query(query);
+        }
+
+        KeyCode::Backspace => {
+            let mut query = String::from(history.query());
+
+            query.pop();
+            history.set_query(query);
+        }
+
+        KeyCode::Enter => { open_selected(uistate); }
+
+        KeyCode::F(5) => {
+            let secret_dropped = history.selected()
+                .map(|entry| entry.secret_dropped())
+                .unwrap_or(false);
+
+            let opened = open_selected(uistate);
+
+            // It would be sent without its secret
+            if opened && secret_dropped {
+                uistate.set_app_error(String::from(
+                    "Not sent: the history doesn't keep the secret of the \
+                    Authorization tab, type it again and send the request"
+                ));
+                uistate.set_app_status(AppStatus::ERROR);
+            } else if opened {
+                super::send_request(uistate);
+            }
+        }
+
+        KeyCode::Esc => { history.set_opened(false); }
+
+        _ => {}
+    }
+}
+
+/// Loads the selected entry into the editor and the response area, closing
+/// the panel. Returns `false` if no entry is selected.
+fn open_selected(uistate: &mut UiState) -> bool {
+    let entry = match uistate.history().selected() {
+        Some(entry) => entry.clone(),
+        None => { return false; }
+    };
+
+    info!("Opening the history entry of {}", entry.url());
+
+    uistate.history_mut().set_opened(false);
+    entry.request().apply(uistate);
+    uistate.set_current_request_path(None);
+
+    if entry.status_code().is_some() {
+        apply_response(uistate, entry.response_data());
+    } else {
+        uistate.set_app_error(String::from(entry.error()));
+        uistate.set_app_status(AppStatus::ERROR);
+    }
+
+    if entry.secret_dropped() && entry.status_code().is_some() {
+        uistate.set_app_message(String::from(
+            "The history doesn't keep the secret of the Authorization tab, \
+            type it again before sending the request"
+        ));
+        uistate.set_app_status(AppStatus::INFO);
+    }
+
+    true
+}
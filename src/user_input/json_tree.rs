@@ -0,0 +1,91 @@
+//! Keys of the tree view of JSON responses.
+
+use log::info;
+use crossterm::event::{ KeyCode, KeyEvent, KeyModifiers };
+use copypasta::{ ClipboardContext, ClipboardProvider };
+
+use crate::ui::state::{
+    UiState, app_status::AppStatus, response_tabs::ResponseTabs,
+};
+
+/// Switches the "Body" tab of the response between the text and the tree
+/// view.
+pub fn toggle_tree_view(uistate: &mut UiState) {
+    let tree_view = !uistate.response().tree_view();
+
+    if let Err(e) = uistate.response_mut().set_tree_view(tree_view) {
+        uistate.set_app_error(e);
+        uistate.set_app_status(AppStatus::ERROR);
+        return;
+    }
+
+    uistate.response_mut().set_active_tab(ResponseTabs::Body);
+}
+
+/// Handles the keys of the tree view. Returns `false` if the tree view isn't
+/// shown or the key isn't one of its keys.
+pub fn process_json_tree_input(key: KeyEvent, uistate: &mut UiState) -> bool {
+    let response = uistate.response();
+
+    if !response.tree_view() || response.active_tab() != ResponseTabs::Body {
+        return false;
+    }
+
+    let Some(tree) = uistate.response_mut().tree_mut() else { return false; };
+
+    let rows = match key.modifiers == KeyModifiers::CONTROL {
+        true => 4,
+        false => 1,
+    };
+
+    match key.code {
+        KeyCode::Up => { tree.move_cursor(-rows); }
+        KeyCode::Down => { tree.move_cursor(rows); }
+        KeyCode::Enter | KeyCode::Char(' ') => { tree.toggle(); }
+        KeyCode::Char('e') => { tree.expand_all(); }
+        KeyCode::Char('c') => { tree.collapse_all(); }
+
+        // Expands the nodes up to the depth typed
+        KeyCode::Char(c @ '1'..='9') => {
+            tree.expand_to_depth(c.to_digit(10).unwrap_or_default() as usize);
+        }
+
+        KeyCode::Char('p') => {
+            let path = tree.path(tree.cursor());
+            copy(uistate, path, "path");
+        }
+
+        KeyCode::Char('y') => {
+            let value = tree.value(tree.cursor());
+            copy(uistate, value, "value");
+        }
+
+        _ => { return false; }
+    }
+
+    true
+}
+
+/// Copies the `what` of the node under the cursor to the clipboard.
+fn copy(uistate: &mut UiState, text: String, what: &str) {
+    let result = ClipboardContext::new()
+        .and_then(|mut ctx| ctx.set_contents(text));
+
+    match result {
+        Ok(()) => {
+            uistate.set_app_message(
+                format!("Copied the {} of the node to the clipboard", what)
+            );
+            uistate.set_app_status(AppStatus::INFO);
+        }
+
+        Err(e) => {
+            info!("Couldn't copy the {}: {}", what, e);
+
+            uistate.set_app_error(
+                format!("Couldn't write to the clipboard: {}", e)
+            );
+            uistate.set_app_status(AppStatus::ERROR);
+        }
+    }
+}
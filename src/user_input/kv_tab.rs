@@ -11,10 +11,13 @@ pub enum KVTabOperation {
     PopText(),
 }
 
+/// Processes the key events of a key-value tab. `col_count` is the number
+/// of columns of the tab, the last two being the add and remove buttons.
 pub fn process_kv_tab_input(
     key: KeyEvent,
     row: u16,
     col: u8,
+    col_count: u8,
     mut update_func: impl FnMut(KVTabOperation),
     mut change_func: impl FnMut(bool),
 ) {
@@ -23,13 +26,13 @@ pub fn process_kv_tab_input(
 
     match key.code {
         KeyCode::Enter => {
-            if col == 2 {
+            if col == col_count - 2 {
                 update_func(KVTabOperation::Insert(row+1));
                 change_func(true);
             }
 
             // The "-" or remove parameter button
-            if col == 3 {
+            if col == col_count - 1 {
                 if row > 0 {
                     update_func(KVTabOperation::MoveRow(row-1));
                 }
@@ -49,7 +52,7 @@ pub fn process_kv_tab_input(
 
         KeyCode::Right => {
             if ctrl_down {
-                if col < 3 {
+                if col < col_count - 1 {
                     update_func(KVTabOperation::MoveColumn(col + 1));
                 }
             }
@@ -1,36 +1,266 @@
 pub mod kv_tab;
-
-use std::io::Error;
+pub mod auth;
+pub mod sidebar;
+pub mod environment;
+pub mod export;
+pub mod history;
+pub mod vim;
+pub mod undo;
+pub mod json_tree;
+pub mod response_filter;
+pub mod search;
+pub mod text_area;
+pub mod scripts;
+pub mod runner;
+pub mod websocket;
+pub mod graphql;
+pub mod cookies;
+pub mod settings;
+
+use std::{ io::Error, time::Duration };
 use log::info;
+use reqwest::Method;
 
 use crossterm::event::{ self, Event, KeyCode, KeyModifiers };
+use copypasta::{ ClipboardContext, ClipboardProvider };
 use crate::{
     ui::state::{
-        UiState, InputMode, EditorMode, UIElement,
+        UiState, EditorMode, UIElement,
         request_tabs::RequestTabs, kv_data::KVData, app_status::AppStatus,
-        text_edit::TextEditMoveDirection, body::{ BodyUIElement, BodyContent },
+        kv_tab_state::{ KV_TAB_COLUMNS, FORM_DATA_COLUMNS },
+        body::{ BodyUIElement, BodyContent },
+        text_field::{ active_text_field, field_state },
+    },
+    api::{ call_api, worker },
+    websocket::{ is_websocket, worker as ws_worker },
+    import::curl::{ self, is_curl_command },
+    user_input::{
+        kv_tab::{ KVTabOperation, process_kv_tab_input },
+        auth::process_auth_input,
+        settings::process_settings_input,
+        sidebar::{
+            process_sidebar_input, toggle_sidebar, save_current_request,
+        },
+        environment::process_environment_input,
+        export::{ process_export_input, toggle_export_menu },
+        history::{ process_history_input, toggle_history },
+        vim::{
+            VimInput, process_vim_input, toggle_vim_mode, ctrl_r_redoes,
+        },
+        undo::{ undo, redo, record_edit },
+        json_tree::{ process_json_tree_input, toggle_tree_view },
+        response_filter::{ process_filter_input, open_filter },
+        search::{ process_search_input, open_search },
+        text_area::process_text_area_input,
+        scripts::process_scripts_input,
+        runner::process_runner_input,
+        websocket::process_websocket_input,
+        graphql::{
+            process_operation_input, process_completion_input,
+            refresh_completion, toggle_schema_browser,
+            process_schema_browser_input,
+        },
+        cookies::{ toggle_cookie_manager, process_cookie_manager_input },
     },
-    api::call_api,
-    user_input::kv_tab::{ KVTabOperation, process_kv_tab_input },
 };
 
+/// How long to wait for a key press before returning to the ui loop, which
+/// redraws the ui and collects request results in the meantime.
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// User interaction related code...
 /// Returning `Ok(true)` shall exit the program.
 pub fn process_user_input(uistate: &mut UiState) -> Result<bool, Error> {
-    if uistate.clone().editor_mode() == EditorMode::Normal
-        || uistate.clone().input_mode() == InputMode::Normal
+    if !event::poll(INPUT_POLL_INTERVAL)? {
+        return Ok(false);
+    }
+
     {
         if let Event::Key(key) = event::read()? {
             let mut update_url: bool = false;
 
+            if key.modifiers == KeyModifiers::CONTROL {
+                match key.code {
+                    KeyCode::Char('o') => {
+                        if uistate.sidebar().visible()
+                            && !uistate.sidebar().focused()
+                        {
+                            uistate.sidebar_mut().set_focused(true);
+                        } else {
+                            toggle_sidebar(uistate);
+                        }
+
+                        return Ok(false);
+                    }
+
+                    KeyCode::Char('s') => {
+                        save_current_request(uistate);
+                        return Ok(false);
+                    }
+
+                    KeyCode::Char('e') => {
+                        toggle_export_menu(uistate);
+                        return Ok(false);
+                    }
+
+                    KeyCode::Char('r') if !ctrl_r_redoes(uistate) => {
+                        toggle_history(uistate);
+                        return Ok(false);
+                    }
+
+                    KeyCode::Char('g') => {
+                        toggle_schema_browser(uistate);
+                        return Ok(false);
+                    }
+
+                    KeyCode::Char('k') => {
+                        toggle_cookie_manager(uistate);
+                        return Ok(false);
+                    }
+
+                    _ => {}
+                }
+            }
+
+            if key.code == KeyCode::F(2) {
+                toggle_vim_mode(uistate);
+                return Ok(false);
+            }
+
+            if uistate.export_menu().opened() {
+                process_export_input(key, uistate);
+                return Ok(false);
+            }
+
+            if uistate.history().opened() {
+                process_history_input(key, uistate);
+                return Ok(false);
+            }
+
+            if uistate.runner().opened() {
+                process_runner_input(key, uistate);
+                return Ok(false);
+            }
+
+            if uistate.graphql().browser().opened() {
+                process_schema_browser_input(key, uistate);
+                return Ok(false);
+            }
+
+            if uistate.cookies().opened() {
+                process_cookie_manager_input(key, uistate);
+                return Ok(false);
+            }
+
+            if uistate.sidebar().focused() {
+                process_sidebar_input(key, uistate);
+                return Ok(false);
+            }
+
+            if uistate.response().filter().editing() {
+                process_filter_input(key, uistate);
+                return Ok(false);
+            }
+
+            if uistate.search().opened() {
+                process_search_input(key, uistate);
+                return Ok(false);
+            }
+
+            // Ctrl+Shift+Z reaches us as Ctrl+Z in most terminals, so
+            // Ctrl+Y redoes as well
+            if key.modifiers.contains(KeyModifiers::CONTROL) {
+                match key.code {
+                    KeyCode::Char('z')
+                        if !key.modifiers.contains(KeyModifiers::SHIFT) =>
+                    {
+                        undo(uistate);
+                        return Ok(false);
+                    }
+
+                    KeyCode::Char('z') | KeyCode::Char('Z')
+                        | KeyCode::Char('y') =>
+                    {
+                        redo(uistate);
+                        return Ok(false);
+                    }
+
+                    KeyCode::Char('f') => {
+                        open_search(uistate, false);
+                        return Ok(false);
+                    }
+
+                    // Find and replace in the body editor
+                    KeyCode::Char('h') => {
+                        open_search(uistate, true);
+                        return Ok(false);
+                    }
+
+                    _ => {}
+                }
+            }
+
+            if process_completion_input(key, uistate) {
+                return Ok(false);
+            }
+
+            if uistate.editor_mode() == EditorMode::VIM {
+                match process_vim_input(key, uistate) {
+                    VimInput::Handled => { return Ok(false); }
+
+                    VimInput::Exit => {
+                        info!("Exiting normally");
+                        return Ok(true);
+                    }
+
+                    VimInput::Ignored => {}
+                }
+            }
+
             if key.code == KeyCode::Esc {
+                // Esc aborts the request in flight or closes the WebSocket
+                // connection before it exits the app
+                if worker::cancel(uistate) || ws_worker::close(uistate) {
+                    return Ok(false);
+                }
+
+                // The VIM editor mode exits with `:q`
+                if uistate.editor_mode() == EditorMode::VIM {
+                    return Ok(false);
+                }
+
                 info!("Exiting normally");
                 return Ok(true);
             }
 
+            // The message log of a WebSocket request replaces the response
+            if uistate.active_element() == &UIElement::ResponseArea
+                && is_websocket(uistate)
+                && process_websocket_input(key, uistate)
+            {
+                return Ok(false);
+            }
+
+            // The tree view of the response has keys of its own
+            if uistate.active_element() == &UIElement::ResponseArea
+                && process_json_tree_input(key, uistate)
+            {
+                return Ok(false);
+            }
+
+            // The text field before the key, to record the edit
+            let edited = active_text_field(uistate)
+                .map(|field| (field, field_state(uistate, field)));
+
             match uistate.active_element() {
                 UIElement::URL => {
                     match key.code {
+                        KeyCode::Char('v')
+                            if key.modifiers == KeyModifiers::CONTROL =>
+                        {
+                            paste_url(uistate);
+                        }
+
                         KeyCode::Char(c) => {
                             uistate.append_url(c);
                             let url = uistate.url();
@@ -43,11 +273,12 @@ pub fn process_user_input(uistate: &mut UiState) -> Result<bool, Error> {
                         }
 
                         KeyCode::Enter => {
-                            uistate.set_app_status(AppStatus::PROCESSING);
-
-                            match call_api(uistate) {
-                                Ok(()) => {},
-                                Err(_e) => {},
+                            // A curl command typed or pasted in the url bar
+                            // is imported instead of being sent
+                            if is_curl_command(&uistate.url()) {
+                                import_curl(uistate, &uistate.url());
+                            } else {
+                                send_request(uistate);
                             }
                         }
 
@@ -64,7 +295,7 @@ pub fn process_user_input(uistate: &mut UiState) -> Result<bool, Error> {
 
                     match uistate.active_request_tab() {
                         RequestTabs::UrlParams => {
-                            process_kv_tab_input(key, row, col, |op| {
+                            process_kv_tab_input(key, row, col, KV_TAB_COLUMNS, |op| {
                                 match op {
                                     KVTabOperation::Insert(pos) => {
                                         uistate.insert_url_param(
@@ -148,7 +379,7 @@ pub fn process_user_input(uistate: &mut UiState) -> Result<bool, Error> {
                             let row = uistate.request_headers_ui().active_row();
                             let col = uistate.request_headers_ui().active_col();
 
-                            process_kv_tab_input(key, row, col, |op| {
+                            process_kv_tab_input(key, row, col, KV_TAB_COLUMNS, |op| {
                                 match op {
                                     KVTabOperation::Insert(pos) => {
                                         uistate.insert_header(
@@ -219,141 +450,44 @@ pub fn process_user_input(uistate: &mut UiState) -> Result<bool, Error> {
                             );
                         }
 
-                        RequestTabs::Body => {
-                            let body = uistate.body_mut();
-
-                            match body.clone().active_body_element() {
-                                BodyUIElement::TextArea => {
-                                    match key.code {
-                                        KeyCode::Up => {
-                                            if key.modifiers == KeyModifiers::CONTROL {
-                                                uistate.body_mut()
-                                                    .set_active_body_element(
-                                                        BodyUIElement::ContentType(false)
-                                                    );
-                                            } else {
-                                                uistate.body_mut()
-                                                    .text_data_mut()
-                                                    .move_cursor(
-                                                        TextEditMoveDirection::Up,
-                                                        false,
-                                                        key.modifiers == KeyModifiers::SHIFT,
-                                                    );
-                                            }
-                                        }
-                                        
-                                        KeyCode::Down => {
-                                            uistate.body_mut()
-                                                .text_data_mut()
-                                                .move_cursor(
-                                                    TextEditMoveDirection::Down,
-                                                    false,
-                                                    key.modifiers == KeyModifiers::SHIFT,
-                                                );
-                                        }
-
-                                        KeyCode::Left => {
-                                            uistate.body_mut()
-                                                .text_data_mut()
-                                                .move_cursor(
-                                                    TextEditMoveDirection::Left,
-                                                    key.modifiers.contains(KeyModifiers::CONTROL),
-                                                    key.modifiers.contains(KeyModifiers::SHIFT),
-                                                );
-                                        }
-
-                                        KeyCode::Right => {
-                                            uistate.body_mut()
-                                                .text_data_mut()
-                                                .move_cursor(
-                                                    TextEditMoveDirection::Right,
-                                                    key.modifiers.contains(KeyModifiers::CONTROL),
-                                                    key.modifiers.contains(KeyModifiers::SHIFT),
-                                                );
-                                        }
-
-                                        KeyCode::End => {
-                                            uistate.body_mut().text_data_mut()
-                                                .move_cursor(
-                                                    TextEditMoveDirection::End,
-                                                    false,
-                                                    key.modifiers == KeyModifiers::SHIFT,
-                                                );
-                                        }
-
-                                        KeyCode::Home => {
-                                            uistate.body_mut().text_data_mut()
-                                                .move_cursor(
-                                                    TextEditMoveDirection::Home,
-                                                    false,
-                                                    key.modifiers == KeyModifiers::SHIFT,
-                                                );
-                                        }
-
-                                        KeyCode::Enter => {
-                                            uistate.body_mut().text_data_mut().new_line();
-                                        }
-
-                                        KeyCode::Char(c) => {
-                                            if key.modifiers.contains(KeyModifiers::CONTROL) {
-                                                match c {
-                                                    'a' | 'A' => {
-                                                        uistate.body_mut()
-                                                            .text_data_mut()
-                                                            .select_all();
-                                                    }
-
-                                                    'c' | 'C' => {
-                                                        uistate.body_mut()
-                                                            .text_data_mut()
-                                                            .copy_selected();
-                                                    }
+                        RequestTabs::Authorization => {
+                            process_auth_input(key, uistate.auth_mut());
+                        }
 
-                                                    'v' | 'V' => {
-                                                        uistate.body_mut()
-                                                            .text_data_mut()
-                                                            .paste();
-                                                    }
+                        RequestTabs::Settings => {
+                            process_settings_input(key, uistate.settings_mut());
+                        }
 
-                                                    _ => {}
-                                                }
-                                            } else {
-                                                uistate.body_mut().text_data_mut()
-                                                    .insert_char(c);
-                                            }
-                                        }
+                        RequestTabs::Scripts => {
+                            process_scripts_input(key, uistate);
+                        }
 
-                                        KeyCode::Backspace => {
-                                            if key.modifiers == KeyModifiers::CONTROL {
-                                                uistate.body_mut()
-                                                    .text_data_mut()
-                                                    .delete_word();
-                                            } else if uistate.body().text_data().selecting() {
-                                                uistate.body_mut()
-                                                    .text_data_mut()
-                                                    .delete_selected();
-                                            } else {
-                                                uistate.body_mut()
-                                                    .text_data_mut()
-                                                    .delete_char();
-                                            }
-                                        }
+                        RequestTabs::Assertions => {
+                            process_text_area_input(key, uistate.assertions_mut());
+                        }
 
-                                        KeyCode::Delete => {
-                                            if key.modifiers == KeyModifiers::CONTROL {
-                                                uistate.body_mut().text_data_mut()
-                                                    .delete_word_to_right();
-                                            } else if uistate.body().text_data().selecting() {
-                                                uistate.body_mut()
-                                                    .text_data_mut()
-                                                    .delete_selected();
-                                            } else {
-                                                uistate.body_mut().text_data_mut()
-                                                    .delete_char_to_right();
-                                            }
-                                        }
+                        RequestTabs::Body => {
+                            let body = uistate.body_mut();
 
-                                        _ => {}
+                            match body.clone().active_body_element() {
+                                BodyUIElement::TextArea => {
+                                    if key.code == KeyCode::Up
+                                        && key.modifiers == KeyModifiers::CONTROL
+                                    {
+                                        uistate.body_mut().set_active_body_element(
+                                            BodyUIElement::ContentType(false)
+                                        );
+                                    } else if key.code == KeyCode::Down
+                                        && key.modifiers == KeyModifiers::CONTROL
+                                        && *uistate.body().body_content() == BodyContent::GraphQL
+                                    {
+                                        uistate.body_mut().set_active_body_element(
+                                            BodyUIElement::Variables
+                                        );
+                                    } else {
+                                        process_text_area_input(
+                                            key, uistate.body_mut().text_data_mut(),
+                                        );
                                     }
                                 }
 
@@ -406,6 +540,13 @@ pub fn process_user_input(uistate: &mut UiState) -> Result<bool, Error> {
                                                 body.set_active_body_element(
                                                     BodyUIElement::ContentType(false)
                                                 );
+
+                                                // GraphQL queries are posted
+                                                if *uistate.body().body_content() == BodyContent::GraphQL
+                                                    && uistate.method() == Method::GET
+                                                {
+                                                    uistate.set_method(Method::POST);
+                                                }
                                             } else {
                                                 uistate.body_mut().set_active_body_element(
                                                     BodyUIElement::ContentType(true)
@@ -448,7 +589,8 @@ pub fn process_user_input(uistate: &mut UiState) -> Result<bool, Error> {
                                                         body.set_active_body_element(BodyUIElement::KVArea);
                                                     }
 
-                                                    BodyContent::Text | BodyContent::Html | BodyContent::Xml => {
+                                                    BodyContent::Text | BodyContent::Json | BodyContent::Html | BodyContent::Xml
+                                                        | BodyContent::GraphQL => {
                                                         body.set_active_body_element(BodyUIElement::TextArea);
                                                     }
 
@@ -457,22 +599,66 @@ pub fn process_user_input(uistate: &mut UiState) -> Result<bool, Error> {
                                             }
                                         }
 
+                                        KeyCode::Right if !*opened
+                                            && key.modifiers == KeyModifiers::CONTROL
+                                            && *uistate.body().body_content() == BodyContent::GraphQL =>
+                                        {
+                                            uistate.body_mut().set_active_body_element(
+                                                BodyUIElement::Operation(false)
+                                            );
+                                        }
+
                                         _ => {}
                                     }
                                 }
 
+                                BodyUIElement::Variables => {
+                                    if key.code == KeyCode::Up
+                                        && key.modifiers == KeyModifiers::CONTROL
+                                    {
+                                        uistate.body_mut().set_active_body_element(
+                                            BodyUIElement::TextArea
+                                        );
+                                    } else {
+                                        process_text_area_input(
+                                            key, uistate.body_mut().variables_mut(),
+                                        );
+                                    }
+                                }
+
+                                BodyUIElement::Operation(opened) => {
+                                    process_operation_input(key, uistate, *opened);
+                                }
+
                                 BodyUIElement::KVArea => {
                                     match body.body_content() {
                                         BodyContent::FormData | BodyContent::FormURLEncoded => {
+                                            let is_form_data = *body.body_content() == BodyContent::FormData;
                                             let row = body.kv_tab_state().active_row();
                                             let col = body.kv_tab_state().active_col();
+                                            let col_count = if is_form_data {
+                                                FORM_DATA_COLUMNS
+                                            } else {
+                                                KV_TAB_COLUMNS
+                                            };
 
                                             if row == 0 && key.code == KeyCode::Up {
                                                 body.set_active_body_element(BodyUIElement::ContentType(false));
                                                 return Ok(false);
                                             }
 
-                                            process_kv_tab_input(key, row, col, |op| {
+                                            // Enter or space toggles the part type between text and file
+                                            if is_form_data && col == 2
+                                                && matches!(key.code, KeyCode::Enter | KeyCode::Char(' '))
+                                            {
+                                                if let Some(kv_data) = body.kv_data_mut().get_mut(row as usize) {
+                                                    kv_data.set_part_kind(kv_data.part_kind().toggled());
+                                                }
+
+                                                return Ok(false);
+                                            }
+
+                                            process_kv_tab_input(key, row, col, col_count, |op| {
                                                 match op {
                                                     KVTabOperation::Insert(pos) => {
                                                         body.kv_data_mut().insert(pos as usize, KVData::default());
@@ -497,54 +683,20 @@ pub fn process_user_input(uistate: &mut UiState) -> Result<bool, Error> {
                                                     }
 
                                                     KVTabOperation::AppendText(c) => {
+                                                        if let Some(kv_data) = body.kv_data_mut().get_mut(row as usize) {
+                                                            let mut text = kv_data.field(col, is_form_data);
 
-                                                        let vec_mut = body.kv_data_mut();
-
-                                                        match vec_mut.get_mut(row as usize) {
-                                                            Some(kv_data) => {
-                                                                if col == 0 {
-                                                                    let mut key = kv_data.key();
-                                                                    key.push(c);
-
-                                                                    kv_data.set_key(key);
-                                                                }
-
-                                                                if col == 1 {
-                                                                    let mut val = kv_data.value();
-                                                                    val.push(c);
-
-                                                                    kv_data.set_value(val);
-                                                                }
-                                                            }
-
-                                                            None => {}
+                                                            text.push(c);
+                                                            kv_data.set_field(col, is_form_data, text);
                                                         }
                                                     }
 
                                                     KVTabOperation::PopText() => {
-                                                        let row = body.kv_tab_state().active_row();
-                                                        let col = body.kv_tab_state().active_col();
-
-                                                        let vec_mut = body.kv_data_mut();
-
-                                                        match vec_mut.get_mut(row as usize) {
-                                                            Some(kv_data) => {
-                                                                if col == 0 {
-                                                                    let mut key = kv_data.key();
-                                                                    key.pop();
-
-                                                                    kv_data.set_key(key);
-                                                                }
+                                                        if let Some(kv_data) = body.kv_data_mut().get_mut(row as usize) {
+                                                            let mut text = kv_data.field(col, is_form_data);
 
-                                                                if col == 1 {
-                                                                    let mut val = kv_data.value();
-                                                                    val.pop();
-
-                                                                    kv_data.set_value(val);
-                                                                }
-                                                            }
-
-                                                            None => {}
+                                                            text.pop();
+                                                            kv_data.set_field(col, is_form_data, text);
                                                         }
                                                     }
                                                 }},
@@ -630,8 +782,6 @@ pub fn process_user_input(uistate: &mut UiState) -> Result<bool, Error> {
 //                                }
                             }
                         }
-
-                        _ => {}
                     }
                 }
 
@@ -663,6 +813,10 @@ pub fn process_user_input(uistate: &mut UiState) -> Result<bool, Error> {
                         KeyCode::Up => {
                             let new_pos: i32 = (pos as i32) - scroll_by as i32;
 
+                            // Stops following the body or the events as
+                            // they arrive
+                            uistate.response_mut().set_follow(false);
+
                             if new_pos >= 0 {
                                 uistate.response_mut()
                                     .set_scroll_pos(new_pos as u16);
@@ -674,53 +828,47 @@ pub fn process_user_input(uistate: &mut UiState) -> Result<bool, Error> {
                         KeyCode::Down => {
                             let new_pos = pos + scroll_by;
 
-                            if new_pos < uistate.response().response().len() as u16 {
+                            if new_pos < uistate.response().line_count() as u16 {
                                 uistate.response_mut().set_scroll_pos(new_pos);
                             }
                         }
 
+                        KeyCode::Right => {
+                            uistate.response_mut().activate_next_tab();
+                        }
+
+                        KeyCode::Left => {
+                            uistate.response_mut().activate_previous_tab();
+                        }
+
+                        KeyCode::Char('t') => { toggle_tree_view(uistate); }
+                        KeyCode::Char('f') => { open_filter(uistate); }
+                        KeyCode::Char('/') => { open_search(uistate, false); }
+
                         _ => {}
                     }
                 }
 
                 UIElement::SendButton => {
-                    match key.code {
-                        KeyCode::Enter => {
-                            let req_counter = uistate.request_counter();
-                            info!(
-                                "Making call (#{}) to: {}",
-                                req_counter,
-                                uistate.url_deconst().to_string()
-                            );
-
-                            match call_api(uistate) {
-                                Ok(_) => {
-                                    info!("No Errors for #{}", req_counter);
-                                }
-
-                                Err(msg) => {
-                                    let source: String;
-
-                                    match msg.source() {
-                                        Some(err) => source = err.to_string(),
-                                        None => source = String::default(),
-                                    }
-
-                                    info!("{}", msg.to_string());
-
-                                    uistate.set_app_error(msg.to_string());
-                                    uistate.set_app_status(AppStatus::ERROR);
-                                }
-                            }
-                        }
-
-                        _ => {}
+                    if key.code == KeyCode::Enter {
+                        send_request(uistate);
                     }
                 }
 
+                UIElement::Environment => {
+                    process_environment_input(key, uistate);
+                }
+
                 UIElement::Method => {
                     match key.code {
+                        // W switches to WebSocket, the methods back to HTTP
+                        KeyCode::Char('w') | KeyCode::Char('W') => {
+                            uistate.websocket_mut().set_enabled(true);
+                        }
+
                         KeyCode::Char(c) => {
+                            uistate.websocket_mut().set_enabled(false);
+
                             match c.to_digit(10) {
                                 Some(num) => {
                                     if num > 0 {
@@ -755,9 +903,79 @@ pub fn process_user_input(uistate: &mut UiState) -> Result<bool, Error> {
                     uistate.url_deconst().to_string()
                 );
             }
+
+            if let Some((field, before)) = edited {
+                record_edit(uistate, key, field, before);
+            }
+
+            refresh_completion(uistate);
         }
     }
 
     Ok(false)
 }
 
+/// Dispatches the request to the worker, showing the error in the status bar
+/// if the request couldn't be built.
+fn send_request(uistate: &mut UiState) {
+    if is_websocket(uistate) {
+        ws_worker::send(uistate);
+        return;
+    }
+
+    info!("Making call to: {}", uistate.url_deconst().to_string());
+
+    if let Err(msg) = call_api(uistate) {
+        info!("{}", msg);
+
+        uistate.set_app_error(msg.to_string());
+        uistate.set_app_status(AppStatus::ERROR);
+    }
+}
+
+/// Pastes the clipboard into the url bar. A curl command in the clipboard is
+/// imported instead.
+fn paste_url(uistate: &mut UiState) {
+    let contents = ClipboardContext::new()
+        .and_then(|mut ctx| ctx.get_contents());
+
+    match contents {
+        Ok(text) => {
+            if is_curl_command(&text) {
+                import_curl(uistate, &text);
+                return;
+            }
+
+            for c in text.chars().filter(|c| !c.is_control()) {
+                uistate.append_url(c);
+            }
+
+            let url = uistate.url();
+            uistate.url_deconst_mut().update(url);
+        }
+
+        Err(e) => {
+            info!("{}", e);
+
+            uistate.set_app_error(format!("Couldn't read the clipboard: {}", e));
+            uistate.set_app_status(AppStatus::ERROR);
+        }
+    }
+}
+
+/// Replaces the request being edited with the one in the curl `command`.
+fn import_curl(uistate: &mut UiState, command: &str) {
+    match curl::parse(command) {
+        Ok(request) => {
+            info!("Imported curl command");
+            request.apply(uistate);
+        }
+
+        Err(e) => {
+            info!("{}", e);
+
+            uistate.set_app_error(format!("Couldn't import curl: {}", e));
+            uistate.set_app_status(AppStatus::ERROR);
+        }
+    }
+}
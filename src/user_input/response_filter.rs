@@ -0,0 +1,56 @@
+//! Keys of the filter input of the response.
+
+use crossterm::event::{ KeyCode, KeyEvent, KeyModifiers };
+
+use crate::ui::state::{ UiState, response_tabs::ResponseTabs };
+
+/// Focuses the filter input of the "Body" tab.
+pub fn open_filter(uistate: &mut UiState) {
+    let response = uistate.response_mut();
+
+    response.set_active_tab(ResponseTabs::Body);
+    response.filter_mut().set_editing(true);
+}
+
+/// Handles the keys while the filter input has the focus. The body is
+/// filtered as the expression is typed.
+pub fn process_filter_input(key: KeyEvent, uistate: &mut UiState) {
+    let response = uistate.response_mut();
+    let filter = response.filter_mut();
+
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            let mut expression = String::from(filter.expression());
+            expression.push(c);
+
+            filter.set_expression(expression);
+        }
+
+        KeyCode::Backspace => {
+            let mut expression = String::from(filter.expression());
+            expression.pop();
+
+            filter.set_expression(expression);
+        }
+
+        KeyCode::Up => { filter.history_back(); }
+        KeyCode::Down => { filter.history_forward(); }
+
+        // Keeps the filter applied
+        KeyCode::Enter => {
+            filter.remember();
+            filter.set_editing(false);
+            return;
+        }
+
+        // Shows the whole body again
+        KeyCode::Esc => {
+            filter.set_expression(String::new());
+            filter.set_editing(false);
+        }
+
+        _ => { return; }
+    }
+
+    response.apply_filter();
+}
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use crossterm::event::{ KeyCode, KeyEvent, KeyModifiers };
+use log::info;
+
+use crate::{
+    runner::{ DEFAULT_REPORT_DIR, save_reports, worker },
+    ui::state::{ UiState, app_status::AppStatus, runner::RunnerField },
+};
+
+/// Opens the runner on the collection `folder`.
+pub fn open_runner(uistate: &mut UiState, folder: PathBuf) {
+    let runner = uistate.runner_mut();
+
+    runner.set_folder(folder);
+    runner.set_opened(true);
+}
+
+/// Processes the key events while the runner is open.
+pub fn process_runner_input(key: KeyEvent, uistate: &mut UiState) {
+    let runner = uistate.runner_mut();
+
+    match key.code {
+        KeyCode::Char('x') if key.modifiers == KeyModifiers::CONTROL => {
+            export_reports(uistate);
+        }
+
+        KeyCode::Tab => {
+            let field = runner.active_field().next();
+            runner.set_active_field(field);
+        }
+
+        KeyCode::BackTab => {
+            let field = runner.active_field().previous();
+            runner.set_active_field(field);
+        }
+
+        KeyCode::Up => { runner.scroll(-1); }
+        KeyCode::Down => { runner.scroll(1); }
+        KeyCode::PageUp => { runner.scroll(-10); }
+        KeyCode::PageDown => { runner.scroll(10); }
+
+        KeyCode::Char(c) => match runner.active_field() {
+            RunnerField::DataFile => { runner.data_file_mut().push(c); }
+            RunnerField::Delay if c.is_ascii_digit() => {
+                runner.delay_mut().push(c);
+            }
+
+            RunnerField::StopOnFailure if c == ' ' => {
+                let stop_on_failure = runner.stop_on_failure();
+                runner.set_stop_on_failure(!stop_on_failure);
+            }
+
+            _ => {}
+        },
+
+        KeyCode::Backspace => match runner.active_field() {
+            RunnerField::DataFile => { runner.data_file_mut().pop(); }
+            RunnerField::Delay => { runner.delay_mut().pop(); }
+            RunnerField::StopOnFailure => {}
+        },
+
+        KeyCode::Enter => { worker::start(uistate); }
+
+        // Esc stops the run first, then closes the runner
+        KeyCode::Esc => match runner.running() {
+            true => { worker::cancel(uistate); }
+            false => { runner.set_opened(false); }
+        },
+
+        _ => {}
+    }
+}
+
+/// Saves the JUnit XML and JSON reports of the last run.
+fn export_reports(uistate: &mut UiState) {
+    let report = match uistate.runner().report() {
+        Some(report) => report.clone(),
+        None => { return; }
+    };
+
+    match save_reports(&PathBuf::from(DEFAULT_REPORT_DIR), &report) {
+        Ok((junit_path, json_path)) => {
+            let message = format!(
+                "Saved the reports to {} and {}",
+                junit_path.display(),
+                json_path.display(),
+            );
+
+            info!("{}", message);
+
+            uistate.set_app_message(message);
+            uistate.set_app_status(AppStatus::INFO);
+        }
+
+        Err(e) => {
+            info!("Couldn't save the reports: {}", e);
+
+            uistate.set_app_error(format!("Couldn't save the reports: {}", e));
+            uistate.set_app_status(AppStatus::ERROR);
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! Keys of the "Scripts" tab.
+
+use crossterm::event::{ KeyCode, KeyEvent, KeyModifiers };
+
+use crate::{
+    ui::state::{ UiState, scripts::ScriptKind },
+    user_input::text_area::process_text_area_input,
+};
+
+/// Edits the active script. Ctrl+Up and Ctrl+Down move between the
+/// pre-request and the post-response script.
+pub fn process_scripts_input(key: KeyEvent, uistate: &mut UiState) {
+    let scripts = uistate.scripts_mut();
+
+    match key.code {
+        KeyCode::Up if key.modifiers == KeyModifiers::CONTROL => {
+            scripts.set_active(ScriptKind::PreRequest);
+        }
+
+        KeyCode::Down if key.modifiers == KeyModifiers::CONTROL => {
+            scripts.set_active(ScriptKind::PostResponse);
+        }
+
+        _ => {
+            let active = scripts.active();
+
+            process_text_area_input(key, scripts.editor_mut(active));
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Keys of the search bar of the response and the body editor.
+
+use crossterm::event::{ KeyCode, KeyEvent, KeyModifiers };
+
+use crate::ui::state::{
+    UiState, UIElement, response_tabs::ResponseTabs,
+    edit_history::EditKind, search::SearchTarget,
+    text_field::{
+        TextField, FieldState, active_text_field, field_state, set_field_state,
+    },
+};
+
+/// Opens the search bar of the response or of the body editor, whichever is
+/// active. `replacing` shows the replacement input in the body editor.
+pub fn open_search(uistate: &mut UiState, replacing: bool) {
+    let target = if uistate.active_element() == &UIElement::ResponseArea {
+        // The matches are shown in the text view of the body
+        let response = uistate.response_mut();
+        let _ = response.set_tree_view(false);
+        response.set_active_tab(ResponseTabs::Body);
+
+        SearchTarget::Response
+    } else if active_text_field(uistate) == Some(TextField::BodyText) {
+        SearchTarget::Body
+    } else {
+        return;
+    };
+
+    uistate.search_mut().open(target, replacing);
+    find(uistate);
+}
+
+/// Handles the keys while the search bar is opened. The matches are found as
+/// the query is typed.
+pub fn process_search_input(key: KeyEvent, uistate: &mut UiState) {
+    let alt_down = key.modifiers.contains(KeyModifiers::ALT);
+    let search = uistate.search_mut();
+
+    match key.code {
+        KeyCode::Char('c') | KeyCode::Char('C') if alt_down => {
+            search.toggle_case_sensitive();
+            find(uistate);
+        }
+
+        KeyCode::Char('r') | KeyCode::Char('R') if alt_down => {
+            search.toggle_regex();
+            find(uistate);
+        }
+
+        KeyCode::Char('a') | KeyCode::Char('A') if alt_down => {
+            replace_all(uistate);
+        }
+
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            search.input_mut().push(c);
+
+            if !search.replacement_focused() {
+                find(uistate);
+            }
+        }
+
+        KeyCode::Backspace => {
+            search.input_mut().pop();
+
+            if !search.replacement_focused() {
+                find(uistate);
+            }
+        }
+
+        KeyCode::Tab | KeyCode::BackTab => { search.toggle_focus(); }
+
+        KeyCode::Enter if search.replacement_focused() => {
+            replace_current(uistate);
+        }
+
+        KeyCode::Enter | KeyCode::Down => {
+            search.next();
+            jump(uistate);
+        }
+
+        KeyCode::Up => {
+            search.previous();
+            jump(uistate);
+        }
+
+        KeyCode::Esc => { search.close(); }
+
+        _ => {}
+    }
+}
+
+/// The lines that are searched.
+fn searched_lines(uistate: &UiState) -> Vec<String> {
+    match uistate.search().target() {
+        SearchTarget::Response => uistate.response().body_lines().clone(),
+        SearchTarget::Body => uistate.body().text_data().text_vec(),
+    }
+}
+
+/// Finds the matches of the query, jumping to the first one from the top of
+/// the response or from the cursor of the body editor.
+fn find(uistate: &mut UiState) {
+    let lines = searched_lines(uistate);
+
+    let from = match uistate.search().target() {
+        SearchTarget::Response => {
+            let response = uistate.response();
+
+            response.line_starts().get(response.scroll_pos() as usize)
+                .copied()
+                .unwrap_or_default()
+        }
+
+        SearchTarget::Body => field_state(uistate, TextField::BodyText).cursor,
+    };
+
+    uistate.search_mut().find(&lines, from);
+    jump(uistate);
+}
+
+/// Scrolls the response or moves the cursor of the body editor to the
+/// current match.
+fn jump(uistate: &mut UiState) {
+    let Some(found) = uistate.search().current_match() else { return; };
+
+    match uistate.search().target() {
+        SearchTarget::Response => {
+            uistate.response_mut().scroll_to(found.line, found.start);
+        }
+
+        SearchTarget::Body => {
+            let mut state = field_state(uistate, TextField::BodyText);
+
+            state.cursor = (found.line, found.start);
+            state.selection = None;
+
+            set_field_state(uistate, TextField::BodyText, &state);
+        }
+    }
+}
+
+/// Replaces the current match in the body editor and jumps to the next one.
+fn replace_current(uistate: &mut UiState) {
+    let lines = searched_lines(uistate);
+    let Some(found) = uistate.search().current_match() else { return; };
+
+    if let Some(replaced) = uistate.search().replace_current(&lines) {
+        // The search goes on after the replacement
+        let old_len = lines[found.line].chars().count();
+        let new_len = replaced[found.line].chars().count();
+        let cursor = (found.line, (found.end + new_len).saturating_sub(old_len));
+
+        replace_text(uistate, replaced, cursor);
+    }
+}
+
+/// Replaces all the matches in the body editor.
+fn replace_all(uistate: &mut UiState) {
+    let lines = searched_lines(uistate);
+    let cursor = field_state(uistate, TextField::BodyText).cursor;
+
+    if let Some(replaced) = uistate.search().replace_all(&lines) {
+        replace_text(uistate, replaced, cursor);
+    }
+}
+
+/// Puts `lines` in the body editor as an edit that can be undone, with the
+/// cursor at `cursor`, then finds the matches again.
+fn replace_text(
+    uistate: &mut UiState, lines: Vec<String>, cursor: (usize, usize)
+) {
+    if uistate.search().target() != SearchTarget::Body {
+        return;
+    }
+
+    let before = field_state(uistate, TextField::BodyText);
+
+    if lines == before.lines {
+        return;
+    }
+
+    let len = lines.get(cursor.0).map(|l| l.chars().count()).unwrap_or_default();
+
+    let after = FieldState {
+        lines,
+        cursor: (cursor.0, cursor.1.min(len)),
+        selection: None,
+    };
+
+    set_field_state(uistate, TextField::BodyText, &after);
+
+    uistate.edit_history_mut()
+        .record(TextField::BodyText, before, after, EditKind::Other);
+
+    find(uistate);
+}
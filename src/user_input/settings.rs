@@ -0,0 +1,82 @@
+use crossterm::event::{ KeyCode, KeyModifiers, KeyEvent };
+
+use crate::ui::state::settings::{ Settings, SettingsUIElement, TlsVerification };
+
+/// Processes the key events when the "Settings" tab content is active.
+pub fn process_settings_input(key: KeyEvent, settings: &mut Settings) {
+    let ctrl_down = key.modifiers == KeyModifiers::CONTROL;
+
+    match settings.active_settings_element().clone() {
+        SettingsUIElement::VerifyTls(opened) => {
+            match key.code {
+                KeyCode::Enter => {
+                    if opened {
+                        let index = settings.tls_verification_sel_index() as usize;
+                        let selected_option = settings.tls_verification_options()
+                            [index].clone();
+
+                        settings.set_tls_verification(
+                            TlsVerification::from_str(selected_option.as_str())
+                        );
+                    }
+
+                    settings.set_active_settings_element(
+                        SettingsUIElement::VerifyTls(!opened)
+                    );
+                }
+
+                KeyCode::Up if opened => {
+                    let s = settings.tls_verification_sel_index();
+
+                    if s > 0 {
+                        settings.set_tls_verification_sel_index(s - 1);
+                    }
+                }
+
+                KeyCode::Down if opened => {
+                    let s = settings.tls_verification_sel_index() + 1;
+
+                    if (s as usize) < settings.tls_verification_options().len() {
+                        settings.set_tls_verification_sel_index(s);
+                    }
+                }
+
+                KeyCode::Down | KeyCode::Right if ctrl_down => {
+                    settings.activate_next_element();
+                }
+
+                KeyCode::Up | KeyCode::Left if ctrl_down => {
+                    settings.activate_previous_element();
+                }
+
+                _ => {}
+            }
+        }
+
+        SettingsUIElement::Field(field) => {
+            match key.code {
+                KeyCode::Down | KeyCode::Right if ctrl_down => {
+                    settings.activate_next_element();
+                }
+
+                KeyCode::Up | KeyCode::Left if ctrl_down => {
+                    settings.activate_previous_element();
+                }
+
+                KeyCode::Char(c) => {
+                    if let Some(text) = settings.field_mut(field) {
+                        text.push(c);
+                    }
+                }
+
+                KeyCode::Backspace => {
+                    if let Some(text) = settings.field_mut(field) {
+                        text.pop();
+                    }
+                }
+
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,373 @@
+use std::path::{ Path, PathBuf };
+
+use crossterm::event::{ KeyCode, KeyEvent };
+use log::info;
+
+use crate::{
+    collection::{
+        SavedRequest, load_request, save_request, rename_entry, delete_entry,
+        request_path,
+    },
+    ui::state::{
+        UiState, UIElement, app_status::AppStatus,
+        sidebar::{ SidebarPrompt, SidebarPromptKind },
+    },
+    user_input::runner::open_runner,
+};
+
+/// Shows and focuses the sidebar, or hides it when it is already focused.
+pub fn toggle_sidebar(uistate: &mut UiState) {
+    let sidebar = uistate.sidebar_mut();
+
+    if sidebar.visible() && sidebar.focused() {
+        sidebar.set_visible(false);
+        return;
+    }
+
+    sidebar.set_visible(true);
+    sidebar.set_focused(true);
+
+    if let Err(e) = sidebar.reload() {
+        show_error(uistate, format!("Couldn't read the collection: {}", e));
+    }
+}
+
+/// Saves the request being edited to its collection file. A request that
+/// hasn't been saved yet asks for a name in the sidebar first.
+pub fn save_current_request(uistate: &mut UiState) {
+    match uistate.current_request_path().clone() {
+        Some(path) => {
+            let request = SavedRequest::from_uistate(uistate);
+
+            match save_request(&path, &request) {
+                Ok(stripped) => {
+                    info!("Saved request to {}", path.display());
+
+                    if stripped {
+                        warn_unsaved_secret(uistate);
+                    }
+                }
+
+                Err(e) => {
+                    show_error(uistate, format!("Couldn't save: {}", e));
+                }
+            }
+        }
+
+        None => {
+            if !uistate.sidebar().focused() {
+                toggle_sidebar(uistate);
+            }
+
+            open_save_as_prompt(uistate);
+        }
+    }
+}
+
+fn open_save_as_prompt(uistate: &mut UiState) {
+    let sidebar = uistate.sidebar_mut();
+    let folder = sidebar.selected_folder_name();
+
+    sidebar.set_prompt(
+        Some(SidebarPrompt::new(SidebarPromptKind::SaveAs, folder))
+    );
+}
+
+/// Processes the key events when the sidebar is focused.
+pub fn process_sidebar_input(key: KeyEvent, uistate: &mut UiState) {
+    if uistate.sidebar().prompt().is_some() {
+        process_prompt_input(key, uistate);
+        return;
+    }
+
+    match key.code {
+        KeyCode::Up => { uistate.sidebar_mut().move_selection(-1); }
+        KeyCode::Down => { uistate.sidebar_mut().move_selection(1); }
+        KeyCode::PageUp => { uistate.sidebar_mut().move_selection(-10); }
+        KeyCode::PageDown => { uistate.sidebar_mut().move_selection(10); }
+
+        KeyCode::Enter | KeyCode::Right => {
+            let selected = match uistate.sidebar().selected() {
+                Some(item) => item.clone(),
+                None => { return; }
+            };
+
+            if selected.is_folder() {
+                let sidebar = uistate.sidebar_mut();
+                let expanded = sidebar.is_expanded(selected.path());
+
+                if key.code == KeyCode::Enter || !expanded {
+                    sidebar.set_expanded(selected.path(), !expanded);
+                    reload(uistate);
+                }
+            } else if key.code == KeyCode::Enter {
+                open_request(uistate, selected.path());
+            }
+        }
+
+        KeyCode::Left => {
+            let selected = match uistate.sidebar().selected() {
+                Some(item) => item.clone(),
+                None => { return; }
+            };
+
+            let sidebar = uistate.sidebar_mut();
+
+            if selected.is_folder() && sidebar.is_expanded(selected.path()) {
+                sidebar.set_expanded(selected.path(), false);
+                reload(uistate);
+            } else if let Some(parent) = selected.path().parent() {
+                sidebar.select_path(parent);
+                sidebar.move_selection(0);
+            }
+        }
+
+        KeyCode::Char('a') => { open_save_as_prompt(uistate); }
+
+        KeyCode::Char('r') => {
+            if let Some(item) = uistate.sidebar().selected() {
+                let name = String::from(item.name());
+
+                uistate.sidebar_mut().set_prompt(
+                    Some(SidebarPrompt::new(SidebarPromptKind::Rename, name))
+                );
+            }
+        }
+
+        // Runs the selected folder, or the folder of the selected request
+        KeyCode::Char('R') => {
+            let folder = match uistate.sidebar().selected() {
+                Some(item) if item.is_folder() => item.path().to_path_buf(),
+                Some(item) => item.path().parent()
+                    .map(|parent| parent.to_path_buf())
+                    .unwrap_or_else(|| uistate.sidebar().root().to_path_buf()),
+                None => uistate.sidebar().root().to_path_buf(),
+            };
+
+            open_runner(uistate, folder);
+        }
+
+        KeyCode::Char('d') | KeyCode::Delete
+            if uistate.sidebar().selected().is_some() =>
+        {
+            uistate.sidebar_mut().set_prompt(Some(SidebarPrompt::new(
+                SidebarPromptKind::Delete,
+                String::default(),
+            )));
+        }
+
+        KeyCode::Esc => { uistate.sidebar_mut().set_visible(false); }
+
+        KeyCode::Tab | KeyCode::BackTab => {
+            uistate.sidebar_mut().set_focused(false);
+        }
+
+        _ => {}
+    }
+}
+
+fn process_prompt_input(key: KeyEvent, uistate: &mut UiState) {
+    let prompt = match uistate.sidebar().prompt() {
+        Some(prompt) => prompt.clone(),
+        None => { return; }
+    };
+
+    if *prompt.kind() == SidebarPromptKind::Delete {
+        uistate.sidebar_mut().set_prompt(None);
+
+        if key.code == KeyCode::Char('y') {
+            delete_selected(uistate);
+        }
+
+        return;
+    }
+
+    match key.code {
+        KeyCode::Char(c) => {
+            if let Some(prompt) = uistate.sidebar_mut().prompt_mut() {
+                prompt.input_mut().push(c);
+            }
+        }
+
+        KeyCode::Backspace => {
+            if let Some(prompt) = uistate.sidebar_mut().prompt_mut() {
+                prompt.input_mut().pop();
+            }
+        }
+
+        KeyCode::Esc => { uistate.sidebar_mut().set_prompt(None); }
+
+        KeyCode::Enter => {
+            let input = String::from(prompt.input().trim());
+
+            if input.is_empty() {
+                return;
+            }
+
+            uistate.sidebar_mut().set_prompt(None);
+
+            match prompt.kind() {
+                SidebarPromptKind::SaveAs => { save_as(uistate, &input); }
+                SidebarPromptKind::Rename => { rename_selected(uistate, &input); }
+                SidebarPromptKind::Delete => {}
+            }
+        }
+
+        _ => {}
+    }
+}
+
+fn open_request(uistate: &mut UiState, path: &Path) {
+    match load_request(path) {
+        Ok(request) => {
+            request.apply(uistate);
+            uistate.set_current_request_path(Some(path.to_path_buf()));
+            uistate.sidebar_mut().set_focused(false);
+            uistate.set_active_element(UIElement::URL);
+        }
+
+        Err(e) => {
+            show_error(
+                uistate,
+                format!("Couldn't open {}: {}", path.display(), e),
+            );
+        }
+    }
+}
+
+fn save_as(uistate: &mut UiState, name: &str) {
+    let root = uistate.sidebar().root().to_path_buf();
+    let path = match request_path(&root, name) {
+        Ok(path) => path,
+
+        Err(e) => {
+            show_error(uistate, format!("Couldn't save: {}", e));
+            return;
+        }
+    };
+
+    if path.exists() {
+        show_error(uistate, format!("{} already exists", name));
+        return;
+    }
+
+    let request = SavedRequest::from_uistate(uistate);
+
+    let stripped = match save_request(&path, &request) {
+        Ok(stripped) => stripped,
+        Err(e) => {
+            show_error(uistate, format!("Couldn't save: {}", e));
+            return;
+        }
+    };
+
+    info!("Saved request to {}", path.display());
+
+    if stripped {
+        warn_unsaved_secret(uistate);
+    }
+    uistate.set_current_request_path(Some(path.clone()));
+
+    // Expand the folders leading to the new request so it can be seen
+    let sidebar = uistate.sidebar_mut();
+    let mut folder = path.parent();
+
+    while let Some(f) = folder {
+        if f == root || !f.starts_with(&root) {
+            break;
+        }
+
+        sidebar.set_expanded(f, true);
+        folder = f.parent();
+    }
+
+    reload(uistate);
+    uistate.sidebar_mut().select_path(&path);
+}
+
+fn rename_selected(uistate: &mut UiState, new_name: &str) {
+    let old_path = match uistate.sidebar().selected() {
+        Some(item) => item.path().to_path_buf(),
+        None => { return; }
+    };
+
+    match rename_entry(&old_path, new_name) {
+        Ok(new_path) => {
+            if let Some(current) = uistate.current_request_path().clone() {
+                if let Ok(rest) = current.strip_prefix(&old_path) {
+                    let moved = if rest.as_os_str().is_empty() {
+                        new_path.clone()
+                    } else {
+                        new_path.join(rest)
+                    };
+
+                    uistate.set_current_request_path(Some(moved));
+                }
+            }
+
+            let was_expanded = uistate.sidebar().is_expanded(&old_path);
+            let sidebar = uistate.sidebar_mut();
+
+            sidebar.set_expanded(&old_path, false);
+            sidebar.set_expanded(&new_path, was_expanded);
+
+            reload(uistate);
+            uistate.sidebar_mut().select_path(&new_path);
+        }
+
+        Err(e) => {
+            show_error(uistate, format!("Couldn't rename: {}", e));
+        }
+    }
+}
+
+fn delete_selected(uistate: &mut UiState) {
+    let path: PathBuf = match uistate.sidebar().selected() {
+        Some(item) => item.path().to_path_buf(),
+        None => { return; }
+    };
+
+    match delete_entry(&path) {
+        Ok(()) => {
+            info!("Deleted {}", path.display());
+
+            let current_deleted = match uistate.current_request_path() {
+                Some(current) => current.starts_with(&path),
+                None => false,
+            };
+
+            if current_deleted {
+                uistate.set_current_request_path(None);
+            }
+
+            reload(uistate);
+        }
+
+        Err(e) => {
+            show_error(uistate, format!("Couldn't delete: {}", e));
+        }
+    }
+}
+
+fn reload(uistate: &mut UiState) {
+    if let Err(e) = uistate.sidebar_mut().reload() {
+        show_error(uistate, format!("Couldn't read the collection: {}", e));
+    }
+}
+
+/// Tells that the password, token or API key of the "Authorization" tab
+/// wasn't saved because it is typed as it is.
+fn warn_unsaved_secret(uistate: &mut UiState) {
+    uistate.set_app_message(String::from(
+        "Saved without the secret of the Authorization tab, use a \
+        {{variable}} of the environment to save it"
+    ));
+    uistate.set_app_status(AppStatus::INFO);
+}
+
+fn show_error(uistate: &mut UiState, error: String) {
+    info!("{}", error);
+
+    uistate.set_app_error(error);
+    uistate.set_app_status(AppStatus::ERROR);
+}
@@ -0,0 +1,93 @@
+//! Keys of the multi-line text areas: the body editor and the script
+//! editors.
+
+use crossterm::event::{ KeyCode, KeyEvent, KeyModifiers };
+
+use crate::ui::state::text_edit::{ TextEditState, TextEditMoveDirection };
+
+/// Edits `text_data` or moves its cursor according to `key`.
+pub fn process_text_area_input(key: KeyEvent, text_data: &mut TextEditState) {
+    let ctrl_down = key.modifiers.contains(KeyModifiers::CONTROL);
+    let shift_down = key.modifiers.contains(KeyModifiers::SHIFT);
+
+    match key.code {
+        KeyCode::Up => {
+            text_data.move_cursor(
+                TextEditMoveDirection::Up,
+                false,
+                key.modifiers == KeyModifiers::SHIFT,
+            );
+        }
+
+        KeyCode::Down => {
+            text_data.move_cursor(
+                TextEditMoveDirection::Down,
+                false,
+                key.modifiers == KeyModifiers::SHIFT,
+            );
+        }
+
+        KeyCode::Left => {
+            text_data.move_cursor(
+                TextEditMoveDirection::Left, ctrl_down, shift_down,
+            );
+        }
+
+        KeyCode::Right => {
+            text_data.move_cursor(
+                TextEditMoveDirection::Right, ctrl_down, shift_down,
+            );
+        }
+
+        KeyCode::End => {
+            text_data.move_cursor(
+                TextEditMoveDirection::End,
+                false,
+                key.modifiers == KeyModifiers::SHIFT,
+            );
+        }
+
+        KeyCode::Home => {
+            text_data.move_cursor(
+                TextEditMoveDirection::Home,
+                false,
+                key.modifiers == KeyModifiers::SHIFT,
+            );
+        }
+
+        KeyCode::Enter => { text_data.new_line(); }
+
+        KeyCode::Char(c) if ctrl_down => {
+            match c {
+                'a' | 'A' => { text_data.select_all(); }
+                'c' | 'C' => { text_data.copy_selected(); }
+                'v' | 'V' => { text_data.paste(); }
+                _ => {}
+            }
+        }
+
+        KeyCode::Char(c) => { text_data.insert_char(c); }
+
+        KeyCode::Backspace => {
+            if key.modifiers == KeyModifiers::CONTROL {
+                text_data.delete_word();
+            } else if text_data.selecting() {
+                text_data.delete_selected();
+            } else {
+                text_data.delete_char();
+            }
+        }
+
+        KeyCode::Delete => {
+            if key.modifiers == KeyModifiers::CONTROL {
+                text_data.delete_word_to_right();
+            } else if text_data.selecting() {
+                text_data.delete_selected();
+            } else {
+                text_data.delete_char_to_right();
+            }
+        }
+
+        _ => {}
+    }
+}
@@ -0,0 +1,110 @@
+//! Undo and redo of the edits of the text fields.
+
+use crossterm::event::{ KeyCode, KeyEvent, KeyModifiers };
+
+use crate::ui::state::{
+    UiState, EditorMode, InputMode, app_status::AppStatus,
+    edit_history::EditKind,
+    text_field::{
+        TextField, FieldState, active_text_field, field_state, set_field_state,
+    },
+};
+
+/// Undoes the last edit of the active text field.
+pub fn undo(uistate: &mut UiState) {
+    let Some(field) = active_text_field(uistate) else { return; };
+    let current = field_state(uistate, field);
+
+    match uistate.edit_history_mut().undo(field, &current) {
+        Some(state) => { restore(uistate, field, state); }
+        None => { show_message(uistate, "Already at oldest change"); }
+    }
+}
+
+/// Redoes the last undone edit of the active text field.
+pub fn redo(uistate: &mut UiState) {
+    let Some(field) = active_text_field(uistate) else { return; };
+    let current = field_state(uistate, field);
+
+    match uistate.edit_history_mut().redo(field, &current) {
+        Some(state) => { restore(uistate, field, state); }
+        None => { show_message(uistate, "Already at newest change"); }
+    }
+}
+
+/// Records the edit that `key` made to `field`. `before` is the state of the
+/// field before the key was handled. Keys that don't edit the text end the
+/// current run of typed characters.
+pub fn record_edit(
+    uistate: &mut UiState, key: KeyEvent, field: TextField, before: FieldState
+) {
+    let kind = edit_kind(key, field, &before);
+
+    if active_text_field(uistate) != Some(field) {
+        uistate.edit_history_mut().break_run();
+        return;
+    }
+
+    let after = field_state(uistate, field);
+
+    match kind {
+        Some(kind) if after.lines != before.lines => {
+            uistate.edit_history_mut().record(field, before, after, kind);
+        }
+
+        _ => { uistate.edit_history_mut().break_run(); }
+    }
+}
+
+/// The kind of edit `key` makes to `field`, `None` if it doesn't change the
+/// text.
+fn edit_kind(
+    key: KeyEvent, field: TextField, before: &FieldState
+) -> Option<EditKind> {
+    let ctrl_down = key.modifiers.contains(KeyModifiers::CONTROL);
+    let selecting = before.selection.is_some();
+
+    match key.code {
+        // Pasting
+        KeyCode::Char(_) if ctrl_down => Some(EditKind::Other),
+
+        // Replacing the selection
+        KeyCode::Char(_) if selecting => Some(EditKind::Other),
+        KeyCode::Char(_) => Some(EditKind::Insert),
+
+        // Deleting a word or the selection
+        KeyCode::Backspace | KeyCode::Delete if ctrl_down || selecting => {
+            Some(EditKind::Other)
+        }
+
+        KeyCode::Backspace | KeyCode::Delete => Some(EditKind::Delete),
+        KeyCode::Enter if field.multi_line() => Some(EditKind::Other),
+
+        _ => None,
+    }
+}
+
+/// Puts `state` back into `field`. The `NORMAL` mode of the VIM editor mode
+/// keeps the cursor on a character.
+fn restore(uistate: &mut UiState, field: TextField, mut state: FieldState) {
+    if uistate.editor_mode() == EditorMode::VIM {
+        if uistate.input_mode() == InputMode::VISUAL {
+            uistate.set_input_mode(InputMode::Normal);
+        }
+
+        if uistate.input_mode() == InputMode::Normal {
+            let len = state.lines.get(state.cursor.0)
+                .map(|line| line.chars().count())
+                .unwrap_or_default();
+
+            state.cursor.1 = state.cursor.1.min(len.saturating_sub(1));
+        }
+    }
+
+    set_field_state(uistate, field, &state);
+}
+
+fn show_message(uistate: &mut UiState, message: &str) {
+    uistate.set_app_message(String::from(message));
+    uistate.set_app_status(AppStatus::INFO);
+}
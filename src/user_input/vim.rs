@@ -0,0 +1,1644 @@
+//! Key handling of the VIM editor mode.
+//!
+//! The text of the active field is copied into a [Buffer], edited with the
+//! vim motions and operators, and written back to the field.
+
+use crossterm::event::{ KeyCode, KeyEvent, KeyModifiers };
+
+use crate::{
+    ui::state::{
+        UiState, EditorMode, InputMode, app_status::AppStatus,
+        edit_history::EditKind, scripts::ScriptKind,
+        text_field::{
+            TextField, FieldState, active_text_field, field_text, field_state,
+            set_field_state, text_edit_mut,
+        },
+    },
+    user_input::{ sidebar::save_current_request, undo::{ undo, redo } },
+};
+
+/// What [process_vim_input] did with a key.
+#[derive(PartialEq)]
+pub enum VimInput {
+    /// The key is left to the normal key handling, e.g. `Tab`.
+    Ignored,
+    Handled,
+
+    /// `:q` was entered.
+    Exit,
+}
+
+/// The text being edited, with the cursor at (`line`, `col`).
+#[derive(Clone, PartialEq)]
+struct Buffer {
+    lines: Vec<Vec<char>>,
+    line: usize,
+    col: usize,
+
+    /// Whether new lines can be added, i.e. the field is the body text area.
+    multi_line: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+
+    /// `w` and `W` (`true`).
+    WordStart(bool),
+
+    /// `e` and `E` (`true`).
+    WordEnd(bool),
+
+    /// `b` and `B` (`true`).
+    WordBack(bool),
+    LineStart,
+    FirstNonBlank,
+    LineEnd,
+
+    /// `gg`, or the line given by the count.
+    FirstLine,
+
+    /// `G`, or the line given by the count.
+    LastLine,
+}
+
+/// How much text a motion covers when it's used with an operator.
+#[derive(PartialEq)]
+enum MotionKind {
+    /// Up to, but not including, the target character.
+    Exclusive,
+    Inclusive,
+
+    /// Whole lines.
+    Linewise,
+}
+
+/// What an operator (`d`, `c` or `y`) works on.
+#[derive(Clone, Copy)]
+enum OperatorRange {
+    Motion(Motion),
+
+    /// `dd`, `cc` and `yy`.
+    Line,
+
+    /// A text object, e.g. `iw` is `Object(true, 'w')`.
+    Object(bool, char),
+}
+
+enum Command {
+    Move(Option<usize>, Motion),
+    Operator(Option<usize>, char, OperatorRange),
+
+    /// A single key command like `x` or `p`.
+    Action(Option<usize>, char),
+}
+
+enum Parse {
+    Incomplete,
+    Invalid,
+    Done(Command),
+}
+
+/// Text between two positions. `end` is exclusive, unless `linewise`, when
+/// the lines `start.0` to `end.0` are included completely.
+struct Range {
+    start: (usize, usize),
+    end: (usize, usize),
+    linewise: bool,
+}
+
+/// Switches between the normal and the VIM editor modes.
+pub fn toggle_vim_mode(uistate: &mut UiState) {
+    let editor_mode = match uistate.editor_mode() {
+        EditorMode::Normal => EditorMode::VIM,
+        EditorMode::VIM => EditorMode::Normal,
+    };
+
+    leave_visual(uistate);
+
+    uistate.set_editor_mode(editor_mode);
+    uistate.set_input_mode(InputMode::Normal);
+
+    let vim = uistate.vim_mut();
+
+    vim.pending_mut().clear();
+    vim.set_command_line(None);
+    vim.set_last_target(None);
+}
+
+/// Whether Ctrl+R redoes, like in vim, instead of opening the history. It
+/// does in the `NORMAL` mode, when the keys go to a text field.
+pub fn ctrl_r_redoes(uistate: &UiState) -> bool {
+    uistate.editor_mode() == EditorMode::VIM
+        && uistate.input_mode() == InputMode::Normal
+        && uistate.vim().command_line().is_none()
+        && !uistate.export_menu().opened()
+        && !uistate.history().opened()
+        && !uistate.sidebar().focused()
+        && active_text_field(uistate).is_some()
+}
+
+/// Handles a key in the VIM editor mode.
+pub fn process_vim_input(key: KeyEvent, uistate: &mut UiState) -> VimInput {
+    if uistate.vim().command_line().is_some() {
+        return process_command_line(key, uistate);
+    }
+
+    let target = active_text_field(uistate);
+
+    // The pending keys and the selection don't carry over to another field
+    if target != uistate.vim().last_target() {
+        leave_visual(uistate);
+
+        let cursor = target
+            .map(|t| field_text(uistate, t)[0].chars().count())
+            .unwrap_or_default();
+
+        let vim = uistate.vim_mut();
+
+        vim.pending_mut().clear();
+        vim.set_last_target(target);
+        vim.set_field_cursor(cursor);
+    }
+
+    if uistate.input_mode() == InputMode::INSERT {
+        if key.code == KeyCode::Esc {
+            finish_insert(uistate, target);
+            return VimInput::Handled;
+        }
+
+        return match target {
+            Some(target) => process_insert_key(key, uistate, target),
+            None => VimInput::Ignored,
+        };
+    }
+
+    if key.code == KeyCode::Char(':') && uistate.vim().pending().is_empty()
+        && uistate.input_mode() == InputMode::Normal
+    {
+        uistate.vim_mut().set_command_line(Some(String::new()));
+        return VimInput::Handled;
+    }
+
+    match target {
+        Some(target) => process_normal_key(key, uistate, target),
+        None => VimInput::Ignored,
+    }
+}
+
+/// Handles the keys of the `:` command line.
+fn process_command_line(key: KeyEvent, uistate: &mut UiState) -> VimInput {
+    let mut command = uistate.vim().command_line().clone().unwrap_or_default();
+
+    match key.code {
+        KeyCode::Char(c) => { command.push(c); }
+
+        // Backspace on the empty command line closes it
+        KeyCode::Backspace if command.is_empty() => {
+            uistate.vim_mut().set_command_line(None);
+            return VimInput::Handled;
+        }
+
+        KeyCode::Backspace => { command.pop(); }
+
+        KeyCode::Esc => {
+            uistate.vim_mut().set_command_line(None);
+            return VimInput::Handled;
+        }
+
+        KeyCode::Enter => {
+            uistate.vim_mut().set_command_line(None);
+            return run_command(command.trim(), uistate);
+        }
+
+        _ => {}
+    }
+
+    uistate.vim_mut().set_command_line(Some(command));
+
+    VimInput::Handled
+}
+
+/// Runs a `:` command.
+fn run_command(command: &str, uistate: &mut UiState) -> VimInput {
+    match command {
+        "q" | "q!" | "qa" | "qa!" => VimInput::Exit,
+
+        "w" => {
+            save_current_request(uistate);
+            VimInput::Handled
+        }
+
+        "wq" | "x" => {
+            // A new request asks for a name first
+            if uistate.current_request_path().is_none() {
+                save_current_request(uistate);
+                return VimInput::Handled;
+            }
+
+            save_current_request(uistate);
+
+            if matches!(uistate.app_status(), AppStatus::ERROR) {
+                return VimInput::Handled;
+            }
+
+            VimInput::Exit
+        }
+
+        "" => VimInput::Handled,
+
+        _ => {
+            uistate.set_app_error(format!("Not an editor command: {}", command));
+            uistate.set_app_status(AppStatus::ERROR);
+
+            VimInput::Handled
+        }
+    }
+}
+
+/// Handles a key in the `INSERT` mode.
+fn process_insert_key(
+    key: KeyEvent, uistate: &mut UiState, target: TextField
+) -> VimInput {
+    let before = field_state(uistate, target);
+    let mut buf = read_buffer(&before, target);
+
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.intersects(
+            KeyModifiers::CONTROL | KeyModifiers::ALT
+        ) => {
+            buf.lines[buf.line].insert(buf.col, c);
+            buf.col += 1;
+        }
+
+        KeyCode::Enter if buf.multi_line => {
+            let rest = buf.lines[buf.line].split_off(buf.col);
+
+            buf.lines.insert(buf.line + 1, rest);
+            buf.line += 1;
+            buf.col = 0;
+        }
+
+        KeyCode::Backspace => {
+            if buf.col > 0 {
+                buf.col -= 1;
+                buf.lines[buf.line].remove(buf.col);
+            } else if buf.line > 0 {
+                let line = buf.lines.remove(buf.line);
+
+                buf.line -= 1;
+                buf.col = buf.lines[buf.line].len();
+                buf.lines[buf.line].extend(line);
+            }
+        }
+
+        KeyCode::Delete => {
+            if buf.col < buf.line_len(buf.line) {
+                buf.lines[buf.line].remove(buf.col);
+            } else if buf.line + 1 < buf.lines.len() {
+                let line = buf.lines.remove(buf.line + 1);
+
+                buf.lines[buf.line].extend(line);
+            }
+        }
+
+        KeyCode::Left => { buf.col = buf.col.saturating_sub(1); }
+        KeyCode::Right => { buf.col = (buf.col + 1).min(buf.line_len(buf.line)); }
+        KeyCode::Home => { buf.col = 0; }
+        KeyCode::End => { buf.col = buf.line_len(buf.line); }
+
+        KeyCode::Up if buf.multi_line => {
+            buf.line = buf.line.saturating_sub(1);
+            buf.col = buf.col.min(buf.line_len(buf.line));
+        }
+
+        KeyCode::Down if buf.multi_line => {
+            buf.line = (buf.line + 1).min(buf.lines.len() - 1);
+            buf.col = buf.col.min(buf.line_len(buf.line));
+        }
+
+        _ => { return VimInput::Ignored; }
+    }
+
+    uistate.vim_mut().record(key);
+    write_buffer(uistate, target, &buf);
+    record_change(uistate, target, before);
+
+    VimInput::Handled
+}
+
+/// Goes back to the `NORMAL` mode, moving the cursor onto the last inserted
+/// character like vim does.
+fn finish_insert(uistate: &mut UiState, target: Option<TextField>) {
+    uistate.set_input_mode(InputMode::Normal);
+
+    let vim = uistate.vim_mut();
+
+    vim.record(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+    vim.set_recording(false);
+
+    // The change, including the typed text, is undone at once
+    uistate.edit_history_mut().break_run();
+
+    let Some(target) = target else { return; };
+
+    let mut buf = read_buffer(&field_state(uistate, target), target);
+
+    buf.col = buf.col.saturating_sub(1);
+    buf.clamp_normal();
+
+    write_buffer(uistate, target, &buf);
+}
+
+/// Handles a key in the `NORMAL` and `VISUAL` modes.
+fn process_normal_key(
+    key: KeyEvent, uistate: &mut UiState, target: TextField
+) -> VimInput {
+    let multi_line = target.multi_line();
+
+    let key = match key.code {
+        KeyCode::Esc => {
+            if !uistate.vim().pending().is_empty() {
+                uistate.vim_mut().pending_mut().clear();
+                return VimInput::Handled;
+            }
+
+            if uistate.input_mode() == InputMode::VISUAL {
+                leave_visual(uistate);
+                return VimInput::Handled;
+            }
+
+            return VimInput::Ignored;
+        }
+
+        KeyCode::Char('r') if key.modifiers == KeyModifiers::CONTROL
+            && uistate.input_mode() == InputMode::Normal =>
+        {
+            uistate.vim_mut().pending_mut().clear();
+            redo(uistate);
+
+            return VimInput::Handled;
+        }
+
+        KeyCode::Char(_) if key.modifiers.intersects(
+            KeyModifiers::CONTROL | KeyModifiers::ALT
+        ) => {
+            return VimInput::Ignored;
+        }
+
+        KeyCode::Char(_) => key,
+        KeyCode::Backspace => char_key('h'),
+        KeyCode::Delete => char_key('x'),
+        KeyCode::Enter if multi_line => char_key('j'),
+
+        _ => { return VimInput::Ignored; }
+    };
+
+    uistate.vim_mut().pending_mut().push(key);
+
+    let keys: Vec<char> = uistate.vim().pending().iter()
+        .filter_map(|k| match k.code {
+            KeyCode::Char(c) => Some(c),
+            _ => None,
+        })
+        .collect();
+
+    let visual = uistate.input_mode() == InputMode::VISUAL;
+
+    match parse(&keys, visual) {
+        Parse::Incomplete => {}
+        Parse::Invalid => { uistate.vim_mut().pending_mut().clear(); }
+
+        Parse::Done(command) => {
+            let keys = uistate.vim().pending().clone();
+
+            uistate.vim_mut().pending_mut().clear();
+
+            if visual {
+                run_visual(command, uistate, target);
+            } else {
+                run_normal(command, keys, uistate, target);
+            }
+        }
+    }
+
+    VimInput::Handled
+}
+
+fn char_key(c: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+}
+
+/// Parses the keys of a `NORMAL` or `VISUAL` mode command.
+fn parse(keys: &[char], visual: bool) -> Parse {
+    let (count, rest) = parse_count(keys);
+
+    let Some((&c, rest)) = rest.split_first() else {
+        return Parse::Incomplete;
+    };
+
+    if let Some(motion) = motion(c) {
+        return Parse::Done(Command::Move(count, motion));
+    }
+
+    match c {
+        'g' => match rest.first() {
+            None => Parse::Incomplete,
+            Some('g') => Parse::Done(Command::Move(count, Motion::FirstLine)),
+            Some(_) => Parse::Invalid,
+        },
+
+        'd' | 'c' | 'y' if !visual => {
+            let (count2, rest) = parse_count(rest);
+
+            let count = match (count, count2) {
+                (Some(a), Some(b)) => Some(a * b),
+                (a, b) => a.or(b),
+            };
+
+            let range = match rest {
+                [] => { return Parse::Incomplete; }
+                [o] if *o == c => OperatorRange::Line,
+                ['i' | 'a'] | ['g'] => { return Parse::Incomplete; }
+                ['g', 'g'] => OperatorRange::Motion(Motion::FirstLine),
+
+                [i @ ('i' | 'a'), kind] if is_object(*kind) => {
+                    OperatorRange::Object(*i == 'i', *kind)
+                }
+
+                [m] => match motion(*m) {
+                    Some(motion) => OperatorRange::Motion(motion),
+                    None => { return Parse::Invalid; }
+                },
+
+                _ => { return Parse::Invalid; }
+            };
+
+            Parse::Done(Command::Operator(count, c, range))
+        }
+
+        'd' | 'c' | 'y' | 'x' | 'X' | 'D' | 'C' | 'Y' | 'p' | 'P' | 'o' | 'v'
+            | 'V' => Parse::Done(Command::Action(count, c)),
+
+        'u' | '.' | 'i' | 'a' | 'I' | 'A' | 'O' if !visual => {
+            Parse::Done(Command::Action(count, c))
+        }
+
+        _ => Parse::Invalid,
+    }
+}
+
+/// Splits the count off the start of `keys`. A leading `0` is the motion to
+/// the start of the line, not a count.
+fn parse_count(keys: &[char]) -> (Option<usize>, &[char]) {
+    if !matches!(keys.first(), Some('1'..='9')) {
+        return (None, keys);
+    }
+
+    let digits = keys.iter().take_while(|c| c.is_ascii_digit()).count();
+    let count = keys[..digits].iter().collect::<String>().parse().ok();
+
+    (count, &keys[digits..])
+}
+
+fn motion(c: char) -> Option<Motion> {
+    match c {
+        'h' => Some(Motion::Left),
+        'l' | ' ' => Some(Motion::Right),
+        'k' => Some(Motion::Up),
+        'j' => Some(Motion::Down),
+        'w' => Some(Motion::WordStart(false)),
+        'W' => Some(Motion::WordStart(true)),
+        'e' => Some(Motion::WordEnd(false)),
+        'E' => Some(Motion::WordEnd(true)),
+        'b' => Some(Motion::WordBack(false)),
+        'B' => Some(Motion::WordBack(true)),
+        '0' => Some(Motion::LineStart),
+        '^' => Some(Motion::FirstNonBlank),
+        '$' => Some(Motion::LineEnd),
+        'G' => Some(Motion::LastLine),
+        _ => None,
+    }
+}
+
+fn is_object(c: char) -> bool {
+    "wW\"'`()b{}B[]<>".contains(c)
+}
+
+/// Runs a command of the `NORMAL` mode.
+fn run_normal(
+    command: Command, keys: Vec<KeyEvent>, uistate: &mut UiState,
+    target: TextField,
+) {
+    let before = field_state(uistate, target);
+    let mut buf = read_buffer(&before, target);
+    let mut insert = false;
+
+    match command {
+        Command::Move(count, motion) => {
+            let (line, col) = buf.motion_target(motion, count, false);
+
+            buf.line = line;
+            buf.col = col;
+        }
+
+        Command::Operator(count, op, range) => {
+            // `cw` changes to the end of the word, like `ce`
+            let range = match range {
+                OperatorRange::Motion(Motion::WordStart(big))
+                    if op == 'c' && !buf.on_blank() =>
+                {
+                    OperatorRange::Motion(Motion::WordEnd(big))
+                }
+
+                range => range,
+            };
+
+            if let Some(range) = buf.operator_range(range, count) {
+                insert = operate(uistate, &mut buf, op, range);
+            }
+        }
+
+        Command::Action(count, c) => {
+            let n = count.unwrap_or(1);
+
+            match c {
+                'x' | 'X' => {
+                    let len = buf.line_len(buf.line);
+                    let (start, end) = if c == 'x' {
+                        (buf.col, (buf.col + n).min(len))
+                    } else {
+                        (buf.col.saturating_sub(n), buf.col)
+                    };
+
+                    if start < end {
+                        let range = Range {
+                            start: (buf.line, start),
+                            end: (buf.line, end),
+                            linewise: false,
+                        };
+
+                        operate(uistate, &mut buf, 'd', range);
+                    }
+                }
+
+                'D' | 'C' | 'Y' => {
+                    let (op, range) = match c {
+                        'D' => ('d', OperatorRange::Motion(Motion::LineEnd)),
+                        'C' => ('c', OperatorRange::Motion(Motion::LineEnd)),
+                        _ => ('y', OperatorRange::Line),
+                    };
+
+                    match buf.operator_range(range, count) {
+                        Some(range) => {
+                            insert = operate(uistate, &mut buf, op, range);
+                        }
+
+                        // `C` on an empty line
+                        None => { insert = op == 'c'; }
+                    }
+                }
+
+                'p' | 'P' => {
+                    let (text, linewise) = uistate.vim().register();
+                    let (text, linewise) = (text.to_string(), linewise);
+
+                    buf.put(&text.repeat(n), linewise, c == 'p');
+                }
+
+                'u' => {
+                    for _ in 0..n {
+                        undo(uistate);
+                    }
+
+                    return;
+                }
+
+                '.' => {
+                    let keys = uistate.vim().last_change().clone();
+
+                    for key in keys {
+                        process_vim_input(key, uistate);
+                    }
+
+                    return;
+                }
+
+                'i' => { insert = true; }
+
+                'a' => {
+                    buf.col = (buf.col + 1).min(buf.line_len(buf.line));
+                    insert = true;
+                }
+
+                'I' => {
+                    buf.col = buf.first_non_blank(buf.line);
+                    insert = true;
+                }
+
+                'A' => {
+                    buf.col = buf.line_len(buf.line);
+                    insert = true;
+                }
+
+                'o' | 'O' if buf.multi_line => {
+                    let indent: Vec<char> = buf.lines[buf.line].iter()
+                        .take_while(|c| c.is_whitespace())
+                        .copied()
+                        .collect();
+
+                    if c == 'o' {
+                        buf.line += 1;
+                    }
+
+                    buf.col = indent.len();
+                    buf.lines.insert(buf.line, indent);
+                    insert = true;
+                }
+
+                'v' | 'V' => {
+                    uistate.vim_mut().start_visual((buf.line, buf.col), c == 'V');
+                    uistate.set_input_mode(InputMode::VISUAL);
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    if buf.text_lines() != before.lines || insert {
+        let vim = uistate.vim_mut();
+
+        vim.set_last_change(keys);
+        vim.set_recording(insert);
+    }
+
+    if insert {
+        uistate.set_input_mode(InputMode::INSERT);
+    } else {
+        buf.clamp_normal();
+    }
+
+    write_buffer(uistate, target, &buf);
+    record_change(uistate, target, before);
+
+    // The text typed in the `INSERT` mode is part of the change
+    if !insert {
+        uistate.edit_history_mut().break_run();
+    }
+
+    if uistate.input_mode() == InputMode::VISUAL {
+        show_selection(uistate, target, &buf);
+    }
+}
+
+/// Runs a command of the `VISUAL` mode.
+fn run_visual(command: Command, uistate: &mut UiState, target: TextField) {
+    let before = field_state(uistate, target);
+    let mut buf = read_buffer(&before, target);
+    let start = uistate.vim().visual_start();
+    let mut insert = false;
+
+    match command {
+        Command::Move(count, motion) => {
+            let (line, col) = buf.motion_target(motion, count, false);
+
+            buf.line = line;
+            buf.col = col;
+        }
+
+        Command::Action(_, c) => {
+            let linewise = uistate.vim().visual_line() || "XDY".contains(c);
+            let cursor = (buf.line, buf.col);
+            let (first, last) = if start <= cursor {
+                (start, cursor)
+            } else {
+                (cursor, start)
+            };
+
+            let range = Range {
+                start: first,
+                end: (last.0, (last.1 + 1).min(buf.line_len(last.0))),
+                linewise,
+            };
+
+            match c {
+                'o' => {
+                    uistate.vim_mut().start_visual(cursor, linewise);
+                    buf.line = start.0;
+                    buf.col = start.1;
+                }
+
+                'v' | 'V' if linewise != (c == 'V') => {
+                    uistate.vim_mut().start_visual(start, c == 'V');
+                }
+
+                'v' | 'V' => { leave_visual(uistate); }
+
+                'p' | 'P' => {
+                    let (text, register_linewise) = uistate.vim().register();
+                    let (text, register_linewise) =
+                        (text.to_string(), register_linewise);
+
+                    leave_visual(uistate);
+                    buf.delete(&range);
+
+                    // The selected lines are replaced by a line with the text
+                    if linewise && buf.multi_line {
+                        let at = range.start.0.min(buf.lines.len());
+
+                        if buf.lines != [vec![]] {
+                            buf.lines.insert(at, vec![]);
+                        }
+
+                        buf.line = at.min(buf.lines.len() - 1);
+                        buf.col = 0;
+
+                        if register_linewise {
+                            let new_lines: Vec<Vec<char>> = text.split('\n')
+                                .map(|line| line.chars().collect())
+                                .collect();
+
+                            buf.lines.splice(buf.line..=buf.line, new_lines);
+                            buf.col = buf.first_non_blank(buf.line);
+                        } else {
+                            buf.put(&text, false, false);
+                        }
+                    } else {
+                        buf.put(&text, register_linewise, false);
+                    }
+                }
+
+                _ => {
+                    leave_visual(uistate);
+
+                    let op = match c {
+                        'x' | 'X' | 'D' => 'd',
+                        'Y' => 'y',
+                        op => op,
+                    };
+
+                    insert = operate(uistate, &mut buf, op, range);
+                }
+            }
+        }
+
+        Command::Operator(..) => {}
+    }
+
+    if insert {
+        uistate.set_input_mode(InputMode::INSERT);
+    } else {
+        buf.clamp_normal();
+    }
+
+    write_buffer(uistate, target, &buf);
+    record_change(uistate, target, before);
+
+    if !insert {
+        uistate.edit_history_mut().break_run();
+    }
+
+    if uistate.input_mode() == InputMode::VISUAL {
+        show_selection(uistate, target, &buf);
+    }
+}
+
+/// Runs the operator `op` on `range`. Returns whether the `INSERT` mode
+/// starts, which `c` does.
+fn operate(uistate: &mut UiState, buf: &mut Buffer, op: char, range: Range) -> bool {
+    let text = buf.text_in(&range);
+
+    uistate.vim_mut().set_register(text, range.linewise);
+
+    match op {
+        'd' => {
+            buf.delete(&range);
+            false
+        }
+
+        'c' => {
+            if range.linewise && buf.multi_line {
+                let indent: Vec<char> = buf.lines[range.start.0].iter()
+                    .take_while(|c| c.is_whitespace())
+                    .copied()
+                    .collect();
+
+                buf.delete(&range);
+                buf.line = range.start.0.min(buf.lines.len());
+                buf.col = indent.len();
+                buf.lines.insert(buf.line, indent);
+            } else if range.linewise {
+                buf.lines = vec![vec![]];
+                buf.line = 0;
+                buf.col = 0;
+            } else {
+                buf.delete(&range);
+            }
+
+            true
+        }
+
+        _ => {
+            if range.linewise {
+                buf.line = buf.line.min(range.start.0);
+            } else {
+                buf.line = range.start.0;
+                buf.col = range.start.1;
+            }
+
+            false
+        }
+    }
+}
+
+/// Drops the `VISUAL` mode and the selection shown in the text areas.
+fn leave_visual(uistate: &mut UiState) {
+    if uistate.input_mode() == InputMode::VISUAL {
+        uistate.set_input_mode(InputMode::Normal);
+    }
+
+    uistate.body_mut().text_data_mut().reset_selection();
+
+    for kind in ScriptKind::iter() {
+        uistate.scripts_mut().editor_mut(*kind).reset_selection();
+    }
+
+    uistate.assertions_mut().reset_selection();
+}
+
+/// Shows the `VISUAL` selection in the text areas. The single line fields
+/// read it from the state when rendering.
+fn show_selection(uistate: &mut UiState, target: TextField, buf: &Buffer) {
+    if !target.multi_line() {
+        return;
+    }
+
+    let start = uistate.vim().visual_start();
+    let cursor = (buf.line, buf.col);
+    let (mut first, mut last) = if start <= cursor {
+        (start, cursor)
+    } else {
+        (cursor, start)
+    };
+
+    if uistate.vim().visual_line() {
+        first.1 = 0;
+        last.1 = buf.line_len(last.0).saturating_sub(1);
+    }
+
+    let Some(text_data) = text_edit_mut(uistate, target) else { return; };
+
+    text_data.set_selecting(true);
+    text_data.set_sel_start_pos((first.0 as u16, first.1 as u16));
+    text_data.set_sel_end_pos((last.0 as u16, last.1 as u16));
+}
+
+/// Copies the text and cursor of the `state` of `target` into a buffer.
+fn read_buffer(state: &FieldState, target: TextField) -> Buffer {
+    let mut buf = Buffer {
+        lines: state.lines.iter().map(|line| line.chars().collect()).collect(),
+        line: state.cursor.0,
+        col: state.cursor.1,
+        multi_line: target.multi_line(),
+    };
+
+    if buf.lines.is_empty() {
+        buf.lines.push(vec![]);
+    }
+
+    buf.line = buf.line.min(buf.lines.len() - 1);
+    buf.col = buf.col.min(buf.line_len(buf.line));
+
+    buf
+}
+
+/// Writes the text and cursor of the buffer back to `target`.
+fn write_buffer(uistate: &mut UiState, target: TextField, buf: &Buffer) {
+    let state = FieldState {
+        lines: buf.text_lines(),
+        cursor: (buf.line, buf.col),
+        selection: None,
+    };
+
+    set_field_state(uistate, target, &state);
+}
+
+/// Adds the change of `target` since `before` to the undo history.
+fn record_change(uistate: &mut UiState, target: TextField, before: FieldState) {
+    let after = field_state(uistate, target);
+
+    if after.lines != before.lines {
+        uistate.edit_history_mut()
+            .record(target, before, after, EditKind::VimChange);
+    }
+}
+
+/// Class of a character for the word motions. Blanks are `0`, a "WORD"
+/// (`big`) is anything else, and a "word" is either letters, digits and
+/// underscores, or other non-blank characters.
+fn char_class(c: char, big: bool) -> u8 {
+    if c.is_whitespace() {
+        0
+    } else if big || c.is_alphanumeric() || c == '_' {
+        1
+    } else {
+        2
+    }
+}
+
+impl Buffer {
+    fn line_len(&self, line: usize) -> usize { self.lines[line].len() }
+
+    fn text_lines(&self) -> Vec<String> {
+        self.lines.iter().map(|line| line.iter().collect()).collect()
+    }
+
+    /// Keeps the cursor on a character, as the `NORMAL` mode does.
+    fn clamp_normal(&mut self) {
+        self.line = self.line.min(self.lines.len() - 1);
+        self.col = self.col.min(self.line_len(self.line).saturating_sub(1));
+    }
+
+    fn on_blank(&self) -> bool {
+        self.lines[self.line].get(self.col).is_none_or(|c| c.is_whitespace())
+    }
+
+    fn first_non_blank(&self, line: usize) -> usize {
+        self.lines[line].iter().take_while(|c| c.is_whitespace()).count()
+    }
+
+    /// The text with the lines joined by `\n`, for the word motions and the
+    /// bracket objects.
+    fn flat(&self) -> Vec<char> {
+        let mut chars = vec![];
+
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                chars.push('\n');
+            }
+
+            chars.extend(line);
+        }
+
+        chars
+    }
+
+    fn to_index(&self, (line, col): (usize, usize)) -> usize {
+        self.lines[..line].iter().map(|l| l.len() + 1).sum::<usize>() + col
+    }
+
+    fn to_pos(&self, mut index: usize) -> (usize, usize) {
+        for (line, chars) in self.lines.iter().enumerate() {
+            if index <= chars.len() {
+                return (line, index);
+            }
+
+            index -= chars.len() + 1;
+        }
+
+        let last = self.lines.len() - 1;
+
+        (last, self.line_len(last))
+    }
+
+    /// Where `motion` moves the cursor. `operator` allows moving past the
+    /// last character of the line, so that `dl` can delete it.
+    fn motion_target(
+        &self, motion: Motion, count: Option<usize>, operator: bool
+    ) -> (usize, usize) {
+        let n = count.unwrap_or(1);
+        let last_line = self.lines.len() - 1;
+        let max_col = |line: usize| {
+            if operator {
+                self.line_len(line)
+            } else {
+                self.line_len(line).saturating_sub(1)
+            }
+        };
+
+        match motion {
+            Motion::Left => (self.line, self.col.saturating_sub(n)),
+            Motion::Right => (self.line, (self.col + n).min(max_col(self.line))),
+
+            Motion::Up | Motion::Down => {
+                let line = if motion == Motion::Up {
+                    self.line.saturating_sub(n)
+                } else {
+                    (self.line + n).min(last_line)
+                };
+
+                (line, self.col.min(max_col(line)))
+            }
+
+            Motion::WordStart(big) | Motion::WordEnd(big) | Motion::WordBack(big) => {
+                let chars = self.flat();
+                let mut index = self.to_index((self.line, self.col));
+
+                for _ in 0..n {
+                    index = match motion {
+                        Motion::WordStart(_) => word_start(&chars, index, big),
+                        Motion::WordEnd(_) => word_end(&chars, index, big),
+                        _ => word_back(&chars, index, big),
+                    };
+                }
+
+                self.to_pos(index)
+            }
+
+            Motion::LineStart => (self.line, 0),
+            Motion::FirstNonBlank => (self.line, self.first_non_blank(self.line)),
+
+            Motion::LineEnd => {
+                let line = (self.line + n - 1).min(last_line);
+
+                (line, self.line_len(line).saturating_sub(1))
+            }
+
+            Motion::FirstLine | Motion::LastLine => {
+                let line = match (motion, count) {
+                    (_, Some(n)) => (n - 1).min(last_line),
+                    (Motion::FirstLine, None) => 0,
+                    _ => last_line,
+                };
+
+                (line, self.first_non_blank(line))
+            }
+        }
+    }
+
+    /// The text an operator works on, `None` if it's empty.
+    fn operator_range(
+        &self, range: OperatorRange, count: Option<usize>
+    ) -> Option<Range> {
+        let cursor = (self.line, self.col);
+
+        match range {
+            OperatorRange::Line => {
+                let last = (self.line + count.unwrap_or(1) - 1)
+                    .min(self.lines.len() - 1);
+
+                Some(Range { start: cursor, end: (last, 0), linewise: true })
+            }
+
+            OperatorRange::Motion(motion) => {
+                let target = self.motion_target(motion, count, true);
+                let kind = match motion {
+                    Motion::Up | Motion::Down | Motion::FirstLine
+                        | Motion::LastLine => MotionKind::Linewise,
+                    Motion::WordEnd(_) | Motion::LineEnd => MotionKind::Inclusive,
+                    _ => MotionKind::Exclusive,
+                };
+
+                let (start, mut end) = if target < cursor {
+                    (target, cursor)
+                } else {
+                    (cursor, target)
+                };
+
+                match kind {
+                    MotionKind::Linewise => {
+                        return Some(Range { start, end, linewise: true });
+                    }
+
+                    MotionKind::Inclusive => {
+                        end.1 = (end.1 + 1).min(self.line_len(end.0));
+                    }
+
+                    // `dw` on the last word of a line stops at its end
+                    MotionKind::Exclusive => {
+                        if end.0 > start.0 && end.1 == 0 && target > cursor {
+                            end = (end.0 - 1, self.line_len(end.0 - 1));
+                        }
+                    }
+                }
+
+                (start < end).then_some(Range { start, end, linewise: false })
+            }
+
+            OperatorRange::Object(inner, kind) => {
+                let (start, end) = self.object(inner, kind)?;
+
+                (start < end).then_some(Range {
+                    start: self.to_pos(start),
+                    end: self.to_pos(end),
+                    linewise: false,
+                })
+            }
+        }
+    }
+
+    /// Start and (exclusive) end of a text object, as indexes of
+    /// [Buffer::flat].
+    fn object(&self, inner: bool, kind: char) -> Option<(usize, usize)> {
+        let line_start = self.to_index((self.line, 0));
+        let chars = &self.lines[self.line];
+        let col = self.col.min(chars.len().checked_sub(1)?);
+
+        match kind {
+            'w' | 'W' => {
+                let big = kind == 'W';
+                let class = char_class(chars[col], big);
+                let mut start = col;
+                let mut end = col + 1;
+
+                while start > 0 && char_class(chars[start - 1], big) == class {
+                    start -= 1;
+                }
+
+                while end < chars.len() && char_class(chars[end], big) == class {
+                    end += 1;
+                }
+
+                // `aw` takes the blanks after the word, or before it at the
+                // end of the line
+                if !inner && class != 0 {
+                    if end < chars.len() && chars[end].is_whitespace() {
+                        while end < chars.len() && chars[end].is_whitespace() {
+                            end += 1;
+                        }
+                    } else {
+                        while start > 0 && chars[start - 1].is_whitespace() {
+                            start -= 1;
+                        }
+                    }
+                }
+
+                Some((line_start + start, line_start + end))
+            }
+
+            '"' | '\'' | '`' => {
+                let quotes: Vec<usize> = chars.iter().enumerate()
+                    .filter(|(_, c)| **c == kind)
+                    .map(|(i, _)| i)
+                    .collect();
+
+                // The quoted string around the cursor, or the next one
+                let (open, close) = quotes.chunks_exact(2)
+                    .map(|pair| (pair[0], pair[1]))
+                    .find(|(_, close)| *close >= col)?;
+
+                if inner {
+                    Some((line_start + open + 1, line_start + close))
+                } else {
+                    Some((line_start + open, line_start + close + 1))
+                }
+            }
+
+            _ => {
+                let (open, close) = match kind {
+                    '(' | ')' | 'b' => ('(', ')'),
+                    '{' | '}' | 'B' => ('{', '}'),
+                    '[' | ']' => ('[', ']'),
+                    _ => ('<', '>'),
+                };
+
+                let flat = self.flat();
+                let index = line_start + col;
+
+                // The unmatched opening bracket before the cursor
+                let mut depth = 0;
+                let mut start = None;
+
+                for i in (0..=index).rev() {
+                    if flat[i] == close && i != index {
+                        depth += 1;
+                    } else if flat[i] == open {
+                        if depth == 0 {
+                            start = Some(i);
+                            break;
+                        }
+
+                        depth -= 1;
+                    }
+                }
+
+                let start = start?;
+                let mut depth = 0;
+                let mut end = None;
+
+                for (i, c) in flat.iter().enumerate().skip(start + 1) {
+                    if *c == open {
+                        depth += 1;
+                    } else if *c == close {
+                        if depth == 0 {
+                            end = Some(i);
+                            break;
+                        }
+
+                        depth -= 1;
+                    }
+                }
+
+                let end = end?;
+
+                if inner {
+                    Some((start + 1, end))
+                } else {
+                    Some((start, end + 1))
+                }
+            }
+        }
+    }
+
+    fn text_in(&self, range: &Range) -> String {
+        if range.linewise {
+            let last = range.end.0.max(range.start.0);
+            let first = range.start.0.min(range.end.0);
+
+            return self.text_lines()[first..=last].join("\n");
+        }
+
+        let chars = self.flat();
+
+        chars[self.to_index(range.start)..self.to_index(range.end)]
+            .iter()
+            .collect()
+    }
+
+    /// Deletes the range, moving the cursor to where it started.
+    fn delete(&mut self, range: &Range) {
+        if range.linewise {
+            let first = range.start.0.min(range.end.0);
+            let last = range.start.0.max(range.end.0);
+
+            self.lines.drain(first..=last);
+
+            if self.lines.is_empty() {
+                self.lines.push(vec![]);
+            }
+
+            self.line = first.min(self.lines.len() - 1);
+            self.col = self.first_non_blank(self.line);
+
+            return;
+        }
+
+        let (start, end) = (range.start, range.end);
+        let rest = self.lines[end.0][end.1..].to_vec();
+
+        self.lines[start.0].truncate(start.1);
+        self.lines[start.0].extend(rest);
+        self.lines.drain(start.0 + 1..=end.0);
+
+        self.line = start.0;
+        self.col = start.1;
+    }
+
+    /// Puts `text` after (`p`) or before (`P`) the cursor.
+    fn put(&mut self, text: &str, linewise: bool, after: bool) {
+        if text.is_empty() {
+            return;
+        }
+
+        if linewise && self.multi_line {
+            let at = if after { self.line + 1 } else { self.line };
+            let new_lines: Vec<Vec<char>> = text.split('\n')
+                .map(|line| line.chars().collect())
+                .collect();
+
+            self.lines.splice(at..at, new_lines);
+            self.line = at;
+            self.col = self.first_non_blank(at);
+
+            return;
+        }
+
+        let mut col = self.col;
+
+        if after && !self.lines[self.line].is_empty() {
+            col += 1;
+        }
+
+        let text: String = if self.multi_line {
+            String::from(text)
+        } else {
+            text.replace('\n', " ")
+        };
+
+        let rest = self.lines[self.line].split_off(col);
+        let mut parts = text.split('\n');
+
+        self.lines[self.line].extend(parts.next().unwrap_or_default().chars());
+
+        for part in parts {
+            self.line += 1;
+            self.lines.insert(self.line, part.chars().collect());
+        }
+
+        // The cursor ends on the last character put
+        self.col = self.line_len(self.line).saturating_sub(1);
+        self.lines[self.line].extend(rest);
+    }
+}
+
+/// Index of the start of the next word, for `w`. An empty line counts as a
+/// word.
+fn word_start(chars: &[char], index: usize, big: bool) -> usize {
+    let len = chars.len();
+
+    if index >= len {
+        return len;
+    }
+
+    let class = char_class(chars[index], big);
+    let mut i = index;
+
+    if class != 0 {
+        while i < len && char_class(chars[i], big) == class {
+            i += 1;
+        }
+    }
+
+    while i < len && char_class(chars[i], big) == 0 {
+        if chars[i] == '\n' && i + 1 < len && chars[i + 1] == '\n' {
+            return i + 1;
+        }
+
+        i += 1;
+    }
+
+    i
+}
+
+/// Index of the end of the word, or of the next one when the cursor is
+/// already at the end, for `e`.
+fn word_end(chars: &[char], index: usize, big: bool) -> usize {
+    let len = chars.len();
+
+    if len == 0 {
+        return 0;
+    }
+
+    let mut i = index + 1;
+
+    while i < len && char_class(chars[i], big) == 0 {
+        i += 1;
+    }
+
+    if i >= len {
+        return len - 1;
+    }
+
+    let class = char_class(chars[i], big);
+
+    while i + 1 < len && char_class(chars[i + 1], big) == class {
+        i += 1;
+    }
+
+    i
+}
+
+/// Index of the start of the word, or of the previous one when the cursor is
+/// already at the start, for `b`.
+fn word_back(chars: &[char], index: usize, big: bool) -> usize {
+    if index == 0 || chars.is_empty() {
+        return 0;
+    }
+
+    let mut i = (index - 1).min(chars.len() - 1);
+
+    while i > 0 && char_class(chars[i], big) == 0 {
+        if chars[i] == '\n' && chars[i - 1] == '\n' {
+            return i;
+        }
+
+        i -= 1;
+    }
+
+    let class = char_class(chars[i], big);
+
+    if class == 0 {
+        return i;
+    }
+
+    while i > 0 && char_class(chars[i - 1], big) == class {
+        i -= 1;
+    }
+
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::state::{ UIElement, request_tabs::RequestTabs };
+
+    /// The ui in the VIM mode, editing `text` in the "Assertions" text area
+    /// with the cursor at `cursor`.
+    fn editing(text: &str, cursor: (usize, usize)) -> UiState {
+        let mut uistate = UiState::default();
+
+        uistate.set_editor_mode(EditorMode::VIM);
+        uistate.set_active_element(UIElement::RequestTabsElem);
+        uistate.set_active_request_tab(RequestTabs::Assertions);
+
+        set_field_state(&mut uistate, TextField::Assertions, &FieldState {
+            lines: text.split('\n').map(String::from).collect(),
+            cursor,
+            selection: None,
+        });
+
+        uistate
+    }
+
+    /// Types `keys`, `\x1b` being Esc and `\x12` Ctrl+R.
+    fn press(uistate: &mut UiState, keys: &str) {
+        for c in keys.chars() {
+            let key = match c {
+                '\x1b' => KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+                '\x12' => KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL),
+                c => char_key(c),
+            };
+
+            process_vim_input(key, uistate);
+        }
+    }
+
+    /// Text and cursor of the "Assertions" text area.
+    fn edited(uistate: &UiState) -> (String, (usize, usize)) {
+        let state = field_state(uistate, TextField::Assertions);
+
+        (state.lines.join("\n"), state.cursor)
+    }
+
+    /// Runs `keys` on `text` with the cursor at `cursor`.
+    fn run(text: &str, cursor: (usize, usize), keys: &str) -> (String, (usize, usize)) {
+        let mut uistate = editing(text, cursor);
+
+        press(&mut uistate, keys);
+
+        edited(&uistate)
+    }
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    #[test]
+    fn word_motions_stop_at_word_and_punctuation_boundaries() {
+        let text = chars("foo.bar  baz");
+
+        assert_eq!(word_start(&text, 0, false), 3);
+        assert_eq!(word_start(&text, 3, false), 4);
+        assert_eq!(word_start(&text, 0, true), 9);
+        assert_eq!(word_end(&text, 0, false), 2);
+        assert_eq!(word_end(&text, 2, false), 3);
+        assert_eq!(word_end(&text, 0, true), 6);
+        assert_eq!(word_back(&text, 9, false), 4);
+        assert_eq!(word_back(&text, 9, true), 0);
+    }
+
+    #[test]
+    fn word_motions_stay_in_the_text() {
+        let text = chars("one two");
+
+        assert_eq!(word_start(&text, 4, false), 7);
+        assert_eq!(word_start(&text, 7, false), 7);
+        assert_eq!(word_end(&text, 6, false), 6);
+        assert_eq!(word_back(&text, 0, false), 0);
+        assert_eq!(word_back(&[], 3, false), 0);
+        assert_eq!(word_end(&[], 0, false), 0);
+    }
+
+    #[test]
+    fn word_motions_stop_on_empty_lines() {
+        let text = chars("a\n\nb");
+
+        assert_eq!(word_start(&text, 0, false), 2);
+        assert_eq!(word_back(&text, 3, false), 2);
+    }
+
+    #[test]
+    fn parse_reads_counts_operators_and_objects() {
+        let parsed = |keys: &str, visual: bool| match parse(&chars(keys), visual) {
+            Parse::Incomplete => String::from("incomplete"),
+            Parse::Invalid => String::from("invalid"),
+            Parse::Done(Command::Move(count, _)) => format!("move {:?}", count),
+            Parse::Done(Command::Operator(count, op, range)) => {
+                let range = match range {
+                    OperatorRange::Motion(_) => String::from("motion"),
+                    OperatorRange::Line => String::from("line"),
+                    OperatorRange::Object(inner, kind) => {
+                        format!("object {} {}", inner, kind)
+                    }
+                };
+
+                format!("{} {:?} {}", op, count, range)
+            }
+            Parse::Done(Command::Action(count, c)) => format!("action {} {:?}", c, count),
+        };
+
+        let cases = [
+            ("w", false, "move None"),
+            ("12j", false, "move Some(12)"),
+            ("0", false, "move None"),
+            ("10x", false, "action x Some(10)"),
+            ("2d3w", false, "d Some(6) motion"),
+            ("dd", false, "d None line"),
+            ("3yy", false, "y Some(3) line"),
+            ("ciw", false, "c None object true w"),
+            ("da(", false, "d None object false ("),
+            ("d", false, "incomplete"),
+            ("di", false, "incomplete"),
+            ("g", false, "incomplete"),
+            ("dz", false, "invalid"),
+            ("dcw", false, "invalid"),
+            ("gx", false, "invalid"),
+            ("d", true, "action d None"),
+            ("u", true, "invalid"),
+        ];
+
+        for (keys, visual, expected) in cases {
+            assert_eq!(parsed(keys, visual), expected, "{}", keys);
+        }
+    }
+
+    #[test]
+    fn motions_stop_at_the_buffer_edges() {
+        let text = "abc\ndef";
+        let cases = [
+            ((0, 0), "h", (0, 0)),
+            ((0, 2), "l", (0, 2)),
+            ((0, 1), "k", (0, 1)),
+            ((1, 1), "j", (1, 1)),
+            ((1, 0), "w", (1, 2)),
+            ((0, 0), "b", (0, 0)),
+            ((0, 1), "5l", (0, 2)),
+            ((1, 2), "9h", (1, 0)),
+            ((0, 0), "G", (1, 0)),
+            ((1, 2), "gg", (0, 0)),
+            ((0, 0), "$", (0, 2)),
+            ((1, 2), "0", (1, 0)),
+        ];
+
+        for (cursor, keys, expected) in cases {
+            let (edited, moved) = run(text, cursor, keys);
+
+            assert_eq!(edited, text, "{}", keys);
+            assert_eq!(moved, expected, "{}", keys);
+        }
+    }
+
+    #[test]
+    fn counts_repeat_motions_and_actions() {
+        assert_eq!(run("a b c d", (0, 0), "2w").1, (0, 4));
+        assert_eq!(run("1\n2\n3\n4", (0, 0), "2j").1, (2, 0));
+        assert_eq!(run("abcdef", (0, 1), "3x").0, "aef");
+        assert_eq!(run("1\n2\n3\n4", (1, 0), "2dd").0, "1\n4");
+        assert_eq!(run("a b c d", (0, 0), "d2w").0, "c d");
+    }
+
+    #[test]
+    fn operators_delete_change_and_yank() {
+        assert_eq!(run("one two", (0, 0), "dw"), (String::from("two"), (0, 0)));
+        assert_eq!(run("one two", (0, 4), "dw"), (String::from("one "), (0, 3)));
+        assert_eq!(run("one two", (0, 0), "cwsix\x1b").0, "six two");
+        assert_eq!(run("a\nb\nc", (1, 0), "dd"), (String::from("a\nc"), (1, 0)));
+        assert_eq!(run("a\nb\nc", (2, 0), "dd"), (String::from("a\nb"), (1, 0)));
+        assert_eq!(run("a\nb", (0, 0), "yyjp"), (String::from("a\nb\na"), (2, 0)));
+        assert_eq!(run("a\nb", (1, 0), "yyP").0, "a\nb\nb");
+        assert_eq!(run("abc", (0, 0), "xp").0, "bac");
+        assert_eq!(run("say (hi there)", (0, 6), "di(").0, "say ()");
+    }
+
+    #[test]
+    fn visual_deletes_multi_byte_text() {
+        let text = |text: &str| String::from(text);
+
+        assert_eq!(run("héllo wörld", (0, 1), "vlld"), (text("ho wörld"), (0, 1)));
+        assert_eq!(run("日本語 テキスト", (0, 0), "vex"), (text(" テキスト"), (0, 0)));
+        assert_eq!(run("ä\nö\nü", (0, 0), "Vjd").0, "ü");
+    }
+
+    #[test]
+    fn undo_and_redo_whole_commands() {
+        let mut uistate = editing("one two three", (0, 0));
+
+        press(&mut uistate, "dwdw");
+        assert_eq!(edited(&uistate).0, "three");
+
+        press(&mut uistate, "u");
+        assert_eq!(edited(&uistate).0, "two three");
+
+        press(&mut uistate, "u");
+        assert_eq!(edited(&uistate).0, "one two three");
+
+        press(&mut uistate, "\x12");
+        assert_eq!(edited(&uistate).0, "two three");
+    }
+
+    #[test]
+    fn undo_takes_back_an_insert_as_one_change() {
+        let mut uistate = editing("abc", (0, 0));
+
+        press(&mut uistate, "ciwxyz\x1b");
+        assert_eq!(edited(&uistate).0, "xyz");
+
+        press(&mut uistate, "u");
+        assert_eq!(edited(&uistate).0, "abc");
+    }
+}
@@ -0,0 +1,27 @@
+use crossterm::event::{ KeyCode, KeyEvent };
+
+use crate::ui::state::UiState;
+
+/// Processes the keys of the message log when the response area shows it.
+/// Returns `true` if the key was used.
+pub fn process_websocket_input(key: KeyEvent, uistate: &mut UiState) -> bool {
+    let websocket = uistate.websocket_mut();
+
+    match key.code {
+        KeyCode::Up => { websocket.scroll(-1); }
+        KeyCode::Down => { websocket.scroll(1); }
+        KeyCode::PageUp => { websocket.scroll(-10); }
+        KeyCode::PageDown => { websocket.scroll(10); }
+
+        KeyCode::Char('b') => {
+            let frame_kind = websocket.frame_kind().toggle();
+            websocket.set_frame_kind(frame_kind);
+        }
+
+        KeyCode::Char('c') => { websocket.clear_log(); }
+
+        _ => { return false; }
+    }
+
+    true
+}
@@ -0,0 +1,262 @@
+//! WebSocket connections.
+//!
+//! A request is sent over WebSocket when its url has the `ws://` or `wss://`
+//! scheme, or when the WebSocket switch of the method box is on. The
+//! connection stays open: the body editor sends text or binary frames over
+//! it and every message sent or received is logged with its time.
+
+pub mod worker;
+
+use std::error::Error;
+
+use chrono::{ DateTime, Local };
+use tungstenite::{
+    client::IntoClientRequest, handshake::client::Request,
+    http::{ HeaderValue, header::COOKIE },
+};
+
+use crate::{
+    api::{ build_request, resolved_url },
+    environment::resolve,
+    ui::state::{ UiState, url::Url },
+};
+
+/// How the body editor is sent over the connection.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum FrameKind {
+    /// The text of the body editor.
+    #[default]
+    Text,
+
+    /// The bytes written in hex in the body editor, e.g. `0a ff 10`.
+    Binary,
+}
+
+impl FrameKind {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Binary => "binary",
+        }
+    }
+
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Text => Self::Binary,
+            Self::Binary => Self::Text,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+/// What happened on the connection.
+#[derive(Clone)]
+pub enum LogEvent {
+    /// The handshake with the url completed.
+    Connected(String),
+
+    /// A message, binary ones written in hex.
+    Message(Direction, FrameKind, String),
+
+    /// The close code and reason.
+    Closed(u16, String),
+
+    Error(String),
+}
+
+/// A line of the message log.
+#[derive(Clone)]
+pub struct LogEntry {
+    at: DateTime<Local>,
+    event: LogEvent,
+}
+
+impl LogEntry {
+    pub fn new(event: LogEvent) -> Self {
+        Self { at: Local::now(), event }
+    }
+
+    pub fn at(&self) -> &DateTime<Local> { &self.at }
+    pub fn event(&self) -> &LogEvent { &self.event }
+}
+
+/// Whether the request in `uistate` is sent over WebSocket.
+pub fn is_websocket(uistate: &UiState) -> bool {
+    if uistate.websocket().enabled() {
+        return true;
+    }
+
+    let mut url = Url::default();
+    url.update(resolved_url(uistate));
+
+    url.is_websocket()
+}
+
+/// Builds the handshake request. The headers and the authorization of the
+/// request are sent with it, and `http` urls are turned into `ws` ones.
+pub fn handshake_request(uistate: &UiState)
+    -> Result<Request, Box<dyn Error + 'static>> {
+    // The handshake is a GET without a body
+    let built = build_request(uistate, reqwest::Method::GET, None)?.build()?;
+
+    let mut url = built.url().clone();
+
+    let scheme = match url.scheme() {
+        "http" => Some("ws"),
+        "https" => Some("wss"),
+        _ => None,
+    };
+
+    if let Some(scheme) = scheme {
+        url.set_scheme(scheme)
+            .map_err(|_| format!("can't connect to {}", url))?;
+    }
+
+    let mut request = url.as_str().into_client_request()?;
+
+    for (name, value) in built.headers() {
+        request.headers_mut().append(name.clone(), value.clone());
+    }
+
+    // reqwest only adds the cookies of the jar when it sends the request
+    if !request.headers().contains_key(COOKIE) {
+        let cookies = uistate.cookies().jars()
+            .with_jar(&uistate.cookie_jar(), |jar| jar.header(built.url()));
+
+        if let Some(cookies) = cookies {
+            request.headers_mut().insert(COOKIE, HeaderValue::from_str(&cookies)?);
+        }
+    }
+
+    Ok(request)
+}
+
+/// The body editor as a frame of `kind`, with the `{{variables}}` of the
+/// active environment resolved.
+pub fn body_frame(uistate: &UiState, kind: FrameKind)
+    -> Result<Vec<u8>, String> {
+    let text = resolve(
+        &uistate.body().text_data().text(),
+        uistate.active_environment(),
+    );
+
+    match kind {
+        FrameKind::Text => Ok(text.into_bytes()),
+        FrameKind::Binary => parse_hex(&text),
+    }
+}
+
+/// Parses bytes written in hex. Whitespace between the bytes is ignored.
+fn parse_hex(text: &str) -> Result<Vec<u8>, String> {
+    let digits: Vec<char> = text.chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+
+    digits.chunks(2)
+        .map(|pair| {
+            let byte: String = pair.iter().collect();
+
+            if pair.len() < 2 {
+                return Err(format!("\"{}\" is missing a hex digit", byte));
+            }
+
+            // from_str_radix takes a sign as well
+            if !pair.iter().all(char::is_ascii_hexdigit) {
+                return Err(format!("\"{}\" is not a hex byte", byte));
+            }
+
+            u8::from_str_radix(&byte, 16)
+                .map_err(|_| format!("\"{}\" is not a hex byte", byte))
+        })
+        .collect()
+}
+
+/// Writes `bytes` in hex, e.g. `0a ff 10`.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ env, process };
+
+    use super::*;
+    use crate::{
+        cookie::{ Cookie, CookieJars },
+        ui::state::auth::AuthScheme,
+    };
+
+    fn connecting(url: &str) -> UiState {
+        let mut uistate = UiState::default();
+
+        uistate.load_url(String::from(url));
+
+        // A jar of its own, so that the cookies folder isn't read
+        uistate.cookies_mut().set_jars(CookieJars::new(
+            env::temp_dir().join(format!("dakia-test-{}-ws-cookies", process::id()))
+        ));
+
+        uistate
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        let bytes = parse_hex("0a ff 10\n00").unwrap();
+
+        assert_eq!(bytes, [0x0a, 0xff, 0x10, 0x00]);
+        assert_eq!(to_hex(&bytes), "0a ff 10 00");
+        assert_eq!(parse_hex("0AfF").unwrap(), [0x0a, 0xff]);
+        assert!(parse_hex(" ").unwrap().is_empty());
+        assert_eq!(to_hex(&[]), "");
+    }
+
+    #[test]
+    fn hex_with_an_odd_or_invalid_digit_is_rejected() {
+        assert_eq!(parse_hex("0a f").unwrap_err(), "\"f\" is missing a hex digit");
+        assert_eq!(parse_hex("0g").unwrap_err(), "\"0g\" is not a hex byte");
+        assert_eq!(parse_hex("+1").unwrap_err(), "\"+1\" is not a hex byte");
+    }
+
+    #[test]
+    fn handshake_rewrites_http_urls() {
+        for (url, expected) in [
+            ("http://example.com/chat", "ws://example.com/chat"),
+            ("https://example.com/chat?room=1", "wss://example.com/chat?room=1"),
+            ("wss://example.com/chat", "wss://example.com/chat"),
+        ] {
+            let request = handshake_request(&connecting(url)).unwrap();
+
+            assert_eq!(request.uri().to_string(), expected, "{}", url);
+        }
+    }
+
+    #[test]
+    fn handshake_sends_the_auth_and_cookie_headers() {
+        let mut uistate = connecting("https://example.com/chat");
+
+        uistate.auth_mut().set_auth_scheme(AuthScheme::Bearer);
+        uistate.auth_mut().set_token(String::from("t0ken"));
+
+        uistate.cookies().jars().with_jar(&uistate.cookie_jar(), |jar| {
+            jar.insert(Cookie::new(
+                String::from("session"), String::from("abc"),
+                String::from("example.com"), String::from("/"),
+            ));
+        });
+
+        let request = handshake_request(&uistate).unwrap();
+        let headers = request.headers();
+
+        assert_eq!(headers["authorization"], "Bearer t0ken");
+        assert_eq!(headers[COOKIE], "session=abc");
+        assert!(headers.contains_key("sec-websocket-key"));
+    }
+}
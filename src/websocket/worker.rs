@@ -0,0 +1,332 @@
+//! Keeps a WebSocket connection open on a worker thread. The ui sends the
+//! frames to write over a channel of [Command]s, and the thread sends what
+//! happens on the connection back to the `run_app` loop, where it is applied
+//! with [receive].
+
+use std::{
+    error::Error, io, net::TcpStream, thread,
+    sync::mpsc::{ Receiver, TryRecvError, channel },
+    time::Duration,
+};
+
+use log::info;
+use tungstenite::{
+    Message, WebSocket, connect,
+    error::Error as WsError,
+    handshake::client::Request,
+    protocol::{ CloseFrame, frame::coding::CloseCode },
+    stream::MaybeTlsStream,
+};
+
+use crate::{
+    websocket::{
+        Direction, FrameKind, LogEntry, LogEvent, handshake_request,
+        body_frame, to_hex,
+    },
+    ui::state::{ UiState, app_status::AppStatus },
+};
+
+/// How long a read waits for a message before the commands are checked.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Close code logged when the connection ends without a close frame.
+const ABNORMAL_CLOSE: u16 = 1006;
+
+/// Message sent from the worker thread back to the ui loop.
+pub struct WsEvent {
+    /// Id of the connection this event belongs to.
+    connection_id: u64,
+    kind: WsEventKind,
+}
+
+enum WsEventKind {
+    Log(LogEntry),
+
+    /// The connection is closed and the thread is done.
+    Ended,
+}
+
+/// What the ui asks the worker thread to do.
+pub enum Command {
+    Send(Message),
+
+    /// Closes the connection with the normal close code.
+    Close,
+}
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+/// Connects when there is no connection, sends the body editor as a frame
+/// otherwise. Frames sent while connecting are sent once connected.
+pub fn send(uistate: &mut UiState) {
+    let result = match uistate.websocket().connection() {
+        Some(_) => send_body(uistate),
+        None => connect_socket(uistate),
+    };
+
+    if let Err(e) = result {
+        info!("{}", e);
+
+        uistate.set_app_error(format!("WebSocket: {}", e));
+        uistate.set_app_status(AppStatus::ERROR);
+    }
+}
+
+fn connect_socket(uistate: &mut UiState) -> Result<(), Box<dyn Error>> {
+    let sender = match uistate.websocket().sender() {
+        Some(sender) => sender.clone(),
+        None => { return Err("WebSocket is not available".into()); }
+    };
+
+    let request = handshake_request(uistate)?;
+    let url = request.uri().to_string();
+
+    info!("Connecting to {}", url);
+
+    let (commands, command_receiver) = channel();
+    let connection_id = uistate.websocket_mut().open(url.clone(), commands);
+
+    uistate.set_app_message(format!("Connecting to {}", url));
+    uistate.set_app_status(AppStatus::INFO);
+
+    thread::spawn(move || {
+        run_connection(request, url, &command_receiver, &|kind| {
+            // The receiver is gone only when the app is exiting.
+            let _ = sender.send(WsEvent { connection_id, kind });
+        });
+    });
+
+    Ok(())
+}
+
+fn send_body(uistate: &mut UiState) -> Result<(), Box<dyn Error>> {
+    let websocket = uistate.websocket();
+    let kind = websocket.frame_kind();
+    let frame = body_frame(uistate, kind)?;
+
+    let message = match kind {
+        FrameKind::Text => Message::Text(String::from_utf8(frame)?),
+        FrameKind::Binary => Message::Binary(frame),
+    };
+
+    if let Some(connection) = websocket.connection() {
+        connection.command(Command::Send(message))?;
+    }
+
+    Ok(())
+}
+
+/// Closes the open connection. Returns `true` if there was one.
+pub fn close(uistate: &mut UiState) -> bool {
+    match uistate.websocket().connection() {
+        Some(connection) => {
+            info!("Closing connection #{}", connection.id());
+
+            // The thread is gone already when the command can't be sent
+            if connection.command(Command::Close).is_err() {
+                uistate.websocket_mut().end();
+            }
+
+            true
+        }
+
+        None => false,
+    }
+}
+
+/// Applies an event sent by the worker to the `uistate`. Events of closed
+/// connections are ignored.
+pub fn receive(uistate: &mut UiState, event: WsEvent) {
+    let url = match uistate.websocket().connection() {
+        Some(connection) if connection.id() == event.connection_id => {
+            connection.url().to_string()
+        }
+
+        _ => { return; }
+    };
+
+    match event.kind {
+        WsEventKind::Log(entry) => {
+            match entry.event() {
+                LogEvent::Connected(url) => {
+                    uistate.set_app_message(format!("Connected to {}", url));
+                    uistate.set_app_status(AppStatus::INFO);
+                    uistate.websocket_mut().set_connected();
+                }
+
+                LogEvent::Error(e) => {
+                    uistate.set_app_error(format!("WebSocket: {}", e));
+                    uistate.set_app_status(AppStatus::ERROR);
+                }
+
+                _ => {}
+            }
+
+            uistate.websocket_mut().push_log(entry);
+        }
+
+        WsEventKind::Ended => {
+            info!("Connection to {} ended", url);
+
+            uistate.websocket_mut().end();
+
+            // An error of the connection stays in the status bar
+            if !matches!(uistate.app_status(), AppStatus::ERROR) {
+                uistate.set_app_message(format!("Disconnected from {}", url));
+                uistate.set_app_status(AppStatus::INFO);
+            }
+        }
+    }
+}
+
+/// Connects and runs the connection until it is closed. `emit` sends the
+/// events back to the ui loop.
+fn run_connection(
+    request: Request,
+    url: String,
+    commands: &Receiver<Command>,
+    emit: &dyn Fn(WsEventKind),
+) {
+    let log = |event: LogEvent| emit(WsEventKind::Log(LogEntry::new(event)));
+
+    match connect(request) {
+        Ok((mut socket, _)) => {
+            log(LogEvent::Connected(url));
+
+            if let Err(e) = set_read_timeout(&mut socket) {
+                log(LogEvent::Error(e.to_string()));
+            } else {
+                run_socket(&mut socket, commands, &log);
+            }
+        }
+
+        Err(e) => { log(LogEvent::Error(e.to_string())); }
+    }
+
+    emit(WsEventKind::Ended);
+}
+
+/// Writes the frames asked by the ui and logs the messages received, until
+/// the connection is closed.
+fn run_socket(
+    socket: &mut Socket,
+    commands: &Receiver<Command>,
+    log: &dyn Fn(LogEvent),
+) {
+    let mut closed = false;
+    let mut closing = false;
+
+    loop {
+        loop {
+            let command = match commands.try_recv() {
+                Ok(command) => command,
+                Err(TryRecvError::Empty) => { break; }
+
+                // The ui has gone away
+                Err(TryRecvError::Disconnected) if !closing => Command::Close,
+                Err(TryRecvError::Disconnected) => { break; }
+            };
+
+            let result = match command {
+                Command::Send(message) => {
+                    let event = message_event(Direction::Sent, &message);
+                    let result = socket.write_message(message);
+
+                    if result.is_ok() {
+                        if let Some(event) = event {
+                            log(event);
+                        }
+                    }
+
+                    result
+                }
+
+                Command::Close => {
+                    closing = true;
+
+                    socket.close(Some(CloseFrame {
+                        code: CloseCode::Normal,
+                        reason: "".into(),
+                    }))
+                }
+            };
+
+            match result {
+                Ok(()) | Err(WsError::AlreadyClosed) => {}
+                Err(e) => { log(LogEvent::Error(e.to_string())); }
+            }
+        }
+
+        match socket.read_message() {
+            Ok(Message::Close(frame)) => {
+                let (code, reason) = match frame {
+                    Some(frame) => (
+                        u16::from(frame.code),
+                        frame.reason.to_string(),
+                    ),
+
+                    None => (u16::from(CloseCode::Status), String::new()),
+                };
+
+                closed = true;
+                log(LogEvent::Closed(code, reason));
+            }
+
+            Ok(message) => {
+                if let Some(event) = message_event(Direction::Received, &message) {
+                    log(event);
+                }
+            }
+
+            Err(WsError::Io(e)) if is_timeout(&e) => {}
+
+            Err(WsError::ConnectionClosed) | Err(WsError::AlreadyClosed) => {
+                break;
+            }
+
+            Err(e) => {
+                log(LogEvent::Error(e.to_string()));
+                break;
+            }
+        }
+    }
+
+    if !closed {
+        log(LogEvent::Closed(ABNORMAL_CLOSE, String::new()));
+    }
+}
+
+/// The log event of a text or binary `message`.
+fn message_event(direction: Direction, message: &Message) -> Option<LogEvent> {
+    match message {
+        Message::Text(text) => Some(
+            LogEvent::Message(direction, FrameKind::Text, text.clone())
+        ),
+
+        Message::Binary(bytes) => Some(
+            LogEvent::Message(direction, FrameKind::Binary, to_hex(bytes))
+        ),
+
+        _ => None,
+    }
+}
+
+/// Makes the reads return after [POLL_INTERVAL] so that the commands of the
+/// ui are written without waiting for a message.
+fn set_read_timeout(socket: &mut Socket) -> io::Result<()> {
+    match socket.get_mut() {
+        MaybeTlsStream::Plain(stream) => {
+            stream.set_read_timeout(Some(POLL_INTERVAL))
+        }
+
+        MaybeTlsStream::NativeTls(stream) => {
+            stream.get_ref().set_read_timeout(Some(POLL_INTERVAL))
+        }
+
+        _ => Ok(()),
+    }
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+}